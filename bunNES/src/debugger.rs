@@ -0,0 +1,576 @@
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use bitflags::bitflags;
+use crate::nes::cpu::Cpu;
+
+// breakpoints are checked after every instruction against the pc of the next one, so a break
+// always happens before the instruction at the breakpoint runs. memory accesses are recorded by
+// the bus, which only looks at its watch list while a debugger is attached
+
+const JSR: u8 = 0x20;
+const RTS: u8 = 0x60;
+const RTI: u8 = 0x40;
+
+bitflags! {
+    /// accesses a watchpoint triggers on
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+    pub struct AccessKind: u8 {
+        const READ    = 0b0000_0001;
+        const WRITE   = 0b0000_0010;
+        /// cpu space only, the pc entering the range
+        const EXECUTE = 0b0000_0100;
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AddressSpace {
+    Cpu,
+    /// accessed through $2007
+    Ppu,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    /// only breaks when this holds
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Watchpoint {
+    pub space: AddressSpace,
+    pub range: RangeInclusive<u16>,
+    pub kind: AccessKind,
+    pub enabled: bool,
+}
+
+/// why the debugger paused the emulator
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Breakpoint { id: u32, pc: u16 },
+    /// `value` is the byte read or written, the opcode for executes
+    Watchpoint { id: u32, space: AddressSpace, addr: u16, value: u8, kind: AccessKind },
+    /// a step, step over or step out finished
+    Step { pc: u16 },
+    Scanline { scanline: u16 },
+    Frame { frame: u64 },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Mode {
+    Running,
+    Paused,
+    StepInto,
+    /// the return address and stack pointer of a jsr, resolved when the emulator runs
+    StepOver(Option<(u16, u8)>),
+    /// the stack pointer to return above and the opcode about to run
+    StepOut(Option<(u8, u8)>),
+    Scanline(u16),
+    Frame(u64),
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger {
+    mode: Mode,
+    breakpoints: Vec<(u32, Breakpoint)>,
+    watchpoints: Vec<(u32, Watchpoint)>,
+    /// the bus needs a new copy of the watchpoints
+    watch_changed: bool,
+    next_id: u32,
+    events: VecDeque<Event>,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Debugger {
+            mode: Mode::Running,
+            breakpoints: Vec::new(),
+            watchpoints: Vec::new(),
+            watch_changed: true,
+            next_id: 0,
+            events: VecDeque::new(),
+        }
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger::default()
+    }
+
+    /// breaks before the instruction at `addr` runs, returns the id of the breakpoint
+    pub fn add_breakpoint(&mut self, addr: u16) -> u32 {
+        self.insert_breakpoint(Breakpoint { addr, condition: None, enabled: true })
+    }
+
+    /// like [`Debugger::add_breakpoint`], only breaking when `condition` holds, see [`Condition`]
+    pub fn add_conditional_breakpoint(&mut self, addr: u16, condition: &str) -> Result<u32, ConditionError> {
+        let condition = Condition::parse(condition)?;
+        Ok(self.insert_breakpoint(Breakpoint { addr, condition: Some(condition), enabled: true }))
+    }
+
+    pub fn add_watchpoint(&mut self, space: AddressSpace, range: RangeInclusive<u16>, kind: AccessKind) -> u32 {
+        let id = self.take_id();
+        self.watchpoints.push((id, Watchpoint { space, range, kind, enabled: true }));
+        self.watch_changed = true;
+        id
+    }
+
+    /// removes a breakpoint or watchpoint, false if there is none with `id`
+    pub fn remove(&mut self, id: u32) -> bool {
+        let count = self.breakpoints.len() + self.watchpoints.len();
+        self.breakpoints.retain(|(other, _)| *other != id);
+        self.watchpoints.retain(|(other, _)| *other != id);
+        self.watch_changed = true;
+        count != self.breakpoints.len() + self.watchpoints.len()
+    }
+
+    pub fn set_enabled(&mut self, id: u32, enabled: bool) -> bool {
+        self.watch_changed = true;
+        if let Some((_, breakpoint)) = self.breakpoints.iter_mut().find(|(other, _)| *other == id) {
+            breakpoint.enabled = enabled;
+            return true;
+        }
+        if let Some((_, watchpoint)) = self.watchpoints.iter_mut().find(|(other, _)| *other == id) {
+            watchpoint.enabled = enabled;
+            return true;
+        }
+        false
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (u32, &Breakpoint)> {
+        self.breakpoints.iter().map(|(id, breakpoint)| (*id, breakpoint))
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = (u32, &Watchpoint)> {
+        self.watchpoints.iter().map(|(id, watchpoint)| (*id, watchpoint))
+    }
+
+    /// stops the emulator before its next frame
+    pub fn pause(&mut self) {
+        self.mode = Mode::Paused;
+    }
+
+    /// runs until a breakpoint or watchpoint triggers
+    pub fn resume(&mut self) {
+        self.mode = Mode::Running;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.mode == Mode::Paused
+    }
+
+    /// runs one instruction
+    pub fn step_into(&mut self) {
+        self.mode = Mode::StepInto;
+    }
+
+    /// runs one instruction, a jsr runs until the subroutine returns
+    pub fn step_over(&mut self) {
+        self.mode = Mode::StepOver(None);
+    }
+
+    /// runs until the current subroutine or interrupt handler returns
+    pub fn step_out(&mut self) {
+        self.mode = Mode::StepOut(None);
+    }
+
+    /// runs until the ppu starts `scanline`
+    pub fn run_to_scanline(&mut self, scanline: u16) {
+        self.mode = Mode::Scanline(scanline);
+    }
+
+    /// runs until the ppu frame count reaches `frame`, it goes up when vblank starts
+    pub fn run_to_frame(&mut self, frame: u64) {
+        self.mode = Mode::Frame(frame);
+    }
+
+    /// oldest event not taken yet
+    pub fn poll_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    fn take_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn insert_breakpoint(&mut self, breakpoint: Breakpoint) -> u32 {
+        let id = self.take_id();
+        self.breakpoints.push((id, breakpoint));
+        id
+    }
+
+    /// what the bus checks accesses against
+    pub(crate) fn access_watch(&self) -> AccessWatch {
+        let watchpoints = self.watchpoints.iter()
+            .filter(|(_, watchpoint)| watchpoint.enabled)
+            .cloned()
+            .collect();
+        AccessWatch { watchpoints, hits: Vec::new() }
+    }
+
+    /// called before the emulator runs, false while paused
+    pub(crate) fn prepare(&mut self, cpu: &mut Cpu) -> bool {
+        if self.watch_changed {
+            cpu.bus.watch = Some(self.access_watch());
+            self.watch_changed = false;
+        }
+
+        match self.mode {
+            Mode::Paused => return false,
+            Mode::StepOver(None) => {
+                self.mode = match opcode(cpu) {
+                    JSR => Mode::StepOver(Some((cpu.pc.wrapping_add(3), cpu.sp))),
+                    _ => Mode::StepInto,
+                };
+            }
+            Mode::StepOut(None) => self.mode = Mode::StepOut(Some((cpu.sp, opcode(cpu)))),
+            _ => {}
+        }
+        true
+    }
+
+    /// called after every emulator tick, true when it should stop
+    pub(crate) fn check(&mut self, cpu: &mut Cpu, started: bool) -> bool {
+        let mut events: Vec<Event> = match cpu.bus.watch.as_mut() {
+            Some(watch) => watch.hits.drain(..).collect(),
+            None => Vec::new(),
+        };
+
+        if started {
+            let pc = cpu.pc;
+            match self.mode {
+                Mode::StepInto => events.push(Event::Step { pc }),
+                Mode::StepOver(Some((return_addr, sp))) if pc == return_addr && cpu.sp == sp => {
+                    events.push(Event::Step { pc });
+                }
+                Mode::StepOut(Some((sp, previous))) => {
+                    if matches!(previous, RTS | RTI) && cpu.sp > sp {
+                        events.push(Event::Step { pc });
+                    } else {
+                        self.mode = Mode::StepOut(Some((sp, opcode(cpu))));
+                    }
+                }
+                _ => {}
+            }
+
+            for (id, breakpoint) in &self.breakpoints {
+                if breakpoint.enabled && breakpoint.addr == pc
+                    && breakpoint.condition.as_ref().is_none_or(|condition| condition.holds(cpu)) {
+                    events.push(Event::Breakpoint { id: *id, pc });
+                }
+            }
+            for (id, watchpoint) in &self.watchpoints {
+                if watchpoint.enabled && watchpoint.space == AddressSpace::Cpu
+                    && watchpoint.kind.contains(AccessKind::EXECUTE) && watchpoint.range.contains(&pc) {
+                    let value = opcode(cpu);
+                    events.push(Event::Watchpoint { id: *id, space: AddressSpace::Cpu, addr: pc, value, kind: AccessKind::EXECUTE });
+                }
+            }
+        }
+
+        let ppu = &cpu.bus.ppu;
+        match self.mode {
+            Mode::Scanline(scanline) if ppu.scanline() == scanline && ppu.dot() == 0 => {
+                events.push(Event::Scanline { scanline });
+            }
+            Mode::Frame(frame) if ppu.frame_count() >= frame => {
+                events.push(Event::Frame { frame: ppu.frame_count() });
+            }
+            _ => {}
+        }
+
+        if events.is_empty() {
+            return false;
+        }
+        self.events.extend(events);
+        self.mode = Mode::Paused;
+        true
+    }
+}
+
+fn opcode(cpu: &Cpu) -> u8 {
+    cpu.bus.peek(cpu.pc)
+}
+
+/// watchpoints the bus checks its accesses against, only there while a debugger is attached
+#[derive(Debug, Default)]
+pub(crate) struct AccessWatch {
+    watchpoints: Vec<(u32, Watchpoint)>,
+    hits: Vec<Event>,
+}
+
+impl AccessWatch {
+    pub(crate) fn clear(&mut self) {
+        self.hits.clear();
+    }
+
+    pub(crate) fn record(&mut self, space: AddressSpace, addr: u16, value: u8, kind: AccessKind) {
+        for (id, watchpoint) in &self.watchpoints {
+            if watchpoint.space == space && watchpoint.kind.contains(kind) && watchpoint.range.contains(&addr) {
+                self.hits.push(Event::Watchpoint { id: *id, space, addr, value, kind });
+            }
+        }
+    }
+}
+
+/// expression a conditional breakpoint checks, e.g. `a == $10 && [$0300] != 0`.
+///
+/// - numbers are decimal, or hex with a `$` or `0x` prefix
+/// - `a x y sp p pc` are the cpu registers, `scanline dot frame` the ppu position
+/// - `[addr]` is the byte a read would return, peeked so checking doesn't change anything
+/// - operators from loosest to tightest: `||`, `&&`, `== != < <= > >=`, `&`, `!`
+///
+/// a value on its own holds when it isn't 0
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(u64),
+    Register(Register),
+    Memory(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    P,
+    Pc,
+    Scanline,
+    Dot,
+    Frame,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Operator {
+    Or,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    BitAnd,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConditionError {
+    /// byte offset into the condition
+    pub position: usize,
+    pub message: String,
+}
+
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "column {}: {}", self.position + 1, self.message)
+    }
+}
+
+impl Error for ConditionError {}
+
+impl Condition {
+    pub fn parse(text: &str) -> Result<Condition, ConditionError> {
+        let mut parser = Parser { text, position: 0 };
+        let expr = parser.or()?;
+        parser.skip_whitespace();
+        if parser.position < text.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(Condition { expr })
+    }
+
+    pub fn holds(&self, cpu: &Cpu) -> bool {
+        self.expr.eval(cpu) != 0
+    }
+}
+
+impl Expr {
+    fn eval(&self, cpu: &Cpu) -> u64 {
+        match self {
+            Expr::Number(value) => *value,
+            Expr::Register(register) => match register {
+                Register::A => cpu.acc as u64,
+                Register::X => cpu.x as u64,
+                Register::Y => cpu.y as u64,
+                Register::Sp => cpu.sp as u64,
+                Register::P => cpu.ps.get_reg() as u64,
+                Register::Pc => cpu.pc as u64,
+                Register::Scanline => cpu.bus.ppu.scanline() as u64,
+                Register::Dot => cpu.bus.ppu.dot() as u64,
+                Register::Frame => cpu.bus.ppu.frame_count(),
+            },
+            Expr::Memory(addr) => cpu.bus.peek(addr.eval(cpu) as u16) as u64,
+            Expr::Not(expr) => (expr.eval(cpu) == 0) as u64,
+            Expr::Binary(operator, left, right) => {
+                let left = left.eval(cpu);
+                // short circuit like everywhere else
+                match operator {
+                    Operator::Or if left != 0 => return 1,
+                    Operator::And if left == 0 => return 0,
+                    _ => {}
+                }
+                let right = right.eval(cpu);
+                match operator {
+                    Operator::Or | Operator::And => (right != 0) as u64,
+                    Operator::Equal => (left == right) as u64,
+                    Operator::NotEqual => (left != right) as u64,
+                    Operator::Less => (left < right) as u64,
+                    Operator::LessEqual => (left <= right) as u64,
+                    Operator::Greater => (left > right) as u64,
+                    Operator::GreaterEqual => (left >= right) as u64,
+                    Operator::BitAnd => left & right,
+                }
+            }
+        }
+    }
+}
+
+/// recursive descent, one function per precedence level
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn or(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = Expr::Binary(Operator::Or, Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.comparison()?;
+        while self.eat("&&") {
+            expr = Expr::Binary(Operator::And, Box::new(expr), Box::new(self.comparison()?));
+        }
+        Ok(expr)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ConditionError> {
+        let expr = self.bit_and()?;
+        // longer operators first so `<=` isn't taken for `<`
+        let operators = [
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessEqual),
+            (">=", Operator::GreaterEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ];
+        for (token, operator) in operators {
+            if self.eat(token) {
+                return Ok(Expr::Binary(operator, Box::new(expr), Box::new(self.bit_and()?)));
+            }
+        }
+        Ok(expr)
+    }
+
+    fn bit_and(&mut self) -> Result<Expr, ConditionError> {
+        let mut expr = self.unary()?;
+        while !self.peek("&&") && self.eat("&") {
+            expr = Expr::Binary(Operator::BitAnd, Box::new(expr), Box::new(self.unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ConditionError> {
+        if !self.peek("!=") && self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Expr, ConditionError> {
+        if self.eat("(") {
+            let expr = self.or()?;
+            self.expect(")")?;
+            return Ok(expr);
+        }
+        if self.eat("[") {
+            let addr = self.or()?;
+            self.expect("]")?;
+            return Ok(Expr::Memory(Box::new(addr)));
+        }
+
+        self.skip_whitespace();
+        let start = self.position;
+        let word: String = self.text[start..].chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '$' || *c == '_')
+            .collect();
+        if word.is_empty() {
+            return Err(self.error("expected a value"));
+        }
+        self.position += word.len();
+
+        let register = match word.to_lowercase().as_str() {
+            "a" => Some(Register::A),
+            "x" => Some(Register::X),
+            "y" => Some(Register::Y),
+            "sp" => Some(Register::Sp),
+            "p" => Some(Register::P),
+            "pc" => Some(Register::Pc),
+            "scanline" => Some(Register::Scanline),
+            "dot" => Some(Register::Dot),
+            "frame" => Some(Register::Frame),
+            _ => None,
+        };
+        if let Some(register) = register {
+            return Ok(Expr::Register(register));
+        }
+
+        let value = match word.strip_prefix('$').or_else(|| word.strip_prefix("0x")) {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => word.parse(),
+        };
+        value.map(Expr::Number).map_err(|_| ConditionError {
+            position: start,
+            message: format!("unknown value {word}"),
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.text[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        self.text[self.position..].starts_with(token)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.peek(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, token: &str) -> Result<(), ConditionError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected {token}")))
+        }
+    }
+
+    fn error(&self, message: &str) -> ConditionError {
+        ConditionError { position: self.position, message: message.to_string() }
+    }
+}
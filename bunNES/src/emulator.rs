@@ -1,23 +1,90 @@
+use std::io;
+use std::io::{Seek, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use crate::debugger::Debugger;
+use crate::nes::apu::buffer::AudioBuffer;
+use crate::nes::apu::Channel;
+use crate::nes::input::controller::Buttons;
+use crate::nes::input::four_score::FourScore;
+use crate::nes::input::{InputPorts, Port, PLAYERS};
 use crate::nes::cpu::{Cpu, HEIGHT, RenderImage, WIDTH};
+use crate::nes::palette;
+use crate::nes::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use crate::movie::{rom_checksum, Commands, Movie, MovieError, MovieFrame, MovieMode, MovieStart};
 use crate::nes::rom::Cartridge;
+use crate::png;
+use crate::ram_search::Cheat;
+use crate::rewind::{RewindBuffer, RewindConfig};
+use crate::state::{self, SaveState, StateError, StateReader, StateWriter};
+use crate::wav::WavWriter;
 
+/// 262 scanlines of 341 ppu cycles
+const TICKS_PER_FRAME: u32 = 262 * 341;
 
 pub struct Emulator {
     pub cpu: Cpu,
+    rewind: Option<RewindBuffer>,
+    debugger: Option<Debugger>,
+    /// ticks into the current frame, a frame stopped halfway continues from there
+    frame_ticks: u32,
+
+    /// buttons last set for players 1-4, what a movie records
+    buttons: [Buttons; PLAYERS],
+    /// resets since the last frame, recorded with the next one
+    commands: Commands,
+    movie: Option<MovieSession>,
+    cheats: Vec<Cheat>,
+}
+
+#[derive(Debug)]
+struct MovieSession {
+    movie: Movie,
+    mode: MovieMode,
+    /// next frame to play back
+    frame: usize,
 }
 
 impl Emulator {
     pub fn new(cartridge: Cartridge) -> Emulator {
         Emulator {
             cpu: Cpu::new(cartridge),
+            rewind: None,
+            debugger: None,
+            frame_ticks: 0,
+
+            buttons: [Buttons::empty(); PLAYERS],
+            commands: Commands::empty(),
+            movie: None,
+            cheats: Vec::new(),
         }
     }
     
     pub fn reset(&mut self) {
         // probably a good idea
         self.cpu.soft_reset();
+        self.commands |= Commands::SOFT_RESET;
+    }
+
+    /// power cycle, the cartridge and the plugged in devices stay
+    pub fn power(&mut self) {
+        let cartridge = self.cpu.bus.cartridge().clone();
+        let input = std::mem::take(&mut self.cpu.bus.input);
+        let sample_rate = self.cpu.bus.apu.sample_rate();
+        let watch = self.cpu.bus.watch.take();
+
+        self.cpu = Cpu::new(cartridge);
+        self.cpu.bus.input = input;
+        self.cpu.bus.apu.set_sample_rate(sample_rate);
+        self.cpu.bus.watch = watch;
+        self.cpu.soft_reset();
+        self.frame_ticks = 0;
+
+        self.commands |= Commands::POWER;
+        if let Some(rewind) = self.rewind.as_mut() {
+            rewind.clear();
+        }
     }
 
     pub fn run(mut self) {
@@ -35,4 +102,375 @@ impl Emulator {
     pub fn step(&mut self) {
         while !self.cpu.step() {}
     }
+
+    pub fn run_frame(&mut self) {
+        self.run_frame_with(|_, _| true);
+    }
+
+    /// [`Emulator::run_frame`] calling `on_tick` after every ppu cycle with whether the cpu
+    /// started an instruction in it. returning false, or the debugger breaking, stops the frame
+    /// right there and the next call finishes it. returns whether the frame ran to the end
+    pub fn run_frame_with<F: FnMut(&mut Cpu, bool) -> bool>(&mut self, mut on_tick: F) -> bool {
+        if let Some(debugger) = self.debugger.as_mut() {
+            if !debugger.prepare(&mut self.cpu) {
+                return false;
+            }
+        }
+
+        if self.frame_ticks == 0 {
+            self.movie_frame();
+            for cheat in &self.cheats {
+                cheat.apply(&mut self.cpu.bus);
+            }
+        }
+        while self.frame_ticks < TICKS_PER_FRAME {
+            let started = self.cpu.tick();
+            self.frame_ticks += 1;
+            if self.debugger.as_mut().is_some_and(|debugger| debugger.check(&mut self.cpu, started)) {
+                return false;
+            }
+            if !on_tick(&mut self.cpu, started) {
+                return false;
+            }
+        }
+        self.frame_ticks = 0;
+        self.capture_rewind();
+        true
+    }
+
+    /// breakpoints and watchpoints of `debugger` are checked from the next frame on,
+    /// see [`crate::debugger`]. replaces the debugger attached before
+    pub fn attach_debugger(&mut self, debugger: Debugger) {
+        self.cpu.bus.watch = Some(debugger.access_watch());
+        self.debugger = Some(debugger);
+    }
+
+    /// stops checking for breaks, the bus goes back to not recording accesses
+    pub fn detach_debugger(&mut self) -> Option<Debugger> {
+        self.cpu.bus.watch = None;
+        self.debugger.take()
+    }
+
+    pub fn debugger(&self) -> Option<&Debugger> {
+        self.debugger.as_ref()
+    }
+
+    pub fn debugger_mut(&mut self) -> Option<&mut Debugger> {
+        self.debugger.as_mut()
+    }
+
+    /// buttons currently held by `player` 0..=3, see [`InputPorts::set_buttons`].
+    /// ignored for other players and while a movie plays back
+    pub fn set_buttons(&mut self, player: usize, buttons: Buttons) {
+        if player >= PLAYERS || self.movie_mode() == Some(MovieMode::Playing) {
+            return;
+        }
+        self.buttons[player] = buttons;
+        self.cpu.bus.input.set_buttons(player, buttons);
+    }
+
+    /// devices plugged into the controller and expansion ports
+    pub fn input(&mut self) -> &mut InputPorts {
+        &mut self.cpu.bus.input
+    }
+
+    /// holds a byte of ram at a value from the next frame on, replaces a cheat on the same address
+    pub fn add_cheat(&mut self, cheat: Cheat) {
+        self.remove_cheat(cheat.addr);
+        self.cheats.push(cheat);
+    }
+
+    pub fn remove_cheat(&mut self, addr: u16) -> bool {
+        let len = self.cheats.len();
+        self.cheats.retain(|cheat| cheat.addr != addr);
+        self.cheats.len() != len
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    /// last rendered frame as system palette indices, see [`crate::nes::palette`]
+    pub fn frame_buffer(&self) -> &[u8] {
+        self.cpu.bus.ppu.frame_buffer()
+    }
+
+    /// last rendered frame as png
+    pub fn screenshot(&self) -> Vec<u8> {
+        let rgb: Vec<u8> = self.frame_buffer().iter().flat_map(|color| palette::rgb(*color)).collect();
+        png::encode(SCREEN_WIDTH, SCREEN_HEIGHT, &rgb)
+    }
+
+    pub fn save_screenshot<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.screenshot())
+    }
+
+    /// FNV-1a of the frame buffer, cheap to compare rendering against a known frame
+    pub fn frame_hash(&self) -> u64 {
+        self.frame_buffer().iter().fold(0xCBF2_9CE4_8422_2325, |hash, color| {
+            (hash ^ *color as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    }
+
+    /// snapshot of the whole machine, restored with [`Emulator::load_state`]
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut writer = StateWriter::new();
+        for byte in state::MAGIC {
+            writer.u8(byte);
+        }
+        writer.u16(state::VERSION);
+        writer.u64(self.cpu.bus.cartridge().hash());
+        self.cpu.save_state(&mut writer);
+        // a paused debugger can stop mid frame
+        writer.u32(self.frame_ticks);
+        writer.into_inner()
+    }
+
+    /// restores a state from [`Emulator::save_state`] of the same cartridge.
+    /// on error the emulator is left as it was
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let mut reader = StateReader::new(data);
+        let mut magic = [0; 4];
+        for byte in magic.iter_mut() {
+            *byte = reader.u8().map_err(|_| StateError::InvalidMagic)?;
+        }
+        if magic != state::MAGIC {
+            return Err(StateError::InvalidMagic);
+        }
+        let version = reader.u16()?;
+        if version != state::VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+        if reader.u64()? != self.cpu.bus.cartridge().hash() {
+            return Err(StateError::WrongCartridge);
+        }
+
+        // a state can still fail halfway through, keep a copy to roll back to
+        let backup = self.save_state();
+        let result = self.cpu.load_state(&mut reader).and_then(|_| reader.u32()).and_then(|frame_ticks| {
+            if reader.is_empty() { Ok(frame_ticks) } else { Err(StateError::Mismatch("state size")) }
+        });
+        match result {
+            Ok(frame_ticks) => {
+                self.frame_ticks = frame_ticks;
+                // the history belongs to the timeline that was just left
+                if let Some(rewind) = self.rewind.as_mut() {
+                    rewind.clear();
+                }
+            }
+            Err(_) => {
+                let mut reader = StateReader::new(&backup[state::HEADER_SIZE..]);
+                self.cpu.load_state(&mut reader).expect("restoring the backup state");
+            }
+        }
+        result.map(|_| ())
+    }
+
+    /// starts capturing snapshots in [`Emulator::run_frame`] for [`Emulator::rewind`]
+    pub fn enable_rewind(&mut self, config: RewindConfig) {
+        self.rewind = Some(RewindBuffer::new(config));
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    pub fn rewind_buffer(&self) -> Option<&RewindBuffer> {
+        self.rewind.as_ref()
+    }
+
+    /// goes back `frames` frames, or as far as the history reaches.
+    /// lands on the closest snapshot at or before that frame and returns the frames actually rewound
+    pub fn rewind(&mut self, frames: u32) -> u32 {
+        let current = self.cpu.bus.ppu.frame_count();
+        let Some((frame, state)) = self.rewind.as_mut().and_then(|rewind| rewind.restore(current.saturating_sub(frames as u64))) else {
+            return 0;
+        };
+
+        let mut reader = StateReader::new(&state);
+        self.cpu.load_state(&mut reader).expect("restoring a rewind snapshot");
+        self.frame_ticks = 0;
+
+        let rewound = current.saturating_sub(frame) as u32;
+        // rewinding a recording rerecords the rewound frames
+        if let Some(session) = self.movie.as_mut().filter(|session| session.mode == MovieMode::Recording) {
+            let frames = session.movie.frames.len().saturating_sub(rewound as usize);
+            session.movie.frames.truncate(frames);
+            session.movie.rerecord_count += 1;
+        }
+        rewound
+    }
+
+    /// starts recording the input of every following [`Emulator::run_frame`].
+    /// the controller ports get fresh standard controllers, or four scores if one is plugged in
+    pub fn record_movie(&mut self, start: MovieStart, rom_filename: &str) {
+        let mut movie = Movie::new(self.cpu.bus.cartridge(), rom_filename);
+        movie.four_score = self.cpu.bus.input.device_mut::<FourScore>(Port::One).is_some();
+        self.cpu.bus.input = movie_input(&movie);
+
+        match start {
+            MovieStart::PowerOn => self.power(),
+            MovieStart::SaveState => movie.save_state = Some(self.save_state()),
+        }
+        self.buttons = [Buttons::empty(); PLAYERS];
+        self.commands = Commands::empty();
+        self.movie = Some(MovieSession { movie, mode: MovieMode::Recording, frame: 0 });
+    }
+
+    /// plays back `movie` from its start, input comes from the movie until its last frame
+    pub fn play_movie(&mut self, movie: Movie) -> Result<(), MovieError> {
+        if movie.rom_checksum != rom_checksum(self.cpu.bus.cartridge()) {
+            return Err(MovieError::WrongRom);
+        }
+
+        let input = std::mem::replace(&mut self.cpu.bus.input, movie_input(&movie));
+        match &movie.save_state {
+            Some(state) => {
+                if let Err(e) = self.load_state(state) {
+                    self.cpu.bus.input = input;
+                    return Err(e.into());
+                }
+            }
+            None => self.power(),
+        }
+        self.commands = Commands::empty();
+        self.movie = Some(MovieSession { movie, mode: MovieMode::Playing, frame: 0 });
+        Ok(())
+    }
+
+    /// ends recording or playback and hands back the movie
+    pub fn stop_movie(&mut self) -> Option<Movie> {
+        self.movie.take().map(|session| session.movie)
+    }
+
+    pub fn movie_mode(&self) -> Option<MovieMode> {
+        self.movie.as_ref().map(|session| session.mode)
+    }
+
+    pub fn movie(&self) -> Option<&Movie> {
+        self.movie.as_ref().map(|session| &session.movie)
+    }
+
+    /// records or plays back the input of the frame about to run
+    fn movie_frame(&mut self) {
+        let Some(session) = self.movie.as_mut() else {
+            return;
+        };
+        match session.mode {
+            MovieMode::Recording => {
+                let commands = std::mem::take(&mut self.commands);
+                session.movie.frames.push(MovieFrame { commands, buttons: self.buttons });
+            }
+            MovieMode::Playing => {
+                let Some(frame) = session.movie.frames.get(session.frame).copied() else {
+                    session.mode = MovieMode::Finished;
+                    return;
+                };
+                session.frame += 1;
+
+                if frame.commands.contains(Commands::POWER) {
+                    self.power();
+                }
+                if frame.commands.contains(Commands::SOFT_RESET) {
+                    self.reset();
+                }
+                for (player, buttons) in frame.buttons.into_iter().enumerate() {
+                    self.buttons[player] = buttons;
+                    self.cpu.bus.input.set_buttons(player, buttons);
+                }
+            }
+            MovieMode::Finished => {}
+        }
+    }
+
+    fn capture_rewind(&mut self) {
+        let frame = self.cpu.bus.ppu.frame_count();
+        if !self.rewind.as_ref().is_some_and(|rewind| rewind.wants(frame)) {
+            return;
+        }
+        let mut writer = StateWriter::new();
+        self.cpu.save_state(&mut writer);
+        let state = writer.into_inner();
+        if let Some(rewind) = self.rewind.as_mut() {
+            rewind.capture(frame, &state);
+        }
+    }
+
+    /// host sample rate the audio output is resampled to
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.cpu.bus.apu.set_sample_rate(sample_rate);
+    }
+
+    /// pull audio produced since the last call
+    pub fn audio_samples(&mut self) -> &mut AudioBuffer {
+        self.cpu.bus.apu.samples()
+    }
+
+    /// runs the emulator for `frames` frames and records the audio output to a 16 bit wav file.
+    ///
+    /// with `split_channels` every channel is additionally written on its own next to `path`,
+    /// e.g. `music.wav` gets `music.pulse_1.wav`, `music.triangle.wav`, ...
+    pub fn record_audio<P: AsRef<Path>>(&mut self, path: P, frames: u32, split_channels: bool) -> io::Result<()> {
+        let path = path.as_ref();
+        let sample_rate = self.cpu.bus.apu.sample_rate();
+
+        let mut mixed = WavWriter::create(path, sample_rate)?;
+        let mut channels = Vec::new();
+        if split_channels {
+            for channel in Channel::ALL {
+                channels.push((channel, WavWriter::create(channel_path(path, channel), sample_rate)?));
+            }
+        }
+
+        self.cpu.bus.apu.samples().clear();
+        self.cpu.bus.apu.set_channel_capture(split_channels);
+        let recorded = self.record_frames(frames, &mut mixed, &mut channels);
+        // also when writing failed halfway
+        self.cpu.bus.apu.set_channel_capture(false);
+        recorded?;
+
+        mixed.finish()?;
+        for (_, writer) in channels {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+
+    fn record_frames<W: Write + Seek>(&mut self, frames: u32, mixed: &mut WavWriter<W>, channels: &mut [(Channel, WavWriter<W>)]) -> io::Result<()> {
+        let mut samples = Vec::new();
+        for _ in 0..frames {
+            self.run_frame();
+
+            drain_samples(self.cpu.bus.apu.samples(), &mut samples);
+            mixed.write_samples(&samples)?;
+
+            for (channel, writer) in channels.iter_mut() {
+                if let Some(buffer) = self.cpu.bus.apu.channel_samples(*channel) {
+                    drain_samples(buffer, &mut samples);
+                    writer.write_samples(&samples)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn drain_samples(buffer: &mut AudioBuffer, samples: &mut Vec<i16>) {
+    samples.resize(buffer.len(), 0);
+    buffer.pop_i16(samples);
+}
+
+fn channel_path(path: &Path, channel: Channel) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.{}.wav", stem, channel.name()))
+}
+
+/// the devices a movie was recorded with
+fn movie_input(movie: &Movie) -> InputPorts {
+    let mut input = InputPorts::new();
+    if movie.four_score {
+        input.set_device(Port::One, Box::new(FourScore::new(Port::One)));
+        input.set_device(Port::Two, Box::new(FourScore::new(Port::Two)));
+    }
+    input
 }
@@ -0,0 +1,391 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use crate::debugger::{AccessKind, AddressSpace, Debugger, Event};
+use crate::emulator::Emulator;
+
+// https://sourceware.org/gdb/current/onlinedocs/gdb.html/Remote-Protocol.html
+// a gdb remote serial protocol stub on top of the debugger. there is no 6502 target description
+// in gdb, registers are sent in this order, each as little endian hex:
+//
+//   0 a, 1 x, 2 y, 3 p, 4 sp (8 bit)   5 pc (16 bit)
+//
+// memory is peeked, reading ppu or apu registers doesn't change them. writes go through the cpu
+// bus like a program's and have the usual side effects. Z0/Z1 are pc breakpoints, Z2-Z4 write, read and access watchpoints
+
+const REGISTERS: usize = 6;
+/// SIGTRAP, a breakpoint, watchpoint or finished step
+const SIGTRAP: u8 = 5;
+/// SIGINT, stopped by a ctrl-c from the client
+const SIGINT: u8 = 2;
+/// what the client sends to stop a running target
+const INTERRUPT: u8 = 0x03;
+const PACKET_SIZE: usize = 0x1000;
+
+/// listens on localhost only, the protocol has no authentication
+pub fn listen(port: u16) -> io::Result<TcpListener> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+}
+
+/// a connected gdb client controlling the emulator until it detaches or kills it
+pub struct GdbStub<'a> {
+    emulator: &'a mut Emulator,
+    stream: TcpStream,
+    /// received but not handled yet
+    input: Vec<u8>,
+    no_ack: bool,
+    /// the debugger was attached for this session and goes away with it
+    owns_debugger: bool,
+    /// Z packet type and address of the breakpoints and watchpoints set by the client
+    points: HashMap<(u8, u16), u32>,
+}
+
+impl<'a> GdbStub<'a> {
+    /// pauses the emulator, attaching a debugger if there is none
+    pub fn new(emulator: &'a mut Emulator, stream: TcpStream) -> GdbStub<'a> {
+        let owns_debugger = emulator.debugger().is_none();
+        if owns_debugger {
+            emulator.attach_debugger(Debugger::new());
+        }
+        emulator.debugger_mut().unwrap().pause();
+
+        GdbStub {
+            emulator,
+            stream,
+            input: Vec::new(),
+            no_ack: false,
+            owns_debugger,
+            points: HashMap::new(),
+        }
+    }
+
+    /// handles packets until the client detaches, kills or disconnects. the emulator is left running
+    pub fn run(mut self) -> io::Result<()> {
+        let result = self.serve();
+
+        let debugger = self.emulator.debugger_mut().unwrap();
+        for id in self.points.values() {
+            debugger.remove(*id);
+        }
+        debugger.resume();
+        if self.owns_debugger {
+            self.emulator.detach_debugger();
+        }
+        match result {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(()),
+            result => result,
+        }
+    }
+
+    fn serve(&mut self) -> io::Result<()> {
+        loop {
+            let packet = self.read_packet()?;
+            let reply = match packet.first() {
+                Some(b'?') => format!("S{SIGTRAP:02x}"),
+                Some(b'g') => self.read_registers(),
+                Some(b'G') => self.write_registers(&packet[1..]),
+                Some(b'p') => self.read_register(&packet[1..]),
+                Some(b'P') => self.write_register(&packet[1..]),
+                Some(b'm') => self.read_memory(&packet[1..]),
+                Some(b'M') => self.write_memory(&packet[1..]),
+                Some(b'Z') => self.insert_point(&packet[1..]),
+                Some(b'z') => self.remove_point(&packet[1..]),
+                Some(b'c') => {
+                    self.emulator.debugger_mut().unwrap().resume();
+                    self.run_until_stop()?
+                }
+                Some(b's') => {
+                    self.emulator.debugger_mut().unwrap().step_into();
+                    self.run_until_stop()?
+                }
+                Some(b'D') => {
+                    self.write_packet("OK")?;
+                    return Ok(());
+                }
+                Some(b'k') => return Ok(()),
+                Some(b'H') => "OK".to_string(),
+                Some(b'q') | Some(b'Q') => self.query(&packet),
+                _ => String::new(),
+            };
+            self.write_packet(&reply)?;
+        }
+    }
+
+    fn query(&mut self, packet: &[u8]) -> String {
+        let packet = String::from_utf8_lossy(packet);
+        match packet.split(':').next().unwrap_or_default() {
+            "qSupported" => format!("PacketSize={PACKET_SIZE:x};QStartNoAckMode+"),
+            "QStartNoAckMode" => {
+                // the OK is still acknowledged
+                self.no_ack = true;
+                "OK".to_string()
+            }
+            "qAttached" => "1".to_string(),
+            "qC" => "QC1".to_string(),
+            "qfThreadInfo" => "m1".to_string(),
+            "qsThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// runs frames until the debugger breaks or the client interrupts
+    fn run_until_stop(&mut self) -> io::Result<String> {
+        loop {
+            self.emulator.run_frame();
+            let debugger = self.emulator.debugger_mut().unwrap();
+            if let Some(event) = debugger.poll_event() {
+                // only the first reason is reported
+                while debugger.poll_event().is_some() {}
+                return Ok(self.stop_reply(&event));
+            }
+            if self.interrupted()? {
+                self.emulator.debugger_mut().unwrap().pause();
+                return Ok(format!("S{SIGINT:02x}"));
+            }
+        }
+    }
+
+    fn stop_reply(&self, event: &Event) -> String {
+        match event {
+            Event::Watchpoint { id, space: AddressSpace::Cpu, addr, .. } => {
+                let kind = self.points.iter()
+                    .find(|(_, point)| *point == id)
+                    .map(|((kind, _), _)| *kind);
+                let name = match kind {
+                    Some(3) => "rwatch",
+                    Some(4) => "awatch",
+                    _ => "watch",
+                };
+                format!("T{SIGTRAP:02x}{name}:{addr:04x};")
+            }
+            _ => format!("S{SIGTRAP:02x}"),
+        }
+    }
+
+    fn interrupted(&mut self) -> io::Result<bool> {
+        let mut buffer = [0; 64];
+        self.stream.set_nonblocking(true)?;
+        let read = self.stream.read(&mut buffer);
+        self.stream.set_nonblocking(false)?;
+        match read {
+            Ok(0) => Err(ErrorKind::UnexpectedEof.into()),
+            Ok(count) => {
+                self.input.extend_from_slice(&buffer[..count]);
+                Ok(self.input.contains(&INTERRUPT))
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn registers(&self) -> [u16; REGISTERS] {
+        let cpu = &self.emulator.cpu;
+        [cpu.acc as u16, cpu.x as u16, cpu.y as u16, cpu.ps.get_reg() as u16, cpu.sp as u16, cpu.pc]
+    }
+
+    fn set_register(&mut self, register: usize, value: u16) -> bool {
+        let cpu = &mut self.emulator.cpu;
+        match register {
+            0 => cpu.acc = value as u8,
+            1 => cpu.x = value as u8,
+            2 => cpu.y = value as u8,
+            3 => cpu.ps.set_reg(value as u8),
+            4 => cpu.sp = value as u8,
+            5 => cpu.pc = value,
+            _ => return false,
+        }
+        true
+    }
+
+    fn read_registers(&self) -> String {
+        self.registers().iter().enumerate().map(|(register, value)| register_hex(register, *value)).collect()
+    }
+
+    fn write_registers(&mut self, data: &[u8]) -> String {
+        let Some(bytes) = decode_hex(data).filter(|bytes| bytes.len() == REGISTERS + 1) else {
+            return error(1);
+        };
+        for (register, value) in bytes.iter().take(REGISTERS - 1).enumerate() {
+            self.set_register(register, *value as u16);
+        }
+        self.set_register(5, u16::from_le_bytes([bytes[5], bytes[6]]));
+        "OK".to_string()
+    }
+
+    fn read_register(&self, data: &[u8]) -> String {
+        match parse_hex(data).filter(|register| *register < REGISTERS) {
+            Some(register) => register_hex(register, self.registers()[register]),
+            None => error(1),
+        }
+    }
+
+    fn write_register(&mut self, data: &[u8]) -> String {
+        let Some((register, value)) = split(data, b'=') else {
+            return error(1);
+        };
+        let value = decode_hex(value).map(|bytes| bytes.iter().rev().fold(0u16, |value, byte| value << 8 | *byte as u16));
+        match (parse_hex(register), value) {
+            (Some(register), Some(value)) if self.set_register(register, value) => "OK".to_string(),
+            _ => error(1),
+        }
+    }
+
+    fn read_memory(&self, data: &[u8]) -> String {
+        let Some((addr, len)) = addr_len(data) else {
+            return error(1);
+        };
+        let memory = self.emulator.cpu.bus.memory_chunk(addr, len.min(PACKET_SIZE / 2));
+        memory.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    fn write_memory(&mut self, data: &[u8]) -> String {
+        let Some((range, bytes)) = split(data, b':') else {
+            return error(1);
+        };
+        let (Some((addr, len)), Some(bytes)) = (addr_len(range), decode_hex(bytes)) else {
+            return error(1);
+        };
+        if bytes.len() != len {
+            return error(1);
+        }
+        let bus = &mut self.emulator.cpu.bus;
+        for (i, byte) in bytes.into_iter().enumerate() {
+            bus.write(addr.wrapping_add(i as u16), byte);
+        }
+        bus.discard_watch_hits();
+        "OK".to_string()
+    }
+
+    /// `type,addr,kind`, kind is the length for watchpoints
+    fn insert_point(&mut self, data: &[u8]) -> String {
+        let Some((kind, addr, len)) = point(data) else {
+            return error(1);
+        };
+        let Ok(len) = u16::try_from(len) else {
+            return error(1);
+        };
+        let end = addr.saturating_add(len.max(1) - 1);
+        let debugger = self.emulator.debugger_mut().unwrap();
+        let id = match kind {
+            0 | 1 => debugger.add_breakpoint(addr),
+            2 => debugger.add_watchpoint(AddressSpace::Cpu, addr..=end, AccessKind::WRITE),
+            3 => debugger.add_watchpoint(AddressSpace::Cpu, addr..=end, AccessKind::READ),
+            4 => debugger.add_watchpoint(AddressSpace::Cpu, addr..=end, AccessKind::READ | AccessKind::WRITE),
+            _ => return String::new(),
+        };
+        if let Some(old) = self.points.insert((kind, addr), id) {
+            debugger.remove(old);
+        }
+        "OK".to_string()
+    }
+
+    fn remove_point(&mut self, data: &[u8]) -> String {
+        let Some((kind, addr, _)) = point(data) else {
+            return error(1);
+        };
+        if let Some(id) = self.points.remove(&(kind, addr)) {
+            self.emulator.debugger_mut().unwrap().remove(id);
+        }
+        "OK".to_string()
+    }
+
+    /// the next `$data#checksum` packet, acknowledged unless in no ack mode
+    fn read_packet(&mut self) -> io::Result<Vec<u8>> {
+        loop {
+            // acks, interrupts while stopped and line noise before a packet
+            let start = self.input.iter().position(|byte| *byte == b'$');
+            let Some(start) = start else {
+                self.input.clear();
+                self.fill()?;
+                continue;
+            };
+            self.input.drain(..start);
+
+            let Some(end) = self.input.iter().position(|byte| *byte == b'#') else {
+                self.fill()?;
+                continue;
+            };
+            if self.input.len() < end + 3 {
+                self.fill()?;
+                continue;
+            }
+
+            let packet: Vec<u8> = self.input[1..end].to_vec();
+            let checksum = std::str::from_utf8(&self.input[end + 1..end + 3]).ok()
+                .and_then(|checksum| u8::from_str_radix(checksum, 16).ok());
+            self.input.drain(..end + 3);
+
+            if self.no_ack {
+                return Ok(packet);
+            }
+            if checksum == Some(checksum_of(&packet)) {
+                self.stream.write_all(b"+")?;
+                return Ok(packet);
+            }
+            self.stream.write_all(b"-")?;
+        }
+    }
+
+    fn fill(&mut self) -> io::Result<()> {
+        let mut buffer = [0; 1024];
+        let count = self.stream.read(&mut buffer)?;
+        if count == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        self.input.extend_from_slice(&buffer[..count]);
+        Ok(())
+    }
+
+    /// acks of replies aren't waited for, a resend request is answered like any other packet
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${data}#{:02x}", checksum_of(data.as_bytes()));
+        self.stream.write_all(packet.as_bytes())
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn error(code: u8) -> String {
+    format!("E{code:02x}")
+}
+
+/// pc is 2 bytes, the others 1
+fn register_hex(register: usize, value: u16) -> String {
+    match register {
+        5 => value.to_le_bytes().iter().map(|byte| format!("{byte:02x}")).collect(),
+        _ => format!("{:02x}", value as u8),
+    }
+}
+
+fn split(data: &[u8], separator: u8) -> Option<(&[u8], &[u8])> {
+    let position = data.iter().position(|byte| *byte == separator)?;
+    Some((&data[..position], &data[position + 1..]))
+}
+
+fn parse_hex(data: &[u8]) -> Option<usize> {
+    usize::from_str_radix(std::str::from_utf8(data).ok()?, 16).ok()
+}
+
+fn decode_hex(data: &[u8]) -> Option<Vec<u8>> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    data.chunks(2)
+        .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect()
+}
+
+/// `addr,len`
+fn addr_len(data: &[u8]) -> Option<(u16, usize)> {
+    let (addr, len) = split(data, b',')?;
+    Some((u16::try_from(parse_hex(addr)?).ok()?, parse_hex(len)?))
+}
+
+/// `type,addr,kind`
+fn point(data: &[u8]) -> Option<(u8, u16, usize)> {
+    let (kind, rest) = split(data, b',')?;
+    let (addr, len) = addr_len(rest)?;
+    Some((u8::try_from(parse_hex(kind)?).ok()?, addr, len))
+}
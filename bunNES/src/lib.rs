@@ -1,3 +1,10 @@
-
+pub mod debugger;
 pub mod emulator;
-pub mod nes;
\ No newline at end of file
+pub mod gdb;
+pub mod movie;
+pub mod nes;
+pub mod png;
+pub mod ram_search;
+pub mod rewind;
+pub mod state;
+pub mod wav;
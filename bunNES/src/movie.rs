@@ -0,0 +1,402 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fs, io};
+use bitflags::bitflags;
+use crate::nes::input::controller::Buttons;
+use crate::nes::rom::{Cartridge, Region};
+use crate::state::StateError;
+
+// https://fceux.com/web/help/fm2.html
+// text header of `key value` lines followed by one `|commands|port0|port1|port2|` line per frame.
+// only gamepads and the four score are supported, movies starting from a save state carry
+// our own save state format and can't be played back by fceux
+
+const FM2_VERSION: u32 = 3;
+/// gamepad buttons as they appear in an input line, pressed ones by letter
+const BUTTON_CHARS: [char; 8] = ['R', 'L', 'D', 'U', 'T', 'S', 'B', 'A'];
+/// binary header values, the rom checksum and the save state
+const BASE64_PREFIX: &str = "base64:";
+
+bitflags! {
+    /// the commands field of an input line
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+    pub struct Commands: u8 {
+        const SOFT_RESET = 0b0000_0001;
+        const POWER      = 0b0000_0010;
+        const FDS_INSERT = 0b0000_0100;
+        const FDS_SELECT = 0b0000_1000;
+        const VS_COIN    = 0b0001_0000;
+    }
+}
+
+/// what the emulator does before the first frame
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MovieStart {
+    PowerOn,
+    SaveState,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MovieMode {
+    Recording,
+    Playing,
+    /// played back to the last frame, the emulator is back under user control
+    Finished,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct MovieFrame {
+    pub commands: Commands,
+    /// players 1-4, 3 and 4 only with a four score
+    pub buttons: [Buttons; 4],
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Movie {
+    pub rom_filename: String,
+    /// md5 of prg and chr rom
+    pub rom_checksum: [u8; 16],
+    pub guid: String,
+    pub pal: bool,
+    pub four_score: bool,
+    /// gamepads in the controller ports, ignored with a four score
+    pub gamepads: [bool; 2],
+    pub rerecord_count: u32,
+    pub comments: Vec<String>,
+    /// where the movie starts, power on if none
+    pub save_state: Option<Vec<u8>>,
+    pub frames: Vec<MovieFrame>,
+}
+
+#[derive(Debug)]
+pub enum MovieError {
+    Io(io::Error),
+    /// malformed line, 1 based
+    Parse { line: usize, message: String },
+    /// valid fm2 using something that isn't emulated
+    Unsupported(String),
+    /// recorded with a different rom
+    WrongRom,
+    /// the save state the movie starts from
+    State(StateError),
+}
+
+impl Display for MovieError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MovieError::Io(e) => write!(f, "{e}"),
+            MovieError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            MovieError::Unsupported(what) => write!(f, "unsupported movie: {what}"),
+            MovieError::WrongRom => write!(f, "movie was recorded with a different rom"),
+            MovieError::State(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl Error for MovieError {}
+
+impl From<io::Error> for MovieError {
+    fn from(e: io::Error) -> Self {
+        MovieError::Io(e)
+    }
+}
+
+impl From<StateError> for MovieError {
+    fn from(e: StateError) -> Self {
+        MovieError::State(e)
+    }
+}
+
+impl Movie {
+    /// empty movie for `cartridge` with standard controllers in both ports
+    pub fn new(cartridge: &Cartridge, rom_filename: &str) -> Movie {
+        Movie {
+            rom_filename: rom_filename.to_string(),
+            rom_checksum: rom_checksum(cartridge),
+            guid: guid(),
+            pal: cartridge.region() == Region::Pal,
+            four_score: false,
+            gamepads: [true, true],
+            rerecord_count: 0,
+            comments: Vec::new(),
+            save_state: None,
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Movie, MovieError> {
+        Movie::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_fm2())
+    }
+
+    pub fn parse(text: &str) -> Result<Movie, MovieError> {
+        let mut movie = Movie {
+            rom_filename: String::new(),
+            rom_checksum: [0; 16],
+            guid: String::new(),
+            pal: false,
+            four_score: false,
+            gamepads: [false, false],
+            rerecord_count: 0,
+            comments: Vec::new(),
+            save_state: None,
+            frames: Vec::new(),
+        };
+        let mut version = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let error = |message: &str| MovieError::Parse { line: line_number, message: message.to_string() };
+            let line = line.trim_end_matches('\r');
+
+            if line.starts_with('|') {
+                movie.frames.push(movie.parse_frame(line).map_err(|message| error(&message))?);
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !movie.frames.is_empty() {
+                return Err(error("header line after the input log"));
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let flag = || match value {
+                "0" => Ok(false),
+                "1" => Ok(true),
+                _ => Err(error(&format!("expected 0 or 1 for {key}"))),
+            };
+            match key {
+                "version" => version = Some(value.parse::<u32>().map_err(|_| error("invalid version"))?),
+                "romFilename" => movie.rom_filename = value.to_string(),
+                "romChecksum" => {
+                    movie.rom_checksum = base64_value(value)
+                        .and_then(|bytes| bytes.try_into().ok())
+                        .ok_or_else(|| error("invalid rom checksum"))?;
+                }
+                "guid" => movie.guid = value.to_string(),
+                "palFlag" => movie.pal = flag()?,
+                "fourscore" => movie.four_score = flag()?,
+                "port0" | "port1" => {
+                    let port = if key == "port0" { 0 } else { 1 };
+                    movie.gamepads[port] = match value {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(MovieError::Unsupported(format!("{key} device {value}"))),
+                    };
+                }
+                "port2" if value != "0" => {
+                    return Err(MovieError::Unsupported("famicom expansion port device".to_string()));
+                }
+                "rerecordCount" => movie.rerecord_count = value.parse().map_err(|_| error("invalid rerecord count"))?,
+                "comment" => movie.comments.push(value.to_string()),
+                "savestate" => movie.save_state = Some(base64_value(value).ok_or_else(|| error("invalid save state"))?),
+                "binary" if flag()? => return Err(MovieError::Unsupported("binary input log".to_string())),
+                "FDS" if flag()? => return Err(MovieError::Unsupported("famicom disk system".to_string())),
+                // emuVersion, NewPPU, microphone, length, ... don't affect playback
+                _ => {}
+            }
+        }
+
+        match version {
+            Some(FM2_VERSION) => Ok(movie),
+            Some(version) => Err(MovieError::Unsupported(format!("version {version}"))),
+            None => Err(MovieError::Parse { line: 1, message: "missing version".to_string() }),
+        }
+    }
+
+    fn parse_frame(&self, line: &str) -> Result<MovieFrame, String> {
+        let mut fields = line.split('|').skip(1);
+        let commands = fields.next().unwrap_or_default();
+        let commands = commands.parse::<u8>().map_err(|_| format!("invalid commands {commands:?}"))?;
+
+        let mut frame = MovieFrame { commands: Commands::from_bits_retain(commands), ..MovieFrame::default() };
+        let players = if self.four_score { 4 } else { 2 };
+        for player in 0..players {
+            let field = fields.next().ok_or("missing port")?;
+            if !self.four_score && !self.gamepads[player] {
+                continue;
+            }
+            frame.buttons[player] = parse_buttons(field)?;
+        }
+        Ok(frame)
+    }
+
+    pub fn to_fm2(&self) -> String {
+        let mut text = String::new();
+        let mut line = |key: &str, value: &str| {
+            text.push_str(key);
+            text.push(' ');
+            text.push_str(value);
+            text.push('\n');
+        };
+        line("version", &FM2_VERSION.to_string());
+        line("emuVersion", "0");
+        line("rerecordCount", &self.rerecord_count.to_string());
+        line("palFlag", flag(self.pal));
+        line("romFilename", &self.rom_filename);
+        line("romChecksum", &format!("{BASE64_PREFIX}{}", base64_encode(&self.rom_checksum)));
+        line("guid", &self.guid);
+        line("fourscore", flag(self.four_score));
+        line("microphone", "0");
+        line("port0", flag(self.gamepads[0]));
+        line("port1", flag(self.gamepads[1]));
+        line("port2", "0");
+        line("FDS", "0");
+        line("NewPPU", "0");
+        for comment in &self.comments {
+            line("comment", comment);
+        }
+        if let Some(state) = &self.save_state {
+            line("savestate", &format!("{BASE64_PREFIX}{}", base64_encode(state)));
+        }
+
+        for frame in &self.frames {
+            text.push('|');
+            text.push_str(&frame.commands.bits().to_string());
+            text.push('|');
+            let players = if self.four_score { 4 } else { 2 };
+            for player in 0..players {
+                if self.four_score || self.gamepads[player] {
+                    text.push_str(&format_buttons(frame.buttons[player]));
+                }
+                text.push('|');
+            }
+            if !self.four_score {
+                // port2
+                text.push('|');
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+fn flag(value: bool) -> &'static str {
+    if value { "1" } else { "0" }
+}
+
+fn parse_buttons(field: &str) -> Result<Buttons, String> {
+    if field.chars().count() != BUTTON_CHARS.len() {
+        return Err(format!("invalid gamepad {field:?}"));
+    }
+    let mut buttons = Buttons::empty();
+    for (i, c) in field.chars().enumerate() {
+        if c != '.' && c != ' ' {
+            buttons |= Buttons::from_bits_retain(0x80 >> i);
+        }
+    }
+    Ok(buttons)
+}
+
+fn format_buttons(buttons: Buttons) -> String {
+    BUTTON_CHARS.iter().enumerate()
+        .map(|(i, c)| if buttons.bits() & (0x80 >> i) != 0 { *c } else { '.' })
+        .collect()
+}
+
+pub fn rom_checksum(cartridge: &Cartridge) -> [u8; 16] {
+    let mut rom = cartridge.prg().clone();
+    rom.extend_from_slice(cartridge.chr());
+    md5(&rom)
+}
+
+/// fceux style guid, only has to tell movies apart
+fn guid() -> String {
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or_default();
+    let h = md5(&nanos.to_le_bytes());
+    let hex: String = h.iter().map(|byte| format!("{byte:02X}")).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+// https://www.ietf.org/rfc/rfc1321.txt
+fn md5(data: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+        5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let constants: [u32; 64] = std::array::from_fn(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32);
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476];
+    for chunk in message.chunks(64) {
+        let words: [u32; 16] = std::array::from_fn(|i| u32::from_le_bytes(chunk[i * 4..i * 4 + 4].try_into().unwrap()));
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let f = f.wrapping_add(a).wrapping_add(constants[i]).wrapping_add(words[g]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(f.rotate_left(SHIFTS[i]));
+        }
+        state[0] = state[0].wrapping_add(a);
+        state[1] = state[1].wrapping_add(b);
+        state[2] = state[2].wrapping_add(c);
+        state[3] = state[3].wrapping_add(d);
+    }
+
+    let mut digest = [0; 16];
+    for (i, word) in state.iter().enumerate() {
+        digest[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(bits >> (18 - i * 6) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// a `base64:` prefixed header value
+fn base64_value(text: &str) -> Option<Vec<u8>> {
+    text.strip_prefix(BASE64_PREFIX).and_then(base64_decode)
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim().trim_end_matches('=');
+    let mut data = Vec::new();
+    let mut bits = 0u32;
+    let mut count = 0;
+    for c in text.bytes() {
+        let value = BASE64.iter().position(|b| *b == c)? as u32;
+        bits = bits << 6 | value;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            data.push((bits >> count) as u8);
+        }
+    }
+    Some(data)
+}
@@ -0,0 +1,59 @@
+use std::collections::VecDeque;
+
+/// ring buffer between the apu and whoever consumes the audio,
+/// the oldest samples get dropped when it isn't drained fast enough
+#[derive(Debug)]
+pub struct AudioBuffer {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl AudioBuffer {
+    pub fn new(capacity: usize) -> AudioBuffer {
+        AudioBuffer {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, sample: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// moves up to `out.len()` samples into `out` and returns how many were written
+    pub fn pop_f32(&mut self, out: &mut [f32]) -> usize {
+        let count = out.len().min(self.samples.len());
+        for (value, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *value = sample;
+        }
+        count
+    }
+
+    /// same as [`AudioBuffer::pop_f32`] as signed 16 bit pcm
+    pub fn pop_i16(&mut self, out: &mut [i16]) -> usize {
+        let count = out.len().min(self.samples.len());
+        for (value, sample) in out.iter_mut().zip(self.samples.drain(..count)) {
+            *value = to_i16(sample);
+        }
+        count
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+pub fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
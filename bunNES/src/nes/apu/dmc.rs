@@ -0,0 +1,221 @@
+use crate::nes::rom::Region;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_DMC
+
+/// timer periods in cpu cycles
+const RATE_TABLE_NTSC: [u16; 16] = [
+    428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54,
+];
+const RATE_TABLE_PAL: [u16; 16] = [
+    398, 354, 316, 298, 276, 236, 210, 198, 176, 148, 132, 118, 98, 78, 66, 50,
+];
+
+#[derive(Debug)]
+pub struct Dmc {
+    rate_table: &'static [u16; 16],
+
+    irq_enabled: bool,
+    irq: bool,
+    looping: bool,
+
+    timer: u16,
+    timer_period: u16,
+
+    // memory reader
+    sample_address: u16,
+    sample_length: u16,
+    current_address: u16,
+    bytes_remaining: u16,
+    sample_buffer: Option<u8>,
+
+    // output unit
+    shift_register: u8,
+    bits_remaining: u8,
+    silence: bool,
+    output_level: u8,
+}
+
+impl Dmc {
+    pub fn new(region: Region) -> Dmc {
+        let rate_table = match region {
+            Region::Ntsc | Region::Dendy => &RATE_TABLE_NTSC,
+            Region::Pal => &RATE_TABLE_PAL,
+        };
+
+        Dmc {
+            rate_table,
+
+            irq_enabled: false,
+            irq: false,
+            looping: false,
+
+            timer: 0,
+            timer_period: rate_table[0],
+
+            sample_address: 0xC000,
+            sample_length: 1,
+            current_address: 0xC000,
+            bytes_remaining: 0,
+            sample_buffer: None,
+
+            shift_register: 0,
+            bits_remaining: 8,
+            silence: true,
+            output_level: 0,
+        }
+    }
+
+    /// register 0..=3 of the channel ($4010-$4013)
+    pub fn set_register(&mut self, register: u16, value: u8) {
+        match register {
+            // IL-- RRRR
+            0 => {
+                self.irq_enabled = value & 0b1000_0000 != 0;
+                if !self.irq_enabled {
+                    self.irq = false;
+                }
+                self.looping = value & 0b0100_0000 != 0;
+                self.timer_period = self.rate_table[(value & 0b0000_1111) as usize];
+            }
+            // -DDD DDDD
+            1 => self.output_level = value & 0b0111_1111,
+            // AAAA AAAA: %11AAAAAA.AA000000
+            2 => self.sample_address = 0xC000 | (value as u16) << 6,
+            // LLLL LLLL: %LLLL.LLLL0001
+            3 => self.sample_length = (value as u16) << 4 | 1,
+            _ => panic!("unknown dmc register: {register}")
+        }
+    }
+
+    /// bit 4 of $4015, writing $4015 also acknowledges the dmc interrupt
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.irq = false;
+        if !enabled {
+            self.bytes_remaining = 0;
+        } else if self.bytes_remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.current_address = self.sample_address;
+        self.bytes_remaining = self.sample_length;
+    }
+
+    /// address the memory reader wants to fetch from, the bus performs the read
+    /// and stalls the cpu while doing so
+    pub fn dma_request(&self) -> Option<u16> {
+        if self.sample_buffer.is_none() && self.bytes_remaining > 0 {
+            Some(self.current_address)
+        } else {
+            None
+        }
+    }
+
+    pub fn fill_sample_buffer(&mut self, value: u8) {
+        self.sample_buffer = Some(value);
+        self.current_address = if self.current_address == 0xFFFF {
+            0x8000
+        } else {
+            self.current_address + 1
+        };
+
+        self.bytes_remaining -= 1;
+        if self.bytes_remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+    }
+
+    /// clocked every cpu cycle, the rate table is in cpu cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.timer_period - 1;
+
+        if !self.silence {
+            if self.shift_register & 1 == 1 {
+                if self.output_level <= 125 {
+                    self.output_level += 2;
+                }
+            } else if self.output_level >= 2 {
+                self.output_level -= 2;
+            }
+        }
+        self.shift_register >>= 1;
+
+        self.bits_remaining -= 1;
+        if self.bits_remaining == 0 {
+            self.bits_remaining = 8;
+            match self.sample_buffer.take() {
+                Some(sample) => {
+                    self.silence = false;
+                    self.shift_register = sample;
+                }
+                None => self.silence = true,
+            }
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        self.output_level
+    }
+
+    pub fn active(&self) -> bool {
+        self.bytes_remaining > 0
+    }
+
+    pub fn irq(&self) -> bool {
+        self.irq
+    }
+
+    pub fn bytes_remaining(&self) -> u16 {
+        self.bytes_remaining
+    }
+}
+
+impl SaveState for Dmc {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.irq_enabled);
+        writer.bool(self.irq);
+        writer.bool(self.looping);
+        writer.u16(self.timer);
+        writer.u16(self.timer_period);
+        writer.u16(self.sample_address);
+        writer.u16(self.sample_length);
+        writer.u16(self.current_address);
+        writer.u16(self.bytes_remaining);
+        writer.bool(self.sample_buffer.is_some());
+        writer.u8(self.sample_buffer.unwrap_or(0));
+        writer.u8(self.shift_register);
+        writer.u8(self.bits_remaining);
+        writer.bool(self.silence);
+        writer.u8(self.output_level);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.irq_enabled = reader.bool()?;
+        self.irq = reader.bool()?;
+        self.looping = reader.bool()?;
+        self.timer = reader.u16()?;
+        self.timer_period = reader.u16()?;
+        self.sample_address = reader.u16()?;
+        self.sample_length = reader.u16()?;
+        self.current_address = reader.u16()?;
+        self.bytes_remaining = reader.u16()?;
+        let buffered = reader.bool()?;
+        let sample = reader.u8()?;
+        self.sample_buffer = if buffered { Some(sample) } else { None };
+        self.shift_register = reader.u8()?;
+        self.bits_remaining = reader.u8()?;
+        self.silence = reader.bool()?;
+        self.output_level = reader.u8()?;
+        Ok(())
+    }
+}
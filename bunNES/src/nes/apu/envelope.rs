@@ -0,0 +1,77 @@
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Envelope
+
+#[derive(Debug, Default)]
+pub struct Envelope {
+    start: bool,
+    looping: bool,
+    constant_volume: bool,
+    volume: u8,
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    pub fn new() -> Envelope {
+        Envelope::default()
+    }
+
+    /// --LC VVVV part of the channel control register
+    pub fn set_register(&mut self, value: u8) {
+        self.looping = value & 0b0010_0000 != 0;
+        self.constant_volume = value & 0b0001_0000 != 0;
+        self.volume = value & 0b0000_1111;
+    }
+
+    pub fn restart(&mut self) {
+        self.start = true;
+    }
+
+    /// clocked by the frame counter every quarter frame
+    pub fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.volume;
+        } else if self.divider == 0 {
+            self.divider = self.volume;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub fn output(&self) -> u8 {
+        if self.constant_volume {
+            self.volume
+        } else {
+            self.decay
+        }
+    }
+}
+
+impl SaveState for Envelope {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.start);
+        writer.bool(self.looping);
+        writer.bool(self.constant_volume);
+        writer.u8(self.volume);
+        writer.u8(self.divider);
+        writer.u8(self.decay);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.start = reader.bool()?;
+        self.looping = reader.bool()?;
+        self.constant_volume = reader.bool()?;
+        self.volume = reader.u8()?;
+        self.divider = reader.u8()?;
+        self.decay = reader.u8()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use std::f32::consts::PI;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Mixer#Emulation
+// first order filters of the console's output stage
+
+#[derive(Debug)]
+pub struct HighPass {
+    alpha: f32,
+    prev_input: f32,
+    prev_output: f32,
+}
+
+impl HighPass {
+    pub fn new(cutoff: f32, sample_rate: u32) -> HighPass {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate as f32;
+        HighPass {
+            alpha: rc / (rc + dt),
+            prev_input: 0.0,
+            prev_output: 0.0,
+        }
+    }
+
+    pub fn filter(&mut self, input: f32) -> f32 {
+        let output = self.alpha * (self.prev_output + input - self.prev_input);
+        self.prev_input = input;
+        self.prev_output = output;
+        output
+    }
+}
+
+#[derive(Debug)]
+pub struct LowPass {
+    alpha: f32,
+    prev_output: f32,
+}
+
+impl LowPass {
+    pub fn new(cutoff: f32, sample_rate: u32) -> LowPass {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate as f32;
+        LowPass {
+            alpha: dt / (rc + dt),
+            prev_output: 0.0,
+        }
+    }
+
+    pub fn filter(&mut self, input: f32) -> f32 {
+        let output = self.prev_output + self.alpha * (input - self.prev_output);
+        self.prev_output = output;
+        output
+    }
+}
+
+impl SaveState for HighPass {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.f32(self.prev_input);
+        writer.f32(self.prev_output);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.prev_input = reader.f32()?;
+        self.prev_output = reader.f32()?;
+        Ok(())
+    }
+}
+
+impl SaveState for LowPass {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.f32(self.prev_output);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.prev_output = reader.f32()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,179 @@
+use crate::nes::rom::Region;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Frame_Counter
+
+/// cpu cycles after a reset at which the sequencer steps
+#[derive(Debug)]
+struct Timing {
+    steps: [u64; 3],
+    /// the frame irq flag is set on three consecutive cycles around the last step
+    four_step_end: u64,
+    five_step_end: u64,
+}
+
+const TIMING_NTSC: Timing = Timing {
+    steps: [7457, 14913, 22371],
+    four_step_end: 29829,
+    five_step_end: 37281,
+};
+
+const TIMING_PAL: Timing = Timing {
+    steps: [8313, 16627, 24939],
+    four_step_end: 33253,
+    five_step_end: 41565,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FrameMode {
+    FourStep,
+    FiveStep,
+}
+
+/// units the frame counter clocks on a cpu cycle
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FrameStep {
+    None,
+    /// envelopes and triangle linear counter
+    Quarter,
+    /// quarter frame units plus length counters and sweep units
+    Half,
+}
+
+#[derive(Debug)]
+pub struct FrameCounter {
+    timing: &'static Timing,
+
+    mode: FrameMode,
+    irq_inhibit: bool,
+    irq: bool,
+    cycle: u64,
+
+    /// value written to $4017 and cpu cycles until it takes effect
+    pending_write: Option<(u8, u8)>,
+}
+
+impl FrameCounter {
+    pub fn new(region: Region) -> FrameCounter {
+        let timing = match region {
+            Region::Ntsc | Region::Dendy => &TIMING_NTSC,
+            Region::Pal => &TIMING_PAL,
+        };
+
+        FrameCounter {
+            timing,
+
+            mode: FrameMode::FourStep,
+            irq_inhibit: false,
+            irq: false,
+            cycle: 0,
+
+            pending_write: None,
+        }
+    }
+
+    /// MI-- ----
+    ///
+    /// the irq inhibit flag applies immediately, the mode change and sequencer reset
+    /// happen 3 cpu cycles later if the write happened during an apu cycle, 4 otherwise
+    pub fn write(&mut self, value: u8, apu_cycle: bool) {
+        self.irq_inhibit = value & 0b0100_0000 != 0;
+        if self.irq_inhibit {
+            self.irq = false;
+        }
+
+        let delay = if apu_cycle { 3 } else { 4 };
+        self.pending_write = Some((value, delay));
+    }
+
+    /// advances the sequencer by one cpu cycle
+    pub fn clock(&mut self) -> FrameStep {
+        if let Some((value, delay)) = self.pending_write {
+            if delay > 1 {
+                self.pending_write = Some((value, delay - 1));
+            } else {
+                self.pending_write = None;
+                self.cycle = 0;
+                self.mode = if value & 0b1000_0000 == 0 { FrameMode::FourStep } else { FrameMode::FiveStep };
+                // five step mode clocks all units immediately
+                return match self.mode {
+                    FrameMode::FourStep => FrameStep::None,
+                    FrameMode::FiveStep => FrameStep::Half,
+                };
+            }
+        }
+
+        self.cycle += 1;
+
+        let steps = self.timing.steps;
+        match self.mode {
+            FrameMode::FourStep => {
+                let end = self.timing.four_step_end;
+                if self.cycle >= end - 1 && self.cycle <= end + 1 && !self.irq_inhibit {
+                    self.irq = true;
+                }
+
+                if self.cycle == steps[0] || self.cycle == steps[2] {
+                    FrameStep::Quarter
+                } else if self.cycle == steps[1] || self.cycle == end {
+                    FrameStep::Half
+                } else {
+                    if self.cycle == end + 1 {
+                        self.cycle = 0;
+                    }
+                    FrameStep::None
+                }
+            }
+            FrameMode::FiveStep => {
+                let end = self.timing.five_step_end;
+                if self.cycle == steps[0] || self.cycle == steps[2] {
+                    FrameStep::Quarter
+                } else if self.cycle == steps[1] || self.cycle == end {
+                    FrameStep::Half
+                } else {
+                    if self.cycle == end + 1 {
+                        self.cycle = 0;
+                    }
+                    FrameStep::None
+                }
+            }
+        }
+    }
+
+    pub fn irq(&self) -> bool {
+        self.irq
+    }
+
+    /// reading $4015 acknowledges the frame interrupt
+    pub fn clear_irq(&mut self) {
+        self.irq = false;
+    }
+
+    pub fn mode(&self) -> FrameMode {
+        self.mode
+    }
+}
+
+impl SaveState for FrameCounter {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.mode == FrameMode::FiveStep);
+        writer.bool(self.irq_inhibit);
+        writer.bool(self.irq);
+        writer.u64(self.cycle);
+        let (value, delay) = self.pending_write.unwrap_or((0, 0));
+        writer.u8(value);
+        writer.u8(delay);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.mode = if reader.bool()? { FrameMode::FiveStep } else { FrameMode::FourStep };
+        self.irq_inhibit = reader.bool()?;
+        self.irq = reader.bool()?;
+        self.cycle = reader.u64()?;
+        let value = reader.u8()?;
+        let delay = reader.u8()?;
+        // a pending write always has a delay of at least 1
+        self.pending_write = if delay > 0 { Some((value, delay)) } else { None };
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Length_Counter
+
+const LENGTH_TABLE: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14,
+    12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16, 28, 32, 30,
+];
+
+#[derive(Debug, Default)]
+pub struct LengthCounter {
+    enabled: bool,
+    halt: bool,
+    counter: u8,
+}
+
+impl LengthCounter {
+    pub fn new() -> LengthCounter {
+        LengthCounter::default()
+    }
+
+    /// controlled by the channel bits of $4015
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    pub fn set_halt(&mut self, halt: bool) {
+        self.halt = halt;
+    }
+
+    /// loads the counter from the upper 5 bits of the length register
+    pub fn load(&mut self, value: u8) {
+        if self.enabled {
+            self.counter = LENGTH_TABLE[(value >> 3) as usize];
+        }
+    }
+
+    /// clocked by the frame counter every half frame
+    pub fn clock(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub fn active(&self) -> bool {
+        self.counter > 0
+    }
+
+    pub fn counter(&self) -> u8 {
+        self.counter
+    }
+}
+
+impl SaveState for LengthCounter {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.enabled);
+        writer.bool(self.halt);
+        writer.u8(self.counter);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = reader.bool()?;
+        self.halt = reader.bool()?;
+        self.counter = reader.u8()?;
+        Ok(())
+    }
+}
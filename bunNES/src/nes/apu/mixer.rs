@@ -0,0 +1,40 @@
+// https://www.nesdev.org/wiki/APU_Mixer
+
+/// nonlinear dac output of the 2a03 approximated by lookup tables
+#[derive(Debug)]
+pub struct Mixer {
+    pulse_table: [f32; 31],
+    tnd_table: [f32; 203],
+}
+
+impl Default for Mixer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mixer {
+    pub fn new() -> Mixer {
+        let mut pulse_table = [0f32; 31];
+        for (n, value) in pulse_table.iter_mut().enumerate().skip(1) {
+            *value = 95.52 / (8128.0 / n as f32 + 100.0);
+        }
+
+        let mut tnd_table = [0f32; 203];
+        for (n, value) in tnd_table.iter_mut().enumerate().skip(1) {
+            *value = 163.67 / (24329.0 / n as f32 + 100.0);
+        }
+
+        Mixer {
+            pulse_table,
+            tnd_table,
+        }
+    }
+
+    /// mixes the channel outputs into a value between 0.0 and 1.0
+    pub fn mix(&self, pulse_1: u8, pulse_2: u8, triangle: u8, noise: u8, dmc: u8) -> f32 {
+        let pulse = self.pulse_table[(pulse_1 + pulse_2) as usize];
+        let tnd = self.tnd_table[3 * triangle as usize + 2 * noise as usize + dmc as usize];
+        pulse + tnd
+    }
+}
@@ -0,0 +1,321 @@
+mod envelope;
+mod length_counter;
+mod filter;
+mod resampler;
+pub mod mixer;
+pub mod buffer;
+pub mod pulse;
+pub mod triangle;
+pub mod noise;
+pub mod dmc;
+pub mod frame_counter;
+
+use crate::nes::apu::buffer::AudioBuffer;
+use crate::nes::apu::dmc::Dmc;
+use crate::nes::apu::frame_counter::{FrameCounter, FrameStep};
+use crate::nes::apu::mixer::Mixer;
+use crate::nes::apu::noise::Noise;
+use crate::nes::apu::pulse::{Pulse, PulseChannel};
+use crate::nes::apu::resampler::Resampler;
+use crate::nes::apu::triangle::Triangle;
+use crate::nes::rom::Region;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU
+
+const CPU_CLOCK_NTSC: u32 = 1_789_773;
+const CPU_CLOCK_PAL: u32 = 1_662_607;
+const CPU_CLOCK_DENDY: u32 = 1_773_448;
+pub const DEFAULT_SAMPLE_RATE: u32 = 44100;
+/// audio kept around for the consumer before the oldest samples are dropped
+const BUFFER_LENGTH_MS: usize = 500;
+
+fn buffer_capacity(sample_rate: u32) -> usize {
+    sample_rate as usize * BUFFER_LENGTH_MS / 1000
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Channel {
+    Pulse1,
+    Pulse2,
+    Triangle,
+    Noise,
+    Dmc,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 5] = [Channel::Pulse1, Channel::Pulse2, Channel::Triangle, Channel::Noise, Channel::Dmc];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Channel::Pulse1 => "pulse_1",
+            Channel::Pulse2 => "pulse_2",
+            Channel::Triangle => "triangle",
+            Channel::Noise => "noise",
+            Channel::Dmc => "dmc",
+        }
+    }
+}
+
+/// every channel mixed on its own and resampled separately
+#[derive(Debug)]
+struct ChannelCapture {
+    resamplers: Vec<Resampler>,
+    buffers: Vec<AudioBuffer>,
+}
+
+impl ChannelCapture {
+    fn new(clock_rate: u32, sample_rate: u32) -> ChannelCapture {
+        ChannelCapture {
+            resamplers: Channel::ALL.iter().map(|_| Resampler::new(clock_rate, sample_rate)).collect(),
+            buffers: Channel::ALL.iter().map(|_| AudioBuffer::new(buffer_capacity(sample_rate))).collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Apu {
+    pub pulse_1: Pulse,
+    pub pulse_2: Pulse,
+    pub triangle: Triangle,
+    pub noise: Noise,
+    pub dmc: Dmc,
+    pub frame_counter: FrameCounter,
+
+    mixer: Mixer,
+    resampler: Resampler,
+    buffer: AudioBuffer,
+    channel_capture: Option<ChannelCapture>,
+    clock_rate: u32,
+
+    cpu_cycle_count: u64,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new(Region::Ntsc)
+    }
+}
+
+impl Apu {
+    pub fn new(region: Region) -> Apu {
+        let clock_rate = match region {
+            Region::Ntsc => CPU_CLOCK_NTSC,
+            Region::Pal => CPU_CLOCK_PAL,
+            Region::Dendy => CPU_CLOCK_DENDY,
+        };
+
+        Apu {
+            pulse_1: Pulse::new(PulseChannel::One),
+            pulse_2: Pulse::new(PulseChannel::Two),
+            triangle: Triangle::new(),
+            noise: Noise::new(region),
+            dmc: Dmc::new(region),
+            frame_counter: FrameCounter::new(region),
+
+            mixer: Mixer::new(),
+            resampler: Resampler::new(clock_rate, DEFAULT_SAMPLE_RATE),
+            buffer: AudioBuffer::new(buffer_capacity(DEFAULT_SAMPLE_RATE)),
+            channel_capture: None,
+            clock_rate,
+
+            cpu_cycle_count: 0,
+        }
+    }
+
+    /// host sample rate of the audio output, drops any buffered samples
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.resampler = Resampler::new(self.clock_rate, sample_rate);
+        self.buffer = AudioBuffer::new(buffer_capacity(sample_rate));
+        if self.channel_capture.is_some() {
+            self.channel_capture = Some(ChannelCapture::new(self.clock_rate, sample_rate));
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.resampler.sample_rate()
+    }
+
+    /// resampled audio output waiting to be consumed
+    pub fn samples(&mut self) -> &mut AudioBuffer {
+        &mut self.buffer
+    }
+
+    /// additionally resample every channel on its own, see [`Apu::channel_samples`]
+    pub fn set_channel_capture(&mut self, enabled: bool) {
+        self.channel_capture = if enabled {
+            Some(ChannelCapture::new(self.clock_rate, self.sample_rate()))
+        } else {
+            None
+        };
+    }
+
+    /// resampled output of a single channel, only available while channel capture is enabled
+    pub fn channel_samples(&mut self, channel: Channel) -> Option<&mut AudioBuffer> {
+        let index = Channel::ALL.iter().position(|c| *c == channel)?;
+        self.channel_capture.as_mut().map(|capture| &mut capture.buffers[index])
+    }
+
+    /// mixer output of a single channel as if all others were silent
+    pub fn channel_output(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Pulse1 => self.mixer.mix(self.pulse_1.output(), 0, 0, 0, 0),
+            Channel::Pulse2 => self.mixer.mix(0, self.pulse_2.output(), 0, 0, 0),
+            Channel::Triangle => self.mixer.mix(0, 0, self.triangle.output(), 0, 0),
+            Channel::Noise => self.mixer.mix(0, 0, 0, self.noise.output(), 0),
+            Channel::Dmc => self.mixer.mix(0, 0, 0, 0, self.dmc.output()),
+        }
+    }
+
+    /// current mixer output before resampling and filtering
+    pub fn output(&self) -> f32 {
+        self.mixer.mix(
+            self.pulse_1.output(),
+            self.pulse_2.output(),
+            self.triangle.output(),
+            self.noise.output(),
+            self.dmc.output(),
+        )
+    }
+
+    /// write to one of the memory mapped registers in $4000-$4017
+    pub fn set_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x4000..=0x4003 => self.pulse_1.set_register(addr - 0x4000, value),
+            0x4004..=0x4007 => self.pulse_2.set_register(addr - 0x4004, value),
+            0x4008..=0x400B => self.triangle.set_register(addr - 0x4008, value),
+            0x400C..=0x400F => self.noise.set_register(addr - 0x400C, value),
+            0x4010..=0x4013 => self.dmc.set_register(addr - 0x4010, value),
+            0x4015 => {
+                self.pulse_1.set_enabled(value & 0b0000_0001 != 0);
+                self.pulse_2.set_enabled(value & 0b0000_0010 != 0);
+                self.triangle.set_enabled(value & 0b0000_0100 != 0);
+                self.noise.set_enabled(value & 0b0000_1000 != 0);
+                self.dmc.set_enabled(value & 0b0001_0000 != 0);
+            }
+            0x4017 => {
+                // the last stepped cpu cycle was an apu cycle
+                let apu_cycle = self.cpu_cycle_count.is_multiple_of(2);
+                self.frame_counter.write(value, apu_cycle);
+            }
+            _ => panic!("unknown apu register: {addr:#06X}")
+        }
+    }
+
+    /// $4015 read, acknowledges the frame interrupt
+    pub fn status(&mut self) -> u8 {
+        let status = self.peek_status();
+        self.frame_counter.clear_irq();
+        status
+    }
+
+    /// $4015 without acknowledging the frame interrupt
+    pub fn peek_status(&self) -> u8 {
+        let mut status = 0;
+        if self.pulse_1.length_counter() > 0 {
+            status |= 0b0000_0001;
+        }
+        if self.pulse_2.length_counter() > 0 {
+            status |= 0b0000_0010;
+        }
+        if self.triangle.length_counter() > 0 {
+            status |= 0b0000_0100;
+        }
+        if self.noise.length_counter() > 0 {
+            status |= 0b0000_1000;
+        }
+        if self.dmc.active() {
+            status |= 0b0001_0000;
+        }
+        if self.frame_counter.irq() {
+            status |= 0b0100_0000;
+        }
+        if self.dmc.irq() {
+            status |= 0b1000_0000;
+        }
+        status
+    }
+
+    /// irq line of the apu
+    pub fn irq(&self) -> bool {
+        self.frame_counter.irq() || self.dmc.irq()
+    }
+
+    /// advances the apu by one cpu cycle
+    pub fn step(&mut self) {
+        match self.frame_counter.clock() {
+            FrameStep::None => {}
+            FrameStep::Quarter => self.clock_quarter_frame(),
+            FrameStep::Half => {
+                self.clock_quarter_frame();
+                self.clock_half_frame();
+            }
+        }
+
+        // pulse timers are clocked every other cpu cycle
+        if !self.cpu_cycle_count.is_multiple_of(2) {
+            self.pulse_1.clock_timer();
+            self.pulse_2.clock_timer();
+        }
+        self.triangle.clock_timer();
+        self.noise.clock_timer();
+        self.dmc.clock_timer();
+
+        if let Some(sample) = self.resampler.push(self.output()) {
+            self.buffer.push(sample);
+        }
+        // only worked out while capturing
+        let outputs = self.channel_capture.is_some().then(|| Channel::ALL.map(|channel| self.channel_output(channel)));
+        if let (Some(capture), Some(outputs)) = (&mut self.channel_capture, outputs) {
+            for (i, output) in outputs.into_iter().enumerate() {
+                if let Some(sample) = capture.resamplers[i].push(output) {
+                    capture.buffers[i].push(sample);
+                }
+            }
+        }
+
+        self.cpu_cycle_count += 1;
+    }
+
+    /// envelopes and triangle linear counter
+    pub fn clock_quarter_frame(&mut self) {
+        self.pulse_1.clock_quarter_frame();
+        self.pulse_2.clock_quarter_frame();
+        self.triangle.clock_quarter_frame();
+        self.noise.clock_quarter_frame();
+    }
+
+    /// length counters and sweep units
+    pub fn clock_half_frame(&mut self) {
+        self.pulse_1.clock_half_frame();
+        self.pulse_2.clock_half_frame();
+        self.triangle.clock_half_frame();
+        self.noise.clock_half_frame();
+    }
+}
+
+/// buffered output samples aren't part of the state, they belong to the consumer
+impl SaveState for Apu {
+    fn save_state(&self, writer: &mut StateWriter) {
+        self.pulse_1.save_state(writer);
+        self.pulse_2.save_state(writer);
+        self.triangle.save_state(writer);
+        self.noise.save_state(writer);
+        self.dmc.save_state(writer);
+        self.frame_counter.save_state(writer);
+        self.resampler.save_state(writer);
+        writer.u64(self.cpu_cycle_count);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.pulse_1.load_state(reader)?;
+        self.pulse_2.load_state(reader)?;
+        self.triangle.load_state(reader)?;
+        self.noise.load_state(reader)?;
+        self.dmc.load_state(reader)?;
+        self.frame_counter.load_state(reader)?;
+        self.resampler.load_state(reader)?;
+        self.cpu_cycle_count = reader.u64()?;
+        Ok(())
+    }
+}
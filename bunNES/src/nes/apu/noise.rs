@@ -0,0 +1,142 @@
+use crate::nes::apu::envelope::Envelope;
+use crate::nes::apu::length_counter::LengthCounter;
+use crate::nes::rom::Region;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Noise
+
+/// timer periods in cpu cycles
+const PERIOD_TABLE_NTSC: [u16; 16] = [
+    4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068,
+];
+const PERIOD_TABLE_PAL: [u16; 16] = [
+    4, 8, 14, 30, 60, 88, 118, 148, 188, 236, 354, 472, 708, 944, 1890, 3778,
+];
+
+#[derive(Debug)]
+pub struct Noise {
+    period_table: &'static [u16; 16],
+
+    /// 15 bit linear feedback shift register
+    shift_register: u16,
+    mode: bool,
+
+    timer: u16,
+    timer_period: u16,
+
+    envelope: Envelope,
+    length_counter: LengthCounter,
+}
+
+impl Noise {
+    pub fn new(region: Region) -> Noise {
+        let period_table = match region {
+            Region::Ntsc | Region::Dendy => &PERIOD_TABLE_NTSC,
+            Region::Pal => &PERIOD_TABLE_PAL,
+        };
+
+        Noise {
+            period_table,
+
+            // loaded with 1 on power up
+            shift_register: 1,
+            mode: false,
+
+            timer: 0,
+            timer_period: period_table[0],
+
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+        }
+    }
+
+    /// register 0..=3 of the channel ($400C-$400F)
+    pub fn set_register(&mut self, register: u16, value: u8) {
+        match register {
+            // --LC VVVV
+            0 => {
+                self.length_counter.set_halt(value & 0b0010_0000 != 0);
+                self.envelope.set_register(value);
+            }
+            // unused
+            1 => {}
+            // M--- PPPP
+            2 => {
+                self.mode = value & 0b1000_0000 != 0;
+                self.timer_period = self.period_table[(value & 0b0000_1111) as usize];
+            }
+            // LLLL L---
+            3 => {
+                self.length_counter.load(value);
+                self.envelope.restart();
+            }
+            _ => panic!("unknown noise register: {register}")
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length_counter.set_enabled(enabled);
+    }
+
+    /// clocked every cpu cycle, the period table is in cpu cycles
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period - 1;
+
+            let tap = if self.mode { 6 } else { 1 };
+            let feedback = (self.shift_register & 1) ^ ((self.shift_register >> tap) & 1);
+            self.shift_register >>= 1;
+            self.shift_register |= feedback << 14;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.length_counter.active() || self.shift_register & 1 == 1 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+
+    pub fn length_counter(&self) -> u8 {
+        self.length_counter.counter()
+    }
+
+    pub fn timer_period(&self) -> u16 {
+        self.timer_period
+    }
+
+    pub fn shift_register(&self) -> u16 {
+        self.shift_register
+    }
+}
+
+impl SaveState for Noise {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u16(self.shift_register);
+        writer.bool(self.mode);
+        writer.u16(self.timer);
+        writer.u16(self.timer_period);
+        self.envelope.save_state(writer);
+        self.length_counter.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.shift_register = reader.u16()?;
+        self.mode = reader.bool()?;
+        self.timer = reader.u16()?;
+        self.timer_period = reader.u16()?;
+        self.envelope.load_state(reader)?;
+        self.length_counter.load_state(reader)
+    }
+}
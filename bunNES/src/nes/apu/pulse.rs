@@ -0,0 +1,232 @@
+use crate::nes::apu::envelope::Envelope;
+use crate::nes::apu::length_counter::LengthCounter;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Pulse
+
+const DUTY_TABLE: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0], // 12.5%
+    [0, 1, 1, 0, 0, 0, 0, 0], // 25%
+    [0, 1, 1, 1, 1, 0, 0, 0], // 50%
+    [1, 0, 0, 1, 1, 1, 1, 1], // 25% negated
+];
+
+/// the two pulse channels only differ in how the sweep unit negates the change amount
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PulseChannel {
+    /// pulse 1 uses ones' complement: `period - change - 1`
+    One,
+    /// pulse 2 uses two's complement: `period - change`
+    Two,
+}
+
+// https://www.nesdev.org/wiki/APU_Sweep
+#[derive(Debug)]
+struct Sweep {
+    channel: PulseChannel,
+    enabled: bool,
+    period: u8,
+    negate: bool,
+    shift: u8,
+    reload: bool,
+    divider: u8,
+}
+
+impl Sweep {
+    fn new(channel: PulseChannel) -> Sweep {
+        Sweep {
+            channel,
+            enabled: false,
+            period: 0,
+            negate: false,
+            shift: 0,
+            reload: false,
+            divider: 0,
+        }
+    }
+
+    /// EPPP NSSS
+    fn set_register(&mut self, value: u8) {
+        self.enabled = value & 0b1000_0000 != 0;
+        self.period = (value & 0b0111_0000) >> 4;
+        self.negate = value & 0b0000_1000 != 0;
+        self.shift = value & 0b0000_0111;
+        self.reload = true;
+    }
+
+    fn target_period(&self, timer_period: u16) -> u16 {
+        let change = timer_period >> self.shift;
+        if self.negate {
+            match self.channel {
+                PulseChannel::One => timer_period.saturating_sub(change + 1),
+                PulseChannel::Two => timer_period.saturating_sub(change),
+            }
+        } else {
+            timer_period + change
+        }
+    }
+
+    /// the channel is silenced while the period is too low or the target overflows,
+    /// even if the sweep unit is disabled
+    fn muting(&self, timer_period: u16) -> bool {
+        timer_period < 8 || self.target_period(timer_period) > 0x7FF
+    }
+
+    /// clocked by the frame counter every half frame, returns the new timer period
+    fn clock(&mut self, timer_period: u16) -> u16 {
+        let mut timer_period = timer_period;
+        if self.divider == 0 && self.enabled && self.shift > 0 && !self.muting(timer_period) {
+            timer_period = self.target_period(timer_period);
+        }
+
+        if self.divider == 0 || self.reload {
+            self.divider = self.period;
+            self.reload = false;
+        } else {
+            self.divider -= 1;
+        }
+
+        timer_period
+    }
+}
+
+#[derive(Debug)]
+pub struct Pulse {
+    duty: u8,
+    sequence: u8,
+
+    timer: u16,
+    timer_period: u16,
+
+    envelope: Envelope,
+    length_counter: LengthCounter,
+    sweep: Sweep,
+}
+
+impl Pulse {
+    pub fn new(channel: PulseChannel) -> Pulse {
+        Pulse {
+            duty: 0,
+            sequence: 0,
+
+            timer: 0,
+            timer_period: 0,
+
+            envelope: Envelope::new(),
+            length_counter: LengthCounter::new(),
+            sweep: Sweep::new(channel),
+        }
+    }
+
+    /// register 0..=3 of the channel ($4000-$4003 or $4004-$4007)
+    pub fn set_register(&mut self, register: u16, value: u8) {
+        match register {
+            // DDLC VVVV
+            0 => {
+                self.duty = (value & 0b1100_0000) >> 6;
+                self.length_counter.set_halt(value & 0b0010_0000 != 0);
+                self.envelope.set_register(value);
+            }
+            // EPPP NSSS
+            1 => self.sweep.set_register(value),
+            // TTTT TTTT
+            2 => self.timer_period = (self.timer_period & 0x0700) | value as u16,
+            // LLLL LTTT
+            3 => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((value as u16 & 0b111) << 8);
+                self.length_counter.load(value);
+                self.sequence = 0;
+                self.envelope.restart();
+            }
+            _ => panic!("unknown pulse register: {register}")
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length_counter.set_enabled(enabled);
+    }
+
+    /// clocked every apu cycle (every second cpu cycle)
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            self.sequence = self.sequence.wrapping_sub(1) & 0b111;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+        self.timer_period = self.sweep.clock(self.timer_period);
+    }
+
+    pub fn output(&self) -> u8 {
+        if !self.length_counter.active()
+            || self.sweep.muting(self.timer_period)
+            || DUTY_TABLE[self.duty as usize][self.sequence as usize] == 0 {
+            0
+        } else {
+            self.envelope.output()
+        }
+    }
+
+    pub fn length_counter(&self) -> u8 {
+        self.length_counter.counter()
+    }
+
+    pub fn timer_period(&self) -> u16 {
+        self.timer_period
+    }
+
+    pub fn sweep_target_period(&self) -> u16 {
+        self.sweep.target_period(self.timer_period)
+    }
+}
+
+impl SaveState for Sweep {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.enabled);
+        writer.u8(self.period);
+        writer.bool(self.negate);
+        writer.u8(self.shift);
+        writer.bool(self.reload);
+        writer.u8(self.divider);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.enabled = reader.bool()?;
+        self.period = reader.u8()?;
+        self.negate = reader.bool()?;
+        self.shift = reader.u8()?;
+        self.reload = reader.bool()?;
+        self.divider = reader.u8()?;
+        Ok(())
+    }
+}
+
+impl SaveState for Pulse {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.duty);
+        writer.u8(self.sequence);
+        writer.u16(self.timer);
+        writer.u16(self.timer_period);
+        self.envelope.save_state(writer);
+        self.length_counter.save_state(writer);
+        self.sweep.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.duty = reader.u8()?;
+        self.sequence = reader.u8()?;
+        self.timer = reader.u16()?;
+        self.timer_period = reader.u16()?;
+        self.envelope.load_state(reader)?;
+        self.length_counter.load_state(reader)?;
+        self.sweep.load_state(reader)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::nes::apu::filter::{HighPass, LowPass};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+/// downsamples the mixer output from the cpu clock to the host sample rate
+///
+/// every output sample is the average of the cpu cycles it covers, partial cycles are
+/// weighted, so the box filter band-limits the signal before decimation. the result
+/// goes through the high-pass and low-pass filters of the console.
+#[derive(Debug)]
+pub struct Resampler {
+    sample_rate: u32,
+    cycles_per_sample: f64,
+    position: f64,
+    sum: f64,
+
+    high_pass_90: HighPass,
+    high_pass_440: HighPass,
+    low_pass_14k: LowPass,
+}
+
+impl Resampler {
+    pub fn new(clock_rate: u32, sample_rate: u32) -> Resampler {
+        Resampler {
+            sample_rate,
+            cycles_per_sample: clock_rate as f64 / sample_rate as f64,
+            position: 0.0,
+            sum: 0.0,
+
+            high_pass_90: HighPass::new(90.0, sample_rate),
+            high_pass_440: HighPass::new(440.0, sample_rate),
+            low_pass_14k: LowPass::new(14000.0, sample_rate),
+        }
+    }
+
+    /// takes the mixer output of one cpu cycle, returns a sample once enough cycles are collected
+    pub fn push(&mut self, value: f32) -> Option<f32> {
+        let remaining = self.cycles_per_sample - self.position;
+        if remaining > 1.0 {
+            self.sum += value as f64;
+            self.position += 1.0;
+            return None;
+        }
+
+        self.sum += value as f64 * remaining;
+        let sample = (self.sum / self.cycles_per_sample) as f32;
+
+        let leftover = 1.0 - remaining;
+        self.sum = value as f64 * leftover;
+        self.position = leftover;
+
+        let sample = self.high_pass_90.filter(sample);
+        let sample = self.high_pass_440.filter(sample);
+        Some(self.low_pass_14k.filter(sample))
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+}
+
+/// only the position and filter history, the rates belong to the host
+impl SaveState for Resampler {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.f64(self.position);
+        writer.f64(self.sum);
+        self.high_pass_90.save_state(writer);
+        self.high_pass_440.save_state(writer);
+        self.low_pass_14k.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.position = reader.f64()?;
+        self.sum = reader.f64()?;
+        self.high_pass_90.load_state(reader)?;
+        self.high_pass_440.load_state(reader)?;
+        self.low_pass_14k.load_state(reader)
+    }
+}
@@ -0,0 +1,124 @@
+use crate::nes::apu::length_counter::LengthCounter;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/APU_Triangle
+
+const SEQUENCE: [u8; 32] = [
+    15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0,
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+#[derive(Debug, Default)]
+pub struct Triangle {
+    sequence: u8,
+
+    timer: u16,
+    timer_period: u16,
+
+    /// also acts as the length counter halt flag
+    control: bool,
+    linear_counter: u8,
+    linear_counter_reload: u8,
+    linear_counter_reload_flag: bool,
+
+    length_counter: LengthCounter,
+}
+
+impl Triangle {
+    pub fn new() -> Triangle {
+        Triangle::default()
+    }
+
+    /// register 0..=3 of the channel ($4008-$400B)
+    pub fn set_register(&mut self, register: u16, value: u8) {
+        match register {
+            // CRRR RRRR
+            0 => {
+                self.control = value & 0b1000_0000 != 0;
+                self.length_counter.set_halt(self.control);
+                self.linear_counter_reload = value & 0b0111_1111;
+            }
+            // unused
+            1 => {}
+            // TTTT TTTT
+            2 => self.timer_period = (self.timer_period & 0x0700) | value as u16,
+            // LLLL LTTT
+            3 => {
+                self.timer_period = (self.timer_period & 0x00FF) | ((value as u16 & 0b111) << 8);
+                self.length_counter.load(value);
+                self.linear_counter_reload_flag = true;
+            }
+            _ => panic!("unknown triangle register: {register}")
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length_counter.set_enabled(enabled);
+    }
+
+    /// clocked every cpu cycle
+    pub fn clock_timer(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.timer_period;
+            // the sequencer only advances while both counters are non-zero
+            if self.linear_counter > 0 && self.length_counter.active() {
+                self.sequence = (self.sequence + 1) % SEQUENCE.len() as u8;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn clock_quarter_frame(&mut self) {
+        if self.linear_counter_reload_flag {
+            self.linear_counter = self.linear_counter_reload;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+
+        if !self.control {
+            self.linear_counter_reload_flag = false;
+        }
+    }
+
+    pub fn clock_half_frame(&mut self) {
+        self.length_counter.clock();
+    }
+
+    /// silencing the channel just stops the sequencer, so the output holds its last value
+    pub fn output(&self) -> u8 {
+        SEQUENCE[self.sequence as usize]
+    }
+
+    pub fn length_counter(&self) -> u8 {
+        self.length_counter.counter()
+    }
+
+    pub fn linear_counter(&self) -> u8 {
+        self.linear_counter
+    }
+}
+
+impl SaveState for Triangle {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.sequence);
+        writer.u16(self.timer);
+        writer.u16(self.timer_period);
+        writer.bool(self.control);
+        writer.u8(self.linear_counter);
+        writer.u8(self.linear_counter_reload);
+        writer.bool(self.linear_counter_reload_flag);
+        self.length_counter.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.sequence = reader.u8()?;
+        self.timer = reader.u16()?;
+        self.timer_period = reader.u16()?;
+        self.control = reader.bool()?;
+        self.linear_counter = reader.u8()?;
+        self.linear_counter_reload = reader.u8()?;
+        self.linear_counter_reload_flag = reader.bool()?;
+        self.length_counter.load_state(reader)
+    }
+}
@@ -1,15 +1,39 @@
-use crate::nes::cpu::RenderImage;
+use crate::debugger::{AccessKind, AccessWatch, AddressSpace};
+use crate::nes::apu::Apu;
+use crate::nes::input::{InputPorts, Screen};
+use crate::nes::cpu::{CpuBus, RenderImage};
 use crate::nes::ppu::Ppu;
 use crate::nes::rom::Cartridge;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
 use std::sync::{Arc, Mutex};
 
 const RAM_CAP: usize = 2 * 1024;
+/// work ram at $6000-$7FFF, battery backed on some cartridges
+const PRG_RAM_SIZE: usize = 8 * 1024;
+/// cpu cycles stolen by a dmc sample fetch
+const DMC_DMA_CYCLES: u8 = 4;
+/// cpu cycles stolen by an oam dma. the hardware takes one more when started on an odd cycle,
+/// that isn't modelled since instructions run whole and the bus can't tell which cycle the write was on
+const OAM_DMA_CYCLES: u16 = 513;
+/// bits of $4016/$4017 reads the input devices don't drive, they keep the last value on
+/// the data bus, usually the high byte of the address
+const CONTROLLER_OPEN_BUS: u8 = 0b1110_0000;
+/// bit 5 of $4015 isn't driven either
+const APU_STATUS_OPEN_BUS: u8 = 0b0010_0000;
 pub(crate) type Ram = [u8; RAM_CAP];
 
 pub struct Bus {
     pub ppu: Ppu,
+    pub apu: Apu,
+    pub input: InputPorts,
     rom: Arc<Cartridge>,
     pub ram: Ram,
+    prg_ram: Vec<u8>,
+    dma_stall: u16,
+    /// last value read or written, what reads of unmapped addresses return
+    open_bus: u8,
+    /// set while a debugger is attached, accesses are only recorded then
+    pub(crate) watch: Option<AccessWatch>,
 }
 
 impl Bus {
@@ -18,11 +42,19 @@ impl Bus {
         let rom = Arc::new(cartridge);
 
         let ppu = Ppu::new(rom.clone());
+        let apu = Apu::new(rom.region());
+        let input = InputPorts::from_expansion_device(rom.default_expansion_device());
 
         Bus {
             ram,
+            prg_ram: vec![0; if rom.has_prg_ram() { PRG_RAM_SIZE } else { 0 }],
             rom,
             ppu,
+            apu,
+            input,
+            dma_stall: 0,
+            open_bus: 0,
+            watch: None,
         }
     }
 
@@ -30,82 +62,253 @@ impl Bus {
         self.ppu.step(scanline);
     }
 
+    /// advances the apu by one cpu cycle and returns the cycles the cpu is stalled for
+    pub fn step_apu(&mut self) -> u8 {
+        self.apu.step();
+
+        // https://www.nesdev.org/wiki/APU_DMC#Memory_reader
+        if let Some(addr) = self.apu.dmc.dma_request() {
+            let value = self.read_8(addr);
+            self.apu.dmc.fill_sample_buffer(value);
+            return DMC_DMA_CYCLES;
+        }
+        0
+    }
+
+    /// cpu cycles stolen by oam dma since the last call
+    pub fn take_dma_stall(&mut self) -> u16 {
+        std::mem::take(&mut self.dma_stall)
+    }
+
+    pub fn nmi(&mut self) -> bool {
+        self.ppu.take_nmi()
+    }
+
+    pub fn irq(&self) -> bool {
+        self.apu.irq()
+    }
+
+    pub fn cartridge(&self) -> &Cartridge {
+        &self.rom
+    }
+
     pub fn rom_len(&self) -> usize {
         self.rom.prg().len()
     }
 
     pub fn read_8(&mut self, addr: u16) -> u8 {
-        self.map_addr(addr)
+        if self.watch.is_none() {
+            return self.map_addr(addr);
+        }
+        // $2007 moves the vram address
+        let vram_addr = self.ppu.vram_addr();
+        let value = self.map_addr(addr);
+        self.record(addr, vram_addr, value, AccessKind::READ);
+        value
     }
 
     pub fn read_16(&mut self, addr: u16) -> u16 {
-        let lsb = self.map_addr(addr);
-        let msb = self.map_addr(addr + 1);
+        let lsb = self.read_8(addr);
+        let msb = self.read_8(addr.wrapping_add(1));
 
         ((msb as u16) << 8) | lsb as u16
     }
 
     pub fn write(&mut self, addr: u16, value: u8) {
+        self.open_bus = value;
+        if self.watch.is_some() {
+            let vram_addr = self.ppu.vram_addr();
+            self.record(addr, vram_addr, value, AccessKind::WRITE);
+        }
+
         match addr {
             0x2000..=0x3FFF => {
                 self.ppu.set_register((addr % 8) as u8, value);
             }
             // ram
-            0x0000..=0x1FFF => self.ram[(addr % 0x07FF) as usize] = value,
-            _ => unimplemented!("write for addr [{:#04X}]", addr),
-            
+            0x0000..=0x1FFF => self.ram[(addr & 0x07FF) as usize] = value,
+            // apu
+            0x4000..=0x4013 | 0x4015 | 0x4017 => self.apu.set_register(addr, value),
+            // https://www.nesdev.org/wiki/PPU_registers#OAMDMA
+            0x4014 => {
+                let start = (value as u16) << 8;
+                let page: Vec<u8> = (0..256).map(|i| self.read_8(start + i)).collect();
+                self.ppu.write_oam_dma(&page);
+                self.dma_stall += OAM_DMA_CYCLES;
+            }
+            // input devices
+            0x4016 => self.input.write(value),
+            // cartridge ram, if there is any
+            0x6000..=0x7FFF => {
+                if let Some(byte) = self.prg_ram.get_mut(addr as usize - 0x6000) {
+                    *byte = value;
+                }
+            }
+            // disabled apu test registers, the expansion area and mapper 000 rom,
+            // nothing listens to these
+            0x4018..=0x5FFF | 0x8000..=0xFFFF => {}
         }
     }
     
-    pub fn memory_chunk(&mut self, start_addr: u16, size: usize) -> Vec<u8> {
-        let mut result = vec![0; size];
-        for i in 0..size {
-            let addr = start_addr + i as u16;
-                result[i] = self.map_addr(addr);
-        }
-        result
+    /// `size` bytes from `start_addr` on, peeked so looking at memory doesn't change it
+    pub fn memory_chunk(&self, start_addr: u16, size: usize) -> Vec<u8> {
+        (0..size).map(|i| self.peek(start_addr.wrapping_add(i as u16))).collect()
     }
 
-    fn map_addr(&mut self, addr: u16) -> u8 {
+    /// the value a read of `addr` would return, without the side effects of reading
+    /// ppu, apu or controller registers. unmapped addresses read as open bus
+    pub fn peek(&self, addr: u16) -> u8 {
         // TODO: replace with mapper
         // mapper 000 hardcoded
         match addr {
             // ram
-            0x0000..=0x1FFF => self.ram[(addr % 0x07FF) as usize],
+            0x0000..=0x1FFF => self.ram[(addr & 0x07FF) as usize],
             // ppu
-            0x2000..=0x3FFF => {
-                self.ppu.register((addr % 8) as u8)
-            },
+            0x2000..=0x3FFF => self.ppu.peek_register((addr % 8) as u8),
             // apu
-            0x4000..=0x4017 => unimplemented!("APU"),
-            // apu and io
-            0x4018..=0x401F => panic!("APU and IO. Should be disabled"),
+            0x4015 => self.apu.peek_status() | self.open_bus & APU_STATUS_OPEN_BUS,
+            // input devices
+            0x4016 | 0x4017 => self.input.peek(addr, &self.screen()) | self.open_bus & CONTROLLER_OPEN_BUS,
+            // write only apu registers and oam dma, disabled apu test registers and
+            // the expansion area
+            // https://www.nesdev.org/wiki/Open_bus_behavior
+            0x4000..=0x4014 | 0x4018..=0x5FFF => self.open_bus,
+            // cartridge ram, open bus without it
+            0x6000..=0x7FFF => self.prg_ram.get(addr as usize - 0x6000).copied().unwrap_or(self.open_bus),
             // rom (cartridge)
-            0x4020..=0xFFFF => {
-                match addr {
-                    0x6000..=0x7FFF => panic!("Cartridge: Family Basic only"),
-                    0x8000..=0xFFFF => {
-
-                        match self.rom.rom_len() {
-                            0x4000 => {
-                                // 16k rom
-                                // subtract rom location and mirror upper 0x4000 bytes
-                                let addr = (addr as usize -0x8000) % 0x4000;
-                                self.rom.prg()[addr]
-                            },
-                            0x8000 => {
-                                // 32k rom
-                                // subtract rom location
-                                let addr = addr as usize - 0x8000;
-                                self.rom.prg()[addr]
-                            },
-                            _ => panic!("Cartridge: Rom: no matching address")
-                        }
-                    }
-                    _ => panic!("Cartridge: no matching address")
+            0x8000..=0xFFFF => {
+                match self.rom.rom_len() {
+                    0x4000 => {
+                        // 16k rom
+                        // subtract rom location and mirror upper 0x4000 bytes
+                        let addr = (addr as usize -0x8000) % 0x4000;
+                        self.rom.prg()[addr]
+                    },
+                    0x8000 => {
+                        // 32k rom
+                        // subtract rom location
+                        let addr = addr as usize - 0x8000;
+                        self.rom.prg()[addr]
+                    },
+                    _ => panic!("Cartridge: Rom: no matching address")
                 }
+            }
+        }
+    }
+
+    /// byte at `addr` in the ppu address space, without going through $2006/$2007
+    pub fn peek_ppu(&self, addr: u16) -> u8 {
+        self.ppu.peek_vram(addr)
+    }
+
+    /// cartridge ram at $6000-$7FFF, empty if the cartridge has none
+    pub fn prg_ram(&self) -> &[u8] {
+        &self.prg_ram
+    }
+
+    pub fn prg_ram_mut(&mut self) -> &mut [u8] {
+        &mut self.prg_ram
+    }
+
+    /// a cpu access, and the ppu access behind it for $2007
+    fn record(&mut self, addr: u16, vram_addr: u16, value: u8, kind: AccessKind) {
+        if let Some(watch) = self.watch.as_mut() {
+            watch.record(AddressSpace::Cpu, addr, value, kind);
+            if (0x2000..=0x3FFF).contains(&addr) && addr % 8 == 7 {
+                watch.record(AddressSpace::Ppu, vram_addr, value, kind);
+            }
+        }
+    }
+
+    /// accesses since the last debugger check aren't the program's, e.g. the debugger reading memory
+    pub(crate) fn discard_watch_hits(&mut self) {
+        if let Some(watch) = self.watch.as_mut() {
+            watch.clear();
+        }
+    }
+
+    /// reads with side effects, everything else is the same as [`Bus::peek`]
+    fn map_addr(&mut self, addr: u16) -> u8 {
+        let value = match addr {
+            // ppu
+            0x2000..=0x3FFF => {
+                self.ppu.register((addr % 8) as u8)
             },
-            _ => panic!("no matching address")
+            // apu
+            0x4015 => self.apu.status() | self.open_bus & APU_STATUS_OPEN_BUS,
+            // input devices
+            0x4016 | 0x4017 => {
+                let screen = Screen {
+                    frame_buffer: self.ppu.frame_buffer(),
+                    scanline: self.ppu.scanline(),
+                    dot: self.ppu.dot(),
+                };
+                self.input.read(addr, &screen) | self.open_bus & CONTROLLER_OPEN_BUS
+            }
+            _ => self.peek(addr),
+        };
+        self.open_bus = value;
+        value
+    }
+
+    fn screen(&self) -> Screen<'_> {
+        Screen {
+            frame_buffer: self.ppu.frame_buffer(),
+            scanline: self.ppu.scanline(),
+            dot: self.ppu.dot(),
         }
     }
-}
\ No newline at end of file
+}
+
+impl CpuBus for Bus {
+    fn read_8(&mut self, addr: u16) -> u8 {
+        Bus::read_8(self, addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        Bus::write(self, addr, value)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        Bus::peek(self, addr)
+    }
+
+    fn nmi(&mut self) -> bool {
+        Bus::nmi(self)
+    }
+
+    fn irq(&self) -> bool {
+        Bus::irq(self)
+    }
+
+    fn step(&mut self) -> u8 {
+        self.step_apu()
+    }
+
+    fn take_dma_stall(&mut self) -> u16 {
+        Bus::take_dma_stall(self)
+    }
+}
+
+/// prg rom lives in the cartridge and isn't part of the state
+impl SaveState for Bus {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bytes(&self.ram);
+        writer.bytes(&self.prg_ram);
+        writer.u16(self.dma_stall);
+        writer.u8(self.open_bus);
+        self.ppu.save_state(writer);
+        self.apu.save_state(writer);
+        self.input.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        reader.bytes_into(&mut self.ram, "ram")?;
+        reader.bytes_into(&mut self.prg_ram, "cartridge ram")?;
+        self.dma_stall = reader.u16()?;
+        self.open_bus = reader.u8()?;
+        self.ppu.load_state(reader)?;
+        self.apu.load_state(reader)?;
+        self.input.load_state(reader)
+    }
+}
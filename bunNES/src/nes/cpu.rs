@@ -1,6 +1,7 @@
 use crate::nes::bus::Bus;
 use crate::nes::opcodes::{AddrMode, Instruction, OpCode, OP_CODES};
 use crate::nes::rom::Cartridge;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
 use bit::BitIndex;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
@@ -12,6 +13,12 @@ pub type RenderImage = Vec<u8>;
 
 type ExtraStep = u8;
 
+const STACK_PAGE: u16 = 0x0100;
+const NMI_VECTOR: u16 = 0xFFFA;
+const IRQ_VECTOR: u16 = 0xFFFE;
+/// cycles to push pc and status and load a vector
+const INTERRUPT_CYCLES: u16 = 7;
+
 #[derive(Eq, PartialEq, Debug)]
 pub struct ProcessorStatus {
     /// [0] carry
@@ -95,24 +102,81 @@ impl ProcessorStatus {
     }
 
     pub fn get_reg(&self) -> u8 { self.reg }
+
+    pub fn set_reg(&mut self, value: u8) { self.reg = value }
+}
+
+/// what the cpu sees of the machine around it
+pub trait CpuBus {
+    fn read_8(&mut self, addr: u16) -> u8;
+
+    fn read_16(&mut self, addr: u16) -> u16 {
+        let lsb = self.read_8(addr);
+        let msb = self.read_8(addr.wrapping_add(1));
+        u16::from_le_bytes([lsb, msb])
+    }
+
+    fn write(&mut self, addr: u16, value: u8);
+
+    /// the value a read of `addr` would return, without its side effects
+    fn peek(&self, addr: u16) -> u8;
+
+    /// polled before every instruction, true once per nmi
+    fn nmi(&mut self) -> bool;
+
+    /// level of the irq line, the cpu takes it while the i flag is clear
+    fn irq(&self) -> bool;
+
+    /// one cpu cycle went by, returns the cycles a dma steals from the cpu
+    fn step(&mut self) -> u8 {
+        0
+    }
+
+    /// cpu cycles stolen by dma since the last call
+    fn take_dma_stall(&mut self) -> u16 {
+        0
+    }
 }
 
+/// which chip the core behaves like
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Variant {
+    /// the nes cpu, a 6502 with decimal mode cut out
+    #[default]
+    Ricoh2A03,
+    /// a stock nmos 6502, adc and sbc do bcd arithmetic while the decimal flag is set
+    Nmos6502,
+}
+
+/// what an instruction did on top of its size and cycles in [`OP_CODES`]
 struct Step {
-    cycles: u8,
-    pc_inc: u8,
+    page_crossed: bool,
+    /// the instruction set pc, it doesn't move past the instruction
+    jumped: bool,
+    /// a branch was taken
+    taken: bool,
 }
 
 impl Step {
-    fn next(bytes: u8, cycles: u8) -> Step {
-        Step {
-            cycles,
-            pc_inc: bytes,
-        }
+    fn done() -> Step {
+        Step { page_crossed: false, jumped: false, taken: false }
+    }
+
+    fn page_cross(extra_step: ExtraStep) -> Step {
+        Step { page_crossed: extra_step > 0, jumped: false, taken: false }
+    }
+
+    fn jump() -> Step {
+        Step { page_crossed: false, jumped: true, taken: false }
+    }
+
+    fn branch(page_crossed: bool) -> Step {
+        Step { page_crossed, jumped: true, taken: true }
     }
 }
 
 #[allow(unused_variables, dead_code)]
-pub struct Cpu {
+pub struct Cpu<B = Bus> {
     /// program counter
     pub pc: u16,
     /// stack pointer
@@ -126,34 +190,23 @@ pub struct Cpu {
     /// processor status
     pub ps: ProcessorStatus,
 
-    pub bus: Bus,
+    pub bus: B,
+    pub variant: Variant,
 
-    pub cycles_to_finish: u8,
+    pub cycles_to_finish: u16,
 }
 
 
 impl Cpu {
     pub fn new(cartridge: Cartridge) -> Cpu {
-        Cpu {
-            pc: 0,
-            sp: 0xFF,
-            acc: 0,
-            x: 0,
-            y: 0,
-            ps: ProcessorStatus::default(),
-
-            bus: Bus::new(cartridge),
-
-            cycles_to_finish: 0,
-        }
+        Cpu::with_bus(Bus::new(cartridge), Variant::Ricoh2A03)
     }
 
     pub fn soft_reset(&mut self) {
-        println!("reset!");
-        println!("rom size: {}", self.bus.rom_len());
         let reset: u16 = self.bus.read_8(0xFFFC) as u16 | (self.bus.read_8(0xFFFD) as u16) << 8;
-        println!("reset vector: {:#04X}", reset);
         self.set_pc(reset);
+        // https://www.nesdev.org/wiki/CPU_power_up_state
+        self.ps.set_irqb(true);
     }
 
     pub fn run(&mut self) {
@@ -164,16 +217,7 @@ impl Cpu {
         // frame every 16ms (60 fps)
 
         loop {
-            // 262 scanlines per frame
-            for scanline in 0..262 {
-                // 341 ppu cycles per scanline
-                for ppu_cycle in 0..341 {
-                    if ppu_cycle % 3 == 0 {
-                        self.step();
-                    }
-                    self.bus.step_ppu(scanline);
-                }
-            }
+            self.frame();
 
             {
                 // println!("ppu render to image");
@@ -201,6 +245,44 @@ impl Cpu {
     }
 
 
+    /// runs the cpu and ppu for one frame
+    pub fn frame(&mut self) {
+        // 262 scanlines of 341 ppu cycles
+        for _ in 0..262 * 341 {
+            self.tick();
+        }
+    }
+
+    /// runs the ppu for one cycle and the cpu on every third cycle of a scanline.
+    /// the position comes from the ppu so a frame can be picked up anywhere, e.g. after loading a state.
+    /// returns whether the cpu started an instruction or interrupt
+    pub fn tick(&mut self) -> bool {
+        let ppu_cycle = self.bus.ppu.cycle_count();
+        let started = (ppu_cycle % 341).is_multiple_of(3) && self.step();
+        self.bus.step_ppu(ppu_cycle / 341);
+        started
+    }
+}
+
+impl<B: CpuBus> Cpu<B> {
+    /// a cpu on any bus, e.g. a [`RamBus`](crate::nes::ram_bus::RamBus) and
+    /// [`Variant::Nmos6502`] for generic 6502 programs
+    pub fn with_bus(bus: B, variant: Variant) -> Cpu<B> {
+        Cpu {
+            pc: 0,
+            sp: 0xFF,
+            acc: 0,
+            x: 0,
+            y: 0,
+            ps: ProcessorStatus::default(),
+
+            bus,
+            variant,
+
+            cycles_to_finish: 0,
+        }
+    }
+
     pub fn set_pc(&mut self, value: u16) {
         self.pc = value;
     }
@@ -217,92 +299,107 @@ impl Cpu {
     fn set_negative(&mut self, value: u8) {
         self.ps.set_negative(value.bit(7));
     }
-    
-    fn set_overflow(&mut self, value: u8, old_value: u8) {
-        let overflow = value.bit(7) != old_value.bit(7);
-        self.ps.set_overflow(overflow);
-    }
 
     pub fn step(&mut self) -> bool {
+        // dmc sample fetches stall the cpu
+        self.cycles_to_finish += self.bus.step() as u16;
+
         if self.cycles_to_finish > 0 {
             self.cycles_to_finish -= 1;
             return false;
         }
 
+        // https://www.nesdev.org/wiki/CPU_interrupts
+        if self.bus.nmi() {
+            self.interrupt(NMI_VECTOR);
+            self.cycles_to_finish = INTERRUPT_CYCLES - 1;
+            return true;
+        }
+        if self.bus.irq() && !self.ps.irqb() {
+            self.interrupt(IRQ_VECTOR);
+            self.cycles_to_finish = INTERRUPT_CYCLES - 1;
+            return true;
+        }
+
         let (instruction, byte_code) = self.get_instruction(self.pc);
 
-        let step: Step = if let Some(instruction) = instruction {
-            // TODO: use size from instruction for step size
-            let addr_mode = instruction.addr_mode;
-            match instruction.op_code {
-                OpCode::Adc => self.adc(addr_mode),
-                OpCode::And => self.and(addr_mode),
-                OpCode::Asl => self.asl(addr_mode),
-                OpCode::Bcc => self.bcc(),
-                OpCode::Bcs => self.bcs(),
-                OpCode::Beq => self.beq(),
-                OpCode::Bit => self.bit(addr_mode),
-                OpCode::Bmi => self.bmi(),
-                OpCode::Bne => self.bne(),
-                OpCode::Bpl => self.bpl(),
-                OpCode::Brk => self.brk(),
-                OpCode::Bvc => self.bvc(),
-                OpCode::Bvs => self.bvs(),
-                OpCode::Clc => self.clc(),
-                OpCode::Cld => self.cld(),
-                OpCode::Cli => self.cli(),
-                OpCode::Clv => self.clv(),
-                OpCode::Cmp => self.cmp(addr_mode),
-                OpCode::Cpx => self.cpx(addr_mode),
-                OpCode::Cpy => self.cpy(addr_mode),
-                OpCode::Dec => self.dec(addr_mode),
-                OpCode::Dex => self.dex(),
-                OpCode::Dey => self.dey(),
-                OpCode::Eor => self.eor(addr_mode),
-                OpCode::Inc => self.inc(addr_mode),
-                OpCode::Inx => self.inx(),
-                OpCode::Iny => self.iny(),
-                OpCode::Jmp => self.jmp(addr_mode),
-                OpCode::Jsr => self.jsr(),
-                OpCode::Lda => self.lda(addr_mode),
-                OpCode::Ldx => self.ldx(addr_mode),
-                OpCode::Ldy => self.ldy(addr_mode),
-                OpCode::Lsr => self.lsr(addr_mode),
-                OpCode::Nop => self.nop(),
-                OpCode::Ora => self.ora(addr_mode),
-                OpCode::Pha => self.pha(),
-                OpCode::Php => self.php(),
-                OpCode::Pla => self.pla(),
-                OpCode::Plp => self.plp(),
-                OpCode::Rol => self.rol(addr_mode),
-                OpCode::Ror => self.ror(addr_mode),
-                OpCode::Rti => self.rti(),
-                OpCode::Rts => self.rts(),
-                OpCode::Sbc => self.sbc(addr_mode),
-                OpCode::Sec => self.sec(),
-                OpCode::Sed => self.sed(),
-                OpCode::Sei => self.sei(),
-                OpCode::Sta => self.sta(addr_mode),
-                OpCode::Stx => self.stx(addr_mode),
-                OpCode::Sty => self.sty(addr_mode),
-                OpCode::Tax => self.tax(),
-                OpCode::Tay => self.tay(),
-                OpCode::Tsx => self.tsx(),
-                OpCode::Txs => self.txs(),
-                OpCode::Txa => self.txa(),
-                OpCode::Tya => self.tya(),
-            }
-        } else {
-            panic!("unknown instruction: {:#04X}: {:#04X}", self.pc, byte_code)
+        if !instruction.official {
+            panic!("unofficial instruction: {:#04X}: {:#04X} {}", self.pc, byte_code, instruction.op_code)
+        }
+
+        let addr_mode = instruction.addr_mode;
+        let step = match instruction.op_code {
+            OpCode::Adc => self.adc(addr_mode),
+            OpCode::And => self.and(addr_mode),
+            OpCode::Asl => self.asl(addr_mode),
+            OpCode::Bcc => self.bcc(),
+            OpCode::Bcs => self.bcs(),
+            OpCode::Beq => self.beq(),
+            OpCode::Bit => self.bit(addr_mode),
+            OpCode::Bmi => self.bmi(),
+            OpCode::Bne => self.bne(),
+            OpCode::Bpl => self.bpl(),
+            OpCode::Brk => self.brk(),
+            OpCode::Bvc => self.bvc(),
+            OpCode::Bvs => self.bvs(),
+            OpCode::Clc => self.clc(),
+            OpCode::Cld => self.cld(),
+            OpCode::Cli => self.cli(),
+            OpCode::Clv => self.clv(),
+            OpCode::Cmp => self.cmp(addr_mode),
+            OpCode::Cpx => self.cpx(addr_mode),
+            OpCode::Cpy => self.cpy(addr_mode),
+            OpCode::Dec => self.dec(addr_mode),
+            OpCode::Dex => self.dex(),
+            OpCode::Dey => self.dey(),
+            OpCode::Eor => self.eor(addr_mode),
+            OpCode::Inc => self.inc(addr_mode),
+            OpCode::Inx => self.inx(),
+            OpCode::Iny => self.iny(),
+            OpCode::Jmp => self.jmp(addr_mode),
+            OpCode::Jsr => self.jsr(),
+            OpCode::Lda => self.lda(addr_mode),
+            OpCode::Ldx => self.ldx(addr_mode),
+            OpCode::Ldy => self.ldy(addr_mode),
+            OpCode::Lsr => self.lsr(addr_mode),
+            OpCode::Nop => self.nop(),
+            OpCode::Ora => self.ora(addr_mode),
+            OpCode::Pha => self.pha(),
+            OpCode::Php => self.php(),
+            OpCode::Pla => self.pla(),
+            OpCode::Plp => self.plp(),
+            OpCode::Rol => self.rol(addr_mode),
+            OpCode::Ror => self.ror(addr_mode),
+            OpCode::Rti => self.rti(),
+            OpCode::Rts => self.rts(),
+            OpCode::Sbc => self.sbc(addr_mode),
+            OpCode::Sec => self.sec(),
+            OpCode::Sed => self.sed(),
+            OpCode::Sei => self.sei(),
+            OpCode::Sta => self.sta(addr_mode),
+            OpCode::Stx => self.stx(addr_mode),
+            OpCode::Sty => self.sty(addr_mode),
+            OpCode::Tax => self.tax(),
+            OpCode::Tay => self.tay(),
+            OpCode::Tsx => self.tsx(),
+            OpCode::Txs => self.txs(),
+            OpCode::Txa => self.txa(),
+            OpCode::Tya => self.tya(),
+            op_code => unreachable!("{op_code} is unofficial"),
         };
 
-        self.pc += step.pc_inc as u16;
-        self.cycles_to_finish = step.cycles;
+        if !step.jumped {
+            self.pc = self.pc.wrapping_add(instruction.size as u16);
+        }
+        // indexed reads take one more across a page, branches one more when taken and another across a page
+        let page_penalty = if instruction.page_penalty { step.taken as u16 + step.page_crossed as u16 } else { 0 };
+        // oam dma started by this instruction, less the cycle this step already was
+        self.cycles_to_finish = instruction.cycles as u16 + page_penalty + self.bus.take_dma_stall() - 1;
 
         true
     }
 
-    pub fn get_instruction(&mut self, pc: u16) -> (Option<Instruction>, u8) {
+    pub fn get_instruction(&mut self, pc: u16) -> (Instruction, u8) {
         let byte_code = self.bus.read_8(pc);
         let instruction = OP_CODES[byte_code as usize];
         (instruction, byte_code)
@@ -314,57 +411,53 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(arg);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(arg, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(arg);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::AbsoluteY => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 6))
+                (value, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, extra_step) = self.addr_indirect_y();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 5 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unknown addr_mode: adc {addr_mode:?}")
         };
         
-        let prev_acc = self.acc;
-        self.acc = self.acc.wrapping_add(value);
-        if self.ps.carry() {
-            self.acc = self.acc.wrapping_add(1);
+        if self.decimal_mode() {
+            self.add_decimal(value);
+        } else {
+            self.add(value);
         }
-        self.set_zero(self.acc);
-        self.set_carry(prev_acc, self.acc);
-        self.set_overflow(self.acc, prev_acc);
-        self.set_negative(self.acc);
         step
     }
     
@@ -372,44 +465,44 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(arg);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(arg, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(arg);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::AbsoluteY => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 6))
+                (value, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, extra_step) = self.addr_indirect_y();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 5 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unknown addr_mode: adc {addr_mode:?}")
         };
@@ -427,26 +520,26 @@ impl Cpu {
             
             self.set_zero(self.acc);
             self.set_negative(self.acc);
-            Step::next(1, 2)
+            Step::done()
         } else {
             let (addr, step) = match addr_mode {
                 AddrMode::Zp => {
                     let addr = self.bus.read_8(self.pc + 1) as u16;
-                    (addr, Step::next(2, 5))
+                    (addr, Step::done())
                 }
                 AddrMode::ZpX => {
                     let addr = self.bus.read_8(self.pc + 1);
                     let addr = addr.wrapping_add(self.x) as u16;
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::Absolute => {
                     let addr = self.bus.read_16(self.pc + 1);
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::AbsoluteX => {
                     let arg = self.bus.read_16(self.pc + 1);
                     let (addr, _) = self.addr_absolute_with_offset(arg, self.x as u16);
-                    (addr, Step::next(2, 7))
+                    (addr, Step::done())
                 }
                 _ => panic!("unknown addr_mode: asl {addr_mode:?}")
             };
@@ -454,6 +547,7 @@ impl Cpu {
             let mut value = self.bus.read_8(addr);
             self.ps.set_carry(value.bit(7));
             value <<= 1;
+            self.set_zero(value);
             self.set_negative(value);
             self.bus.write(addr, value);
             
@@ -461,16 +555,16 @@ impl Cpu {
         }
     }
     
-    fn bcc(&mut self, ) -> Step {
-        unimplemented!()
+    fn bcc(&mut self) -> Step {
+        self.branch(!self.ps.carry())
     }
     
-    fn bcs(&mut self, ) -> Step {
-        unimplemented!()
+    fn bcs(&mut self) -> Step {
+        self.branch(self.ps.carry())
     }
     
-    fn beq(&mut self, ) -> Step {
-        unimplemented!()
+    fn beq(&mut self) -> Step {
+        self.branch(self.ps.zero())
     }
     
     fn bit(&mut self, addr_mode: AddrMode) -> Step {
@@ -478,12 +572,12 @@ impl Cpu {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.bus.read_8(addr as u16);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             },
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             _ => panic!("unknown addr_mode: bit {addr_mode:?}")
         };
@@ -496,180 +590,139 @@ impl Cpu {
         step
     }
     
-    fn bmi(&mut self, ) -> Step {
-        unimplemented!()
+    fn bmi(&mut self) -> Step {
+        self.branch(self.ps.negative())
     }
     
     fn bne(&mut self) -> Step {
-        if self.ps.zero() == false {
-            let offset: i8 = unsafe {
-                std::mem::transmute(self.bus.read_8(self.pc + 1))
-            };
-            
-            let mut addr = self.pc as i32;
-            addr += offset as i32;
-            self.pc = addr as u16;
-        }
-        // todo: cycles can be 2, 3 or 4
-        // https://www.nesdev.org/obelisk-6502-guide/reference.html#BNE
-        Step::next(2, 2)
+        self.branch(!self.ps.zero())
     }
     
     fn bpl(&mut self) -> Step {
-        if self.ps.negative() == false {
-            let offset: i8 = unsafe {
-                std::mem::transmute(self.bus.read_8(self.pc + 1))
-            };
-            
-            let mut addr = self.pc as i32;
-            addr += offset as i32;
-            self.pc = addr as u16;
-        }
-        // todo: cycles can be 2, 3 or 4
-        // https://www.nesdev.org/obelisk-6502-guide/reference.html#BPL
-        Step::next(2, 2)
+        self.branch(!self.ps.negative())
     }
     
+    // https://www.nesdev.org/wiki/Visual6502wiki/6502_BRK_and_B_bit
     fn brk(&mut self) -> Step {
-        unimplemented!()
+        // the byte after brk is skipped on return
+        let ret = self.pc.wrapping_add(2);
+        self.push((ret >> 8) as u8);
+        self.push(ret as u8);
+        self.push(self.ps.get_reg() | 0b0011_0000);
+        self.ps.set_irqb(true);
+        self.pc = self.bus.read_16(IRQ_VECTOR);
+        Step::jump()
     }
     
     fn bvc(&mut self) -> Step {
-        unimplemented!()
+        self.branch(!self.ps.overflow())
     }
     
     fn bvs(&mut self) -> Step {
-        unimplemented!()
+        self.branch(self.ps.overflow())
     }
     
     fn clc(&mut self) -> Step {
         self.ps.set_carry(false);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn cld(&mut self) -> Step {
         self.ps.set_decimal(false);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn cli(&mut self) -> Step {
         self.ps.set_irqb(false);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn clv(&mut self) -> Step {
         self.ps.set_overflow(false);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn cmp(&mut self, addr_mode: AddrMode) -> Step {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(arg);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(arg, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(arg);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::AbsoluteY => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 6))
+                (value, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, extra_step) = self.addr_indirect_y();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 5 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unknown addr_mode: adc {addr_mode:?}")
         };
         
-        // TODO: :(
-        let result = self.acc.wrapping_sub(value);
-        
-        if result < value {
-            self.ps.set_carry(false);
-            self.ps.set_zero(false);
-            self.ps.set_negative(result.bit(7));
-        } else if result == value {
-            self.ps.set_carry(true);
-            self.ps.set_zero(true);
-            self.ps.set_negative(false);
-        } else {
-            self.ps.set_carry(true);
-            self.ps.set_zero(false);
-            self.ps.set_negative(result.bit(7));
-        }
-        // self.ps.set_carry(self.acc >= value);
-        // self.ps.set_zero(self.acc == value);
-        // 
-        // self.ps.set_negative(result.bit(7));
-        
+        self.compare(self.acc, value);
         step
-        
     }
     
     fn cpx(&mut self, addr_mode: AddrMode) -> Step {
-        let (value, step) = match addr_mode {
-            AddrMode::Immediate => {
-                let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
-            }
-            _ => panic!("unimplemented: cpx {addr_mode:?}")
-        };
-        self.set_carry(self.x, value);
-        self.set_zero(value);
-        self.set_negative(value);
+        let (value, step) = self.operand(addr_mode);
+        self.compare(self.x, value);
         step
     }
     
     fn cpy(&mut self, addr_mode: AddrMode) -> Step {
-        unimplemented!()
+        let (value, step) = self.operand(addr_mode);
+        self.compare(self.y, value);
+        step
     }
     
     fn dec(&mut self, addr_mode: AddrMode) -> Step {
         let (addr, step) = match addr_mode {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1) as u16;
-                (addr, Step::next(2, 5))
+                (addr, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let addr = addr.wrapping_add(self.x) as u16;
-                (addr, Step::next(2, 6))
+                (addr, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
-                (addr, Step::next(3, 6))
+                (addr, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let addr = addr.wrapping_add(self.x as u16);
-                (addr, Step::next(3, 7))
+                (addr, Step::done())
             }
             _ => panic!("unimplemented: cpx {addr_mode:?}")
         };
@@ -690,39 +743,43 @@ impl Cpu {
         self.x = self.x.wrapping_sub(1);
         self.set_zero(self.x);
         self.set_negative(self.x);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn dey(&mut self) -> Step {
         self.y = self.y.wrapping_sub(1);
         self.set_zero(self.y);
         self.set_negative(self.y);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn eor(&mut self, addr_mode: AddrMode) -> Step {
-        unimplemented!()
+        let (value, step) = self.operand(addr_mode);
+        self.acc ^= value;
+        self.set_zero(self.acc);
+        self.set_negative(self.acc);
+        step
     }
     
     fn inc(&mut self, addr_mode: AddrMode) -> Step {
         let (addr, step) = match addr_mode {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1) as u16;
-                (addr, Step::next(2, 5))
+                (addr, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let addr = addr.wrapping_add(self.x) as u16;
-                (addr, Step::next(2, 6))
+                (addr, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
-                (addr, Step::next(3, 6))
+                (addr, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let addr = addr.wrapping_add(self.x as u16);
-                (addr, Step::next(3, 7))
+                (addr, Step::done())
             }
             _ => panic!("unimplemented: cpx {addr_mode:?}")
         };
@@ -743,66 +800,83 @@ impl Cpu {
         self.x = self.x.wrapping_add(1);
         self.set_zero(self.x);
         self.set_negative(self.x);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn iny(&mut self) -> Step {
         self.y = self.y.wrapping_add(1);
         self.set_zero(self.y);
         self.set_negative(self.y);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn jmp(&mut self, addr_mode: AddrMode) -> Step {
-        unimplemented!()
+        self.pc = match addr_mode {
+            AddrMode::Absolute => self.bus.read_16(self.pc + 1),
+            AddrMode::Indirect => {
+                let pointer = self.bus.read_16(self.pc + 1);
+                // the msb comes from the start of the page when the pointer is at its end
+                let lsb = self.bus.read_8(pointer);
+                let msb = self.bus.read_8((pointer & 0xFF00) | (pointer as u8).wrapping_add(1) as u16);
+                u16::from_le_bytes([lsb, msb])
+            }
+            _ => panic!("unknown addr_mode: jmp {addr_mode:?}")
+        };
+        Step::jump()
     }
     
     fn jsr(&mut self) -> Step {
-        unimplemented!()
+        let addr = self.bus.read_16(self.pc + 1);
+        // the last byte of the jsr, rts adds one
+        let ret = self.pc.wrapping_add(2);
+        self.push((ret >> 8) as u8);
+        self.push(ret as u8);
+        self.pc = addr;
+        Step::jump()
     }
     
     fn lda(&mut self, addr_mode: AddrMode) -> Step {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(addr);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(addr, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(addr, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::AbsoluteY => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(addr, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 6))
+                (value, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, extra_step) = self.addr_indirect_y();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 5 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -817,28 +891,28 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(addr);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpY => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(addr, self.y);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteY => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(addr, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -853,28 +927,28 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(addr);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(addr, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(addr, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(3, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -892,26 +966,26 @@ impl Cpu {
             
             self.set_zero(self.acc);
             self.set_negative(self.acc);
-            Step::next(1, 2)
+            Step::done()
         } else {
             let (addr, step) = match addr_mode {
                 AddrMode::Zp => {
                     let addr = self.bus.read_8(self.pc + 1) as u16;
-                    (addr, Step::next(2, 5))
+                    (addr, Step::done())
                 }
                 AddrMode::ZpX => {
                     let addr = self.bus.read_8(self.pc + 1);
                     let addr = addr.wrapping_add(self.x) as u16;
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::Absolute => {
                     let addr = self.bus.read_16(self.pc + 1);
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::AbsoluteX => {
                     let arg = self.bus.read_16(self.pc + 1);
                     let (addr, _) = self.addr_absolute_with_offset(arg, self.x as u16);
-                    (addr, Step::next(2, 7))
+                    (addr, Step::done())
                 }
                 _ => panic!("unknown addr_mode: lsr {addr_mode:?}")
             };
@@ -919,6 +993,7 @@ impl Cpu {
             let mut value = self.bus.read_8(addr);
             self.ps.set_carry(value.bit(0));
             value >>= 1;
+            self.set_zero(value);
             self.set_negative(value);
             self.bus.write(addr, value);
             
@@ -927,35 +1002,38 @@ impl Cpu {
     }
     
     fn nop(&self) -> Step {
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn ora(&mut self, addr_mode: AddrMode) -> Step {
-        unimplemented!()
+        let (value, step) = self.operand(addr_mode);
+        self.acc |= value;
+        self.set_zero(self.acc);
+        self.set_negative(self.acc);
+        step
     }
     
     fn pha(&mut self) -> Step {
-        unimplemented!();
-        Step::next(1, 3)
+        self.push(self.acc);
+        Step::done()
     }
     
     fn php(&mut self) -> Step {
-        unimplemented!();
-        Step::next(1, 3)
+        // pushed with the break flag and bit 5 set
+        self.push(self.ps.get_reg() | 0b0011_0000);
+        Step::done()
     }
     
     fn pla(&mut self) -> Step {
-        unimplemented!();
-        // self.set_zero(value);
-        // self.set_negative(value);
-        Step::next(1, 4)
+        self.acc = self.pull();
+        self.set_zero(self.acc);
+        self.set_negative(self.acc);
+        Step::done()
     }
     
     fn plp(&mut self) -> Step {
-        unimplemented!();
-        // self.set_zero(value);
-        // self.set_negative(value);
-        Step::next(1, 4)
+        self.pull_status();
+        Step::done()
     }
     
     fn rol(&mut self, addr_mode: AddrMode) -> Step {
@@ -966,26 +1044,26 @@ impl Cpu {
             self.acc.set_bit(0, carry);
             self.set_zero(self.acc);
             self.set_negative(self.acc);
-            Step::next(1, 2)
+            Step::done()
         } else {
             let (addr, step) = match addr_mode {
                 AddrMode::Zp => {
                     let addr = self.bus.read_8(self.pc + 1) as u16;
-                    (addr, Step::next(2, 5))
+                    (addr, Step::done())
                 }
                 AddrMode::ZpX => {
                     let addr = self.bus.read_8(self.pc + 1);
                     let addr = addr.wrapping_add(self.x) as u16;
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::Absolute => {
                     let addr = self.bus.read_16(self.pc + 1);
-                    (addr, Step::next(3, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::AbsoluteX => {
                     let arg = self.bus.read_16(self.pc + 1);
                     let (addr, _) = self.addr_absolute_with_offset(arg, self.x as u16);
-                    (addr, Step::next(3, 7))
+                    (addr, Step::done())
                 }
                 _ => panic!("unknown addr_mode: rol {addr_mode:?}")
             };
@@ -1011,26 +1089,26 @@ impl Cpu {
             self.acc.set_bit(7, carry);
             self.set_zero(self.acc);
             self.set_negative(self.acc);
-            Step::next(1, 2)
+            Step::done()
         } else {
             let (addr, step) = match addr_mode {
                 AddrMode::Zp => {
                     let addr = self.bus.read_8(self.pc + 1) as u16;
-                    (addr, Step::next(2, 5))
+                    (addr, Step::done())
                 }
                 AddrMode::ZpX => {
                     let addr = self.bus.read_8(self.pc + 1);
                     let addr = addr.wrapping_add(self.x) as u16;
-                    (addr, Step::next(2, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::Absolute => {
                     let addr = self.bus.read_16(self.pc + 1);
-                    (addr, Step::next(3, 6))
+                    (addr, Step::done())
                 }
                 AddrMode::AbsoluteX => {
                     let arg = self.bus.read_16(self.pc + 1);
                     let (addr, _) = self.addr_absolute_with_offset(arg, self.x as u16);
-                    (addr, Step::next(3, 7))
+                    (addr, Step::done())
                 }
                 _ => panic!("unknown addr_mode: rol {addr_mode:?}")
             };
@@ -1048,120 +1126,123 @@ impl Cpu {
         }
     }
     
-    fn rti(&mut self, ) -> Step {
-        unimplemented!()
+    fn rti(&mut self) -> Step {
+        self.pull_status();
+        let lsb = self.pull();
+        let msb = self.pull();
+        self.pc = u16::from_le_bytes([lsb, msb]);
+        Step::jump()
     }
     
-    fn rts(&mut self, ) -> Step {
-        unimplemented!()
+    fn rts(&mut self) -> Step {
+        let lsb = self.pull();
+        let msb = self.pull();
+        self.pc = u16::from_le_bytes([lsb, msb]).wrapping_add(1);
+        Step::jump()
     }
     
     fn sbc(&mut self, addr_mode: AddrMode) -> Step {
         let (value, step) = match addr_mode {
             AddrMode::Immediate => {
                 let value = self.bus.read_8(self.pc + 1);
-                (value, Step::next(2, 2))
+                (value, Step::done())
             }
             AddrMode::Zp => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp(arg);
-                (value, Step::next(2, 3))
+                (value, Step::done())
             }
             AddrMode::ZpX => {
                 let arg = self.bus.read_8(self.pc + 1);
                 let value = self.value_zp_offset(arg, self.x);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::Absolute => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let value = self.bus.read_8(arg);
-                (value, Step::next(2, 4))
+                (value, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.x as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::AbsoluteY => {
                 let arg = self.bus.read_16(self.pc + 1);
                 let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.y as u16);
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 4 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 6))
+                (value, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, extra_step) = self.addr_indirect_y();
                 let value = self.bus.read_8(addr);
-                (value, Step::next(2, 5 + extra_step))
+                (value, Step::page_cross(extra_step))
             }
             _ => panic!("unknown addr_mode: adc {addr_mode:?}")
         };
-        // TODO: yek
-        let prev_acc = self.acc;
-        self.acc = self.acc.wrapping_sub(value);
-        if !self.ps.carry() {
-            self.acc = self.acc.wrapping_sub(1);
+        if self.decimal_mode() {
+            self.subtract_decimal(value);
+        } else {
+            // a - b - !c is a + !b + c
+            self.add(!value);
         }
-        self.set_zero(self.acc);
-        self.set_carry(prev_acc, self.acc);
-        self.set_overflow(self.acc, prev_acc);
-        self.set_negative(self.acc);
         step
     }
     
     
     fn sec(&mut self) -> Step {
         self.ps.set_carry(true);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn sed(&mut self) -> Step {
         self.ps.set_decimal(true);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn sei(&mut self) -> Step {
         self.ps.set_irqb(true);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn sta(&mut self, addr_mode: AddrMode) -> Step {
         let (value, step) = match addr_mode {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1) as u16;
-                (addr, Step::next(2, 3))
+                (addr, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let addr = addr.wrapping_add(self.x) as u16;
-                (addr, Step::next(2, 3))
+                (addr, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
-                (addr, Step::next(3, 4))
+                (addr, Step::done())
             }
             AddrMode::AbsoluteX => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let addr = addr.wrapping_add(self.x as u16);
-                (addr, Step::next(3, 5))
+                (addr, Step::done())
             }
             AddrMode::AbsoluteY => {
                 let addr = self.bus.read_16(self.pc + 1);
                 let addr = addr.wrapping_add(self.y as u16);
-                (addr, Step::next(3, 5))
+                (addr, Step::done())
             }
             AddrMode::IndirectX => {
                 let addr = self.addr_indirect_x();
-                (addr, Step::next(2, 6))
+                (addr, Step::done())
             }
             AddrMode::IndirectY => {
                 let (addr, _) = self.addr_indirect_y();
-                (addr, Step::next(2, 6))
+                (addr, Step::done())
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -1174,16 +1255,16 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1) as u16;
-                (addr, Step::next(2, 4))
+                (addr, Step::done())
             }
             AddrMode::ZpY => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let addr = addr.wrapping_add(self.y) as u16;
-                (addr, Step::next(2, 4))
+                (addr, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
-                (addr, Step::next(3, 4))
+                (addr, Step::done())
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -1196,16 +1277,16 @@ impl Cpu {
         let (value, step) = match addr_mode {
             AddrMode::Zp => {
                 let addr = self.bus.read_8(self.pc + 1) as u16;
-                (addr, Step::next(2, 4))
+                (addr, Step::done())
             }
             AddrMode::ZpX => {
                 let addr = self.bus.read_8(self.pc + 1);
                 let addr = addr.wrapping_add(self.x) as u16;
-                (addr, Step::next(2, 4))
+                (addr, Step::done())
             }
             AddrMode::Absolute => {
                 let addr = self.bus.read_16(self.pc + 1);
-                (addr, Step::next(3, 4))
+                (addr, Step::done())
             }
             _ => panic!("unimplemented: lda {addr_mode:?}")
         };
@@ -1218,33 +1299,33 @@ impl Cpu {
         self.x = self.acc;
         self.set_zero(self.x);
         self.set_negative(self.x);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn tay(&mut self) -> Step {
         self.y = self.acc;
         self.set_zero(self.y);
         self.set_negative(self.y);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn tsx(&mut self) -> Step {
         self.x = self.sp;
         self.set_zero(self.x);
         self.set_negative(self.x);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn txa(&mut self) -> Step {
         self.acc = self.x;
         self.set_zero(self.acc);
         self.set_negative(self.acc);
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn txs(&mut self) -> Step {
         self.sp = self.x;
-        Step::next(1, 2)
+        Step::done()
     }
     
     fn tya(&mut self) -> Step {
@@ -1252,18 +1333,158 @@ impl Cpu {
         self.set_zero(self.acc);
         self.set_negative(self.acc);
         
-        Step::next(1, 2)
+        Step::done()
     }
     
     
     // helper
-    fn value_zp(&self, addr: u8) -> u8 {
+    fn push(&mut self, value: u8) {
+        self.bus.write(STACK_PAGE + self.sp as u16, value);
+        self.sp = self.sp.wrapping_sub(1);
+    }
+
+    fn pull(&mut self) -> u8 {
+        self.sp = self.sp.wrapping_add(1);
+        self.bus.read_8(STACK_PAGE + self.sp as u16)
+    }
+
+    /// the break flag and bit 5 only exist on the stack
+    fn pull_status(&mut self) {
+        let value = self.pull();
+        self.ps.set_reg(value & 0b1100_1111);
+    }
+
+    /// moves pc by the relative operand when `condition` holds
+    // https://www.nesdev.org/obelisk-6502-guide/reference.html#BNE
+    fn branch(&mut self, condition: bool) -> Step {
+        if !condition {
+            return Step::done();
+        }
+        let offset = self.bus.read_8(self.pc + 1) as i8;
+        // relative to the instruction after the branch
+        let next = self.pc.wrapping_add(2);
+        self.pc = next.wrapping_add(offset as u16);
+        Step::branch(next & 0xFF00 != self.pc & 0xFF00)
+    }
+
+    fn compare(&mut self, reg: u8, value: u8) {
+        self.set_carry(reg, value);
+        self.set_zero(reg.wrapping_sub(value));
+        self.set_negative(reg.wrapping_sub(value));
+    }
+
+    fn decimal_mode(&self) -> bool {
+        self.variant == Variant::Nmos6502 && self.ps.decimal()
+    }
+
+    /// binary adc, sbc is this with the operand inverted
+    fn add(&mut self, value: u8) {
+        let sum = self.acc as u16 + value as u16 + self.ps.carry() as u16;
+        let result = sum as u8;
+        // both operands have the same sign and the result doesn't
+        self.ps.set_overflow((self.acc ^ result) & (value ^ result) & 0x80 != 0);
+        self.ps.set_carry(sum > 0xFF);
+        self.acc = result;
+        self.set_zero(self.acc);
+        self.set_negative(self.acc);
+    }
+
+    // http://www.6502.org/tutorials/decimal_mode.html#A
+    fn add_decimal(&mut self, value: u8) {
+        let (acc, operand, carry) = (self.acc as i16, value as i16, self.ps.carry() as i16);
+        let mut low = (acc & 0x0F) + (operand & 0x0F) + carry;
+        if low >= 0x0A {
+            low = ((low + 0x06) & 0x0F) + 0x10;
+        }
+        // n and v come from the sum before the high digit is adjusted, z from the binary sum
+        let signed = (self.acc & 0xF0) as i8 as i16 + (value & 0xF0) as i8 as i16 + low;
+        self.ps.set_negative(signed & 0x80 != 0);
+        self.ps.set_overflow(!(-128..=127).contains(&signed));
+        self.set_zero(self.acc.wrapping_add(value).wrapping_add(carry as u8));
+
+        let mut sum = (acc & 0xF0) + (operand & 0xF0) + low;
+        if sum >= 0xA0 {
+            sum += 0x60;
+        }
+        self.ps.set_carry(sum >= 0x100);
+        self.acc = sum as u8;
+    }
+
+    /// all flags are the ones of the binary sbc
+    fn subtract_decimal(&mut self, value: u8) {
+        let (acc, operand, carry) = (self.acc as i16, value as i16, self.ps.carry() as i16);
+        self.add(!value);
+
+        let mut low = (acc & 0x0F) - (operand & 0x0F) + carry - 1;
+        if low < 0 {
+            low = ((low - 0x06) & 0x0F) - 0x10;
+        }
+        let mut difference = (acc & 0xF0) - (operand & 0xF0) + low;
+        if difference < 0 {
+            difference -= 0x60;
+        }
+        self.acc = difference as u8;
+    }
+
+    /// the value a read instruction works on
+    fn operand(&mut self, addr_mode: AddrMode) -> (u8, Step) {
+        match addr_mode {
+            AddrMode::Immediate => (self.bus.read_8(self.pc + 1), Step::done()),
+            AddrMode::Zp => {
+                let arg = self.bus.read_8(self.pc + 1);
+                (self.value_zp(arg), Step::done())
+            }
+            AddrMode::ZpX => {
+                let arg = self.bus.read_8(self.pc + 1);
+                (self.value_zp_offset(arg, self.x), Step::done())
+            }
+            AddrMode::ZpY => {
+                let arg = self.bus.read_8(self.pc + 1);
+                (self.value_zp_offset(arg, self.y), Step::done())
+            }
+            AddrMode::Absolute => {
+                let arg = self.bus.read_16(self.pc + 1);
+                (self.bus.read_8(arg), Step::done())
+            }
+            AddrMode::AbsoluteX => {
+                let arg = self.bus.read_16(self.pc + 1);
+                let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.x as u16);
+                (self.bus.read_8(addr), Step::page_cross(extra_step))
+            }
+            AddrMode::AbsoluteY => {
+                let arg = self.bus.read_16(self.pc + 1);
+                let (addr, extra_step) = self.addr_absolute_with_offset(arg, self.y as u16);
+                (self.bus.read_8(addr), Step::page_cross(extra_step))
+            }
+            AddrMode::IndirectX => {
+                let addr = self.addr_indirect_x();
+                (self.bus.read_8(addr), Step::done())
+            }
+            AddrMode::IndirectY => {
+                let (addr, extra_step) = self.addr_indirect_y();
+                (self.bus.read_8(addr), Step::page_cross(extra_step))
+            }
+            _ => panic!("no operand to read: {addr_mode:?}")
+        }
+    }
+
+    /// pushes pc and status and jumps to the handler at `vector`
+    fn interrupt(&mut self, vector: u16) {
+        self.push((self.pc >> 8) as u8);
+        self.push(self.pc as u8);
+        // break flag clear, bit 5 always set
+        self.push((self.ps.get_reg() & 0b1110_1111) | 0b0010_0000);
+        self.ps.set_irqb(true);
+        self.pc = self.bus.read_16(vector);
+    }
+
+    fn value_zp(&mut self, addr: u8) -> u8 {
         self.value_zp_offset(addr, 0)
     }
     
-    fn value_zp_offset(&self, addr: u8, offset: u8) -> u8 {
+    fn value_zp_offset(&mut self, addr: u8, offset: u8) -> u8 {
         let addr = addr.wrapping_add(offset);
-        self.bus.ram[addr as usize]
+        self.bus.read_8(addr as u16)
     }
     
     /// returns address with offset and if it needed an extra cycle on page boundary cross
@@ -1277,16 +1498,43 @@ impl Cpu {
     fn addr_indirect_x(&mut self) -> u16 {
         let addr = self.bus.read_8(self.pc + 1);
         let addr = addr.wrapping_add(self.x);
-        self.bus.read_16(addr as u16)
-        
+        self.pointer_zp(addr)
     }
     
     fn addr_indirect_y(&mut self) -> (u16, ExtraStep) {
-        let param = self.bus.read_16(self.pc + 1);
-        let fetched_addr = self.bus.read_16(param);
-        let addr = fetched_addr + self.y as u16;
-        let page_index = (addr & 0xFF00) / 256;
-        let extra_step = if page_index != ((addr & 0xFF00) / 256) { 1 } else { 0 };
-        (addr, extra_step)
+        let param = self.bus.read_8(self.pc + 1);
+        let fetched_addr = self.pointer_zp(param);
+        self.addr_absolute_with_offset(fetched_addr, self.y as u16)
+    }
+
+    /// the pointer at `addr`, the msb wraps around to $00 instead of leaving the zero page
+    fn pointer_zp(&mut self, addr: u8) -> u16 {
+        let lsb = self.bus.read_8(addr as u16);
+        let msb = self.bus.read_8(addr.wrapping_add(1) as u16);
+        u16::from_le_bytes([lsb, msb])
+    }
+}
+
+impl SaveState for Cpu {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u16(self.pc);
+        writer.u8(self.sp);
+        writer.u8(self.acc);
+        writer.u8(self.x);
+        writer.u8(self.y);
+        writer.u8(self.ps.reg);
+        writer.u16(self.cycles_to_finish);
+        self.bus.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.pc = reader.u16()?;
+        self.sp = reader.u8()?;
+        self.acc = reader.u8()?;
+        self.x = reader.u8()?;
+        self.y = reader.u8()?;
+        self.ps.reg = reader.u8()?;
+        self.cycles_to_finish = reader.u16()?;
+        self.bus.load_state(reader)
     }
 }
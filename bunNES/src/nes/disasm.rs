@@ -0,0 +1,186 @@
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use crate::nes::opcodes::{AddrMode, Instruction, OpCode, OP_CODES};
+
+/// assembler dialect of the output
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum Syntax {
+    /// lowercase, `a:` prefix for absolute operands that would fit in the zero page
+    #[default]
+    Ca65,
+    /// uppercase, instructions asm6 would shrink to zero page are kept as `.db`
+    Asm6,
+    /// like nestest.log, without the memory values
+    Nestest,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Disassembled {
+    pub addr: u16,
+    /// opcode followed by the operand bytes
+    pub bytes: Vec<u8>,
+    pub mnemonic: String,
+    /// formatted for the syntax, empty for implied instructions
+    pub operand: String,
+    /// where a branch, jmp or jsr goes
+    pub target: Option<u16>,
+}
+
+impl Display for Disassembled {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.operand.is_empty() {
+            write!(f, "{}", self.mnemonic)
+        } else {
+            write!(f, "{} {}", self.mnemonic, self.operand)
+        }
+    }
+}
+
+/// the instruction at `addr`, `read` is only called for the bytes it's made of
+pub fn disassemble<F: FnMut(u16) -> u8>(mut read: F, addr: u16, syntax: Syntax) -> Disassembled {
+    let opcode = read(addr);
+    let instruction = OP_CODES[opcode as usize];
+    let bytes: Vec<u8> = std::iter::once(opcode)
+        .chain((1..instruction.size as u16).map(|i| read(addr.wrapping_add(i))))
+        .collect();
+    // neither assembler takes unofficial opcodes without extra setup
+    if !instruction.official && syntax != Syntax::Nestest {
+        return data(addr, bytes, syntax);
+    }
+
+    let value = match bytes.len() {
+        2 => bytes[1] as u16,
+        3 => u16::from_le_bytes([bytes[1], bytes[2]]),
+        _ => 0,
+    };
+
+    let wide = instruction.size == 3 && value <= 0xFF;
+    if syntax == Syntax::Asm6 && wide && !matches!(instruction.op_code, OpCode::Jmp | OpCode::Jsr) {
+        return data(addr, bytes, syntax);
+    }
+
+    let target = target(&instruction, addr, value);
+    Disassembled {
+        addr,
+        mnemonic: mnemonic(&instruction, syntax),
+        operand: operand(&instruction, value, target, wide, None, syntax),
+        bytes,
+        target,
+    }
+}
+
+/// listing of a whole prg bank mapped at `origin`. jsr, jmp and branch targets
+/// inside the bank get a label, `L8000:` style
+pub fn disassemble_bank(bank: &[u8], origin: u16, syntax: Syntax) -> String {
+    let read = |addr: u16| bank.get(addr.wrapping_sub(origin) as usize).copied().unwrap_or(0);
+    let end = origin as usize + bank.len();
+
+    let mut lines = Vec::new();
+    let mut addr = origin as usize;
+    while addr < end {
+        let mut line = disassemble(read, addr as u16, syntax);
+        // an instruction cut off by the end of the bank
+        if addr + line.bytes.len() > end {
+            line = data(addr as u16, bank[addr - origin as usize..].to_vec(), syntax);
+        }
+        addr += line.bytes.len();
+        lines.push(line);
+    }
+
+    // only targets that land on the start of an instruction can be labeled
+    let starts: BTreeSet<u16> = lines.iter().map(|line| line.addr).collect();
+    let labels: BTreeSet<u16> = lines.iter()
+        .filter_map(|line| line.target)
+        .filter(|target| starts.contains(target))
+        .collect();
+
+    let mut listing = format!("{} ${origin:04X}\n", directive(".org", syntax));
+    for line in lines {
+        if labels.contains(&line.addr) {
+            listing.push_str(&format!("{}:\n", label(line.addr)));
+        }
+        let text = match line.target {
+            Some(target) if labels.contains(&target) => {
+                let instruction = OP_CODES[line.bytes[0] as usize];
+                let operand = operand(&instruction, 0, Some(target), false, Some(&label(target)), syntax);
+                format!("{} {operand}", line.mnemonic)
+            }
+            _ => line.to_string(),
+        };
+        listing.push_str(&format!("    {text}\n"));
+    }
+    listing
+}
+
+fn label(addr: u16) -> String {
+    format!("L{addr:04X}")
+}
+
+fn target(instruction: &Instruction, addr: u16, value: u16) -> Option<u16> {
+    match (instruction.op_code, instruction.addr_mode) {
+        (_, AddrMode::Relative) => Some(addr.wrapping_add(2).wrapping_add(value as i8 as u16)),
+        (OpCode::Jmp | OpCode::Jsr, AddrMode::Absolute) => Some(value),
+        _ => None,
+    }
+}
+
+fn operand(instruction: &Instruction, value: u16, target: Option<u16>, wide: bool, label: Option<&str>, syntax: Syntax) -> String {
+    let register = |name: &str| match syntax {
+        Syntax::Ca65 => name.to_lowercase(),
+        _ => name.to_string(),
+    };
+    let absolute = || match label {
+        Some(label) => label.to_string(),
+        // ca65 would turn these into zero page instructions
+        None if wide && syntax == Syntax::Ca65 => format!("a:${value:04X}"),
+        None => format!("${value:04X}"),
+    };
+
+    match instruction.addr_mode {
+        AddrMode::Implicit => String::new(),
+        AddrMode::Accumulator => register("A"),
+        AddrMode::Immediate => format!("#${value:02X}"),
+        AddrMode::Zp => format!("${value:02X}"),
+        AddrMode::ZpX => format!("${value:02X},{}", register("X")),
+        AddrMode::ZpY => format!("${value:02X},{}", register("Y")),
+        AddrMode::Relative => match label {
+            Some(label) => label.to_string(),
+            None => format!("${:04X}", target.unwrap_or_default()),
+        },
+        AddrMode::Absolute => absolute(),
+        AddrMode::AbsoluteX => format!("{},{}", absolute(), register("X")),
+        AddrMode::AbsoluteY => format!("{},{}", absolute(), register("Y")),
+        AddrMode::Indirect => format!("(${value:04X})"),
+        AddrMode::IndirectX => format!("(${value:02X},{})", register("X")),
+        AddrMode::IndirectY => format!("(${value:02X}),{}", register("Y")),
+    }
+}
+
+fn mnemonic(instruction: &Instruction, syntax: Syntax) -> String {
+    match (syntax, instruction.op_code) {
+        (Syntax::Ca65, op_code) => op_code.to_string().to_lowercase(),
+        (Syntax::Asm6, op_code) => op_code.to_string(),
+        // nestest marks unofficial opcodes with a star and calls isc isb
+        (Syntax::Nestest, _) if instruction.official => instruction.op_code.to_string(),
+        (Syntax::Nestest, OpCode::Isc) => "*ISB".to_string(),
+        (Syntax::Nestest, op_code) => format!("*{op_code}"),
+    }
+}
+
+fn directive(name: &str, syntax: Syntax) -> String {
+    match syntax {
+        Syntax::Ca65 => name.to_string(),
+        _ => name.to_uppercase(),
+    }
+}
+
+/// raw bytes, for what the assembler couldn't reproduce
+fn data(addr: u16, bytes: Vec<u8>, syntax: Syntax) -> Disassembled {
+    let mnemonic = match syntax {
+        Syntax::Ca65 => ".byte".to_string(),
+        Syntax::Asm6 => ".DB".to_string(),
+        Syntax::Nestest => "???".to_string(),
+    };
+    let operand = bytes.iter().map(|byte| format!("${byte:02X}")).collect::<Vec<_>>().join(", ");
+    Disassembled { addr, bytes, mnemonic, operand, target: None }
+}
@@ -0,0 +1,110 @@
+use std::any::Any;
+use crate::nes::input::{InputDevice, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Arkanoid_controller
+
+/// potentiometer range of a typical controller, left to right
+pub const PADDLE_MIN: u8 = 0x62;
+pub const PADDLE_MAX: u8 = 0xF2;
+
+#[derive(Debug)]
+pub struct ArkanoidPaddle {
+    /// famicom version on the expansion port: button on $4016 D1, position on $4017 D1.
+    /// the NES version has the position on D3 and the button on D4
+    famicom: bool,
+    position: u8,
+    button: bool,
+    /// position latched on strobe, shifted out msb first and inverted
+    shift_register: u8,
+}
+
+impl Default for ArkanoidPaddle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArkanoidPaddle {
+    /// NES controller, usually in port two
+    pub fn new() -> ArkanoidPaddle {
+        ArkanoidPaddle {
+            famicom: false,
+            position: PADDLE_MIN,
+            button: false,
+            shift_register: 0,
+        }
+    }
+
+    /// famicom controller on the expansion port
+    pub fn famicom() -> ArkanoidPaddle {
+        ArkanoidPaddle {
+            famicom: true,
+            ..ArkanoidPaddle::new()
+        }
+    }
+
+    /// raw potentiometer value, see [`PADDLE_MIN`] and [`PADDLE_MAX`]
+    pub fn set_position(&mut self, position: u8) {
+        self.position = position;
+    }
+
+    pub fn set_button(&mut self, pressed: bool) {
+        self.button = pressed;
+    }
+}
+
+impl InputDevice for ArkanoidPaddle {
+    fn strobe(&mut self, out: u8) {
+        if out & 1 == 1 {
+            self.shift_register = !self.position;
+        }
+    }
+
+    fn poll(&mut self, addr: u16, screen: &Screen) -> u8 {
+        let value = self.peek(addr, screen);
+        if !(self.famicom && addr == 0x4016) {
+            self.shift_register <<= 1;
+        }
+        value
+    }
+
+    fn peek(&self, addr: u16, _screen: &Screen) -> u8 {
+        let button = self.button as u8;
+        if self.famicom && addr == 0x4016 {
+            return button << 1;
+        }
+
+        let data = self.shift_register >> 7;
+        if self.famicom {
+            data << 1
+        } else {
+            data << 3 | button << 4
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "arkanoid paddle"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for ArkanoidPaddle {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.bool(self.famicom);
+        writer.u8(self.position);
+        writer.bool(self.button);
+        writer.u8(self.shift_register);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.famicom = reader.bool()?;
+        self.position = reader.u8()?;
+        self.button = reader.bool()?;
+        self.shift_register = reader.u8()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::any::Any;
+use bitflags::bitflags;
+use crate::nes::input::{InputDevice, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Standard_controller
+
+bitflags! {
+    /// buttons in the order they are shifted out
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+    pub struct Buttons: u8 {
+        const A      = 0b0000_0001;
+        const B      = 0b0000_0010;
+        const SELECT = 0b0000_0100;
+        const START  = 0b0000_1000;
+        const UP     = 0b0001_0000;
+        const DOWN   = 0b0010_0000;
+        const LEFT   = 0b0100_0000;
+        const RIGHT  = 0b1000_0000;
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Controller {
+    buttons: Buttons,
+    shift_register: u8,
+    strobe: bool,
+}
+
+impl Controller {
+    pub fn new() -> Controller {
+        Controller::default()
+    }
+
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.buttons = buttons;
+        if self.strobe {
+            self.shift_register = buttons.bits();
+        }
+    }
+
+    pub fn buttons(&self) -> Buttons {
+        self.buttons
+    }
+
+    /// bit 0 of $4016, the shift register is reloaded while strobe is high
+    pub fn write(&mut self, strobe: bool) {
+        self.strobe = strobe;
+        if strobe {
+            self.shift_register = self.buttons.bits();
+        }
+    }
+
+    /// returns the next button in bit 0
+    pub fn read(&mut self) -> u8 {
+        let value = self.peek();
+        if !self.strobe {
+            // official controllers return 1 after all 8 buttons are read
+            self.shift_register = (self.shift_register >> 1) | 0b1000_0000;
+        }
+        value
+    }
+
+    /// what [`Controller::read`] returns next
+    pub fn peek(&self) -> u8 {
+        if self.strobe {
+            return self.buttons.bits() & 1;
+        }
+        self.shift_register & 1
+    }
+}
+
+impl InputDevice for Controller {
+    fn strobe(&mut self, out: u8) {
+        self.write(out & 1 == 1);
+    }
+
+    fn poll(&mut self, _addr: u16, _screen: &Screen) -> u8 {
+        self.read()
+    }
+
+    fn peek(&self, _addr: u16, _screen: &Screen) -> u8 {
+        Controller::peek(self)
+    }
+
+    fn name(&self) -> &'static str {
+        "controller"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for Controller {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.buttons.bits());
+        writer.u8(self.shift_register);
+        writer.bool(self.strobe);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.buttons = Buttons::from_bits_retain(reader.u8()?);
+        self.shift_register = reader.u8()?;
+        self.strobe = reader.bool()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::any::Any;
+use crate::nes::input::controller::{Buttons, Controller};
+use crate::nes::input::{InputDevice, Port, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Four_player_adapters
+
+/// reads 17-24 of each port, $10 and $20 when read msb first
+const SIGNATURE_PORT_ONE: u8 = 0b0000_1000;
+const SIGNATURE_PORT_TWO: u8 = 0b0000_0100;
+
+/// one half of a NES Four Score, port one carries players 1 and 3, port two players 2 and 4.
+/// each port shifts out 8 buttons of both players followed by a signature byte
+#[derive(Debug)]
+pub struct FourScore {
+    buttons: [Buttons; 2],
+    signature: u8,
+    shift_register: u32,
+    strobe: bool,
+}
+
+impl FourScore {
+    pub fn new(port: Port) -> FourScore {
+        FourScore {
+            buttons: [Buttons::empty(); 2],
+            signature: match port {
+                Port::One => SIGNATURE_PORT_ONE,
+                Port::Two => SIGNATURE_PORT_TWO,
+            },
+            shift_register: 0,
+            strobe: false,
+        }
+    }
+
+    /// `index` 0 is the player read first (1 or 2), 1 the second one (3 or 4)
+    pub fn set_buttons(&mut self, index: usize, buttons: Buttons) {
+        self.buttons[index] = buttons;
+        if self.strobe {
+            self.reload();
+        }
+    }
+
+    fn reload(&mut self) {
+        self.shift_register = self.buttons[0].bits() as u32
+            | (self.buttons[1].bits() as u32) << 8
+            | (self.signature as u32) << 16;
+    }
+}
+
+impl InputDevice for FourScore {
+    fn strobe(&mut self, out: u8) {
+        self.strobe = out & 1 == 1;
+        if self.strobe {
+            self.reload();
+        }
+    }
+
+    fn poll(&mut self, addr: u16, screen: &Screen) -> u8 {
+        let value = self.peek(addr, screen);
+        if !self.strobe {
+            // 1s after the signature
+            self.shift_register = (self.shift_register >> 1) | 1 << 23;
+        }
+        value
+    }
+
+    fn peek(&self, _addr: u16, _screen: &Screen) -> u8 {
+        if self.strobe {
+            return self.buttons[0].bits() & 1;
+        }
+        (self.shift_register & 1) as u8
+    }
+
+    fn name(&self) -> &'static str {
+        "four score"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// famicom four player adapter in the simple mode, players 3 and 4 on D1 of $4016 and $4017
+#[derive(Debug, Default)]
+pub struct FourPlayerAdapter {
+    controllers: [Controller; 2],
+}
+
+impl FourPlayerAdapter {
+    pub fn new() -> FourPlayerAdapter {
+        FourPlayerAdapter::default()
+    }
+
+    /// `index` 0 is player 3, 1 is player 4
+    pub fn set_buttons(&mut self, index: usize, buttons: Buttons) {
+        self.controllers[index].set_buttons(buttons);
+    }
+}
+
+impl InputDevice for FourPlayerAdapter {
+    fn strobe(&mut self, out: u8) {
+        for controller in self.controllers.iter_mut() {
+            controller.write(out & 1 == 1);
+        }
+    }
+
+    fn poll(&mut self, addr: u16, _screen: &Screen) -> u8 {
+        let index = if addr == 0x4016 { 0 } else { 1 };
+        self.controllers[index].read() << 1
+    }
+
+    fn peek(&self, addr: u16, _screen: &Screen) -> u8 {
+        let index = if addr == 0x4016 { 0 } else { 1 };
+        self.controllers[index].peek() << 1
+    }
+
+    fn name(&self) -> &'static str {
+        "four player adapter"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for FourScore {
+    fn save_state(&self, writer: &mut StateWriter) {
+        for buttons in self.buttons {
+            writer.u8(buttons.bits());
+        }
+        writer.u8(self.signature);
+        writer.u32(self.shift_register);
+        writer.bool(self.strobe);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for buttons in self.buttons.iter_mut() {
+            *buttons = Buttons::from_bits_retain(reader.u8()?);
+        }
+        self.signature = reader.u8()?;
+        self.shift_register = reader.u32()?;
+        self.strobe = reader.bool()?;
+        Ok(())
+    }
+}
+
+impl SaveState for FourPlayerAdapter {
+    fn save_state(&self, writer: &mut StateWriter) {
+        for controller in self.controllers.iter() {
+            controller.save_state(writer);
+        }
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for controller in self.controllers.iter_mut() {
+            controller.load_state(reader)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use std::any::Any;
+use crate::nes::input::{InputDevice, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Family_BASIC_Keyboard
+
+pub const ROWS: usize = 9;
+pub const COLUMNS: usize = 2;
+
+/// family basic keyboard on the famicom expansion port.
+///
+/// the keys are a matrix of 9 rows with 2 columns of 4 keys each, a $4016 write
+/// selects the column and advances the row, $4017 returns the column on D1-D4
+#[derive(Debug, Default)]
+pub struct FamilyKeyboard {
+    /// pressed keys of every row and column in bits 0-3
+    keys: [[u8; COLUMNS]; ROWS],
+    row: usize,
+    column: usize,
+    enabled: bool,
+}
+
+impl FamilyKeyboard {
+    pub fn new() -> FamilyKeyboard {
+        FamilyKeyboard::default()
+    }
+
+    /// `key` 0..=3 is the data line D1-D4 the key shows up on
+    pub fn set_pressed(&mut self, row: usize, column: usize, key: usize, pressed: bool) {
+        if pressed {
+            self.keys[row][column] |= 1 << key;
+        } else {
+            self.keys[row][column] &= !(1 << key);
+        }
+    }
+
+    pub fn release_all(&mut self) {
+        self.keys = [[0; COLUMNS]; ROWS];
+    }
+}
+
+impl InputDevice for FamilyKeyboard {
+    /// ---- -KCR: keyboard enable, column select, reset to row 0
+    fn strobe(&mut self, out: u8) {
+        self.enabled = out & 0b100 != 0;
+        let column = ((out >> 1) & 1) as usize;
+        if out & 1 == 1 {
+            self.row = 0;
+        } else if self.column == 1 && column == 0 {
+            // one past the last row reads as nothing pressed
+            self.row = (self.row + 1).min(ROWS);
+        }
+        self.column = column;
+    }
+
+    fn poll(&mut self, addr: u16, screen: &Screen) -> u8 {
+        self.peek(addr, screen)
+    }
+
+    fn peek(&self, addr: u16, _screen: &Screen) -> u8 {
+        if addr != 0x4017 || !self.enabled {
+            return 0;
+        }
+        let pressed = if self.row < ROWS { self.keys[self.row][self.column] } else { 0 };
+        // low when pressed
+        (!pressed & 0b1111) << 1
+    }
+
+    fn name(&self) -> &'static str {
+        "family keyboard"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for FamilyKeyboard {
+    fn save_state(&self, writer: &mut StateWriter) {
+        for row in self.keys.iter() {
+            writer.bytes(row);
+        }
+        writer.u8(self.row as u8);
+        writer.u8(self.column as u8);
+        writer.bool(self.enabled);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for row in self.keys.iter_mut() {
+            reader.bytes_into(row, "keyboard matrix")?;
+        }
+        self.row = reader.u8()? as usize;
+        self.column = reader.u8()? as usize;
+        self.enabled = reader.bool()?;
+        Ok(())
+    }
+}
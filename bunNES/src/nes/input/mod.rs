@@ -0,0 +1,230 @@
+pub mod controller;
+pub mod zapper;
+pub mod four_score;
+pub mod power_pad;
+pub mod arkanoid;
+pub mod keyboard;
+
+use std::any::Any;
+use std::fmt::Debug;
+use crate::nes::input::arkanoid::ArkanoidPaddle;
+use crate::nes::input::controller::{Buttons, Controller};
+use crate::nes::input::four_score::{FourPlayerAdapter, FourScore};
+use crate::nes::input::keyboard::FamilyKeyboard;
+use crate::nes::input::power_pad::PowerPad;
+use crate::nes::input::zapper::Zapper;
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Input_devices
+
+/// data lines D0-D4 of $4016/$4017, the rest is open bus
+const DATA_LINES: u8 = 0b0001_1111;
+/// with a Four Score or a four player adapter
+pub const PLAYERS: usize = 4;
+
+/// the ppu output as seen by light sensing devices
+#[derive(Debug)]
+pub struct Screen<'a> {
+    /// system palette indices of the frame being drawn
+    pub frame_buffer: &'a [u8],
+    pub scanline: u16,
+    pub dot: u16,
+}
+
+/// a device plugged into one of the controller ports or the famicom expansion port
+pub trait InputDevice: Debug + Send + SaveState {
+    /// $4016 write, OUT0 is the strobe of every device, expansion port devices also see OUT1 and OUT2
+    fn strobe(&mut self, out: u8);
+
+    /// data lines the device drives during a read of `addr` ($4016 or $4017).
+    /// controller port devices are only polled for their own port
+    fn poll(&mut self, addr: u16, screen: &Screen) -> u8;
+
+    /// what [`InputDevice::poll`] would return, without shifting anything out
+    fn peek(&self, addr: u16, screen: &Screen) -> u8;
+
+    /// identifies the device in save states
+    fn name(&self) -> &'static str;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Port {
+    /// read through $4016
+    One,
+    /// read through $4017
+    Two,
+}
+
+impl Port {
+    fn index(&self) -> usize {
+        match self {
+            Port::One => 0,
+            Port::Two => 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InputPorts {
+    ports: [Box<dyn InputDevice>; 2],
+    expansion: Option<Box<dyn InputDevice>>,
+}
+
+impl Default for InputPorts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InputPorts {
+    /// standard controllers in both ports and nothing in the expansion port
+    pub fn new() -> InputPorts {
+        InputPorts {
+            ports: [Box::new(Controller::new()), Box::new(Controller::new())],
+            expansion: None,
+        }
+    }
+
+    /// devices for the NES 2.0 default expansion device field,
+    /// anything unsupported gets standard controllers
+    // https://www.nesdev.org/wiki/NES_2.0#Default_Expansion_Device
+    pub fn from_expansion_device(device: u8) -> InputPorts {
+        let mut ports = InputPorts::new();
+        match device {
+            0x02 => {
+                ports.set_device(Port::One, Box::new(FourScore::new(Port::One)));
+                ports.set_device(Port::Two, Box::new(FourScore::new(Port::Two)));
+            }
+            0x03 => ports.set_expansion(Some(Box::new(FourPlayerAdapter::new()))),
+            0x08 => ports.set_device(Port::Two, Box::new(Zapper::new())),
+            0x09 => {
+                ports.set_device(Port::One, Box::new(Zapper::new()));
+                ports.set_device(Port::Two, Box::new(Zapper::new()));
+            }
+            0x0B | 0x0C => ports.set_device(Port::Two, Box::new(PowerPad::new())),
+            0x0D | 0x0E => ports.set_expansion(Some(Box::new(PowerPad::family_trainer()))),
+            0x0F => ports.set_device(Port::Two, Box::new(ArkanoidPaddle::new())),
+            0x10 => ports.set_expansion(Some(Box::new(ArkanoidPaddle::famicom()))),
+            // the data recorder isn't emulated
+            0x23 => ports.set_expansion(Some(Box::new(FamilyKeyboard::new()))),
+            _ => {}
+        }
+        ports
+    }
+
+    pub fn set_device(&mut self, port: Port, device: Box<dyn InputDevice>) {
+        self.ports[port.index()] = device;
+    }
+
+    pub fn set_expansion(&mut self, device: Option<Box<dyn InputDevice>>) {
+        self.expansion = device;
+    }
+
+    /// the device in `port` if it is a `T`
+    pub fn device_mut<T: InputDevice + 'static>(&mut self, port: Port) -> Option<&mut T> {
+        self.ports[port.index()].as_any_mut().downcast_mut::<T>()
+    }
+
+    /// the expansion port device if it is a `T`
+    pub fn expansion_mut<T: InputDevice + 'static>(&mut self) -> Option<&mut T> {
+        self.expansion.as_mut()?.as_any_mut().downcast_mut::<T>()
+    }
+
+    /// buttons held by `player` 0..=3, players 3 and 4 need a Four Score or a four player adapter.
+    /// other players are ignored
+    pub fn set_buttons(&mut self, player: usize, buttons: Buttons) {
+        if player >= PLAYERS {
+            return;
+        }
+        let port = if player.is_multiple_of(2) { Port::One } else { Port::Two };
+        if player < 2 {
+            if let Some(controller) = self.device_mut::<Controller>(port) {
+                controller.set_buttons(buttons);
+                return;
+            }
+        }
+        if let Some(four_score) = self.device_mut::<FourScore>(port) {
+            four_score.set_buttons(player / 2, buttons);
+            return;
+        }
+        if player >= 2 {
+            if let Some(adapter) = self.expansion_mut::<FourPlayerAdapter>() {
+                adapter.set_buttons(player - 2, buttons);
+            }
+        }
+    }
+
+    /// $4016 write
+    pub fn write(&mut self, value: u8) {
+        for device in self.ports.iter_mut() {
+            device.strobe(value);
+        }
+        if let Some(expansion) = self.expansion.as_mut() {
+            expansion.strobe(value);
+        }
+    }
+
+    /// $4016/$4017 read, only the data lines driven by the devices
+    pub fn read(&mut self, addr: u16, screen: &Screen) -> u8 {
+        let mut value = self.ports[port(addr).index()].poll(addr, screen);
+        if let Some(expansion) = self.expansion.as_mut() {
+            value |= expansion.poll(addr, screen);
+        }
+        value & DATA_LINES
+    }
+
+    /// [`InputPorts::read`] leaving the devices as they are
+    pub fn peek(&self, addr: u16, screen: &Screen) -> u8 {
+        let mut value = self.ports[port(addr).index()].peek(addr, screen);
+        if let Some(expansion) = self.expansion.as_ref() {
+            value |= expansion.peek(addr, screen);
+        }
+        value & DATA_LINES
+    }
+}
+
+fn port(addr: u16) -> Port {
+    match addr {
+        0x4016 => Port::One,
+        0x4017 => Port::Two,
+        _ => panic!("not an input port: {addr:#06X}")
+    }
+}
+
+/// the devices themselves aren't swapped on load, the state has to match what is plugged in
+impl SaveState for InputPorts {
+    fn save_state(&self, writer: &mut StateWriter) {
+        for device in self.ports.iter() {
+            save_device(device.as_ref(), writer);
+        }
+        writer.bool(self.expansion.is_some());
+        if let Some(expansion) = self.expansion.as_ref() {
+            save_device(expansion.as_ref(), writer);
+        }
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for device in self.ports.iter_mut() {
+            load_device(device.as_mut(), reader)?;
+        }
+        match (reader.bool()?, self.expansion.as_mut()) {
+            (true, Some(expansion)) => load_device(expansion.as_mut(), reader),
+            (false, None) => Ok(()),
+            _ => Err(StateError::Mismatch("input device")),
+        }
+    }
+}
+
+fn save_device(device: &dyn InputDevice, writer: &mut StateWriter) {
+    writer.str(device.name());
+    device.save_state(writer);
+}
+
+fn load_device(device: &mut dyn InputDevice, reader: &mut StateReader) -> Result<(), StateError> {
+    if reader.str()? != device.name() {
+        return Err(StateError::Mismatch("input device"));
+    }
+    device.load_state(reader)
+}
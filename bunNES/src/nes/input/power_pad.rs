@@ -0,0 +1,140 @@
+use std::any::Any;
+use crate::nes::input::{InputDevice, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+
+// https://www.nesdev.org/wiki/Power_Pad
+
+/// buttons shifted out on D3 and D4, the pad is numbered like side B:
+///  1  2  3  4
+///  5  6  7  8
+///  9 10 11 12
+const D3_BUTTONS: [u8; 8] = [2, 1, 5, 9, 6, 10, 11, 7];
+const D4_BUTTONS: [u8; 4] = [4, 3, 12, 8];
+
+/// rows selected by pulling OUT2, OUT1 or OUT0 low on the family trainer
+const FAMILY_TRAINER_ROWS: [[u8; 4]; 3] = [[9, 10, 11, 12], [5, 6, 7, 8], [1, 2, 3, 4]];
+
+#[derive(Debug, Clone, Default)]
+pub struct PowerPad {
+    /// bit n - 1 is set while button n is pressed
+    pressed: u16,
+    /// famicom family trainer on the expansion port instead of the serial protocol
+    family_trainer: bool,
+
+    strobe: bool,
+    shift_d3: u8,
+    shift_d4: u8,
+    out: u8,
+}
+
+impl PowerPad {
+    /// power pad in a controller port, usually port two
+    pub fn new() -> PowerPad {
+        PowerPad::default()
+    }
+
+    /// family trainer mat on the famicom expansion port
+    pub fn family_trainer() -> PowerPad {
+        PowerPad {
+            family_trainer: true,
+            ..PowerPad::new()
+        }
+    }
+
+    /// `button` 1..=12
+    pub fn set_pressed(&mut self, button: u8, pressed: bool) {
+        let bit = 1 << (button - 1);
+        if pressed {
+            self.pressed |= bit;
+        } else {
+            self.pressed &= !bit;
+        }
+    }
+
+    pub fn pressed(&self, button: u8) -> bool {
+        self.pressed & 1 << (button - 1) != 0
+    }
+
+    fn reload(&mut self) {
+        self.shift_d3 = pack(self, &D3_BUTTONS);
+        // 1s after the 4 buttons
+        self.shift_d4 = pack(self, &D4_BUTTONS) | 0b1111_0000;
+    }
+}
+
+/// pressed buttons of `buttons` in shift order
+fn pack(pad: &PowerPad, buttons: &[u8]) -> u8 {
+    buttons.iter().enumerate()
+        .filter(|(_, button)| pad.pressed(**button))
+        .fold(0, |value, (i, _)| value | 1 << i)
+}
+
+impl InputDevice for PowerPad {
+    fn strobe(&mut self, out: u8) {
+        self.out = out;
+        self.strobe = out & 1 == 1;
+        if self.strobe {
+            self.reload();
+        }
+    }
+
+    fn poll(&mut self, addr: u16, _screen: &Screen) -> u8 {
+        if self.family_trainer {
+            // four buttons of the selected row on D1-D4 of $4017, low when pressed
+            if addr != 0x4017 {
+                return 0;
+            }
+            let mut pressed = 0;
+            for (row, buttons) in FAMILY_TRAINER_ROWS.iter().enumerate() {
+                if self.out & 1 << row == 0 {
+                    pressed |= pack(self, buttons);
+                }
+            }
+            return (!pressed & 0b1111) << 1;
+        }
+
+        if self.strobe {
+            self.reload();
+        }
+        let value = (self.shift_d3 & 1) << 3 | (self.shift_d4 & 1) << 4;
+        if !self.strobe {
+            self.shift_d3 = (self.shift_d3 >> 1) | 0b1000_0000;
+            self.shift_d4 = (self.shift_d4 >> 1) | 0b1000_0000;
+        }
+        value
+    }
+
+    fn peek(&self, addr: u16, screen: &Screen) -> u8 {
+        // polling only changes the shift registers, a copy of them is enough
+        self.clone().poll(addr, screen)
+    }
+
+    fn name(&self) -> &'static str {
+        "power pad"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for PowerPad {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u16(self.pressed);
+        writer.bool(self.family_trainer);
+        writer.bool(self.strobe);
+        writer.u8(self.shift_d3);
+        writer.u8(self.shift_d4);
+        writer.u8(self.out);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.pressed = reader.u16()?;
+        self.family_trainer = reader.bool()?;
+        self.strobe = reader.bool()?;
+        self.shift_d3 = reader.u8()?;
+        self.shift_d4 = reader.u8()?;
+        self.out = reader.u8()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::any::Any;
+use crate::nes::input::{InputDevice, Screen};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
+use crate::nes::palette::luminance;
+use crate::nes::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+// https://www.nesdev.org/wiki/Zapper
+
+/// pixels around the aimed one the photodiode picks up
+const SENSOR_RADIUS: usize = 2;
+/// the sensor stays lit for a while after the beam passed the aimed spot
+const SENSOR_SCANLINES: u16 = 26;
+const LIGHT_THRESHOLD: u8 = 0xC0;
+
+const LIGHT_NOT_DETECTED: u8 = 0b0000_1000;
+const TRIGGER_PULLED: u8 = 0b0001_0000;
+
+#[derive(Debug, Default)]
+pub struct Zapper {
+    /// aimed pixel, none while pointing away from the screen
+    aim: Option<(usize, usize)>,
+    trigger: bool,
+}
+
+impl Zapper {
+    pub fn new() -> Zapper {
+        Zapper::default()
+    }
+
+    /// aim at a pixel of the nes screen, anything outside of it points away from the screen
+    pub fn aim(&mut self, x: i32, y: i32) {
+        self.aim = if (0..SCREEN_WIDTH as i32).contains(&x) && (0..SCREEN_HEIGHT as i32).contains(&y) {
+            Some((x as usize, y as usize))
+        } else {
+            None
+        };
+    }
+
+    pub fn set_trigger(&mut self, pulled: bool) {
+        self.trigger = pulled;
+    }
+
+    /// bright pixels near the aimed one that were drawn shortly before the current beam position
+    pub fn light_detected(&self, screen: &Screen) -> bool {
+        let Some((x, y)) = self.aim else {
+            return false;
+        };
+
+        let scanline = screen.scanline as usize;
+        if scanline < y || scanline >= y + SENSOR_SCANLINES as usize {
+            return false;
+        }
+
+        // only rows that were already drawn this frame
+        let bottom = (y + SENSOR_RADIUS).min(scanline).min(SCREEN_HEIGHT - 1);
+        let right = (x + SENSOR_RADIUS).min(SCREEN_WIDTH - 1);
+        (y.saturating_sub(SENSOR_RADIUS)..=bottom).any(|row| {
+            (x.saturating_sub(SENSOR_RADIUS)..=right).any(|column| {
+                luminance(screen.frame_buffer[row * SCREEN_WIDTH + column]) >= LIGHT_THRESHOLD
+            })
+        })
+    }
+}
+
+impl InputDevice for Zapper {
+    fn strobe(&mut self, _out: u8) {}
+
+    fn poll(&mut self, addr: u16, screen: &Screen) -> u8 {
+        self.peek(addr, screen)
+    }
+
+    /// D3 light sense (0 when detected), D4 trigger
+    fn peek(&self, _addr: u16, screen: &Screen) -> u8 {
+        let mut value = 0;
+        if !self.light_detected(screen) {
+            value |= LIGHT_NOT_DETECTED;
+        }
+        if self.trigger {
+            value |= TRIGGER_PULLED;
+        }
+        value
+    }
+
+    fn name(&self) -> &'static str {
+        "zapper"
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl SaveState for Zapper {
+    fn save_state(&self, writer: &mut StateWriter) {
+        let (x, y) = self.aim.unwrap_or((0, 0));
+        writer.bool(self.aim.is_some());
+        writer.u16(x as u16);
+        writer.u16(y as u16);
+        writer.bool(self.trigger);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        let aimed = reader.bool()?;
+        let x = reader.u16()? as usize;
+        let y = reader.u16()? as usize;
+        self.aim = if aimed { Some((x, y)) } else { None };
+        self.trigger = reader.bool()?;
+        Ok(())
+    }
+}
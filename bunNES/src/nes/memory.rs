@@ -0,0 +1,90 @@
+use crate::nes::bus::Bus;
+
+const CPU_SIZE: usize = 0x10000;
+const PPU_SIZE: usize = 0x4000;
+const OAM_SIZE: usize = 256;
+const PALETTE_SIZE: usize = 32;
+/// the pattern tables, mapper 000 has no chr banks to switch
+const CHR_SIZE: usize = 0x2000;
+
+/// what a memory editor looks at, every space is addressed from 0.
+/// reads are peeks, so looking doesn't disturb the running game
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MemorySpace {
+    /// $0000-$FFFF as the cpu sees it
+    Cpu,
+    /// $0000-$3FFF as the ppu sees it
+    Ppu,
+    Oam,
+    /// $3F00-$3F1F of the ppu
+    Palette,
+    /// cartridge ram, $6000 on the cpu
+    PrgRam,
+    /// pattern tables, $0000-$1FFF of the ppu
+    Chr,
+}
+
+impl MemorySpace {
+    pub const ALL: [MemorySpace; 6] = [
+        MemorySpace::Cpu, MemorySpace::Ppu, MemorySpace::Oam,
+        MemorySpace::Palette, MemorySpace::PrgRam, MemorySpace::Chr,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemorySpace::Cpu => "CPU",
+            MemorySpace::Ppu => "PPU",
+            MemorySpace::Oam => "OAM",
+            MemorySpace::Palette => "Palette",
+            MemorySpace::PrgRam => "PRG-RAM",
+            MemorySpace::Chr => "CHR",
+        }
+    }
+
+    /// addresses go from 0 to `len - 1`, cartridges without prg ram have none
+    pub fn len(&self, bus: &Bus) -> usize {
+        match self {
+            MemorySpace::Cpu => CPU_SIZE,
+            MemorySpace::Ppu => PPU_SIZE,
+            MemorySpace::Oam => OAM_SIZE,
+            MemorySpace::Palette => PALETTE_SIZE,
+            MemorySpace::PrgRam => bus.prg_ram().len(),
+            MemorySpace::Chr => CHR_SIZE,
+        }
+    }
+
+    pub fn peek(&self, bus: &Bus, addr: usize) -> u8 {
+        match self {
+            MemorySpace::Cpu => bus.peek(addr as u16),
+            MemorySpace::Ppu | MemorySpace::Chr => bus.peek_ppu(addr as u16),
+            MemorySpace::Oam => bus.ppu.oam()[addr],
+            MemorySpace::Palette => bus.peek_ppu(0x3F00 + addr as u16),
+            MemorySpace::PrgRam => bus.prg_ram()[addr],
+        }
+    }
+
+    /// `len` bytes from `start` on, cut short at the end of the space
+    pub fn peek_range(&self, bus: &Bus, start: usize, len: usize) -> Vec<u8> {
+        (start..(start + len).min(self.len(bus))).map(|addr| self.peek(bus, addr)).collect()
+    }
+
+    /// changes the byte at `addr` without the side effects of a write. cpu registers and
+    /// rom aren't memory and are left alone, chr rom can be changed
+    pub fn poke(&self, bus: &mut Bus, addr: usize, value: u8) {
+        match self {
+            MemorySpace::Cpu => match addr {
+                0x0000..=0x1FFF => bus.ram[addr & 0x07FF] = value,
+                0x6000..=0x7FFF => {
+                    if let Some(byte) = bus.prg_ram_mut().get_mut(addr - 0x6000) {
+                        *byte = value;
+                    }
+                }
+                _ => {}
+            },
+            MemorySpace::Ppu | MemorySpace::Chr => bus.ppu.poke_vram(addr as u16, value),
+            MemorySpace::Oam => bus.ppu.poke_oam(addr as u8, value),
+            MemorySpace::Palette => bus.ppu.poke_vram(0x3F00 + addr as u16, value),
+            MemorySpace::PrgRam => bus.prg_ram_mut()[addr] = value,
+        }
+    }
+}
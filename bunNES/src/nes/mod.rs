@@ -2,4 +2,11 @@ pub mod rom;
 pub mod cpu;
 pub mod opcodes;
 pub mod ppu;
-pub mod bus;
\ No newline at end of file
+pub mod ppu_viewer;
+pub mod bus;
+pub mod ram_bus;
+pub mod apu;
+pub mod input;
+pub mod palette;
+pub mod disasm;
+pub mod memory;
@@ -1,5 +1,6 @@
 use std::fmt::{Display, Formatter};
 use std::path::absolute;
+use crate::nes::opcodes::Access::{Read, ReadModifyWrite, Write};
 use crate::nes::opcodes::AddrMode::*;
 use crate::nes::opcodes::OpCode::*;
 
@@ -21,6 +22,15 @@ pub enum AddrMode {
 }
 
 impl AddrMode {
+    /// bytes of an instruction using this mode, opcode included
+    pub const fn size(&self) -> u8 {
+        match self {
+            Implicit | Accumulator => 1,
+            Immediate | Zp | ZpX | ZpY | Relative | IndirectX | IndirectY => 2,
+            Absolute | AbsoluteX | AbsoluteY | Indirect => 3,
+        }
+    }
+
     fn to_string(&self) -> &str {
         match self {
             Implicit => "Implicit",
@@ -46,13 +56,52 @@ impl Display for AddrMode {
     }
 }
 
+/// how an instruction touches the memory its operand points to
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Access {
+    /// implied, accumulator, branches and jumps
+    None,
+    Read,
+    Write,
+    ReadModifyWrite,
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Instruction {
     pub op_code: OpCode,
     pub addr_mode: AddrMode,
-    // TODO: remove (actually dont!)
-    // use in op code logic
     pub size: u8,
+    /// cycles without the page cross penalty
+    pub cycles: u8,
+    /// one more cycle when the indexed address crosses a page.
+    /// branches take one more when taken and another one when the target is on another page
+    pub page_penalty: bool,
+    pub official: bool,
+    pub access: Access,
+}
+
+impl Instruction {
+    const fn new(op_code: OpCode, addr_mode: AddrMode, cycles: u8, access: Access) -> Instruction {
+        Instruction {
+            op_code,
+            addr_mode,
+            size: addr_mode.size(),
+            cycles,
+            page_penalty: false,
+            official: true,
+            access,
+        }
+    }
+
+    const fn page_penalty(mut self) -> Instruction {
+        self.page_penalty = true;
+        self
+    }
+
+    const fn unofficial(mut self) -> Instruction {
+        self.official = false;
+        self
+    }
 }
 
 
@@ -114,6 +163,27 @@ pub enum OpCode {
     Txa,
     Txs,
     Tya,
+
+    // unofficial
+    Ahx,
+    Alr,
+    Anc,
+    Arr,
+    Axs,
+    Dcp,
+    Isc,
+    Kil,
+    Las,
+    Lax,
+    Rla,
+    Rra,
+    Sax,
+    Shx,
+    Shy,
+    Slo,
+    Sre,
+    Tas,
+    Xaa,
 }
 
 impl OpCode {
@@ -175,6 +245,26 @@ impl OpCode {
             Txa => "TXA",
             Txs => "TXS",
             Tya => "TYA",
+
+            Ahx => "AHX",
+            Alr => "ALR",
+            Anc => "ANC",
+            Arr => "ARR",
+            Axs => "AXS",
+            Dcp => "DCP",
+            Isc => "ISC",
+            Kil => "KIL",
+            Las => "LAS",
+            Lax => "LAX",
+            Rla => "RLA",
+            Rra => "RRA",
+            Sax => "SAX",
+            Shx => "SHX",
+            Shy => "SHY",
+            Slo => "SLO",
+            Sre => "SRE",
+            Tas => "TAS",
+            Xaa => "XAA",
         }
     }
 }
@@ -185,271 +275,270 @@ impl Display for OpCode {
     }
 }
 
-pub fn op_code_from_instruction(to_find: Instruction) -> Option<usize> {
+/// byte code of the official instruction with `op_code` and `addr_mode`
+pub fn op_code_from_instruction(op_code: OpCode, addr_mode: AddrMode) -> Option<usize> {
     OP_CODES.iter().position(|instruction| {
-        if let Some(instruction) = instruction {
-            instruction.op_code == to_find.op_code && instruction.addr_mode == to_find.addr_mode
-        } else {
-            false
-        }
+        instruction.official && instruction.op_code == op_code && instruction.addr_mode == addr_mode
     })
 }
 
-pub static OP_CODES: [(Option<Instruction>); 256] = [
-    Some(Instruction { op_code: Brk, addr_mode: Implicit, size: 1 }), // 0x00
-    Some(Instruction { op_code: Ora, addr_mode: IndirectY, size: 2 }), // 0x01
-    None, // 0x02
-    None, // 0x03
-    None, // 0x04
-    Some(Instruction { op_code: Ora, addr_mode: Zp, size: 2 }), // 0x05
-    Some(Instruction { op_code: Asl, addr_mode: Zp, size: 2 }), // 0x06
-    None, // 0x07
-    Some(Instruction { op_code: Php, addr_mode: Implicit, size: 1 }), // 0x08
-    Some(Instruction { op_code: Ora, addr_mode: Immediate, size: 2 }), // 0x09
-    Some(Instruction { op_code: Asl, addr_mode: Accumulator, size: 1 }), // 0x0a
-    None, // 0x0b
-    None, // 0x0c
-    Some(Instruction { op_code: Ora, addr_mode: Absolute, size: 3 }), // 0x0d
-    Some(Instruction { op_code: Asl, addr_mode: Absolute, size: 3 }), // 0x0e
-    None, // 0x0f
-    Some(Instruction { op_code: Bpl, addr_mode: Relative, size: 2 }), // 0x10
-    Some(Instruction { op_code: Ora, addr_mode: IndirectX, size: 2 }), // 0x11
-    Some(Instruction { op_code: Ora, addr_mode: Zp, size: 2 }), // 0x12
-    None, // 0x13
-    None, // 0x14
-    Some(Instruction { op_code: Ora, addr_mode: ZpX, size: 2 }), // 0x15
-    Some(Instruction { op_code: Asl, addr_mode: ZpX, size: 2 }), // 0x16
-    None, // 0x17
-    Some(Instruction { op_code: Clc, addr_mode: Implicit, size: 1 }), // 0x18
-    Some(Instruction { op_code: Ora, addr_mode: AbsoluteY, size: 3 }), // 0x19
-    Some(Instruction { op_code: Inc, addr_mode: Accumulator, size: 1 }), // 0x1a
-    None, // 0x1b
-    None, // 0x1c
-    Some(Instruction { op_code: Ora, addr_mode: AbsoluteX, size: 3 }), // 0x1d
-    Some(Instruction { op_code: Asl, addr_mode: AbsoluteX, size: 3 }), // 0x1e
-    None, // 0x1f
-    Some(Instruction { op_code: Jsr, addr_mode: Absolute, size: 3 }), // 0x20
-    Some(Instruction { op_code: And, addr_mode: IndirectY, size: 2 }), // 0x21
-    None, // 0x22
-    None, // 0x23
-    Some(Instruction { op_code: Bit, addr_mode: Zp, size: 2 }), // 0x24
-    Some(Instruction { op_code: And, addr_mode: Zp, size: 2 }), // 0x25
-    Some(Instruction { op_code: Rol, addr_mode: Zp, size: 2 }), // 0x26
-    None, // 0x27
-    Some(Instruction { op_code: Plp, addr_mode: Implicit, size: 1 }), // 0x28
-    Some(Instruction { op_code: And, addr_mode: Immediate, size: 2 }), // 0x29
-    Some(Instruction { op_code: Rol, addr_mode: Accumulator, size: 1 }), // 0x2a
-    None, // 0x2b
-    Some(Instruction { op_code: Bit, addr_mode: Absolute, size: 3 }), // 0x2c
-    Some(Instruction { op_code: And, addr_mode: Absolute, size: 3 }), // 0x2d
-    Some(Instruction { op_code: Rol, addr_mode: Absolute, size: 3 }), // 0x2e
-    None, // 0x2f
-    Some(Instruction { op_code: Bmi, addr_mode: Relative, size: 2 }), // 0x30
-    Some(Instruction { op_code: And, addr_mode: IndirectX, size: 2 }), // 0x31
-    Some(Instruction { op_code: And, addr_mode: Zp, size: 2 }), // 0x32
-    None, // 0x33
-    Some(Instruction { op_code: Bit, addr_mode: ZpX, size: 2 }), // 0x34
-    Some(Instruction { op_code: And, addr_mode: ZpX, size: 2 }), // 0x35
-    Some(Instruction { op_code: Rol, addr_mode: ZpX, size: 2 }), // 0x36
-    None, // 0x37
-    Some(Instruction { op_code: Sec, addr_mode: Implicit, size: 1 }), // 0x38
-    Some(Instruction { op_code: And, addr_mode: AbsoluteY, size: 3 }), // 0x39
-    Some(Instruction { op_code: Dec, addr_mode: Accumulator, size: 1 }), // 0x3a
-    None, // 0x3b
-    Some(Instruction { op_code: Bit, addr_mode: AbsoluteX, size: 3 }), // 0x3c
-    Some(Instruction { op_code: And, addr_mode: AbsoluteX, size: 3 }), // 0x3d
-    Some(Instruction { op_code: Rol, addr_mode: AbsoluteX, size: 3 }), // 0x3e
-    None, // 0x3f
-    Some(Instruction { op_code: Rti, addr_mode: Implicit, size: 1 }), // 0x40
-    Some(Instruction { op_code: Eor, addr_mode: IndirectY, size: 2 }), // 0x41
-    None, // 0x42
-    None, // 0x43
-    None, // 0x44
-    Some(Instruction { op_code: Eor, addr_mode: Zp, size: 2 }), // 0x45
-    Some(Instruction { op_code: Lsr, addr_mode: Zp, size: 2 }), // 0x46
-    None, // 0x47
-    Some(Instruction { op_code: Pha, addr_mode: Implicit, size: 1 }), // 0x48
-    Some(Instruction { op_code: Eor, addr_mode: Immediate, size: 2 }), // 0x49
-    Some(Instruction { op_code: Lsr, addr_mode: Accumulator, size: 1 }), // 0x4a
-    None, // 0x4b
-    Some(Instruction { op_code: Jmp, addr_mode: Absolute, size: 3 }), // 0x4c
-    Some(Instruction { op_code: Eor, addr_mode: Absolute, size: 3 }), // 0x4d
-    Some(Instruction { op_code: Lsr, addr_mode: Absolute, size: 3 }), // 0x4e
-    None, // 0x4f
-    Some(Instruction { op_code: Bvc, addr_mode: Relative, size: 2 }), // 0x50
-    Some(Instruction { op_code: Eor, addr_mode: IndirectX, size: 2 }), // 0x51
-    Some(Instruction { op_code: Eor, addr_mode: Zp, size: 2 }), // 0x52
-    None, // 0x53
-    None, // 0x54
-    Some(Instruction { op_code: Eor, addr_mode: ZpX, size: 2 }), // 0x55
-    Some(Instruction { op_code: Lsr, addr_mode: ZpX, size: 2 }), // 0x56
-    None, // 0x57
-    Some(Instruction { op_code: Cli, addr_mode: Implicit, size: 1 }), // 0x58
-    Some(Instruction { op_code: Eor, addr_mode: AbsoluteY, size: 3 }), // 0x59
-    None, // 0x5a
-    None, // 0x5b
-    None, // 0x5c
-    Some(Instruction { op_code: Eor, addr_mode: AbsoluteX, size: 3 }), // 0x5d
-    Some(Instruction { op_code: Lsr, addr_mode: AbsoluteX, size: 3 }), // 0x5e
-    None, // 0x5f
-    Some(Instruction { op_code: Rts, addr_mode: Implicit, size: 1 }), // 0x60
-    Some(Instruction { op_code: Adc, addr_mode: IndirectY, size: 2 }), // 0x61
-    None, // 0x62
-    None, // 0x63
-    None, // 0x64
-    Some(Instruction { op_code: Adc, addr_mode: Zp, size: 2 }), // 0x65
-    Some(Instruction { op_code: Ror, addr_mode: Zp, size: 2 }), // 0x66
-    None, // 0x67
-    Some(Instruction { op_code: Pla, addr_mode: Implicit, size: 1 }), // 0x68
-    Some(Instruction { op_code: Adc, addr_mode: Immediate, size: 2 }), // 0x69
-    Some(Instruction { op_code: Ror, addr_mode: Accumulator, size: 1 }), // 0x6a
-    None, // 0x6b
-    Some(Instruction { op_code: Jmp, addr_mode: Indirect, size: 3 }), // 0x6c
-    Some(Instruction { op_code: Adc, addr_mode: Absolute, size: 3 }), // 0x6d
-    Some(Instruction { op_code: Ror, addr_mode: Absolute, size: 3 }), // 0x6e
-    None, // 0x6f
-    Some(Instruction { op_code: Bvs, addr_mode: Relative, size: 2 }), // 0x70
-    Some(Instruction { op_code: Adc, addr_mode: IndirectX, size: 2 }), // 0x71
-    Some(Instruction { op_code: Adc, addr_mode: Zp, size: 2 }), // 0x72
-    None, // 0x73
-    None, // 0x74
-    Some(Instruction { op_code: Adc, addr_mode: ZpX, size: 2 }), // 0x75
-    Some(Instruction { op_code: Ror, addr_mode: ZpX, size: 2 }), // 0x76
-    None, // 0x77
-    Some(Instruction { op_code: Sei, addr_mode: Implicit, size: 1 }), // 0x78
-    Some(Instruction { op_code: Adc, addr_mode: AbsoluteY, size: 3 }), // 0x79
-    None, // 0x7a
-    None, // 0x7b
-    Some(Instruction { op_code: Jmp, addr_mode: AbsoluteX, size: 3 }), // 0x7c
-    Some(Instruction { op_code: Adc, addr_mode: AbsoluteX, size: 3 }), // 0x7d
-    Some(Instruction { op_code: Ror, addr_mode: AbsoluteX, size: 3 }), // 0x7e
-    None, // 0x7f
-    None, // 0x80
-    Some(Instruction { op_code: Sta, addr_mode: IndirectY, size: 2 }), // 0x81
-    None, // 0x82
-    None, // 0x83
-    Some(Instruction { op_code: Sty, addr_mode: Zp, size: 2 }), // 0x84
-    Some(Instruction { op_code: Sta, addr_mode: Zp, size: 2 }), // 0x85
-    Some(Instruction { op_code: Stx, addr_mode: Zp, size: 2 }), // 0x86
-    None, // 0x87
-    Some(Instruction { op_code: Dey, addr_mode: Implicit, size: 1 }), // 0x88
-    Some(Instruction { op_code: Bit, addr_mode: Immediate, size: 2 }), // 0x89
-    Some(Instruction { op_code: Txa, addr_mode: Implicit, size: 1 }), // 0x8a
-    None, // 0x8b
-    Some(Instruction { op_code: Sty, addr_mode: Absolute, size: 3 }), // 0x8c
-    Some(Instruction { op_code: Sta, addr_mode: Absolute, size: 3 }), // 0x8d
-    Some(Instruction { op_code: Stx, addr_mode: Absolute, size: 3 }), // 0x8e
-    None, // 0x8f
-    Some(Instruction { op_code: Bcc, addr_mode: Relative, size: 2 }), // 0x90
-    Some(Instruction { op_code: Sta, addr_mode: IndirectX, size: 2 }), // 0x91
-    Some(Instruction { op_code: Sta, addr_mode: Zp, size: 2 }), // 0x92
-    None, // 0x93
-    Some(Instruction { op_code: Sty, addr_mode: ZpX, size: 2 }), // 0x94
-    Some(Instruction { op_code: Sta, addr_mode: ZpX, size: 2 }), // 0x95
-    Some(Instruction { op_code: Stx, addr_mode: ZpY, size: 2 }), // 0x96
-    None, // 0x97
-    Some(Instruction { op_code: Tya, addr_mode: Implicit, size: 1 }), // 0x98
-    Some(Instruction { op_code: Sta, addr_mode: AbsoluteY, size: 3 }), // 0x99
-    Some(Instruction { op_code: Txs, addr_mode: Implicit, size: 1 }), // 0x9a
-    None, // 0x9b
-    None, // 0x9c
-    Some(Instruction { op_code: Sta, addr_mode: AbsoluteX, size: 3 }), // 0x9d
-    None, // 0x9e
-    None, // 0x9f
-    Some(Instruction { op_code: Ldy, addr_mode: Immediate, size: 2 }), // 0xa0
-    Some(Instruction { op_code: Lda, addr_mode: IndirectY, size: 2 }), // 0xa1
-    Some(Instruction { op_code: Ldx, addr_mode: Immediate, size: 2 }), // 0xa2
-    None, // 0xa3
-    Some(Instruction { op_code: Ldy, addr_mode: Zp, size: 2 }), // 0xa4
-    Some(Instruction { op_code: Lda, addr_mode: Zp, size: 2 }), // 0xa5
-    Some(Instruction { op_code: Ldx, addr_mode: Zp, size: 2 }), // 0xa6
-    None, // 0xa7
-    Some(Instruction { op_code: Tay, addr_mode: Implicit, size: 1 }), // 0xa8
-    Some(Instruction { op_code: Lda, addr_mode: Immediate, size: 2 }), // 0xa9
-    Some(Instruction { op_code: Tax, addr_mode: Implicit, size: 1 }), // 0xaa
-    None, // 0xab
-    Some(Instruction { op_code: Ldy, addr_mode: Absolute, size: 3 }), // 0xac
-    Some(Instruction { op_code: Lda, addr_mode: Absolute, size: 3 }), // 0xad
-    Some(Instruction { op_code: Ldx, addr_mode: Absolute, size: 3 }), // 0xae
-    None, // 0xaf
-    Some(Instruction { op_code: Bcs, addr_mode: Relative, size: 2 }), // 0xb0
-    Some(Instruction { op_code: Lda, addr_mode: IndirectX, size: 2 }), // 0xb1
-    Some(Instruction { op_code: Lda, addr_mode: Zp, size: 2 }), // 0xb2
-    None, // 0xb3
-    Some(Instruction { op_code: Ldy, addr_mode: ZpX, size: 2 }), // 0xb4
-    Some(Instruction { op_code: Lda, addr_mode: ZpX, size: 2 }), // 0xb5
-    Some(Instruction { op_code: Ldx, addr_mode: ZpY, size: 2 }), // 0xb6
-    None, // 0xb7
-    Some(Instruction { op_code: Clv, addr_mode: Implicit, size: 1 }), // 0xb8
-    Some(Instruction { op_code: Lda, addr_mode: AbsoluteY, size: 3 }), // 0xb9
-    Some(Instruction { op_code: Tsx, addr_mode: Implicit, size: 1 }), // 0xba
-    None, // 0xbb
-    Some(Instruction { op_code: Ldy, addr_mode: AbsoluteX, size: 3 }), // 0xbc
-    Some(Instruction { op_code: Lda, addr_mode: AbsoluteX, size: 3 }), // 0xbd
-    Some(Instruction { op_code: Ldx, addr_mode: AbsoluteY, size: 3 }), // 0xbe
-    None, // 0xbf
-    Some(Instruction { op_code: Cpy, addr_mode: Immediate, size: 2 }), // 0xc0
-    Some(Instruction { op_code: Cmp, addr_mode: IndirectY, size: 2 }), // 0xc1
-    None, // 0xc2
-    None, // 0xc3
-    Some(Instruction { op_code: Cpy, addr_mode: Zp, size: 2 }), // 0xc4
-    Some(Instruction { op_code: Cmp, addr_mode: Zp, size: 2 }), // 0xc5
-    Some(Instruction { op_code: Dec, addr_mode: Zp, size: 2 }), // 0xc6
-    None, // 0xc7
-    Some(Instruction { op_code: Iny, addr_mode: Implicit, size: 1 }), // 0xc8
-    Some(Instruction { op_code: Cmp, addr_mode: Immediate, size: 2 }), // 0xc9
-    Some(Instruction { op_code: Dex, addr_mode: Implicit, size: 1 }), // 0xca
-    None, // 0xcb
-    Some(Instruction { op_code: Cpy, addr_mode: Absolute, size: 3 }), // 0xcc
-    Some(Instruction { op_code: Cmp, addr_mode: Absolute, size: 3 }), // 0xcd
-    Some(Instruction { op_code: Dec, addr_mode: Absolute, size: 3 }), // 0xce
-    None, // 0xcf
-    Some(Instruction { op_code: Bne, addr_mode: Relative, size: 2 }), // 0xd0
-    Some(Instruction { op_code: Cmp, addr_mode: IndirectX, size: 2 }), // 0xd1
-    Some(Instruction { op_code: Cmp, addr_mode: Zp, size: 2 }), // 0xd2
-    None, // 0xd3
-    None, // 0xd4
-    Some(Instruction { op_code: Cmp, addr_mode: ZpX, size: 2 }), // 0xd5
-    Some(Instruction { op_code: Dec, addr_mode: ZpX, size: 2 }), // 0xd6
-    None, // 0xd7
-    Some(Instruction { op_code: Cld, addr_mode: Implicit, size: 1 }), // 0xd8
-    Some(Instruction { op_code: Cmp, addr_mode: AbsoluteY, size: 3 }), // 0xd9
-    None, // 0xda
-    None, // 0xdb
-    None, // 0xdc
-    Some(Instruction { op_code: Cmp, addr_mode: AbsoluteX, size: 3 }), // 0xdd
-    Some(Instruction { op_code: Dec, addr_mode: AbsoluteX, size: 3 }), // 0xde
-    None, // 0xdf
-    Some(Instruction { op_code: Cpx, addr_mode: Immediate, size: 2 }), // 0xe0
-    Some(Instruction { op_code: Sbc, addr_mode: IndirectY, size: 2 }), // 0xe1
-    None, // 0xe2
-    None, // 0xe3
-    Some(Instruction { op_code: Cpx, addr_mode: Zp, size: 2 }), // 0xe4
-    Some(Instruction { op_code: Sbc, addr_mode: Zp, size: 2 }), // 0xe5
-    Some(Instruction { op_code: Inc, addr_mode: Zp, size: 2 }), // 0xe6
-    None, // 0xe7
-    Some(Instruction { op_code: Inx, addr_mode: Implicit, size: 1 }), // 0xe8
-    Some(Instruction { op_code: Sbc, addr_mode: Immediate, size: 2 }), // 0xe9
-    Some(Instruction { op_code: Nop, addr_mode: Implicit, size: 1 }), // 0xea
-    None, // 0xeb
-    Some(Instruction { op_code: Cpx, addr_mode: Absolute, size: 3 }), // 0xec
-    Some(Instruction { op_code: Sbc, addr_mode: Absolute, size: 3 }), // 0xed
-    Some(Instruction { op_code: Inc, addr_mode: Absolute, size: 3 }), // 0xee
-    None, // 0xef
-    Some(Instruction { op_code: Beq, addr_mode: Relative, size: 2 }), // 0xf0
-    Some(Instruction { op_code: Sbc, addr_mode: IndirectX, size: 2 }), // 0xf1
-    Some(Instruction { op_code: Sbc, addr_mode: Zp, size: 2 }), // 0xf2
-    None, // 0xf3
-    None, // 0xf4
-    Some(Instruction { op_code: Sbc, addr_mode: ZpX, size: 2 }), // 0xf5
-    Some(Instruction { op_code: Inc, addr_mode: ZpX, size: 2 }), // 0xf6
-    None, // 0xf7
-    Some(Instruction { op_code: Sed, addr_mode: Implicit, size: 1 }), // 0xf8
-    Some(Instruction { op_code: Sbc, addr_mode: AbsoluteY, size: 3 }), // 0xf9
-    None, // 0xfa
-    None, // 0xfb
-    None, // 0xfc
-    Some(Instruction { op_code: Sbc, addr_mode: AbsoluteX, size: 3 }), // 0xfd
-    Some(Instruction { op_code: Inc, addr_mode: AbsoluteX, size: 3 }), // 0xfe
-    None, // 0xff
-];
\ No newline at end of file
+// https://www.nesdev.org/wiki/CPU_unofficial_opcodes
+// kil/jam locks up the cpu, its cycles are meaningless
+pub static OP_CODES: [Instruction; 256] = [
+    Instruction::new(Brk, Implicit, 7, Access::None), // 0x00
+    Instruction::new(Ora, IndirectX, 6, Read), // 0x01
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x02
+    Instruction::new(Slo, IndirectX, 8, ReadModifyWrite).unofficial(), // 0x03
+    Instruction::new(Nop, Zp, 3, Read).unofficial(), // 0x04
+    Instruction::new(Ora, Zp, 3, Read), // 0x05
+    Instruction::new(Asl, Zp, 5, ReadModifyWrite), // 0x06
+    Instruction::new(Slo, Zp, 5, ReadModifyWrite).unofficial(), // 0x07
+    Instruction::new(Php, Implicit, 3, Access::None), // 0x08
+    Instruction::new(Ora, Immediate, 2, Read), // 0x09
+    Instruction::new(Asl, Accumulator, 2, Access::None), // 0x0a
+    Instruction::new(Anc, Immediate, 2, Read).unofficial(), // 0x0b
+    Instruction::new(Nop, Absolute, 4, Read).unofficial(), // 0x0c
+    Instruction::new(Ora, Absolute, 4, Read), // 0x0d
+    Instruction::new(Asl, Absolute, 6, ReadModifyWrite), // 0x0e
+    Instruction::new(Slo, Absolute, 6, ReadModifyWrite).unofficial(), // 0x0f
+    Instruction::new(Bpl, Relative, 2, Access::None).page_penalty(), // 0x10
+    Instruction::new(Ora, IndirectY, 5, Read).page_penalty(), // 0x11
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x12
+    Instruction::new(Slo, IndirectY, 8, ReadModifyWrite).unofficial(), // 0x13
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0x14
+    Instruction::new(Ora, ZpX, 4, Read), // 0x15
+    Instruction::new(Asl, ZpX, 6, ReadModifyWrite), // 0x16
+    Instruction::new(Slo, ZpX, 6, ReadModifyWrite).unofficial(), // 0x17
+    Instruction::new(Clc, Implicit, 2, Access::None), // 0x18
+    Instruction::new(Ora, AbsoluteY, 4, Read).page_penalty(), // 0x19
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0x1a
+    Instruction::new(Slo, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0x1b
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0x1c
+    Instruction::new(Ora, AbsoluteX, 4, Read).page_penalty(), // 0x1d
+    Instruction::new(Asl, AbsoluteX, 7, ReadModifyWrite), // 0x1e
+    Instruction::new(Slo, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0x1f
+    Instruction::new(Jsr, Absolute, 6, Access::None), // 0x20
+    Instruction::new(And, IndirectX, 6, Read), // 0x21
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x22
+    Instruction::new(Rla, IndirectX, 8, ReadModifyWrite).unofficial(), // 0x23
+    Instruction::new(Bit, Zp, 3, Read), // 0x24
+    Instruction::new(And, Zp, 3, Read), // 0x25
+    Instruction::new(Rol, Zp, 5, ReadModifyWrite), // 0x26
+    Instruction::new(Rla, Zp, 5, ReadModifyWrite).unofficial(), // 0x27
+    Instruction::new(Plp, Implicit, 4, Access::None), // 0x28
+    Instruction::new(And, Immediate, 2, Read), // 0x29
+    Instruction::new(Rol, Accumulator, 2, Access::None), // 0x2a
+    Instruction::new(Anc, Immediate, 2, Read).unofficial(), // 0x2b
+    Instruction::new(Bit, Absolute, 4, Read), // 0x2c
+    Instruction::new(And, Absolute, 4, Read), // 0x2d
+    Instruction::new(Rol, Absolute, 6, ReadModifyWrite), // 0x2e
+    Instruction::new(Rla, Absolute, 6, ReadModifyWrite).unofficial(), // 0x2f
+    Instruction::new(Bmi, Relative, 2, Access::None).page_penalty(), // 0x30
+    Instruction::new(And, IndirectY, 5, Read).page_penalty(), // 0x31
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x32
+    Instruction::new(Rla, IndirectY, 8, ReadModifyWrite).unofficial(), // 0x33
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0x34
+    Instruction::new(And, ZpX, 4, Read), // 0x35
+    Instruction::new(Rol, ZpX, 6, ReadModifyWrite), // 0x36
+    Instruction::new(Rla, ZpX, 6, ReadModifyWrite).unofficial(), // 0x37
+    Instruction::new(Sec, Implicit, 2, Access::None), // 0x38
+    Instruction::new(And, AbsoluteY, 4, Read).page_penalty(), // 0x39
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0x3a
+    Instruction::new(Rla, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0x3b
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0x3c
+    Instruction::new(And, AbsoluteX, 4, Read).page_penalty(), // 0x3d
+    Instruction::new(Rol, AbsoluteX, 7, ReadModifyWrite), // 0x3e
+    Instruction::new(Rla, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0x3f
+    Instruction::new(Rti, Implicit, 6, Access::None), // 0x40
+    Instruction::new(Eor, IndirectX, 6, Read), // 0x41
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x42
+    Instruction::new(Sre, IndirectX, 8, ReadModifyWrite).unofficial(), // 0x43
+    Instruction::new(Nop, Zp, 3, Read).unofficial(), // 0x44
+    Instruction::new(Eor, Zp, 3, Read), // 0x45
+    Instruction::new(Lsr, Zp, 5, ReadModifyWrite), // 0x46
+    Instruction::new(Sre, Zp, 5, ReadModifyWrite).unofficial(), // 0x47
+    Instruction::new(Pha, Implicit, 3, Access::None), // 0x48
+    Instruction::new(Eor, Immediate, 2, Read), // 0x49
+    Instruction::new(Lsr, Accumulator, 2, Access::None), // 0x4a
+    Instruction::new(Alr, Immediate, 2, Read).unofficial(), // 0x4b
+    Instruction::new(Jmp, Absolute, 3, Access::None), // 0x4c
+    Instruction::new(Eor, Absolute, 4, Read), // 0x4d
+    Instruction::new(Lsr, Absolute, 6, ReadModifyWrite), // 0x4e
+    Instruction::new(Sre, Absolute, 6, ReadModifyWrite).unofficial(), // 0x4f
+    Instruction::new(Bvc, Relative, 2, Access::None).page_penalty(), // 0x50
+    Instruction::new(Eor, IndirectY, 5, Read).page_penalty(), // 0x51
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x52
+    Instruction::new(Sre, IndirectY, 8, ReadModifyWrite).unofficial(), // 0x53
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0x54
+    Instruction::new(Eor, ZpX, 4, Read), // 0x55
+    Instruction::new(Lsr, ZpX, 6, ReadModifyWrite), // 0x56
+    Instruction::new(Sre, ZpX, 6, ReadModifyWrite).unofficial(), // 0x57
+    Instruction::new(Cli, Implicit, 2, Access::None), // 0x58
+    Instruction::new(Eor, AbsoluteY, 4, Read).page_penalty(), // 0x59
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0x5a
+    Instruction::new(Sre, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0x5b
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0x5c
+    Instruction::new(Eor, AbsoluteX, 4, Read).page_penalty(), // 0x5d
+    Instruction::new(Lsr, AbsoluteX, 7, ReadModifyWrite), // 0x5e
+    Instruction::new(Sre, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0x5f
+    Instruction::new(Rts, Implicit, 6, Access::None), // 0x60
+    Instruction::new(Adc, IndirectX, 6, Read), // 0x61
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x62
+    Instruction::new(Rra, IndirectX, 8, ReadModifyWrite).unofficial(), // 0x63
+    Instruction::new(Nop, Zp, 3, Read).unofficial(), // 0x64
+    Instruction::new(Adc, Zp, 3, Read), // 0x65
+    Instruction::new(Ror, Zp, 5, ReadModifyWrite), // 0x66
+    Instruction::new(Rra, Zp, 5, ReadModifyWrite).unofficial(), // 0x67
+    Instruction::new(Pla, Implicit, 4, Access::None), // 0x68
+    Instruction::new(Adc, Immediate, 2, Read), // 0x69
+    Instruction::new(Ror, Accumulator, 2, Access::None), // 0x6a
+    Instruction::new(Arr, Immediate, 2, Read).unofficial(), // 0x6b
+    Instruction::new(Jmp, Indirect, 5, Access::None), // 0x6c
+    Instruction::new(Adc, Absolute, 4, Read), // 0x6d
+    Instruction::new(Ror, Absolute, 6, ReadModifyWrite), // 0x6e
+    Instruction::new(Rra, Absolute, 6, ReadModifyWrite).unofficial(), // 0x6f
+    Instruction::new(Bvs, Relative, 2, Access::None).page_penalty(), // 0x70
+    Instruction::new(Adc, IndirectY, 5, Read).page_penalty(), // 0x71
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x72
+    Instruction::new(Rra, IndirectY, 8, ReadModifyWrite).unofficial(), // 0x73
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0x74
+    Instruction::new(Adc, ZpX, 4, Read), // 0x75
+    Instruction::new(Ror, ZpX, 6, ReadModifyWrite), // 0x76
+    Instruction::new(Rra, ZpX, 6, ReadModifyWrite).unofficial(), // 0x77
+    Instruction::new(Sei, Implicit, 2, Access::None), // 0x78
+    Instruction::new(Adc, AbsoluteY, 4, Read).page_penalty(), // 0x79
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0x7a
+    Instruction::new(Rra, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0x7b
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0x7c
+    Instruction::new(Adc, AbsoluteX, 4, Read).page_penalty(), // 0x7d
+    Instruction::new(Ror, AbsoluteX, 7, ReadModifyWrite), // 0x7e
+    Instruction::new(Rra, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0x7f
+    Instruction::new(Nop, Immediate, 2, Read).unofficial(), // 0x80
+    Instruction::new(Sta, IndirectX, 6, Write), // 0x81
+    Instruction::new(Nop, Immediate, 2, Read).unofficial(), // 0x82
+    Instruction::new(Sax, IndirectX, 6, Write).unofficial(), // 0x83
+    Instruction::new(Sty, Zp, 3, Write), // 0x84
+    Instruction::new(Sta, Zp, 3, Write), // 0x85
+    Instruction::new(Stx, Zp, 3, Write), // 0x86
+    Instruction::new(Sax, Zp, 3, Write).unofficial(), // 0x87
+    Instruction::new(Dey, Implicit, 2, Access::None), // 0x88
+    Instruction::new(Nop, Immediate, 2, Read).unofficial(), // 0x89
+    Instruction::new(Txa, Implicit, 2, Access::None), // 0x8a
+    Instruction::new(Xaa, Immediate, 2, Read).unofficial(), // 0x8b
+    Instruction::new(Sty, Absolute, 4, Write), // 0x8c
+    Instruction::new(Sta, Absolute, 4, Write), // 0x8d
+    Instruction::new(Stx, Absolute, 4, Write), // 0x8e
+    Instruction::new(Sax, Absolute, 4, Write).unofficial(), // 0x8f
+    Instruction::new(Bcc, Relative, 2, Access::None).page_penalty(), // 0x90
+    Instruction::new(Sta, IndirectY, 6, Write), // 0x91
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0x92
+    Instruction::new(Ahx, IndirectY, 6, Write).unofficial(), // 0x93
+    Instruction::new(Sty, ZpX, 4, Write), // 0x94
+    Instruction::new(Sta, ZpX, 4, Write), // 0x95
+    Instruction::new(Stx, ZpY, 4, Write), // 0x96
+    Instruction::new(Sax, ZpY, 4, Write).unofficial(), // 0x97
+    Instruction::new(Tya, Implicit, 2, Access::None), // 0x98
+    Instruction::new(Sta, AbsoluteY, 5, Write), // 0x99
+    Instruction::new(Txs, Implicit, 2, Access::None), // 0x9a
+    Instruction::new(Tas, AbsoluteY, 5, Write).unofficial(), // 0x9b
+    Instruction::new(Shy, AbsoluteX, 5, Write).unofficial(), // 0x9c
+    Instruction::new(Sta, AbsoluteX, 5, Write), // 0x9d
+    Instruction::new(Shx, AbsoluteY, 5, Write).unofficial(), // 0x9e
+    Instruction::new(Ahx, AbsoluteY, 5, Write).unofficial(), // 0x9f
+    Instruction::new(Ldy, Immediate, 2, Read), // 0xa0
+    Instruction::new(Lda, IndirectX, 6, Read), // 0xa1
+    Instruction::new(Ldx, Immediate, 2, Read), // 0xa2
+    Instruction::new(Lax, IndirectX, 6, Read).unofficial(), // 0xa3
+    Instruction::new(Ldy, Zp, 3, Read), // 0xa4
+    Instruction::new(Lda, Zp, 3, Read), // 0xa5
+    Instruction::new(Ldx, Zp, 3, Read), // 0xa6
+    Instruction::new(Lax, Zp, 3, Read).unofficial(), // 0xa7
+    Instruction::new(Tay, Implicit, 2, Access::None), // 0xa8
+    Instruction::new(Lda, Immediate, 2, Read), // 0xa9
+    Instruction::new(Tax, Implicit, 2, Access::None), // 0xaa
+    Instruction::new(Lax, Immediate, 2, Read).unofficial(), // 0xab
+    Instruction::new(Ldy, Absolute, 4, Read), // 0xac
+    Instruction::new(Lda, Absolute, 4, Read), // 0xad
+    Instruction::new(Ldx, Absolute, 4, Read), // 0xae
+    Instruction::new(Lax, Absolute, 4, Read).unofficial(), // 0xaf
+    Instruction::new(Bcs, Relative, 2, Access::None).page_penalty(), // 0xb0
+    Instruction::new(Lda, IndirectY, 5, Read).page_penalty(), // 0xb1
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0xb2
+    Instruction::new(Lax, IndirectY, 5, Read).page_penalty().unofficial(), // 0xb3
+    Instruction::new(Ldy, ZpX, 4, Read), // 0xb4
+    Instruction::new(Lda, ZpX, 4, Read), // 0xb5
+    Instruction::new(Ldx, ZpY, 4, Read), // 0xb6
+    Instruction::new(Lax, ZpY, 4, Read).unofficial(), // 0xb7
+    Instruction::new(Clv, Implicit, 2, Access::None), // 0xb8
+    Instruction::new(Lda, AbsoluteY, 4, Read).page_penalty(), // 0xb9
+    Instruction::new(Tsx, Implicit, 2, Access::None), // 0xba
+    Instruction::new(Las, AbsoluteY, 4, Read).page_penalty().unofficial(), // 0xbb
+    Instruction::new(Ldy, AbsoluteX, 4, Read).page_penalty(), // 0xbc
+    Instruction::new(Lda, AbsoluteX, 4, Read).page_penalty(), // 0xbd
+    Instruction::new(Ldx, AbsoluteY, 4, Read).page_penalty(), // 0xbe
+    Instruction::new(Lax, AbsoluteY, 4, Read).page_penalty().unofficial(), // 0xbf
+    Instruction::new(Cpy, Immediate, 2, Read), // 0xc0
+    Instruction::new(Cmp, IndirectX, 6, Read), // 0xc1
+    Instruction::new(Nop, Immediate, 2, Read).unofficial(), // 0xc2
+    Instruction::new(Dcp, IndirectX, 8, ReadModifyWrite).unofficial(), // 0xc3
+    Instruction::new(Cpy, Zp, 3, Read), // 0xc4
+    Instruction::new(Cmp, Zp, 3, Read), // 0xc5
+    Instruction::new(Dec, Zp, 5, ReadModifyWrite), // 0xc6
+    Instruction::new(Dcp, Zp, 5, ReadModifyWrite).unofficial(), // 0xc7
+    Instruction::new(Iny, Implicit, 2, Access::None), // 0xc8
+    Instruction::new(Cmp, Immediate, 2, Read), // 0xc9
+    Instruction::new(Dex, Implicit, 2, Access::None), // 0xca
+    Instruction::new(Axs, Immediate, 2, Read).unofficial(), // 0xcb
+    Instruction::new(Cpy, Absolute, 4, Read), // 0xcc
+    Instruction::new(Cmp, Absolute, 4, Read), // 0xcd
+    Instruction::new(Dec, Absolute, 6, ReadModifyWrite), // 0xce
+    Instruction::new(Dcp, Absolute, 6, ReadModifyWrite).unofficial(), // 0xcf
+    Instruction::new(Bne, Relative, 2, Access::None).page_penalty(), // 0xd0
+    Instruction::new(Cmp, IndirectY, 5, Read).page_penalty(), // 0xd1
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0xd2
+    Instruction::new(Dcp, IndirectY, 8, ReadModifyWrite).unofficial(), // 0xd3
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0xd4
+    Instruction::new(Cmp, ZpX, 4, Read), // 0xd5
+    Instruction::new(Dec, ZpX, 6, ReadModifyWrite), // 0xd6
+    Instruction::new(Dcp, ZpX, 6, ReadModifyWrite).unofficial(), // 0xd7
+    Instruction::new(Cld, Implicit, 2, Access::None), // 0xd8
+    Instruction::new(Cmp, AbsoluteY, 4, Read).page_penalty(), // 0xd9
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0xda
+    Instruction::new(Dcp, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0xdb
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0xdc
+    Instruction::new(Cmp, AbsoluteX, 4, Read).page_penalty(), // 0xdd
+    Instruction::new(Dec, AbsoluteX, 7, ReadModifyWrite), // 0xde
+    Instruction::new(Dcp, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0xdf
+    Instruction::new(Cpx, Immediate, 2, Read), // 0xe0
+    Instruction::new(Sbc, IndirectX, 6, Read), // 0xe1
+    Instruction::new(Nop, Immediate, 2, Read).unofficial(), // 0xe2
+    Instruction::new(Isc, IndirectX, 8, ReadModifyWrite).unofficial(), // 0xe3
+    Instruction::new(Cpx, Zp, 3, Read), // 0xe4
+    Instruction::new(Sbc, Zp, 3, Read), // 0xe5
+    Instruction::new(Inc, Zp, 5, ReadModifyWrite), // 0xe6
+    Instruction::new(Isc, Zp, 5, ReadModifyWrite).unofficial(), // 0xe7
+    Instruction::new(Inx, Implicit, 2, Access::None), // 0xe8
+    Instruction::new(Sbc, Immediate, 2, Read), // 0xe9
+    Instruction::new(Nop, Implicit, 2, Access::None), // 0xea
+    Instruction::new(Sbc, Immediate, 2, Read).unofficial(), // 0xeb
+    Instruction::new(Cpx, Absolute, 4, Read), // 0xec
+    Instruction::new(Sbc, Absolute, 4, Read), // 0xed
+    Instruction::new(Inc, Absolute, 6, ReadModifyWrite), // 0xee
+    Instruction::new(Isc, Absolute, 6, ReadModifyWrite).unofficial(), // 0xef
+    Instruction::new(Beq, Relative, 2, Access::None).page_penalty(), // 0xf0
+    Instruction::new(Sbc, IndirectY, 5, Read).page_penalty(), // 0xf1
+    Instruction::new(Kil, Implicit, 0, Access::None).unofficial(), // 0xf2
+    Instruction::new(Isc, IndirectY, 8, ReadModifyWrite).unofficial(), // 0xf3
+    Instruction::new(Nop, ZpX, 4, Read).unofficial(), // 0xf4
+    Instruction::new(Sbc, ZpX, 4, Read), // 0xf5
+    Instruction::new(Inc, ZpX, 6, ReadModifyWrite), // 0xf6
+    Instruction::new(Isc, ZpX, 6, ReadModifyWrite).unofficial(), // 0xf7
+    Instruction::new(Sed, Implicit, 2, Access::None), // 0xf8
+    Instruction::new(Sbc, AbsoluteY, 4, Read).page_penalty(), // 0xf9
+    Instruction::new(Nop, Implicit, 2, Access::None).unofficial(), // 0xfa
+    Instruction::new(Isc, AbsoluteY, 7, ReadModifyWrite).unofficial(), // 0xfb
+    Instruction::new(Nop, AbsoluteX, 4, Read).page_penalty().unofficial(), // 0xfc
+    Instruction::new(Sbc, AbsoluteX, 4, Read).page_penalty(), // 0xfd
+    Instruction::new(Inc, AbsoluteX, 7, ReadModifyWrite), // 0xfe
+    Instruction::new(Isc, AbsoluteX, 7, ReadModifyWrite).unofficial(), // 0xff
+];
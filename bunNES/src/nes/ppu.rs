@@ -1,15 +1,30 @@
 use std::process::exit;
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
-use bit::BitIndex;
 use bitflags::bitflags;
-use rand::random;
 use crate::nes::cpu::{HEIGHT, RenderImage, WIDTH};
-use crate::nes::rom::Cartridge;
+use crate::nes::rom::{Cartridge, Mirroring};
+use crate::state::{SaveState, StateError, StateReader, StateWriter};
 
 const PPU_INIT_TIME: u64 = 29658;
 const MAX_DOT_COUNT: u32 = 283 * 242;
 
+pub const SCREEN_WIDTH: usize = 256;
+pub const SCREEN_HEIGHT: usize = 240;
+
+const CHR_RAM_SIZE: usize = 0x2000;
+const OAM_SIZE: usize = 256;
+const PALETTE_SIZE: usize = 32;
+/// bits of a palette entry, $2007 reads of the palettes leave the top two on the latch
+const PALETTE_DATA: u8 = 0b0011_1111;
+
+const DOTS_PER_SCANLINE: u64 = 341;
+/// dots a bit of the io latch holds its value without being refreshed, about 600ms
+/// https://www.nesdev.org/wiki/Open_bus_behavior#PPU_open_bus
+const IO_LATCH_DECAY: u64 = 36 * 262 * DOTS_PER_SCANLINE;
+const VBLANK_SCANLINE: u16 = 241;
+const PRE_RENDER_SCANLINE: u16 = 261;
+
 #[allow(unused_variables, dead_code)]
 #[derive(Debug)]
 pub struct Ppu {
@@ -17,18 +32,41 @@ pub struct Ppu {
     ppu_mask: PpuMask,
     ppu_status: PpuStatus,
     oam_addr: u8,
-    oam_data: u8,
-    ppu_scroll: u8,
-    ppu_addr: PpuAddr,
-    ppu_data: PpuData,
-    oam_dma: u8,
-
-    cartridge: Arc<Cartridge>,
 
+    // https://www.nesdev.org/wiki/PPU_scrolling#PPU_internal_registers
+    /// current vram address
+    v: u16,
+    /// temporary vram address, the top left onscreen tile
+    t: u16,
+    /// fine x scroll
+    x: u8,
+    /// first or second write of $2005/$2006
+    w: bool,
+
+    /// $2007 reads below the palettes return the previously read value
+    read_buffer: u8,
+    /// last value written to any register, write only registers read back as this
+    io_latch: u8,
+    /// dot each bit of the io latch was last driven on, it fades to 0 after [`IO_LATCH_DECAY`]
+    io_latch_refreshed: [u64; 8],
 
-    cpu_cycle_count: u64,
+    cartridge: Arc<Cartridge>,
+    mirroring: Mirroring,
+    /// chr rom of the cartridge or chr ram if it has none
+    chr: Vec<u8>,
+    chr_ram: bool,
+    vram: Vec<u8>,
+    palette: [u8; PALETTE_SIZE],
+    oam: [u8; OAM_SIZE],
+
+    /// colors of the last rendered frame as indices into the system palette
+    frame: Vec<u8>,
+    nmi: bool,
+
+    scanline: u16,
+    dot: u16,
+    frame_count: u64,
     ppu_cycle_count: u64,
-    dot_count: u64,
 }
 
 #[derive(Debug)]
@@ -74,132 +112,300 @@ bitflags! {
     }
 }
 
-type PpuAddr = u8;
-type PpuData = u8;
-
-struct PatternTable {
-    tbl: [u8; 256 * 16 * 2],
-}
-
-struct NameTable {
-    tbl: [u8; 1024],
-}
-
-struct AttributeTable {
-    tbl: [u8; 64],
-}
-
-struct OAM {
-    tbl: [u8; 64 * 4],
-}
-
-struct Palette {
-
+/// a sprite pixel of the current scanline
+#[derive(Debug, Copy, Clone, Default)]
+struct SpritePixel {
+    /// palette ram index, 0 if transparent
+    color: u8,
+    behind_background: bool,
+    sprite_0: bool,
 }
 
-
 impl Ppu {
     pub fn new(cartridge: Arc<Cartridge>) -> Ppu {
+        let chr_ram = cartridge.chr().is_empty();
+        let chr = if chr_ram { vec![0; CHR_RAM_SIZE] } else { cartridge.chr().clone() };
+        let mirroring = cartridge.mirroring();
+        // four screen carts bring their own 2k of vram
+        let vram_size = if mirroring == Mirroring::FourScreen { 0x1000 } else { 0x0800 };
 
         Ppu {
             ppu_ctrl: PpuCtrl(0),
             ppu_mask: PpuMask(0),
-            ppu_status: PpuStatus(255),
+            // https://www.nesdev.org/wiki/PPU_power_up_state
+            ppu_status: PpuStatus(0b1010_0000),
             oam_addr: 0,
-            oam_data: 0,
-            ppu_scroll: 0,
-            ppu_addr: 0,
-            ppu_data: 0,
-            oam_dma: 0,
 
-            cartridge,
+            v: 0,
+            t: 0,
+            x: 0,
+            w: false,
 
+            read_buffer: 0,
+            io_latch: 0,
+            io_latch_refreshed: [0; 8],
 
-            cpu_cycle_count: 0,
+            cartridge,
+            mirroring,
+            chr,
+            chr_ram,
+            vram: vec![0; vram_size],
+            palette: [0; PALETTE_SIZE],
+            oam: [0; OAM_SIZE],
+
+            frame: vec![0; SCREEN_WIDTH * SCREEN_HEIGHT],
+            nmi: false,
+
+            scanline: 0,
+            dot: 0,
+            frame_count: 0,
             ppu_cycle_count: 0,
-            dot_count: 0,
         }
     }
 
     pub fn register(&mut self, register: u8) -> u8 {
-        let value = match register {
-            0 => self.ppu_ctrl.0,
-            1 => self.ppu_mask.0,
+        let value = self.peek_register(register);
+        // bits of the latch the read drove
+        let mut refreshed = 0xFF;
+        match register {
             2 => {
-                
+                refreshed = !PpuStatus::stale_bus.bits();
                 // set vblank to false after read
                 // https://www.nesdev.org/wiki/PPU_registers#PPUSTATUS
-                self.ppu_status.bits().set_bit(7, false);
-                self.ppu_status.0
+                self.ppu_status.remove(PpuStatus::vblank_start);
+                self.w = false;
             },
-            3 => self.oam_addr,
-            4 => self.oam_data,
-            5 => self.ppu_scroll,
-            6 => self.ppu_addr,
-            7 => self.ppu_data,
-            _ => panic!("unknown register: {register:#04X}")
-        };
+            7 => {
+                // https://www.nesdev.org/wiki/PPU_registers#The_PPUDATA_read_buffer
+                let addr = self.v & 0x3FFF;
+                // palettes are returned directly, the buffer gets the nametable byte underneath
+                let buffered = if addr < 0x3F00 { addr } else { addr - 0x1000 };
+                if addr >= 0x3F00 {
+                    refreshed = PALETTE_DATA;
+                }
+                self.read_buffer = self.read_vram(buffered);
+                self.increment_vram_addr();
+            },
+            // write only
+            0 | 1 | 3 | 5 | 6 => refreshed = 0,
+            _ => {}
+        }
 
-        // println!("PPU: get register: {register}, value: {value:#04X}");
+        self.refresh_latch(value, refreshed);
         value
     }
 
+    /// what reading `register` returns, without clearing vblank or moving the vram address
+    pub fn peek_register(&self, register: u8) -> u8 {
+        match register {
+            2 => (self.ppu_status.0 & 0b1110_0000) | (self.io_latch() & PpuStatus::stale_bus.bits()),
+            4 => self.oam[self.oam_addr as usize],
+            7 => {
+                let addr = self.v & 0x3FFF;
+                if addr < 0x3F00 {
+                    self.read_buffer
+                } else {
+                    // palette entries are 6 bits, the rest is open bus
+                    self.read_vram(addr) & PALETTE_DATA | self.io_latch() & !PALETTE_DATA
+                }
+            },
+            // write only
+            0 | 1 | 3 | 5 | 6 => self.io_latch(),
+            _ => panic!("unknown register: {register:#04X}")
+        }
+    }
+
+    /// byte at `addr` in the ppu address space, pattern tables, nametables and palettes
+    pub fn peek_vram(&self, addr: u16) -> u8 {
+        self.read_vram(addr)
+    }
+
+    /// writes `addr` in the ppu address space without touching the vram address, unlike $2007
+    /// chr rom can be changed too, it's the ppu's own copy
+    pub fn poke_vram(&mut self, addr: u16, value: u8) {
+        match addr & 0x3FFF {
+            0x0000..=0x1FFF => {
+                let len = self.chr.len();
+                self.chr[addr as usize % len] = value;
+            },
+            _ => self.write_vram(addr, value),
+        }
+    }
+
+    /// the io latch with the bits that weren't driven for too long faded to 0
+    fn io_latch(&self) -> u8 {
+        (0..8)
+            .filter(|&bit| self.ppu_cycle_count - self.io_latch_refreshed[bit] < IO_LATCH_DECAY)
+            .fold(0, |latch, bit| latch | self.io_latch & 1 << bit)
+    }
+
+    /// drives the `mask` bits of the latch with `value`
+    fn refresh_latch(&mut self, value: u8, mask: u8) {
+        self.io_latch = self.io_latch() & !mask | value & mask;
+        for bit in (0..8).filter(|bit| mask & 1 << bit != 0) {
+            self.io_latch_refreshed[bit] = self.ppu_cycle_count;
+        }
+    }
+
     pub fn set_register(&mut self, register: u8, value: u8) {
-        // delay until ppu boots i guess?
-        let mut could_write = true;
+        self.refresh_latch(value, 0xFF);
         match register {
-            0 => self.ppu_ctrl.0 = value,
-            1 => self.ppu_mask.0 = value,
-            2 => self.ppu_status.0 = value,
+            0 => {
+                let nmi_enabled = self.ppu_ctrl.contains(PpuCtrl::gen_nmi_vblank);
+                self.ppu_ctrl = PpuCtrl(value);
+                self.t = (self.t & !0x0C00) | ((value & PpuCtrl::base_nametable.bits()) as u16) << 10;
+                // enabling nmi while in vblank triggers it right away
+                if !nmi_enabled && self.ppu_ctrl.contains(PpuCtrl::gen_nmi_vblank)
+                    && self.ppu_status.contains(PpuStatus::vblank_start) {
+                    self.nmi = true;
+                }
+            },
+            1 => self.ppu_mask = PpuMask(value),
+            // read only
+            2 => {},
             3 => self.oam_addr = value,
-            4 => self.oam_data = value,
-            5 => self.ppu_scroll = value,
-            6 => self.ppu_addr = value,
-            7 => self.ppu_data = value,
+            4 => {
+                self.oam[self.oam_addr as usize] = value;
+                self.oam_addr = self.oam_addr.wrapping_add(1);
+            },
+            5 => {
+                if !self.w {
+                    self.t = (self.t & !0x001F) | (value >> 3) as u16;
+                    self.x = value & 0b111;
+                } else {
+                    self.t = (self.t & !0x73E0) | ((value & 0b111) as u16) << 12 | ((value >> 3) as u16) << 5;
+                }
+                self.w = !self.w;
+            },
+            6 => {
+                if !self.w {
+                    self.t = (self.t & 0x00FF) | ((value & 0x3F) as u16) << 8;
+                } else {
+                    self.t = (self.t & 0xFF00) | value as u16;
+                    self.v = self.t;
+                }
+                self.w = !self.w;
+            },
+            7 => {
+                self.write_vram(self.v & 0x3FFF, value);
+                self.increment_vram_addr();
+            },
             _ => panic!("unknown register: {register:#04X}")
         };
+    }
+
+    /// $4014, the bus copies a whole page into oam starting at the current oam address
+    pub fn write_oam_dma(&mut self, data: &[u8]) {
+        for value in data {
+            self.oam[self.oam_addr as usize] = *value;
+            self.oam_addr = self.oam_addr.wrapping_add(1);
+        }
+    }
+
+    /// nmi requested since the last call
+    pub fn take_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi)
+    }
+
+    /// last rendered frame, `SCREEN_WIDTH * SCREEN_HEIGHT` system palette indices
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame
+    }
+
+    /// where the next $2007 access goes
+    pub fn vram_addr(&self) -> u16 {
+        self.v & 0x3FFF
+    }
+
+    pub fn scanline(&self) -> u16 {
+        self.scanline
+    }
+
+    pub fn dot(&self) -> u16 {
+        self.dot
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// dots stepped since power up
+    pub fn cycle_count(&self) -> u64 {
+        self.ppu_cycle_count
+    }
+
+    /// 64 sprites of 4 bytes, y, tile, attributes and x
+    pub fn oam(&self) -> &[u8] {
+        &self.oam
+    }
+
+    pub fn poke_oam(&mut self, index: u8, value: u8) {
+        self.oam[index as usize] = value;
+    }
+
+    /// $0000 or $1000
+    pub fn background_pattern_table(&self) -> u16 {
+        if self.ppu_ctrl.contains(PpuCtrl::bgr_ptrn_addr) { 0x1000 } else { 0 }
+    }
 
-        // println!("PPU: set register: {register}, value: {value:#04X}");
+    /// $0000 or $1000, 8x16 sprites ignore it and pick one with bit 0 of their tile
+    pub fn sprite_pattern_table(&self) -> u16 {
+        if self.ppu_ctrl.contains(PpuCtrl::spr_ptrn_addr) { 0x1000 } else { 0 }
+    }
+
+    /// 8 or 16
+    pub fn sprite_height(&self) -> u16 {
+        if self.ppu_ctrl.contains(PpuCtrl::spr_size) { 16 } else { 8 }
+    }
+
+    /// top left of the screen in the 512x480 pixels of the four nametables. comes from t,
+    /// so it's where the next frame starts unless the game scrolls midway through it
+    pub fn scroll(&self) -> (u16, u16) {
+        let coarse_x = self.t & 0x001F;
+        let coarse_y = (self.t >> 5) & 0x001F;
+        let fine_y = (self.t >> 12) & 0b111;
+        let nametable = (self.t >> 10) & 0b11;
+        let x = (nametable & 1) * 256 + coarse_x * 8 + self.x as u16;
+        let y = (nametable >> 1) * 240 + coarse_y * 8 + fine_y;
+        (x, y)
     }
 
     pub fn step(&mut self, scanline: u64) {
-        let scanline = scanline % 262;
+        let scanline = (scanline % 262) as u16;
+        let dot = (self.ppu_cycle_count % DOTS_PER_SCANLINE) as u16;
+        self.scanline = scanline;
+        self.dot = dot;
+
         match scanline {
             0..=239 => {
-                // visible scanlines
-                match self.ppu_cycle_count % 340 {
-                    0 => {
-                        // idle cycle
-                    },
-                    1..=256 => {
-                        // fetching data for tiles
-                    },
-                    257..=320 => {
-                        // next scanline tile data fetch
-                    },
-                    321..=336 => {
-                        // first two tiles for next scanline
-                    },
-                    337..=340 => {
-                        // unknown fetches
-                    },
-                    _ => panic!("ppu cycle not matched: {} [{}]", self.ppu_cycle_count, self.ppu_cycle_count % 340)
+                // visible scanlines, drawn in one go instead of fetching tiles per dot
+                if dot == 1 {
+                    self.render_scanline();
                 }
-
-
             },
             240 => {
             },
-            241..=260 => {
-                if self.ppu_cycle_count % 340 == 1 {
-                    self.ppu_status.bits().set_bit(7, true);
+            VBLANK_SCANLINE => {
+                if dot == 1 {
+                    self.ppu_status.insert(PpuStatus::vblank_start);
+                    if self.ppu_ctrl.contains(PpuCtrl::gen_nmi_vblank) {
+                        self.nmi = true;
+                    }
+                    self.frame_count += 1;
                 }
             },
-            261 => {
-                // or -1
-                // dummy
-                if self.ppu_cycle_count % 340 == 1 {
-                    self.ppu_status.bits().set_bit(7, false);
+            242..=260 => {
+            },
+            PRE_RENDER_SCANLINE => {
+                if dot == 1 {
+                    self.ppu_status.remove(PpuStatus::vblank_start | PpuStatus::spr_0_hit | PpuStatus::spr_oflo);
+                }
+                if dot == 257 && self.rendering() {
+                    self.copy_horizontal();
+                }
+                // https://www.nesdev.org/wiki/PPU_scrolling#During_dots_280_to_304_of_the_pre-render_scanline_(end_of_vblank)
+                if dot == 304 && self.rendering() {
+                    self.v = (self.v & !0x7BE0) | (self.t & 0x7BE0);
                 }
             }
             _ => panic!("scanline not matched: {scanline}")
@@ -208,7 +414,297 @@ impl Ppu {
         self.ppu_cycle_count += 1;
     }
 
-    fn fetch(&self) {
+    fn rendering(&self) -> bool {
+        self.ppu_mask.intersects(PpuMask::show_bgr | PpuMask::show_spr)
+    }
+
+    fn increment_vram_addr(&mut self) {
+        let increment = if self.ppu_ctrl.contains(PpuCtrl::vram_addr_inc) { 32 } else { 1 };
+        self.v = self.v.wrapping_add(increment) & 0x7FFF;
+    }
+
+    // https://www.nesdev.org/wiki/PPU_memory_map
+    fn read_vram(&self, addr: u16) -> u8 {
+        match addr & 0x3FFF {
+            0x0000..=0x1FFF => self.chr[addr as usize % self.chr.len()],
+            0x2000..=0x3EFF => self.vram[self.nametable_index(addr)],
+            _ => self.palette[palette_index(addr)],
+        }
+    }
+
+    fn write_vram(&mut self, addr: u16, value: u8) {
+        match addr & 0x3FFF {
+            0x0000..=0x1FFF => {
+                if self.chr_ram {
+                    let len = self.chr.len();
+                    self.chr[addr as usize % len] = value;
+                }
+            },
+            0x2000..=0x3EFF => {
+                let index = self.nametable_index(addr);
+                self.vram[index] = value;
+            },
+            _ => self.palette[palette_index(addr)] = value,
+        }
+    }
+
+    // https://www.nesdev.org/wiki/Mirroring#Nametable_Mirroring
+    fn nametable_index(&self, addr: u16) -> usize {
+        let addr = (addr & 0x0FFF) as usize;
+        let table = addr / 0x400;
+        let table = match self.mirroring {
+            Mirroring::Horizontal => table / 2,
+            Mirroring::Vertical => table % 2,
+            Mirroring::FourScreen => table,
+        };
+        table * 0x400 + addr % 0x400
+    }
+
+    fn render_scanline(&mut self) {
+        let y = self.scanline as usize;
+
+        let mut background = [0u8; SCREEN_WIDTH];
+        if self.ppu_mask.contains(PpuMask::show_bgr) {
+            self.render_background(&mut background);
+            if !self.ppu_mask.contains(PpuMask::show_bgr_leftmost) {
+                background[..8].fill(0);
+            }
+        }
+
+        let mut sprites = [SpritePixel::default(); SCREEN_WIDTH];
+        if self.ppu_mask.contains(PpuMask::show_spr) {
+            self.render_sprites(&mut sprites);
+            if !self.ppu_mask.contains(PpuMask::show_spr_leftmost) {
+                sprites[..8].fill(SpritePixel::default());
+            }
+        }
+
+        for x in 0..SCREEN_WIDTH {
+            let background_opaque = background[x] & 0b11 != 0;
+            let sprite = sprites[x];
+
+            // https://www.nesdev.org/wiki/PPU_OAM#Sprite_zero_hits
+            if sprite.sprite_0 && sprite.color != 0 && background_opaque && x != 255 {
+                self.ppu_status.insert(PpuStatus::spr_0_hit);
+            }
+
+            // https://www.nesdev.org/wiki/PPU_sprite_priority
+            let index = if sprite.color != 0 && (!background_opaque || !sprite.behind_background) {
+                sprite.color
+            } else if background_opaque {
+                background[x]
+            } else {
+                0
+            };
+
+            let mut color = self.palette[palette_index(index as u16)] & 0x3F;
+            if self.ppu_mask.contains(PpuMask::grayscale) {
+                color &= 0x30;
+            }
+            self.frame[y * SCREEN_WIDTH + x] = color;
+        }
+
+        if self.rendering() {
+            self.v = increment_y(self.v);
+            self.copy_horizontal();
+        }
+    }
 
+    // https://www.nesdev.org/wiki/PPU_scrolling#At_dot_257_of_each_scanline
+    fn copy_horizontal(&mut self) {
+        self.v = (self.v & !0x041F) | (self.t & 0x041F);
     }
-}
\ No newline at end of file
+
+    /// background palette ram indices of the current scanline, 0 is transparent
+    fn render_background(&self, line: &mut [u8; SCREEN_WIDTH]) {
+        let pattern_table = self.background_pattern_table();
+        let fine_y = (self.v >> 12) & 0b111;
+
+        let mut v = self.v;
+        // one extra tile for the fine x scroll
+        for tile in 0..33isize {
+            // https://www.nesdev.org/wiki/PPU_scrolling#Tile_and_attribute_fetching
+            let tile_index = self.read_vram(0x2000 | (v & 0x0FFF)) as u16;
+            let attribute = self.read_vram(0x23C0 | (v & 0x0C00) | ((v >> 4) & 0x38) | ((v >> 2) & 0x07));
+            let shift = ((v >> 4) & 0b100) | (v & 0b10);
+            let palette = (attribute >> shift) & 0b11;
+
+            let addr = pattern_table + tile_index * 16 + fine_y;
+            let low = self.read_vram(addr);
+            let high = self.read_vram(addr + 8);
+
+            for bit in 0..8isize {
+                let x = tile * 8 + bit - self.x as isize;
+                if !(0..SCREEN_WIDTH as isize).contains(&x) {
+                    continue;
+                }
+                let pixel = ((high >> (7 - bit)) & 1) << 1 | (low >> (7 - bit)) & 1;
+                if pixel != 0 {
+                    line[x as usize] = palette << 2 | pixel;
+                }
+            }
+
+            v = increment_x(v);
+        }
+    }
+
+    // https://www.nesdev.org/wiki/PPU_sprite_evaluation
+    fn render_sprites(&mut self, line: &mut [SpritePixel; SCREEN_WIDTH]) {
+        let scanline = self.scanline;
+        let height = self.sprite_height();
+        let pattern_table = self.sprite_pattern_table();
+
+        let mut count = 0;
+        for sprite in 0..64 {
+            let entry = &self.oam[sprite * 4..sprite * 4 + 4];
+            // sprites are drawn one scanline below their y coordinate
+            let top = entry[0] as u16 + 1;
+            if scanline < top || scanline >= top + height {
+                continue;
+            }
+
+            count += 1;
+            if count > 8 {
+                self.ppu_status.insert(PpuStatus::spr_oflo);
+                break;
+            }
+
+            let tile = entry[1] as u16;
+            let attributes = entry[2];
+            let left = entry[3] as usize;
+
+            let mut row = scanline - top;
+            if attributes & 0b1000_0000 != 0 {
+                row = height - 1 - row;
+            }
+            let addr = if height == 16 {
+                // 8x16 sprites pick the pattern table with bit 0 of the tile index
+                (tile & 1) * 0x1000 + (tile & 0xFE) * 16 + if row < 8 { row } else { row + 8 }
+            } else {
+                pattern_table + tile * 16 + row
+            };
+            let low = self.read_vram(addr);
+            let high = self.read_vram(addr + 8);
+
+            for bit in 0..8 {
+                let x = left + bit;
+                // lower oam entries are in front of higher ones
+                if x >= SCREEN_WIDTH || line[x].color != 0 {
+                    continue;
+                }
+                let shift = if attributes & 0b0100_0000 != 0 { bit } else { 7 - bit };
+                let pixel = ((high >> shift) & 1) << 1 | (low >> shift) & 1;
+                if pixel != 0 {
+                    line[x] = SpritePixel {
+                        color: 0x10 | (attributes & 0b11) << 2 | pixel,
+                        behind_background: attributes & 0b0010_0000 != 0,
+                        sprite_0: sprite == 0,
+                    };
+                }
+            }
+        }
+    }
+}
+
+/// the cartridge and its mirroring are fixed, chr is only saved when it's ram
+impl SaveState for Ppu {
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.u8(self.ppu_ctrl.0);
+        writer.u8(self.ppu_mask.0);
+        writer.u8(self.ppu_status.0);
+        writer.u8(self.oam_addr);
+
+        writer.u16(self.v);
+        writer.u16(self.t);
+        writer.u8(self.x);
+        writer.bool(self.w);
+        writer.u8(self.read_buffer);
+        writer.u8(self.io_latch);
+        for refreshed in self.io_latch_refreshed {
+            writer.u64(refreshed);
+        }
+
+        if self.chr_ram {
+            writer.bytes(&self.chr);
+        }
+        writer.bytes(&self.vram);
+        writer.bytes(&self.palette);
+        writer.bytes(&self.oam);
+
+        writer.bytes(&self.frame);
+        writer.bool(self.nmi);
+
+        writer.u16(self.scanline);
+        writer.u16(self.dot);
+        writer.u64(self.frame_count);
+        writer.u64(self.ppu_cycle_count);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.ppu_ctrl = PpuCtrl(reader.u8()?);
+        self.ppu_mask = PpuMask(reader.u8()?);
+        self.ppu_status = PpuStatus(reader.u8()?);
+        self.oam_addr = reader.u8()?;
+
+        self.v = reader.u16()?;
+        self.t = reader.u16()?;
+        self.x = reader.u8()?;
+        self.w = reader.bool()?;
+        self.read_buffer = reader.u8()?;
+        self.io_latch = reader.u8()?;
+        for refreshed in self.io_latch_refreshed.iter_mut() {
+            *refreshed = reader.u64()?;
+        }
+
+        if self.chr_ram {
+            reader.bytes_into(&mut self.chr, "chr ram")?;
+        }
+        reader.bytes_into(&mut self.vram, "vram")?;
+        reader.bytes_into(&mut self.palette, "palette ram")?;
+        reader.bytes_into(&mut self.oam, "oam")?;
+
+        reader.bytes_into(&mut self.frame, "frame buffer")?;
+        self.nmi = reader.bool()?;
+
+        self.scanline = reader.u16()?;
+        self.dot = reader.u16()?;
+        self.frame_count = reader.u64()?;
+        self.ppu_cycle_count = reader.u64()?;
+        Ok(())
+    }
+}
+
+/// $3F10/$3F14/$3F18/$3F1C mirror the backdrop entries of the background palettes
+fn palette_index(addr: u16) -> usize {
+    let index = (addr & 0x1F) as usize;
+    if index >= 0x10 && index.is_multiple_of(4) { index - 0x10 } else { index }
+}
+
+fn increment_x(v: u16) -> u16 {
+    if v & 0x001F == 31 {
+        // wrap coarse x and switch horizontal nametable
+        (v & !0x001F) ^ 0x0400
+    } else {
+        v + 1
+    }
+}
+
+// https://www.nesdev.org/wiki/PPU_scrolling#Y_increment
+fn increment_y(v: u16) -> u16 {
+    if v & 0x7000 != 0x7000 {
+        return v + 0x1000;
+    }
+
+    let mut v = v & !0x7000;
+    let mut coarse_y = (v & 0x03E0) >> 5;
+    if coarse_y == 29 {
+        coarse_y = 0;
+        // switch vertical nametable
+        v ^= 0x0800;
+    } else if coarse_y == 31 {
+        coarse_y = 0;
+    } else {
+        coarse_y += 1;
+    }
+    (v & !0x03E0) | coarse_y << 5
+}
@@ -0,0 +1,192 @@
+// what the ppu has in memory, drawn independently of what ends up on screen.
+// everything is read through peek so looking doesn't disturb the running game
+
+use crate::nes::palette;
+use crate::nes::ppu::{Ppu, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// 16 by 16 tiles of 8x8 pixels
+pub const PATTERN_TABLE_SIZE: usize = 128;
+/// the four nametables in a 2 by 2 grid
+pub const NAMETABLES_WIDTH: usize = 2 * SCREEN_WIDTH;
+pub const NAMETABLES_HEIGHT: usize = 2 * SCREEN_HEIGHT;
+/// background palettes 0-3 followed by sprite palettes 4-7
+pub const PALETTES: u8 = 8;
+
+/// outline of the screen drawn over the nametables
+const SCROLL_WINDOW: [u8; 4] = [255, 0, 255, 255];
+const TRANSPARENT: [u8; 4] = [0; 4];
+
+/// rgba pixels, row by row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    fn new(width: usize, height: usize) -> Image {
+        Image { width, height, pixels: vec![0; width * height * 4] }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let i = (y * self.width + x) * 4;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, rgba: [u8; 4]) {
+        let i = (y * self.width + x) * 4;
+        self.pixels[i..i + 4].copy_from_slice(&rgba);
+    }
+}
+
+/// an oam entry with its attributes decoded
+/// https://www.nesdev.org/wiki/PPU_OAM
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sprite {
+    /// position in oam, 0 is sprite zero
+    pub index: u8,
+    pub x: u8,
+    /// one above the top row, sprites are drawn a scanline lower
+    pub y: u8,
+    pub tile: u8,
+    /// sprite palette 0-3, the same as palette 4-7 of [`pattern_table`]
+    pub palette: u8,
+    pub behind_background: bool,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// the tile flipped and in its palette, 8x8 or 8x16. transparent pixels have an alpha of 0
+    pub image: Image,
+}
+
+/// pattern table 0 or 1 in the colors of `palette`, see [`PALETTES`]
+pub fn pattern_table(ppu: &Ppu, table: u8, palette: u8) -> Image {
+    let mut image = Image::new(PATTERN_TABLE_SIZE, PATTERN_TABLE_SIZE);
+    let base = (table as u16 & 1) * 0x1000;
+    for tile in 0..256u16 {
+        let left = (tile % 16) as usize * 8;
+        let top = (tile / 16) as usize * 8;
+        for row in 0..8 {
+            for (x, pixel) in tile_row(ppu, base + tile * 16, row).into_iter().enumerate() {
+                let color = palette_color(ppu, palette % PALETTES, pixel);
+                image.set_pixel(left + x, top + row as usize, rgba(color));
+            }
+        }
+    }
+    image
+}
+
+/// $2000, $2400, $2800 and $2C00 from the top left, mirrors included, with the screen outlined
+/// where the scroll puts it
+pub fn nametables(ppu: &Ppu) -> Image {
+    let mut image = Image::new(NAMETABLES_WIDTH, NAMETABLES_HEIGHT);
+    let pattern_table = ppu.background_pattern_table();
+
+    for nametable in 0..4u16 {
+        let base = 0x2000 + nametable * 0x400;
+        let left = (nametable % 2) as usize * SCREEN_WIDTH;
+        let top = (nametable / 2) as usize * SCREEN_HEIGHT;
+
+        for tile in 0..960u16 {
+            let column = tile % 32;
+            let row = tile / 32;
+            let tile_index = ppu.peek_vram(base + tile) as u16;
+            // https://www.nesdev.org/wiki/PPU_attribute_tables
+            let attribute = ppu.peek_vram(base + 0x3C0 + row / 4 * 8 + column / 4);
+            let shift = (row & 0b10) << 1 | (column & 0b10);
+            let palette = (attribute >> shift) & 0b11;
+
+            for y in 0..8 {
+                for (x, pixel) in tile_row(ppu, pattern_table + tile_index * 16, y).into_iter().enumerate() {
+                    let color = palette_color(ppu, palette, pixel);
+                    image.set_pixel(left + column as usize * 8 + x, top + (row * 8 + y) as usize, rgba(color));
+                }
+            }
+        }
+    }
+
+    outline_screen(&mut image, ppu.scroll());
+    image
+}
+
+/// the 32 entries of palette ram, background palettes in the top row and sprite palettes below
+pub fn palette_ram(ppu: &Ppu) -> Image {
+    let mut image = Image::new(16, 2);
+    for entry in 0..32u16 {
+        image.set_pixel(entry as usize % 16, entry as usize / 16, rgba(ppu.peek_vram(0x3F00 + entry)));
+    }
+    image
+}
+
+/// all 64 sprites in oam order, drawn with the current sprite size and pattern table
+pub fn sprites(ppu: &Ppu) -> Vec<Sprite> {
+    ppu.oam().chunks(4).enumerate().map(|(index, entry)| {
+        let attributes = entry[2];
+        let mut sprite = Sprite {
+            index: index as u8,
+            x: entry[3],
+            y: entry[0],
+            tile: entry[1],
+            palette: attributes & 0b11,
+            behind_background: attributes & 0b0010_0000 != 0,
+            flip_horizontal: attributes & 0b0100_0000 != 0,
+            flip_vertical: attributes & 0b1000_0000 != 0,
+            image: Image::new(8, ppu.sprite_height() as usize),
+        };
+        draw_sprite(ppu, &mut sprite);
+        sprite
+    }).collect()
+}
+
+fn draw_sprite(ppu: &Ppu, sprite: &mut Sprite) {
+    let height = ppu.sprite_height();
+    let tile = sprite.tile as u16;
+    for y in 0..height {
+        let row = if sprite.flip_vertical { height - 1 - y } else { y };
+        let addr = if height == 16 {
+            // 8x16 sprites pick the pattern table with bit 0 of the tile index
+            (tile & 1) * 0x1000 + (tile & 0xFE) * 16 + if row < 8 { 0 } else { 16 }
+        } else {
+            ppu.sprite_pattern_table() + tile * 16
+        };
+
+        for (x, pixel) in tile_row(ppu, addr, row % 8).into_iter().enumerate() {
+            let x = if sprite.flip_horizontal { 7 - x } else { x };
+            let rgba = if pixel == 0 { TRANSPARENT } else { rgba(palette_color(ppu, 4 + sprite.palette, pixel)) };
+            sprite.image.set_pixel(x, y as usize, rgba);
+        }
+    }
+}
+
+/// 2 bit pixels of a row of the tile at `addr`, leftmost first
+fn tile_row(ppu: &Ppu, addr: u16, row: u16) -> [u8; 8] {
+    let low = ppu.peek_vram(addr + row);
+    let high = ppu.peek_vram(addr + row + 8);
+    std::array::from_fn(|bit| ((high >> (7 - bit)) & 1) << 1 | (low >> (7 - bit)) & 1)
+}
+
+/// system palette color of `pixel` in `palette`, pixel 0 is the backdrop in every palette
+fn palette_color(ppu: &Ppu, palette: u8, pixel: u8) -> u8 {
+    let addr = if pixel == 0 { 0x3F00 } else { 0x3F00 | (palette as u16) << 2 | pixel as u16 };
+    ppu.peek_vram(addr) & 0x3F
+}
+
+fn rgba(color: u8) -> [u8; 4] {
+    let [r, g, b] = palette::rgb(color);
+    [r, g, b, 255]
+}
+
+/// a screen sized rectangle with its top left at `scroll`, wrapping around like the scroll does
+fn outline_screen(image: &mut Image, (x, y): (u16, u16)) {
+    let (x, y) = (x as usize, y as usize);
+    for dx in 0..SCREEN_WIDTH {
+        for dy in [0, SCREEN_HEIGHT - 1] {
+            image.set_pixel((x + dx) % NAMETABLES_WIDTH, (y + dy) % NAMETABLES_HEIGHT, SCROLL_WINDOW);
+        }
+    }
+    for dy in 0..SCREEN_HEIGHT {
+        for dx in [0, SCREEN_WIDTH - 1] {
+            image.set_pixel((x + dx) % NAMETABLES_WIDTH, (y + dy) % NAMETABLES_HEIGHT, SCROLL_WINDOW);
+        }
+    }
+}
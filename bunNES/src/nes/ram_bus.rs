@@ -0,0 +1,60 @@
+use crate::nes::cpu::CpuBus;
+
+/// all of the cpu address space
+const RAM_SIZE: usize = 0x10000;
+
+/// 64k of ram and nothing else, for 6502 code that doesn't need the rest of the nes.
+/// the interrupt lines are whatever they're set to
+pub struct RamBus {
+    pub ram: Vec<u8>,
+    /// the cpu takes it and clears it, like the edge it stands for
+    pub nmi: bool,
+    pub irq: bool,
+}
+
+impl Default for RamBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RamBus {
+    pub fn new() -> RamBus {
+        RamBus::with_memory(Vec::new())
+    }
+
+    /// `memory` loaded at $0000, zero filled to 64k
+    pub fn with_memory(mut memory: Vec<u8>) -> RamBus {
+        memory.resize(RAM_SIZE, 0);
+        RamBus { ram: memory, nmi: false, irq: false }
+    }
+
+    /// copies `bytes` to `addr` on, wrapping around at $FFFF
+    pub fn load(&mut self, addr: u16, bytes: &[u8]) {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.ram[addr.wrapping_add(i as u16) as usize] = *byte;
+        }
+    }
+}
+
+impl CpuBus for RamBus {
+    fn read_8(&mut self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.ram[addr as usize] = value;
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
+    fn nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi)
+    }
+
+    fn irq(&self) -> bool {
+        self.irq
+    }
+}
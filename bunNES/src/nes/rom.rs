@@ -3,8 +3,24 @@ use std::str;
 
 // https://www.nesdev.org/wiki/INES
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Region {
+    Ntsc,
+    Pal,
+    /// famiclones with a pal frame rate and an ntsc apu at a faster clock
+    Dendy,
+}
+
+// https://www.nesdev.org/wiki/Mirroring#Nametable_Mirroring
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    FourScreen,
+}
+
 #[allow(unused_variables)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Cartridge {
     header: RomHeader,
     trainer: Vec<u8>,
@@ -28,7 +44,6 @@ impl Cartridge {
             bytes[offset..512 + offset].to_vec()
         } else { vec!() };
 
-        println!("prg_rom start: {}", offset);
         let prg_rom = bytes[offset..offset + header.prg_len()].to_vec();
         offset += header.prg_len();
 
@@ -64,17 +79,51 @@ impl Cartridge {
     pub fn rom_len(&self) -> usize {
         self.prg_rom.len()
     }
+
+    pub fn chr(&self) -> &Vec<u8> {
+        &self.chr_rom
+    }
+
+    pub fn region(&self) -> Region {
+        self.header.region()
+    }
+
+    pub fn mirroring(&self) -> Mirroring {
+        self.header.mirroring()
+    }
+
+    /// iNES mapper number
+    pub fn mapper(&self) -> u8 {
+        (self.header.flags6 & 0b1111_0000) >> 4 | self.header.flags7 & 0b1111_0000
+    }
+
+    /// FNV-1a of the prg and chr rom, identifies the cartridge a save state belongs to
+    pub fn hash(&self) -> u64 {
+        self.prg_rom.iter().chain(self.chr_rom.iter()).fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01B3)
+        })
+    }
+
+    /// NES 2.0 default expansion device, 0 if unspecified or not a NES 2.0 rom
+    pub fn default_expansion_device(&self) -> u8 {
+        self.header.default_expansion_device()
+    }
+
+    /// whether there's ram at $6000-$7FFF, anything but a NES 2.0 rom saying otherwise has it
+    pub fn has_prg_ram(&self) -> bool {
+        self.header.has_prg_ram()
+    }
 }
 
 impl Display for Cartridge {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.header)?;
-        write!(f, "Mapper: {}", (self.header.flags6 & 0b1111_0000) >> 4 | self.header.flags7 & 0b1111_0000)?;
+        write!(f, "Mapper: {}", self.mapper())?;
         Ok(())
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct RomHeader {
     magic: [u8; 4],
     prg_rom: u8,
@@ -84,6 +133,8 @@ struct RomHeader {
     flags8: u8,
     flags9: u8,
     flags10: u8,
+    flags12: u8,
+    flags15: u8,
 }
 
 impl RomHeader {
@@ -97,6 +148,8 @@ impl RomHeader {
             flags8: values[8],
             flags9: values[9],
             flags10: values[10],
+            flags12: values[12],
+            flags15: values[15],
         }
     }
     
@@ -110,6 +163,8 @@ impl RomHeader {
             flags8: 0,
             flags9: 0,
             flags10: 0,
+            flags12: 0,
+            flags15: 0,
         }
     }
 
@@ -117,6 +172,44 @@ impl RomHeader {
         self.flags6 & 0b0000_0100 >> 2 == 1
     }
 
+    // https://www.nesdev.org/wiki/NES_2.0#CPU/PPU_Timing
+    fn region(&self) -> Region {
+        if !self.nes_2() {
+            return if self.flags9 & 0b0000_0001 == 0 { Region::Ntsc } else { Region::Pal };
+        }
+        match self.flags12 & 0b0000_0011 {
+            1 => Region::Pal,
+            3 => Region::Dendy,
+            // multi-region roms run on either, ntsc is the usual pick
+            _ => Region::Ntsc,
+        }
+    }
+
+    fn nes_2(&self) -> bool {
+        (self.flags7 & 0b0000_1100) >> 2 == 2
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        if self.flags6 & 0b0000_1000 != 0 {
+            Mirroring::FourScreen
+        } else if self.flags6 & 0b0000_0001 == 0 {
+            Mirroring::Horizontal
+        } else {
+            Mirroring::Vertical
+        }
+    }
+
+    // https://www.nesdev.org/wiki/NES_2.0#Default_Expansion_Device
+    fn default_expansion_device(&self) -> u8 {
+        if self.nes_2() { self.flags15 & 0b0011_1111 } else { 0 }
+    }
+
+    // https://www.nesdev.org/wiki/NES_2.0#PRG-(NV)RAM/EEPROM
+    // iNES byte 10 is too often garbage to trust
+    fn has_prg_ram(&self) -> bool {
+        !self.nes_2() || self.flags10 != 0
+    }
+
     fn prg_len(&self) -> usize {
         self.prg_rom as usize * 16384
     }
@@ -128,38 +221,50 @@ impl RomHeader {
 
 impl Display for RomHeader {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Header:\n")?;
-        write!(f, "  Magic: {}\n", str::from_utf8(&self.magic).unwrap())?;
-        write!(f, "  PRG ROM size: {}(KB)\n", self.prg_rom as u32 * 16)?;
-        write!(f, "  CHR ROM size: {}(KB)\n", self.chr_rom as u32 * 8)?;
-
-        write!(f, "  Flags 6\n")?;
-        write!(f, "    Mirroring: {}\n", if (self.flags6 & 0b0000_0001) >> 0 == 0 { "horizontal" } else { "vertical" })?;
-        write!(f, "    Battery backed: {}\n", if (self.flags6 & 0b0000_0010) >> 1 == 1 { "true" } else { "false" })?;
-        write!(f, "    Trainer: {}\n", if (self.flags6 & 0b0000_0100) >> 2 == 1 { "true" } else { "false" })?;
-        write!(f, "    Ignore mirror: {}\n", if (self.flags6 & 0b0000_1000) >> 3 == 1 { "true" } else { "false" })?;
-        write!(f, "    Mapper (lower): {}\n", (self.flags6 & 0b1111_0000) >> 4)?;
-
-        write!(f, "  Flags 7\n")?;
-        write!(f, "    VS Unisystem : {}\n", (self.flags7 & 0b0000_0001) >> 0)?;
-        write!(f, "    PlayChoice-10 : {}\n", (self.flags7 & 0b0000_0010) >> 1)?;
-        write!(f, "    NES 2.0 Format : {}\n", if (self.flags7 & 0b0000_1100) >> 2 == 2 { "true" } else { "false" })?;
-        write!(f, "    Mapper (higher): {}\n", (self.flags6 & 0b1111_0000) >> 4)?;
-
-        write!(f, "  Flags 8")?;
-        write!(f, "    PRG RAM size: {}KB\n", if self.flags8 == 0 { 8 } else { &self.flags8 * 8 })?;
-
-        write!(f, "  Flags 9\n")?;
-        write!(f, "    TV System : {}\n", if (self.flags9 & 0b0000_0001) >> 0 == 0 { "NTSC" } else { "PAL" })?;
-
-        write!(f, "  Flags 10\n")?;
-        write!(f, "    TV System : {}\n", match (self.flags10 & 0b0000_0011) >> 0 {
+        writeln!(f, "Header:")?;
+        writeln!(f, "  Magic: {}", str::from_utf8(&self.magic).unwrap())?;
+        writeln!(f, "  PRG ROM size: {}(KB)", self.prg_rom as u32 * 16)?;
+        writeln!(f, "  CHR ROM size: {}(KB)", self.chr_rom as u32 * 8)?;
+
+        writeln!(f, "  Flags 6")?;
+        writeln!(f, "    Mirroring: {}", if (self.flags6 & 0b0000_0001) >> 0 == 0 { "horizontal" } else { "vertical" })?;
+        writeln!(f, "    Battery backed: {}", if (self.flags6 & 0b0000_0010) >> 1 == 1 { "true" } else { "false" })?;
+        writeln!(f, "    Trainer: {}", if (self.flags6 & 0b0000_0100) >> 2 == 1 { "true" } else { "false" })?;
+        writeln!(f, "    Ignore mirror: {}", if (self.flags6 & 0b0000_1000) >> 3 == 1 { "true" } else { "false" })?;
+        writeln!(f, "    Mapper (lower): {}", (self.flags6 & 0b1111_0000) >> 4)?;
+
+        writeln!(f, "  Flags 7")?;
+        writeln!(f, "    VS Unisystem : {}", (self.flags7 & 0b0000_0001) >> 0)?;
+        writeln!(f, "    PlayChoice-10 : {}", (self.flags7 & 0b0000_0010) >> 1)?;
+        writeln!(f, "    NES 2.0 Format : {}", if (self.flags7 & 0b0000_1100) >> 2 == 2 { "true" } else { "false" })?;
+        writeln!(f, "    Mapper (higher): {}", (self.flags6 & 0b1111_0000) >> 4)?;
+
+        writeln!(f, "  Flags 8")?;
+        writeln!(f, "    PRG RAM size: {}KB", if self.flags8 == 0 { 8 } else { &self.flags8 * 8 })?;
+
+        writeln!(f, "  Flags 9")?;
+        writeln!(f, "    TV System : {}", if (self.flags9 & 0b0000_0001) >> 0 == 0 { "NTSC" } else { "PAL" })?;
+
+        writeln!(f, "  Flags 10")?;
+        writeln!(f, "    TV System : {}", match (self.flags10 & 0b0000_0011) >> 0 {
             0 => "NTSC",
             2 => "PAL",
             _ => "Dual"
         })?;
-        write!(f, "    PRG RAM : {}\n", if (self.flags10 & 0b0001_0000) >> 4 == 0 { "present" } else { "not present" })?;
-        write!(f, "    Bus conflicts : {}\n", if (self.flags10 & 0b0010_0000) >> 5 == 0 { "false" } else { "true" })?;
+        writeln!(f, "    PRG RAM : {}", if (self.flags10 & 0b0001_0000) >> 4 == 0 { "present" } else { "not present" })?;
+        writeln!(f, "    Bus conflicts : {}", if (self.flags10 & 0b0010_0000) >> 5 == 0 { "false" } else { "true" })?;
+
+        if self.nes_2() {
+            writeln!(f, "  Flags 12")?;
+            writeln!(f, "    CPU/PPU timing : {}", match self.flags12 & 0b0000_0011 {
+                0 => "NTSC",
+                1 => "PAL",
+                2 => "Multi-region",
+                _ => "Dendy",
+            })?;
+            writeln!(f, "  Flags 15")?;
+            writeln!(f, "    Default expansion device : {:#04X}", self.default_expansion_device())?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,561 @@
+// https://www.w3.org/TR/png/
+// just enough to write 8 bit rgb images. rows use the sub or up filter, so flat colors and
+// repeated rows turn into runs of zeros that deflate with fixed huffman codes and distance 1
+// matches squeezes down to a few bits.
+// decoding takes what other encoders and optimizers write too, short of interlacing and 16 bit samples
+// https://www.rfc-editor.org/rfc/rfc1951
+
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+const BYTES_PER_PIXEL: usize = 3;
+const FILTER_NONE: u8 = 0;
+const FILTER_SUB: u8 = 1;
+const FILTER_UP: u8 = 2;
+const FILTER_AVERAGE: u8 = 3;
+const FILTER_PAETH: u8 = 4;
+
+const COLOR_GRAY: u8 = 0;
+const COLOR_RGB: u8 = 2;
+const COLOR_PALETTE: u8 = 3;
+const COLOR_GRAY_ALPHA: u8 = 4;
+const COLOR_RGBA: u8 = 6;
+
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const END_OF_BLOCK: u16 = 256;
+
+/// base length and extra bits of the length codes 257..=285
+const LENGTHS: [(u16, u8); 29] = [
+    (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    (11, 1), (13, 1), (15, 1), (17, 1), (19, 2), (23, 2), (27, 2), (31, 2),
+    (35, 3), (43, 3), (51, 3), (59, 3), (67, 4), (83, 4), (99, 4), (115, 4),
+    (131, 5), (163, 5), (195, 5), (227, 5), (258, 0),
+];
+/// base distance and extra bits of the distance codes 0..=29
+const DISTANCES: [(u16, u8); 30] = [
+    (1, 0), (2, 0), (3, 0), (4, 0), (5, 1), (7, 1), (9, 2), (13, 2),
+    (17, 3), (25, 3), (33, 4), (49, 4), (65, 5), (97, 5), (129, 6), (193, 6),
+    (257, 7), (385, 7), (513, 8), (769, 8), (1025, 9), (1537, 9), (2049, 10), (3073, 10),
+    (4097, 11), (6145, 11), (8193, 12), (12289, 12), (16385, 13), (24577, 13),
+];
+/// order the code length code lengths of a dynamic block come in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum PngError {
+    /// not a png, cut short or corrupt
+    Invalid(&'static str),
+    /// valid png using something that isn't decoded
+    Unsupported(String),
+}
+
+impl Display for PngError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PngError::Invalid(what) => write!(f, "invalid png: {what}"),
+            PngError::Unsupported(what) => write!(f, "unsupported png: {what}"),
+        }
+    }
+}
+
+impl Error for PngError {}
+
+/// decoded image in the layout [`encode`] takes
+#[derive(Debug, Eq, PartialEq)]
+pub struct Image {
+    pub width: usize,
+    pub height: usize,
+    /// 3 bytes per pixel row by row, alpha is dropped
+    pub rgb: Vec<u8>,
+}
+
+/// png of `rgb`, 3 bytes per pixel row by row
+pub fn encode(width: usize, height: usize, rgb: &[u8]) -> Vec<u8> {
+    assert_eq!(rgb.len(), width * height * BYTES_PER_PIXEL, "image size doesn't match");
+
+    let mut png = SIGNATURE.to_vec();
+
+    let mut header = Vec::new();
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+    // bit depth 8, truecolor, deflate, adaptive filtering, no interlace
+    header.extend([8, 2, 0, 0, 0]);
+    chunk(&mut png, b"IHDR", &header);
+
+    let stride = width * BYTES_PER_PIXEL;
+    let mut filtered = Vec::with_capacity(height * (stride + 1));
+    for (y, row) in rgb.chunks(stride).enumerate() {
+        let sub: Vec<u8> = row.iter().enumerate()
+            .map(|(i, byte)| byte.wrapping_sub(if i < BYTES_PER_PIXEL { 0 } else { row[i - BYTES_PER_PIXEL] }))
+            .collect();
+        let up: Vec<u8> = match y {
+            0 => row.to_vec(),
+            _ => row.iter().zip(&rgb[(y - 1) * stride..y * stride]).map(|(byte, above)| byte.wrapping_sub(*above)).collect(),
+        };
+
+        // whichever leaves more zeros
+        let zeros = |data: &[u8]| data.iter().filter(|byte| **byte == 0).count();
+        if zeros(&up) > zeros(&sub) {
+            filtered.push(FILTER_UP);
+            filtered.extend(up);
+        } else {
+            filtered.push(FILTER_SUB);
+            filtered.extend(sub);
+        }
+    }
+    chunk(&mut png, b"IDAT", &zlib(&filtered));
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// 8 bit gray, rgb and their alpha variants or palette and gray images of 1 to 8 bits, not interlaced
+pub fn decode(png: &[u8]) -> Result<Image, PngError> {
+    if !png.starts_with(&SIGNATURE) {
+        return Err(PngError::Invalid("signature"));
+    }
+
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    let mut rest = &png[SIGNATURE.len()..];
+    loop {
+        if rest.len() < 12 {
+            return Err(PngError::Invalid("missing IEND"));
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        if rest.len() < 12 + length {
+            return Err(PngError::Invalid("chunk ends early"));
+        }
+        let kind = &rest[4..8];
+        let data = &rest[8..8 + length];
+        let crc = u32::from_be_bytes([rest[8 + length], rest[9 + length], rest[10 + length], rest[11 + length]]);
+        if crc32(&rest[4..8 + length]) != crc {
+            return Err(PngError::Invalid("chunk crc"));
+        }
+        rest = &rest[12 + length..];
+
+        match kind {
+            b"IHDR" => header = Some(Header::parse(data)?),
+            b"PLTE" => palette = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            // ancillary chunks
+            _ if kind[0].is_ascii_lowercase() => {}
+            _ => return Err(PngError::Unsupported(format!("{} chunk", String::from_utf8_lossy(kind)))),
+        }
+    }
+
+    let header = header.ok_or(PngError::Invalid("missing IHDR"))?;
+    let filtered = inflate_zlib(&compressed)?;
+    let rows = unfilter(&header, &filtered)?;
+
+    let mut rgb = Vec::with_capacity(header.width * header.height * BYTES_PER_PIXEL);
+    let max = (1u16 << header.bit_depth) - 1;
+    for row in rows.chunks(header.stride()) {
+        for x in 0..header.width {
+            match header.color_type {
+                COLOR_GRAY | COLOR_PALETTE => {
+                    let bit = x * header.bit_depth as usize;
+                    let value = row[bit / 8] >> (8 - header.bit_depth as usize - bit % 8) & max as u8;
+                    if header.color_type == COLOR_GRAY {
+                        let gray = (value as u16 * 255 / max) as u8;
+                        rgb.extend([gray; 3]);
+                    } else {
+                        let color = palette.get(value as usize * 3..value as usize * 3 + 3)
+                            .ok_or(PngError::Invalid("color outside the palette"))?;
+                        rgb.extend(color);
+                    }
+                }
+                COLOR_GRAY_ALPHA => rgb.extend([row[x * 2]; 3]),
+                COLOR_RGB => rgb.extend(&row[x * 3..x * 3 + 3]),
+                _ => rgb.extend(&row[x * 4..x * 4 + 3]),
+            }
+        }
+    }
+    Ok(Image { width: header.width, height: header.height, rgb })
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl Header {
+    fn parse(data: &[u8]) -> Result<Header, PngError> {
+        if data.len() != 13 {
+            return Err(PngError::Invalid("IHDR length"));
+        }
+        let header = Header {
+            width: u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as usize,
+            height: u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize,
+            bit_depth: data[8],
+            color_type: data[9],
+        };
+        let supported = match header.color_type {
+            COLOR_GRAY | COLOR_PALETTE => matches!(header.bit_depth, 1 | 2 | 4 | 8),
+            COLOR_RGB | COLOR_GRAY_ALPHA | COLOR_RGBA => header.bit_depth == 8,
+            _ => return Err(PngError::Invalid("color type")),
+        };
+        if !supported {
+            return Err(PngError::Unsupported(format!("bit depth {} of color type {}", header.bit_depth, header.color_type)));
+        }
+        if data[10] != 0 || data[11] != 0 {
+            return Err(PngError::Invalid("compression or filter method"));
+        }
+        if data[12] != 0 {
+            return Err(PngError::Unsupported("interlacing".to_string()));
+        }
+        Ok(header)
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        let channels = match self.color_type {
+            COLOR_RGB => 3,
+            COLOR_GRAY_ALPHA => 2,
+            COLOR_RGBA => 4,
+            _ => 1,
+        };
+        channels * self.bit_depth as usize
+    }
+
+    /// bytes per row without the filter type
+    fn stride(&self) -> usize {
+        (self.width * self.bits_per_pixel()).div_ceil(8)
+    }
+}
+
+/// the rows without their filter type bytes
+fn unfilter(header: &Header, filtered: &[u8]) -> Result<Vec<u8>, PngError> {
+    let stride = header.stride();
+    if filtered.len() != header.height * (stride + 1) {
+        return Err(PngError::Invalid("image data size"));
+    }
+    // filters work on whole bytes, the byte to the left for less than 8 bits per pixel
+    let left = header.bits_per_pixel().div_ceil(8);
+
+    let mut rows = vec![0u8; header.height * stride];
+    for (y, line) in filtered.chunks(stride + 1).enumerate() {
+        let (done, current) = rows.split_at_mut(y * stride);
+        let above = if y == 0 { None } else { Some(&done[(y - 1) * stride..]) };
+        let row = &mut current[..stride];
+        for i in 0..stride {
+            let a = if i < left { 0 } else { row[i - left] };
+            let b = above.map_or(0, |above| above[i]);
+            let c = if i < left { 0 } else { above.map_or(0, |above| above[i - left]) };
+            let predicted = match line[0] {
+                FILTER_NONE => 0,
+                FILTER_SUB => a,
+                FILTER_UP => b,
+                FILTER_AVERAGE => ((a as u16 + b as u16) / 2) as u8,
+                FILTER_PAETH => paeth(a, b, c),
+                _ => return Err(PngError::Invalid("filter type")),
+            };
+            row[i] = line[1 + i].wrapping_add(predicted);
+        }
+    }
+    Ok(rows)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = ((p - a as i16).abs(), (p - b as i16).abs(), (p - c as i16).abs());
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+fn inflate_zlib(data: &[u8]) -> Result<Vec<u8>, PngError> {
+    if data.len() < 6 {
+        return Err(PngError::Invalid("zlib stream ends early"));
+    }
+    let (cmf, flags) = (data[0], data[1]);
+    if cmf & 0x0F != 8 || !(u16::from_be_bytes([cmf, flags])).is_multiple_of(31) || flags & 0b0010_0000 != 0 {
+        return Err(PngError::Invalid("zlib header"));
+    }
+
+    let mut bits = BitReader { bytes: &data[2..], position: 0 };
+    let inflated = inflate(&mut bits)?;
+
+    let end = 2 + bits.position.div_ceil(8);
+    let checksum = data.get(end..end + 4).ok_or(PngError::Invalid("missing adler32"))?;
+    if adler32(&inflated).to_be_bytes() != checksum {
+        return Err(PngError::Invalid("adler32"));
+    }
+    Ok(inflated)
+}
+
+fn inflate(bits: &mut BitReader) -> Result<Vec<u8>, PngError> {
+    let mut out = Vec::new();
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            // stored
+            0 => {
+                bits.align();
+                let length = bits.read(16)?;
+                if bits.read(16)? != !length & 0xFFFF {
+                    return Err(PngError::Invalid("stored block length"));
+                }
+                for _ in 0..length {
+                    out.push(bits.read(8)? as u8);
+                }
+            }
+            1 => {
+                let mut lengths = [0u8; 288];
+                lengths[0..144].fill(8);
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                lengths[280..288].fill(8);
+                inflate_block(bits, &mut out, &Huffman::new(&lengths), &Huffman::new(&[5; 30]))?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(bits)?;
+                inflate_block(bits, &mut out, &literals, &distances)?;
+            }
+            _ => return Err(PngError::Invalid("deflate block type")),
+        }
+        if last {
+            return Ok(out);
+        }
+    }
+}
+
+fn dynamic_codes(bits: &mut BitReader) -> Result<(Huffman, Huffman), PngError> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let code_length_count = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for index in CODE_LENGTH_ORDER.iter().take(code_length_count) {
+        code_lengths[*index] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (length, repeat) = match code_lengths.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last().ok_or(PngError::Invalid("repeat without a length"))?, 3 + bits.read(2)?),
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(length, repeat as usize));
+    }
+    if lengths.len() != literal_count + distance_count {
+        return Err(PngError::Invalid("code lengths overrun"));
+    }
+    Ok((Huffman::new(&lengths[..literal_count]), Huffman::new(&lengths[literal_count..])))
+}
+
+fn inflate_block(bits: &mut BitReader, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Result<(), PngError> {
+    loop {
+        let symbol = literals.decode(bits)?;
+        match symbol {
+            0..=255 => out.push(symbol as u8),
+            END_OF_BLOCK => return Ok(()),
+            _ => {
+                let (base, extra) = *LENGTHS.get(symbol as usize - 257).ok_or(PngError::Invalid("length code"))?;
+                let length = base as usize + bits.read(extra)? as usize;
+                let (base, extra) = *DISTANCES.get(distances.decode(bits)? as usize).ok_or(PngError::Invalid("distance code"))?;
+                let distance = base as usize + bits.read(extra)? as usize;
+                if distance > out.len() {
+                    return Err(PngError::Invalid("distance before the start"));
+                }
+                // may overlap what it copies
+                for _ in 0..length {
+                    out.push(out[out.len() - distance]);
+                }
+            }
+        }
+    }
+}
+
+/// canonical huffman code, decoded a bit at a time
+struct Huffman {
+    /// codes of each length
+    counts: [u16; 16],
+    /// symbols ordered by code
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// code length of every symbol, 0 when unused
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for length in lengths {
+            counts[*length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0usize; 16];
+        for length in 1..15 {
+            offsets[length + 1] = offsets[length] + counts[length] as usize;
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, length) in lengths.iter().enumerate() {
+            if *length != 0 {
+                symbols[offsets[*length as usize]] = symbol as u16;
+                offsets[*length as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Result<u16, PngError> {
+        // first code and symbol index of the current length
+        let (mut code, mut first, mut index) = (0usize, 0usize, 0usize);
+        for count in &self.counts[1..] {
+            code |= bits.read(1)? as usize;
+            let count = *count as usize;
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(PngError::Invalid("huffman code"))
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// in bits
+    position: usize,
+}
+
+impl BitReader<'_> {
+    /// least significant bit first
+    fn read(&mut self, count: u8) -> Result<u32, PngError> {
+        let mut value = 0;
+        for bit in 0..count {
+            let byte = self.bytes.get(self.position / 8).ok_or(PngError::Invalid("deflate stream ends early"))?;
+            value |= ((byte >> (self.position % 8)) as u32 & 1) << bit;
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    /// stored blocks start on a byte
+    fn align(&mut self) {
+        self.position = self.position.div_ceil(8) * 8;
+    }
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in data {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+/// a single fixed huffman block, only repeats of the previous byte are matched
+fn zlib(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::default();
+    // deflate with a 32k window, no dictionary, fastest compression
+    bits.bytes.extend([0x78, 0x01]);
+    // last block, fixed huffman codes
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut i = 0;
+    while i < data.len() {
+        let run = if i == 0 {
+            0
+        } else {
+            data[i..].iter().take(MAX_MATCH).take_while(|byte| **byte == data[i - 1]).count()
+        };
+        if run >= MIN_MATCH {
+            let code = LENGTHS.iter().rposition(|(base, _)| *base as usize <= run).unwrap();
+            let (base, extra) = LENGTHS[code];
+            bits.literal(257 + code as u16);
+            bits.write(run as u32 - base as u32, extra);
+            // distance 1 is distance code 0
+            bits.huffman(0, 5);
+            i += run;
+        } else {
+            bits.literal(data[i] as u16);
+            i += 1;
+        }
+    }
+    bits.literal(END_OF_BLOCK);
+
+    let mut zlib = bits.finish();
+    zlib.extend(adler32(data).to_be_bytes());
+    zlib
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u32,
+    count: u8,
+}
+
+impl BitWriter {
+    /// least significant bit first
+    fn write(&mut self, value: u32, count: u8) {
+        for bit in 0..count {
+            self.current |= (value >> bit & 1) << self.count;
+            self.count += 1;
+            if self.count == 8 {
+                self.bytes.push(self.current as u8);
+                self.current = 0;
+                self.count = 0;
+            }
+        }
+    }
+
+    /// huffman codes are stored most significant bit first
+    fn huffman(&mut self, code: u16, length: u8) {
+        let reversed = code.reverse_bits() >> (16 - length);
+        self.write(reversed as u32, length);
+    }
+
+    /// fixed code of a literal/length symbol
+    fn literal(&mut self, symbol: u16) {
+        match symbol {
+            0..=143 => self.huffman(0x30 + symbol, 8),
+            144..=255 => self.huffman(0x190 + symbol - 144, 9),
+            256..=279 => self.huffman(symbol - 256, 7),
+            _ => self.huffman(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.bytes.push(self.current as u8);
+        }
+        self.bytes
+    }
+}
@@ -0,0 +1,157 @@
+use crate::nes::bus::Bus;
+use crate::nes::memory::MemorySpace;
+
+// finds the addresses a game keeps a value at, lives, timers, positions. every address of
+// work ram and cartridge ram starts out as a candidate and each search keeps the ones whose
+// value compares the way it should, against the previous search or a known value
+
+const RAM_SIZE: usize = 0x0800;
+const PRG_RAM_START: u16 = 0x6000;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Size {
+    Byte,
+    /// little endian, the byte at the address and the one after it
+    Word,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    /// the value minus the one compared against is exactly this
+    ChangedBy(i32),
+}
+
+impl Comparison {
+    fn holds(&self, value: i32, other: i32) -> bool {
+        match self {
+            Comparison::Equal => value == other,
+            Comparison::NotEqual => value != other,
+            Comparison::Greater => value > other,
+            Comparison::Less => value < other,
+            Comparison::ChangedBy(n) => value - other == *n,
+        }
+    }
+}
+
+/// what the current values are compared against
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Operand {
+    /// the value at the last search, or when the search started
+    Previous,
+    Value(i32),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SearchResult {
+    /// cpu address, $0000-$07FF or $6000-$7FFF
+    pub addr: u16,
+    pub value: i32,
+    pub previous: i32,
+}
+
+/// a byte of ram held at a value, written at the start of every frame
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Cheat {
+    pub addr: u16,
+    pub value: u8,
+}
+
+impl Cheat {
+    pub(crate) fn apply(&self, bus: &mut Bus) {
+        MemorySpace::Cpu.poke(bus, self.addr as usize, self.value);
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RamSearch {
+    /// how values are read, can change between searches
+    pub size: Size,
+    pub signed: bool,
+    candidates: Vec<u16>,
+    /// work ram and cartridge ram at the last search
+    ram: Vec<u8>,
+    prg_ram: Vec<u8>,
+}
+
+impl RamSearch {
+    /// every byte of work ram and cartridge ram is a candidate
+    pub fn new(bus: &Bus) -> RamSearch {
+        let mut search = RamSearch {
+            size: Size::Byte,
+            signed: false,
+            candidates: Vec::new(),
+            ram: Vec::new(),
+            prg_ram: Vec::new(),
+        };
+        search.reset(bus);
+        search
+    }
+
+    /// starts over with every address and the values they have now
+    pub fn reset(&mut self, bus: &Bus) {
+        let prg_ram_end = PRG_RAM_START + bus.prg_ram().len() as u16;
+        self.candidates = (0..RAM_SIZE as u16).chain(PRG_RAM_START..prg_ram_end).collect();
+        self.ram = bus.ram.to_vec();
+        self.prg_ram = bus.prg_ram().to_vec();
+    }
+
+    /// keeps the candidates whose value now compares to `operand`, the values now become
+    /// the previous ones for the next search
+    pub fn search(&mut self, bus: &Bus, comparison: Comparison, operand: Operand) {
+        let (size, signed) = (self.size, self.signed);
+        self.candidates.retain(|addr| {
+            let value = read(&bus.ram, bus.prg_ram(), *addr, size, signed);
+            let previous = read(&self.ram, &self.prg_ram, *addr, size, signed);
+            match (value, previous, operand) {
+                (Some(value), _, Operand::Value(other)) => comparison.holds(value, other),
+                (Some(value), Some(previous), Operand::Previous) => comparison.holds(value, previous),
+                _ => false,
+            }
+        });
+        self.ram = bus.ram.to_vec();
+        self.prg_ram = bus.prg_ram().to_vec();
+    }
+
+    pub fn candidates(&self) -> &[u16] {
+        &self.candidates
+    }
+
+    /// the candidates with their values now and at the last search, words that don't fit
+    /// before the end of their ram are left out
+    pub fn results(&self, bus: &Bus) -> Vec<SearchResult> {
+        self.candidates.iter().filter_map(|addr| {
+            let value = read(&bus.ram, bus.prg_ram(), *addr, self.size, self.signed)?;
+            let previous = read(&self.ram, &self.prg_ram, *addr, self.size, self.signed)?;
+            Some(SearchResult { addr: *addr, value, previous })
+        }).collect()
+    }
+
+    /// holds `addr` at `value` in the current size, one cheat per byte
+    pub fn cheats(&self, addr: u16, value: i32) -> Vec<Cheat> {
+        let bytes = (value as u16).to_le_bytes();
+        let len = if self.size == Size::Word { 2 } else { 1 };
+        (0..len).map(|i| Cheat { addr: addr.wrapping_add(i as u16), value: bytes[i] }).collect()
+    }
+}
+
+/// the value at `addr` if it's in `ram` or `prg_ram`, both bytes of a word have to be
+fn read(ram: &[u8], prg_ram: &[u8], addr: u16, size: Size, signed: bool) -> Option<i32> {
+    let byte = |addr: u16| match addr {
+        0x0000..=0x07FF => ram.get(addr as usize).copied(),
+        _ => prg_ram.get(addr.checked_sub(PRG_RAM_START)? as usize).copied(),
+    };
+
+    let low = byte(addr)?;
+    match size {
+        Size::Byte if signed => Some(low as i8 as i32),
+        Size::Byte => Some(low as i32),
+        Size::Word => {
+            let value = u16::from_le_bytes([low, byte(addr.checked_add(1)?)?]);
+            Some(if signed { value as i16 as i32 } else { value as i32 })
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+
+// snapshots are save states, every `keyframe_interval`th one is stored whole and the ones
+// in between as the difference to their keyframe. both are xor'd against a reference
+// (zeros for keyframes) and run length encoded, unchanged bytes cost next to nothing
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RewindConfig {
+    /// frames between two snapshots
+    pub interval: u32,
+    /// snapshots between two keyframes
+    pub keyframe_interval: u32,
+    /// bytes the compressed snapshots may take up, the oldest are dropped first
+    pub memory_budget: usize,
+}
+
+impl Default for RewindConfig {
+    /// a snapshot every frame, enough for a minute of most games
+    fn default() -> Self {
+        RewindConfig {
+            interval: 1,
+            keyframe_interval: 60,
+            memory_budget: 64 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    frame: u64,
+    keyframe: bool,
+    data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct RewindBuffer {
+    config: RewindConfig,
+    snapshots: VecDeque<Snapshot>,
+    /// uncompressed state of the newest keyframe, the reference for new deltas
+    keyframe: Vec<u8>,
+    /// snapshots since the newest keyframe
+    since_keyframe: u32,
+    last_capture: Option<u64>,
+    memory_usage: usize,
+}
+
+impl RewindBuffer {
+    pub fn new(config: RewindConfig) -> RewindBuffer {
+        RewindBuffer {
+            config,
+            snapshots: VecDeque::new(),
+            keyframe: Vec::new(),
+            since_keyframe: 0,
+            last_capture: None,
+            memory_usage: 0,
+        }
+    }
+
+    pub fn config(&self) -> RewindConfig {
+        self.config
+    }
+
+    /// whether a snapshot is due at `frame`
+    pub fn wants(&self, frame: u64) -> bool {
+        match self.last_capture {
+            Some(last) => frame >= last + self.config.interval.max(1) as u64,
+            None => true,
+        }
+    }
+
+    /// stores `state` as the snapshot of `frame`
+    pub fn capture(&mut self, frame: u64, state: &[u8]) {
+        let keyframe = self.snapshots.is_empty()
+            || self.since_keyframe + 1 >= self.config.keyframe_interval.max(1)
+            || state.len() != self.keyframe.len();
+
+        let data = if keyframe {
+            self.keyframe = state.to_vec();
+            self.since_keyframe = 0;
+            encode(state, None)
+        } else {
+            self.since_keyframe += 1;
+            encode(state, Some(&self.keyframe))
+        };
+
+        self.memory_usage += data.len();
+        self.snapshots.push_back(Snapshot { frame, keyframe, data });
+        self.last_capture = Some(frame);
+        self.trim();
+    }
+
+    /// the newest snapshot taken at or before `frame`, falling back to the oldest one.
+    /// newer snapshots are discarded since history continues from there
+    pub fn restore(&mut self, frame: u64) -> Option<(u64, Vec<u8>)> {
+        let index = self.snapshots.iter().rposition(|snapshot| snapshot.frame <= frame).unwrap_or(0);
+        let snapshot = self.snapshots.get(index)?;
+        let key_index = self.snapshots.iter().take(index + 1).rposition(|snapshot| snapshot.keyframe)?;
+
+        let keyframe = decode(&self.snapshots[key_index].data, None);
+        let state = if key_index == index { keyframe.clone() } else { decode(&snapshot.data, Some(&keyframe)) };
+        let restored = snapshot.frame;
+
+        for dropped in self.snapshots.drain(index + 1..) {
+            self.memory_usage -= dropped.data.len();
+        }
+        self.keyframe = keyframe;
+        self.since_keyframe = (index - key_index) as u32;
+        self.last_capture = Some(restored);
+        Some((restored, state))
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.keyframe.clear();
+        self.since_keyframe = 0;
+        self.last_capture = None;
+        self.memory_usage = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// frame of the oldest snapshot
+    pub fn oldest_frame(&self) -> Option<u64> {
+        self.snapshots.front().map(|snapshot| snapshot.frame)
+    }
+
+    /// compressed bytes held by the snapshots
+    pub fn memory_usage(&self) -> usize {
+        self.memory_usage
+    }
+
+    /// drops the oldest keyframe along with its deltas until the budget fits,
+    /// the newest keyframe always stays
+    fn trim(&mut self) {
+        while self.memory_usage > self.config.memory_budget {
+            let Some(next_keyframe) = self.snapshots.iter().skip(1).position(|snapshot| snapshot.keyframe) else {
+                break;
+            };
+            for dropped in self.snapshots.drain(..=next_keyframe) {
+                self.memory_usage -= dropped.data.len();
+            }
+        }
+    }
+}
+
+/// runs of unchanged bytes followed by runs of changed ones, both lengths as varints
+fn encode(state: &[u8], reference: Option<&[u8]>) -> Vec<u8> {
+    let diff = |i: usize| state[i] ^ reference.map_or(0, |reference| reference[i]);
+
+    let mut data = Vec::new();
+    let mut i = 0;
+    while i < state.len() {
+        let start = i;
+        while i < state.len() && diff(i) == 0 {
+            i += 1;
+        }
+        write_varint(&mut data, i - start);
+
+        let start = i;
+        while i < state.len() && diff(i) != 0 {
+            i += 1;
+        }
+        write_varint(&mut data, i - start);
+        data.extend((start..i).map(diff));
+    }
+    data
+}
+
+fn decode(data: &[u8], reference: Option<&[u8]>) -> Vec<u8> {
+    let mut state = match reference {
+        Some(reference) => reference.to_vec(),
+        None => Vec::new(),
+    };
+
+    let mut position = 0;
+    let mut i = 0;
+    while position < data.len() {
+        i += read_varint(data, &mut position);
+        let changed = read_varint(data, &mut position);
+        if state.len() < i + changed {
+            state.resize(i + changed, 0);
+        }
+        for byte in &data[position..position + changed] {
+            state[i] ^= byte;
+            i += 1;
+        }
+        position += changed;
+    }
+    // trailing zeros of a keyframe only show up as an unchanged run
+    if state.len() < i {
+        state.resize(i, 0);
+    }
+    state
+}
+
+fn write_varint(data: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        data.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &[u8], position: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = data[*position];
+        *position += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return value;
+        }
+        shift += 7;
+    }
+}
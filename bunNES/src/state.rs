@@ -0,0 +1,173 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+// save state layout, all values little endian:
+//   magic "BNES", version u16, cartridge hash u64,
+//   cpu registers, bus (ram, cartridge ram, ppu, apu, input devices)
+// bump VERSION whenever the layout of any component changes
+
+pub const MAGIC: [u8; 4] = *b"BNES";
+pub const VERSION: u16 = 3;
+/// magic, version and cartridge hash
+pub const HEADER_SIZE: usize = 4 + 2 + 8;
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum StateError {
+    /// not a save state
+    InvalidMagic,
+    /// written by a different version of the format
+    UnsupportedVersion(u16),
+    /// saved while running a different cartridge
+    WrongCartridge,
+    /// the data ended before the state was complete
+    UnexpectedEof,
+    /// the state doesn't fit the current machine, e.g. a different input device
+    Mismatch(&'static str),
+}
+
+impl Display for StateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateError::InvalidMagic => write!(f, "not a save state"),
+            StateError::UnsupportedVersion(version) => write!(f, "unsupported save state version {version}, expected {VERSION}"),
+            StateError::WrongCartridge => write!(f, "save state belongs to a different cartridge"),
+            StateError::UnexpectedEof => write!(f, "save state is truncated"),
+            StateError::Mismatch(what) => write!(f, "save state doesn't match the current {what}"),
+        }
+    }
+}
+
+impl Error for StateError {}
+
+/// components that can be written to and restored from a save state
+pub trait SaveState {
+    fn save_state(&self, writer: &mut StateWriter);
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError>;
+}
+
+#[derive(Debug, Default)]
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> StateWriter {
+        StateWriter::default()
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        self.u64(value.to_bits());
+    }
+
+    /// length prefixed
+    pub fn bytes(&mut self, value: &[u8]) {
+        self.u32(value.len() as u32);
+        self.data.extend_from_slice(value);
+    }
+
+    pub fn str(&mut self, value: &str) {
+        self.bytes(value.as_bytes());
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+#[derive(Debug)]
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> StateReader<'a> {
+    pub fn new(data: &'a [u8]) -> StateReader<'a> {
+        StateReader { data, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        let end = self.position.checked_add(len).ok_or(StateError::UnexpectedEof)?;
+        let bytes = self.data.get(self.position..end).ok_or(StateError::UnexpectedEof)?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], StateError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub fn u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn bool(&mut self) -> Result<bool, StateError> {
+        Ok(self.u8()? != 0)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, StateError> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, StateError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, StateError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    pub fn f32(&mut self) -> Result<f32, StateError> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64, StateError> {
+        Ok(f64::from_bits(self.u64()?))
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], StateError> {
+        let len = self.u32()? as usize;
+        self.take(len)
+    }
+
+    /// length prefixed bytes into a buffer of the same size
+    pub fn bytes_into(&mut self, buffer: &mut [u8], what: &'static str) -> Result<(), StateError> {
+        let bytes = self.bytes()?;
+        if bytes.len() != buffer.len() {
+            return Err(StateError::Mismatch(what));
+        }
+        buffer.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub fn str(&mut self) -> Result<&'a str, StateError> {
+        std::str::from_utf8(self.bytes()?).map_err(|_| StateError::Mismatch("string"))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position == self.data.len()
+    }
+}
@@ -0,0 +1,85 @@
+use crate::apu::helpers::{get_apu, get_bus_with_prg};
+
+#[cfg(test)]
+mod sample_channel {
+    use super::*;
+
+    // rate index 15 on ntsc
+    const RATE: usize = 54;
+
+    #[test]
+    fn direct_load() {
+        let mut apu = get_apu();
+        apu.set_register(0x4011, 0xC0);
+        assert_eq!(apu.dmc.output(), 0x40);
+    }
+
+    #[test]
+    fn sample_fetch() {
+        let mut bus = get_bus_with_prg(vec![0xFF]);
+        // sample at $C000, 17 bytes
+        bus.write(0x4010, 0b0000_1111);
+        bus.write(0x4012, 0x00);
+        bus.write(0x4013, 0x01);
+        bus.write(0x4015, 0b0001_0000);
+        assert_eq!(bus.apu.dmc.bytes_remaining(), 17);
+        assert_eq!(bus.read_8(0x4015) & 0b0001_0000, 0b0001_0000);
+
+        // the fetch stalls the cpu
+        assert_eq!(bus.step_apu(), 4);
+        assert_eq!(bus.apu.dmc.bytes_remaining(), 16);
+        // nothing to fetch until the buffer is emptied
+        assert_eq!(bus.step_apu(), 0);
+    }
+
+    #[test]
+    fn sample_output() {
+        let mut bus = get_bus_with_prg(vec![0xFF]);
+        bus.write(0x4010, 0b0000_1111);
+        bus.write(0x4011, 0x40);
+        bus.write(0x4015, 0b0001_0000);
+
+        // 8 silent bits until the sample buffer is loaded, then 8 bits of $FF
+        for _ in 0..RATE * 16 {
+            bus.step_apu();
+        }
+        assert_eq!(bus.apu.dmc.output(), 0x40 + 8 * 2);
+    }
+
+    #[test]
+    fn irq() {
+        let mut bus = get_bus_with_prg(vec![0xFF]);
+        bus.write(0x4010, 0b1000_1111);
+        bus.write(0x4015, 0b0001_0000);
+        bus.step_apu();
+        assert!(bus.irq());
+        assert_eq!(bus.read_8(0x4015) & 0b1001_0000, 0b1000_0000);
+        // reading status doesn't acknowledge
+        assert!(bus.irq());
+
+        // writing $4015 does
+        bus.write(0x4015, 0b0000_0000);
+        assert!(!bus.irq());
+    }
+
+    #[test]
+    fn irq_disable() {
+        let mut bus = get_bus_with_prg(vec![0xFF]);
+        bus.write(0x4010, 0b1000_1111);
+        bus.write(0x4015, 0b0001_0000);
+        bus.step_apu();
+        assert!(bus.irq());
+        bus.write(0x4010, 0b0000_1111);
+        assert!(!bus.irq());
+    }
+
+    #[test]
+    fn loop_sample() {
+        let mut bus = get_bus_with_prg(vec![0xFF]);
+        bus.write(0x4010, 0b1100_1111);
+        bus.write(0x4015, 0b0001_0000);
+        bus.step_apu();
+        assert!(!bus.irq());
+        assert_eq!(bus.apu.dmc.bytes_remaining(), 1);
+    }
+}
@@ -0,0 +1,173 @@
+use crate::apu::helpers::get_apu;
+use crate::opcodes::helpers::instruction;
+use bunNES::nes::apu::Apu;
+use bunNES::nes::cpu::Cpu;
+use bunNES::nes::opcodes::{AddrMode, OpCode};
+use bunNES::nes::rom::{Cartridge, Region};
+
+#[cfg(test)]
+mod frame_sequencer {
+    use super::*;
+
+    fn step(apu: &mut Apu, cycles: usize) {
+        for _ in 0..cycles {
+            apu.step();
+        }
+    }
+
+    fn with_pulse(mut apu: Apu) -> Apu {
+        apu.set_register(0x4015, 0b0000_0001);
+        apu.set_register(0x4003, 0b0000_1000);
+        apu
+    }
+
+    #[test]
+    fn four_step_half_frames() {
+        let mut apu = with_pulse(get_apu());
+        step(&mut apu, 14912);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        step(&mut apu, 1);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+        step(&mut apu, 29829 - 14913);
+        assert_eq!(apu.pulse_1.length_counter(), 252);
+        // sequence restarts after 29830 cycles
+        step(&mut apu, 1 + 14913);
+        assert_eq!(apu.pulse_1.length_counter(), 251);
+    }
+
+    #[test]
+    fn four_step_irq() {
+        let mut apu = get_apu();
+        step(&mut apu, 29827);
+        assert!(!apu.irq());
+        step(&mut apu, 1);
+        assert!(apu.irq());
+        // stays set until acknowledged
+        step(&mut apu, 10000);
+        assert!(apu.irq());
+    }
+
+    #[test]
+    fn status_acknowledges_irq() {
+        let mut apu = get_apu();
+        step(&mut apu, 29830);
+        assert_eq!(apu.status() & 0b0100_0000, 0b0100_0000);
+        assert!(!apu.irq());
+        assert_eq!(apu.status() & 0b0100_0000, 0);
+    }
+
+    #[test]
+    fn peek_status_keeps_irq() {
+        let mut apu = get_apu();
+        step(&mut apu, 29830);
+        assert_eq!(apu.peek_status() & 0b0100_0000, 0b0100_0000);
+        assert!(apu.irq());
+        assert_eq!(apu.status(), apu.peek_status() | 0b0100_0000);
+    }
+
+    #[test]
+    fn irq_inhibit() {
+        let mut apu = get_apu();
+        apu.set_register(0x4017, 0b0100_0000);
+        step(&mut apu, 40000);
+        assert!(!apu.irq());
+    }
+
+    #[test]
+    fn irq_inhibit_clears_flag() {
+        let mut apu = get_apu();
+        step(&mut apu, 29830);
+        assert!(apu.irq());
+        apu.set_register(0x4017, 0b0100_0000);
+        assert!(!apu.irq());
+    }
+
+    #[test]
+    fn five_step() {
+        let mut apu = with_pulse(get_apu());
+        apu.set_register(0x4017, 0b1000_0000);
+        // writing five step mode clocks all units after the write delay
+        step(&mut apu, 4);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+        step(&mut apu, 14913);
+        assert_eq!(apu.pulse_1.length_counter(), 252);
+        step(&mut apu, 37281 - 14913);
+        assert_eq!(apu.pulse_1.length_counter(), 251);
+        // no irq in five step mode
+        step(&mut apu, 40000);
+        assert!(!apu.irq());
+    }
+
+    #[test]
+    fn write_delay() {
+        // written right after an apu cycle
+        let mut apu = with_pulse(get_apu());
+        apu.set_register(0x4017, 0b1000_0000);
+        step(&mut apu, 2);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        step(&mut apu, 1);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+
+        // written between apu cycles
+        let mut apu = with_pulse(get_apu());
+        step(&mut apu, 1);
+        apu.set_register(0x4017, 0b1000_0000);
+        step(&mut apu, 3);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        step(&mut apu, 1);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+    }
+
+    #[test]
+    fn write_resets_sequencer() {
+        let mut apu = with_pulse(get_apu());
+        step(&mut apu, 10000);
+        apu.set_register(0x4017, 0b0000_0000);
+        step(&mut apu, 3 + 14912);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        step(&mut apu, 1);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+    }
+
+    #[test]
+    fn pal_timing() {
+        let mut apu = with_pulse(Apu::new(Region::Pal));
+        step(&mut apu, 16626);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        step(&mut apu, 1);
+        assert_eq!(apu.pulse_1.length_counter(), 253);
+        step(&mut apu, 33251 - 16627);
+        assert!(!apu.irq());
+        step(&mut apu, 1);
+        assert!(apu.irq());
+    }
+
+    #[test]
+    fn cpu_irq() {
+        let mut code = vec![instruction(OpCode::Nop, AddrMode::Implicit); 0x3000];
+        // loops over the nops until the irq comes
+        code.extend([instruction(OpCode::Jmp, AddrMode::Absolute), 0x00, 0x80]);
+        code.resize(0x4000, 0);
+        // interrupt vector
+        code[0x3FFE] = 0x00;
+        code[0x3FFF] = 0x01;
+        // the irq comes from the apu on the nes bus
+        let mut cpu = Cpu::new(Cartridge::test_cartride(code));
+        cpu.pc = 0x8000;
+        let mut return_addr = cpu.pc;
+        for _ in 0..40000 {
+            return_addr = cpu.pc;
+            cpu.step();
+            if cpu.pc == 0x0100 {
+                break;
+            }
+        }
+        // interrupt vector of the test cartridge
+        assert_eq!(cpu.pc, 0x0100);
+        assert!(cpu.ps.irqb());
+        assert_eq!(cpu.sp, 0xFC);
+        assert_eq!(cpu.bus.ram[0x1FF], (return_addr >> 8) as u8);
+        assert_eq!(cpu.bus.ram[0x1FE], return_addr as u8);
+        assert_eq!(cpu.bus.ram[0x1FD] & 0b0011_0000, 0b0010_0000);
+    }
+}
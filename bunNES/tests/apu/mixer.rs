@@ -0,0 +1,142 @@
+use crate::apu::helpers::get_apu;
+use bunNES::nes::apu::buffer::{to_i16, AudioBuffer};
+use bunNES::nes::apu::mixer::Mixer;
+
+#[cfg(test)]
+mod levels {
+    use super::*;
+
+    fn assert_close(left: f32, right: f32) {
+        assert!((left - right).abs() < 0.0001, "{left} != {right}");
+    }
+
+    #[test]
+    fn silence() {
+        let mixer = Mixer::new();
+        assert_eq!(mixer.mix(0, 0, 0, 0, 0), 0.0);
+    }
+
+    #[test]
+    fn pulse() {
+        let mixer = Mixer::new();
+        assert_close(mixer.mix(15, 15, 0, 0, 0), 95.52 / (8128.0 / 30.0 + 100.0));
+        // nonlinear: one pulse at full volume is louder than half of both
+        assert!(mixer.mix(15, 0, 0, 0, 0) > mixer.mix(15, 15, 0, 0, 0) / 2.0);
+    }
+
+    #[test]
+    fn tnd() {
+        let mixer = Mixer::new();
+        assert_close(mixer.mix(0, 0, 15, 15, 127), 163.67 / (24329.0 / 202.0 + 100.0));
+        assert_close(mixer.mix(0, 0, 1, 0, 0), mixer.mix(0, 0, 0, 0, 3));
+    }
+
+    #[test]
+    fn full_volume() {
+        let mixer = Mixer::new();
+        let max = mixer.mix(15, 15, 15, 15, 127);
+        assert!(max > 0.99 && max < 1.01);
+    }
+}
+
+#[cfg(test)]
+mod output {
+    use super::*;
+
+    const CPU_CLOCK_NTSC: usize = 1_789_773;
+
+    #[test]
+    fn sample_rate() {
+        let mut apu = get_apu();
+        for _ in 0..CPU_CLOCK_NTSC / 10 {
+            apu.step();
+        }
+        let samples = apu.samples().len() as i64;
+        assert!((samples - 4410).abs() <= 1, "{samples}");
+    }
+
+    #[test]
+    fn set_sample_rate() {
+        let mut apu = get_apu();
+        apu.set_sample_rate(48000);
+        for _ in 0..CPU_CLOCK_NTSC / 10 {
+            apu.step();
+        }
+        let samples = apu.samples().len() as i64;
+        assert!((samples - 4800).abs() <= 1, "{samples}");
+    }
+
+    #[test]
+    fn dc_is_filtered() {
+        let mut apu = get_apu();
+        // constant dmc level
+        apu.set_register(0x4011, 0x7F);
+        for _ in 0..CPU_CLOCK_NTSC / 2 {
+            apu.step();
+        }
+        let mut samples = [0f32; 100];
+        let count = apu.samples().pop_f32(&mut samples);
+        assert_eq!(count, 100);
+        // only the tail is settled
+        apu.samples().clear();
+        for _ in 0..CPU_CLOCK_NTSC / 10 {
+            apu.step();
+        }
+        let count = apu.samples().pop_f32(&mut samples);
+        assert_eq!(count, 100);
+        assert!(samples.iter().all(|sample| sample.abs() < 0.001));
+    }
+
+    #[test]
+    fn square_wave() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        apu.set_register(0x4000, 0b1011_1111);
+        // ~440hz
+        apu.set_register(0x4002, 0xFD);
+        apu.set_register(0x4003, 0b0000_1000);
+        for _ in 0..CPU_CLOCK_NTSC / 10 {
+            apu.step();
+        }
+        let mut samples = [0f32; 4000];
+        apu.samples().pop_f32(&mut samples);
+        let max = samples.iter().cloned().fold(f32::MIN, f32::max);
+        let min = samples.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(max > 0.05 && min < -0.05, "{min} {max}");
+    }
+}
+
+#[cfg(test)]
+mod buffer {
+    use super::*;
+
+    #[test]
+    fn drops_oldest() {
+        let mut buffer = AudioBuffer::new(4);
+        for i in 0..6 {
+            buffer.push(i as f32);
+        }
+        let mut out = [0f32; 8];
+        assert_eq!(buffer.pop_f32(&mut out), 4);
+        assert_eq!(out[..4], [2.0, 3.0, 4.0, 5.0]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn partial_pop() {
+        let mut buffer = AudioBuffer::new(4);
+        buffer.push(0.5);
+        buffer.push(-0.5);
+        let mut out = [0i16; 1];
+        assert_eq!(buffer.pop_i16(&mut out), 1);
+        assert_eq!(out[0], to_i16(0.5));
+        assert_eq!(buffer.len(), 1);
+    }
+
+    #[test]
+    fn i16_conversion() {
+        assert_eq!(to_i16(0.0), 0);
+        assert_eq!(to_i16(1.0), i16::MAX);
+        assert_eq!(to_i16(-2.0), -i16::MAX);
+    }
+}
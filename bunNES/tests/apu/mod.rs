@@ -0,0 +1,36 @@
+mod pulse;
+mod triangle;
+mod noise;
+mod dmc;
+mod frame_counter;
+mod mixer;
+
+pub(crate) mod helpers {
+    use bunNES::nes::apu::Apu;
+    use bunNES::nes::bus::Bus;
+    use bunNES::nes::rom::{Cartridge, Region};
+
+    pub fn get_apu() -> Apu {
+        Apu::new(Region::Ntsc)
+    }
+
+    pub fn get_bus() -> Bus {
+        get_bus_with_prg(vec![0; 0x4000])
+    }
+
+    /// 16k prg rom, mirrored to $C000
+    pub fn get_bus_with_prg(mut prg: Vec<u8>) -> Bus {
+        prg.resize(0x4000, 0);
+        Bus::new(Cartridge::test_cartride(prg))
+    }
+
+    /// steps the apu and returns the channel output for every cpu cycle
+    pub fn collect<F: Fn(&Apu) -> u8>(apu: &mut Apu, cycles: usize, output: F) -> Vec<u8> {
+        let mut samples = Vec::with_capacity(cycles);
+        for _ in 0..cycles {
+            apu.step();
+            samples.push(output(apu));
+        }
+        samples
+    }
+}
@@ -0,0 +1,73 @@
+use crate::apu::helpers::get_apu;
+use bunNES::nes::apu::Apu;
+use bunNES::nes::rom::Region;
+
+#[cfg(test)]
+mod noise_channel {
+    use super::*;
+
+    // shortest period is 4 cpu cycles
+    fn clock_shift_register(apu: &mut Apu, times: usize) {
+        for _ in 0..times * 4 {
+            apu.step();
+        }
+    }
+
+    #[test]
+    fn shift_register() {
+        let mut apu = get_apu();
+        apu.set_register(0x400E, 0b0000_0000);
+        clock_shift_register(&mut apu, 1);
+        assert_eq!(apu.noise.shift_register(), 0x4000);
+        clock_shift_register(&mut apu, 1);
+        assert_eq!(apu.noise.shift_register(), 0x2000);
+    }
+
+    #[test]
+    fn long_mode_period() {
+        let mut apu = get_apu();
+        apu.set_register(0x400E, 0b0000_0000);
+        clock_shift_register(&mut apu, 32766);
+        assert_ne!(apu.noise.shift_register(), 1);
+        clock_shift_register(&mut apu, 1);
+        assert_eq!(apu.noise.shift_register(), 1);
+    }
+
+    #[test]
+    fn short_mode_period() {
+        let mut apu = get_apu();
+        apu.set_register(0x400E, 0b1000_0000);
+        clock_shift_register(&mut apu, 92);
+        assert_ne!(apu.noise.shift_register(), 1);
+        clock_shift_register(&mut apu, 1);
+        assert_eq!(apu.noise.shift_register(), 1);
+    }
+
+    #[test]
+    fn output() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_1000);
+        apu.set_register(0x400C, 0b0001_0111);
+        apu.set_register(0x400E, 0b0000_0000);
+        apu.set_register(0x400F, 0b0000_1000);
+        // bit 0 set on power up mutes the channel
+        assert_eq!(apu.noise.output(), 0);
+        clock_shift_register(&mut apu, 1);
+        assert_eq!(apu.noise.output(), 7);
+        assert_eq!(apu.status() & 0b0000_1000, 0b0000_1000);
+    }
+
+    #[test]
+    fn period_table() {
+        let mut ntsc = get_apu();
+        let mut pal = Apu::new(Region::Pal);
+        ntsc.set_register(0x400E, 0b0000_0010);
+        pal.set_register(0x400E, 0b0000_0010);
+        assert_eq!(ntsc.noise.timer_period(), 16);
+        assert_eq!(pal.noise.timer_period(), 14);
+        ntsc.set_register(0x400E, 0b0000_1111);
+        pal.set_register(0x400E, 0b0000_1111);
+        assert_eq!(ntsc.noise.timer_period(), 4068);
+        assert_eq!(pal.noise.timer_period(), 3778);
+    }
+}
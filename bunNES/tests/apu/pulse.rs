@@ -0,0 +1,217 @@
+use crate::apu::helpers::{collect, get_apu, get_bus};
+
+#[cfg(test)]
+mod length_counter {
+    use super::*;
+
+    #[test]
+    fn load_when_enabled() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        // length index 1 -> 254
+        apu.set_register(0x4003, 0b0000_1000);
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        assert_eq!(apu.status() & 0b0000_0001, 1);
+    }
+
+    #[test]
+    fn ignore_load_when_disabled() {
+        let mut apu = get_apu();
+        apu.set_register(0x4007, 0b0000_1000);
+        assert_eq!(apu.pulse_2.length_counter(), 0);
+        assert_eq!(apu.status() & 0b0000_0010, 0);
+    }
+
+    #[test]
+    fn disable_clears_counter() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0011);
+        apu.set_register(0x4003, 0b0000_1000);
+        apu.set_register(0x4007, 0b0000_1000);
+        apu.set_register(0x4015, 0b0000_0010);
+        assert_eq!(apu.pulse_1.length_counter(), 0);
+        assert_eq!(apu.pulse_2.length_counter(), 254);
+    }
+
+    #[test]
+    fn halt() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0011);
+        // halt pulse 1 only
+        apu.set_register(0x4000, 0b0010_0000);
+        apu.set_register(0x4003, 0b0000_1000);
+        apu.set_register(0x4007, 0b0000_1000);
+        apu.clock_half_frame();
+        assert_eq!(apu.pulse_1.length_counter(), 254);
+        assert_eq!(apu.pulse_2.length_counter(), 253);
+    }
+}
+
+#[cfg(test)]
+mod envelope {
+    use super::*;
+
+    #[test]
+    fn constant_volume() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        // duty 3 is high on the first step, constant volume 9
+        apu.set_register(0x4000, 0b1101_1001);
+        apu.set_register(0x4002, 0x40);
+        apu.set_register(0x4003, 0b0000_1000);
+        assert_eq!(apu.pulse_1.output(), 9);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.pulse_1.output(), 9);
+    }
+
+    #[test]
+    fn decay() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        // duty 3, envelope period 0
+        apu.set_register(0x4000, 0b1100_0000);
+        apu.set_register(0x4002, 0x40);
+        apu.set_register(0x4003, 0b0000_1000);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.pulse_1.output(), 15);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.pulse_1.output(), 14);
+        for _ in 0..14 {
+            apu.clock_quarter_frame();
+        }
+        assert_eq!(apu.pulse_1.output(), 0);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.pulse_1.output(), 0);
+    }
+
+    #[test]
+    fn decay_loop() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        // duty 3, loop, envelope period 0
+        apu.set_register(0x4000, 0b1110_0000);
+        apu.set_register(0x4002, 0x40);
+        apu.set_register(0x4003, 0b0000_1000);
+        for _ in 0..16 {
+            apu.clock_quarter_frame();
+        }
+        assert_eq!(apu.pulse_1.output(), 0);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.pulse_1.output(), 15);
+    }
+}
+
+#[cfg(test)]
+mod sequencer {
+    use super::*;
+
+    const TIMER_PERIOD: usize = 8;
+    // one sequencer step every (period + 1) apu cycles, 8 steps
+    const SEQUENCE_CYCLES: usize = (TIMER_PERIOD + 1) * 2 * 8;
+
+    fn high_cycles(duty: u8) -> usize {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        apu.set_register(0x4000, duty << 6 | 0b0001_1111);
+        apu.set_register(0x4002, TIMER_PERIOD as u8);
+        apu.set_register(0x4003, 0b0000_1000);
+        let samples = collect(&mut apu, SEQUENCE_CYCLES, |apu| apu.pulse_1.output());
+        samples.iter().filter(|sample| **sample > 0).count()
+    }
+
+    #[test]
+    fn duty_cycles() {
+        assert_eq!(high_cycles(0), SEQUENCE_CYCLES / 8);
+        assert_eq!(high_cycles(1), SEQUENCE_CYCLES / 4);
+        assert_eq!(high_cycles(2), SEQUENCE_CYCLES / 2);
+        assert_eq!(high_cycles(3), SEQUENCE_CYCLES / 4 * 3);
+    }
+
+    #[test]
+    fn silent_when_length_counter_zero() {
+        let mut apu = get_apu();
+        apu.set_register(0x4000, 0b1101_1111);
+        apu.set_register(0x4002, TIMER_PERIOD as u8);
+        apu.set_register(0x4003, 0b0000_1000);
+        let samples = collect(&mut apu, SEQUENCE_CYCLES, |apu| apu.pulse_1.output());
+        assert!(samples.iter().all(|sample| *sample == 0));
+    }
+}
+
+#[cfg(test)]
+mod sweep {
+    use super::*;
+
+    #[test]
+    fn negate_ones_complement() {
+        let mut apu = get_apu();
+        // enabled, negate, shift 1
+        apu.set_register(0x4001, 0b1000_1001);
+        apu.set_register(0x4002, 0x00);
+        apu.set_register(0x4003, 0x01);
+        assert_eq!(apu.pulse_1.sweep_target_period(), 0x100 - 0x80 - 1);
+    }
+
+    #[test]
+    fn negate_twos_complement() {
+        let mut apu = get_apu();
+        apu.set_register(0x4005, 0b1000_1001);
+        apu.set_register(0x4006, 0x00);
+        apu.set_register(0x4007, 0x01);
+        assert_eq!(apu.pulse_2.sweep_target_period(), 0x100 - 0x80);
+    }
+
+    #[test]
+    fn update_period() {
+        let mut apu = get_apu();
+        // enabled, sweep period 0, shift 2
+        apu.set_register(0x4001, 0b1000_0010);
+        apu.set_register(0x4002, 0x00);
+        apu.set_register(0x4003, 0x01);
+        apu.clock_half_frame();
+        assert_eq!(apu.pulse_1.timer_period(), 0x100 + 0x40);
+        apu.clock_half_frame();
+        assert_eq!(apu.pulse_1.timer_period(), 0x140 + 0x50);
+    }
+
+    #[test]
+    fn mute_on_overflow() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        apu.set_register(0x4000, 0b1101_1111);
+        // sweep disabled, target 0x700 + 0x700 overflows
+        apu.set_register(0x4001, 0b0000_0000);
+        apu.set_register(0x4002, 0x00);
+        apu.set_register(0x4003, 0b0000_1111);
+        assert_eq!(apu.pulse_1.output(), 0);
+        apu.clock_half_frame();
+        assert_eq!(apu.pulse_1.timer_period(), 0x700);
+    }
+
+    #[test]
+    fn mute_on_low_period() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0001);
+        apu.set_register(0x4000, 0b1101_1111);
+        apu.set_register(0x4001, 0b0000_1111);
+        apu.set_register(0x4002, 0x07);
+        apu.set_register(0x4003, 0b0000_1000);
+        assert_eq!(apu.pulse_1.output(), 0);
+    }
+}
+
+#[cfg(test)]
+mod bus {
+    use super::*;
+
+    #[test]
+    fn write_registers() {
+        let mut bus = get_bus();
+        for addr in 0x4000..=0x4017 {
+            bus.write(addr, 0xFF);
+        }
+        bus.write(0x4015, 0b0000_0001);
+        bus.write(0x4003, 0b0000_1000);
+        assert_eq!(bus.read_8(0x4015) & 0b0000_0011, 0b0000_0001);
+    }
+}
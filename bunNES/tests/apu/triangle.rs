@@ -0,0 +1,67 @@
+use crate::apu::helpers::{collect, get_apu};
+
+#[cfg(test)]
+mod triangle_channel {
+    use super::*;
+
+    const TIMER_PERIOD: usize = 2;
+
+    #[test]
+    fn sequence() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0100);
+        apu.set_register(0x4008, 0b0111_1111);
+        apu.set_register(0x400A, TIMER_PERIOD as u8);
+        apu.set_register(0x400B, 0b0000_1000);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.triangle.linear_counter(), 127);
+
+        let samples = collect(&mut apu, (TIMER_PERIOD + 1) * 32, |apu| apu.triangle.output());
+        let steps: Vec<u8> = samples.into_iter().step_by(TIMER_PERIOD + 1).collect();
+        let mut expected: Vec<u8> = (0..15).rev().collect();
+        expected.extend(0..16);
+        expected.push(15);
+        assert_eq!(steps, expected);
+    }
+
+    #[test]
+    fn halted_without_linear_counter() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0100);
+        apu.set_register(0x4008, 0b0111_1111);
+        apu.set_register(0x400A, TIMER_PERIOD as u8);
+        apu.set_register(0x400B, 0b0000_1000);
+
+        let samples = collect(&mut apu, (TIMER_PERIOD + 1) * 32, |apu| apu.triangle.output());
+        assert!(samples.iter().all(|sample| *sample == 15));
+    }
+
+    #[test]
+    fn linear_counter() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0100);
+        apu.set_register(0x4008, 0b0000_0010);
+        apu.set_register(0x400B, 0b0000_1000);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.triangle.linear_counter(), 2);
+        apu.clock_quarter_frame();
+        assert_eq!(apu.triangle.linear_counter(), 1);
+        apu.clock_quarter_frame();
+        apu.clock_quarter_frame();
+        assert_eq!(apu.triangle.linear_counter(), 0);
+    }
+
+    #[test]
+    fn linear_counter_control() {
+        let mut apu = get_apu();
+        apu.set_register(0x4015, 0b0000_0100);
+        // control flag keeps reloading the linear counter
+        apu.set_register(0x4008, 0b1000_0010);
+        apu.set_register(0x400B, 0b0000_1000);
+        apu.clock_quarter_frame();
+        apu.clock_quarter_frame();
+        assert_eq!(apu.triangle.linear_counter(), 2);
+        apu.clock_half_frame();
+        assert_eq!(apu.triangle.length_counter(), 254);
+    }
+}
@@ -0,0 +1,91 @@
+use super::helpers::*;
+use bunNES::nes::bus::Bus;
+use bunNES::nes::rom::Cartridge;
+
+/// NES 2.0 nrom without prg ram
+fn get_bus_without_prg_ram() -> Bus {
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 1, 0, 0, 0b0000_1000, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.resize(16 + 0x4000, 0);
+    Bus::new(Cartridge::new(rom))
+}
+
+#[cfg(test)]
+mod mirroring {
+    use super::*;
+
+    #[test]
+    fn ram() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        let bus = &mut emulator.cpu.bus;
+        bus.write(0x0801, 0x11);
+        bus.write(0x17FF, 0x22);
+        assert_eq!(bus.ram[0x001], 0x11);
+        assert_eq!(bus.ram[0x7FF], 0x22);
+        for mirror in [0x0000, 0x0800, 0x1000, 0x1800] {
+            assert_eq!(bus.read_8(mirror + 0x001), 0x11);
+            assert_eq!(bus.read_8(mirror + 0x7FF), 0x22);
+            assert_eq!(bus.peek(mirror + 0x7FF), 0x22);
+        }
+    }
+}
+
+#[cfg(test)]
+mod open_bus {
+    use super::*;
+
+    #[test]
+    fn unmapped_reads() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        let bus = &mut emulator.cpu.bus;
+        bus.write(0x0000, 0x5A);
+        for addr in [0x4000, 0x4014, 0x4018, 0x401F, 0x4020, 0x5FFF] {
+            assert_eq!(bus.peek(addr), 0x5A);
+            assert_eq!(bus.read_8(addr), 0x5A);
+        }
+        // nothing to write to either
+        bus.write(0x4018, 0x01);
+        bus.write(0x5000, 0x02);
+        assert_eq!(bus.read_8(0x5000), 0x02);
+    }
+
+    #[test]
+    fn last_operand_byte() {
+        let mut emulator = get_emulator(vec![
+            0xAD, 0x00, 0x50, // lda $5000
+            0x85, 0x00,       // sta $00
+            0xAD, 0x18, 0x40, // lda $4018
+            0x85, 0x01,       // sta $01
+            0xD0, 0xFE,       // bne to itself
+        ]);
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0..2], [0x50, 0x40]);
+    }
+
+    #[test]
+    fn apu_status_bit_5() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        let bus = &mut emulator.cpu.bus;
+        bus.write(0x0000, 0x20);
+        assert_eq!(bus.peek(0x4015) & 0x20, 0x20);
+        bus.write(0x0000, 0xDF);
+        assert_eq!(bus.read_8(0x4015) & 0x20, 0);
+    }
+
+    #[test]
+    fn missing_prg_ram() {
+        let mut bus = get_bus_without_prg_ram();
+        bus.write(0x6000, 0x77);
+        bus.write(0x0000, 0x33);
+        assert_eq!(bus.read_8(0x6000), 0x33);
+        assert_eq!(bus.peek(0x7FFF), 0x33);
+    }
+
+    #[test]
+    fn prg_ram() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        let bus = &mut emulator.cpu.bus;
+        bus.write(0x6000, 0x77);
+        bus.write(0x0000, 0x33);
+        assert_eq!(bus.read_8(0x6000), 0x77);
+    }
+}
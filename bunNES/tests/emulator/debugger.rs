@@ -0,0 +1,322 @@
+use super::helpers::*;
+use bunNES::debugger::{AccessKind, AddressSpace, Condition, Debugger, Event};
+use bunNES::emulator::Emulator;
+
+/// counts x up, storing and reading it back on every pass
+const COUNTER: [u8; 11] = [
+    0xA2, 0x00, // $8000 ldx #$00
+    // loop
+    0xE8,       // $8002 inx
+    0x86, 0x10, // $8003 stx $10
+    0xA5, 0x10, // $8005 lda $10
+    0xA9, 0x01, // $8007 lda #$01
+    0xD0, 0xF7, // $8009 bne loop
+];
+
+/// calls a subroutine that counts y up twice, over and over
+const SUBROUTINE: [u8; 10] = [
+    0x20, 0x07, 0x80, // $8000 jsr sub
+    0xE8,             // $8003 inx
+    0x4C, 0x00, 0x80, // $8004 jmp $8000
+    // sub
+    0xC8,             // $8007 iny
+    0xC8,             // $8008 iny
+    0x60,             // $8009 rts
+];
+
+/// writes $AB to vram $2000, then idles
+const VRAM_WRITE: [u8; 17] = [
+    0xA9, 0x20, 0x8D, 0x06, 0x20, // lda #$20, sta $2006
+    0xA9, 0x00, 0x8D, 0x06, 0x20, // lda #$00, sta $2006
+    0xA9, 0xAB, 0x8D, 0x07, 0x20, // lda #$AB, sta $2007
+    0xD0, 0xFE,                   // bne to itself
+];
+
+fn debugged(code: &[u8], debugger: Debugger) -> Emulator {
+    let mut emulator = get_emulator(code.to_vec());
+    emulator.attach_debugger(debugger);
+    emulator
+}
+
+fn poll_event(emulator: &mut Emulator) -> Option<Event> {
+    emulator.debugger_mut().unwrap().poll_event()
+}
+
+#[cfg(test)]
+mod breakpoints {
+    use super::*;
+
+    #[test]
+    fn stops_before_instruction() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_breakpoint(0x8003);
+        let mut emulator = debugged(&COUNTER, debugger);
+
+        assert!(!emulator.run_frame_with(|_, _| true));
+        assert_eq!(poll_event(&mut emulator), Some(Event::Breakpoint { id, pc: 0x8003 }));
+        assert_eq!(emulator.cpu.pc, 0x8003);
+        assert_eq!(emulator.cpu.x, 1);
+        assert_eq!(emulator.cpu.bus.ram[0x10], 0);
+        assert!(emulator.debugger().unwrap().is_paused());
+
+        // nothing runs while paused
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.x, 1);
+        assert_eq!(poll_event(&mut emulator), None);
+
+        emulator.debugger_mut().unwrap().resume();
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Breakpoint { id, pc: 0x8003 }));
+        assert_eq!(emulator.cpu.x, 2);
+        assert_eq!(emulator.cpu.bus.ram[0x10], 1);
+    }
+
+    #[test]
+    fn conditional() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_conditional_breakpoint(0x8007, "[$10] >= 3 && a == 3").unwrap();
+        let mut emulator = debugged(&COUNTER, debugger);
+
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Breakpoint { id, pc: 0x8007 }));
+        assert_eq!(emulator.cpu.x, 3);
+    }
+
+    #[test]
+    fn disabled_and_removed() {
+        let mut debugger = Debugger::new();
+        let disabled = debugger.add_breakpoint(0x8003);
+        let removed = debugger.add_breakpoint(0x8005);
+        assert!(debugger.set_enabled(disabled, false));
+        assert!(debugger.remove(removed));
+        assert!(!debugger.remove(removed));
+        assert_eq!(debugger.breakpoints().count(), 1);
+
+        let mut emulator = debugged(&COUNTER, debugger);
+        assert!(emulator.run_frame_with(|_, _| true));
+        assert_eq!(poll_event(&mut emulator), None);
+    }
+
+    #[test]
+    fn detached() {
+        let mut debugger = Debugger::new();
+        debugger.add_breakpoint(0x8003);
+        let mut emulator = debugged(&COUNTER, debugger);
+        assert!(emulator.detach_debugger().is_some());
+        assert!(emulator.run_frame_with(|_, _| true));
+    }
+
+    #[test]
+    fn resumed_frames_match_undebugged_run() {
+        let mut expected = get_emulator(COUNTER.to_vec());
+        for _ in 0..3 {
+            expected.run_frame();
+        }
+
+        let mut debugger = Debugger::new();
+        debugger.add_conditional_breakpoint(0x8003, "x & 1").unwrap();
+        let mut emulator = debugged(&COUNTER, debugger);
+        let mut frames = 0;
+        while frames < 3 {
+            if emulator.run_frame_with(|_, _| true) {
+                frames += 1;
+            }
+            emulator.debugger_mut().unwrap().resume();
+        }
+        assert_eq!(emulator.save_state(), expected.save_state());
+    }
+}
+
+#[cfg(test)]
+mod watchpoints {
+    use super::*;
+
+    #[test]
+    fn cpu_write() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_watchpoint(AddressSpace::Cpu, 0x10..=0x10, AccessKind::WRITE);
+        let mut emulator = debugged(&COUNTER, debugger);
+
+        emulator.run_frame();
+        let event = Event::Watchpoint { id, space: AddressSpace::Cpu, addr: 0x10, value: 1, kind: AccessKind::WRITE };
+        assert_eq!(poll_event(&mut emulator), Some(event));
+        // right after the instruction that wrote
+        assert_eq!(emulator.cpu.pc, 0x8005);
+        assert_eq!(poll_event(&mut emulator), None);
+    }
+
+    #[test]
+    fn cpu_read() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_watchpoint(AddressSpace::Cpu, 0x00..=0xFF, AccessKind::READ);
+        let mut emulator = debugged(&COUNTER, debugger);
+
+        emulator.run_frame();
+        let event = Event::Watchpoint { id, space: AddressSpace::Cpu, addr: 0x10, value: 1, kind: AccessKind::READ };
+        assert_eq!(poll_event(&mut emulator), Some(event));
+        assert_eq!(emulator.cpu.pc, 0x8007);
+    }
+
+    #[test]
+    fn execute() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_watchpoint(AddressSpace::Cpu, 0x8005..=0x8008, AccessKind::EXECUTE);
+        let mut emulator = debugged(&COUNTER, debugger);
+
+        emulator.run_frame();
+        let event = Event::Watchpoint { id, space: AddressSpace::Cpu, addr: 0x8005, value: 0xA5, kind: AccessKind::EXECUTE };
+        assert_eq!(poll_event(&mut emulator), Some(event));
+        // the debugger reading the opcode isn't a read of the program
+        assert_eq!(poll_event(&mut emulator), None);
+    }
+
+    #[test]
+    fn ppu_write() {
+        let mut debugger = Debugger::new();
+        let id = debugger.add_watchpoint(AddressSpace::Ppu, 0x2000..=0x23FF, AccessKind::WRITE);
+        debugger.add_watchpoint(AddressSpace::Ppu, 0x2400..=0x27FF, AccessKind::WRITE);
+        let mut emulator = debugged(&VRAM_WRITE, debugger);
+
+        emulator.run_frame();
+        let event = Event::Watchpoint { id, space: AddressSpace::Ppu, addr: 0x2000, value: 0xAB, kind: AccessKind::WRITE };
+        assert_eq!(poll_event(&mut emulator), Some(event));
+        assert_eq!(poll_event(&mut emulator), None);
+    }
+
+    #[test]
+    fn added_while_attached() {
+        let mut emulator = debugged(&COUNTER, Debugger::new());
+        emulator.run_frame();
+
+        let debugger = emulator.debugger_mut().unwrap();
+        let id = debugger.add_watchpoint(AddressSpace::Cpu, 0x10..=0x10, AccessKind::READ | AccessKind::WRITE);
+        emulator.run_frame();
+        assert!(matches!(poll_event(&mut emulator), Some(Event::Watchpoint { id: hit, .. }) if hit == id));
+
+        emulator.debugger_mut().unwrap().set_enabled(id, false);
+        emulator.debugger_mut().unwrap().resume();
+        assert!(emulator.run_frame_with(|_, _| true));
+    }
+}
+
+#[cfg(test)]
+mod stepping {
+    use super::*;
+
+    fn paused(code: &[u8]) -> Emulator {
+        let mut debugger = Debugger::new();
+        debugger.pause();
+        debugged(code, debugger)
+    }
+
+    #[test]
+    fn step_into() {
+        let mut emulator = paused(&COUNTER);
+        for pc in [0x8002, 0x8003, 0x8005, 0x8007, 0x8009, 0x8002] {
+            emulator.debugger_mut().unwrap().step_into();
+            emulator.run_frame();
+            assert_eq!(poll_event(&mut emulator), Some(Event::Step { pc }));
+            assert_eq!(emulator.cpu.pc, pc);
+        }
+    }
+
+    #[test]
+    fn step_over_without_jsr() {
+        let mut emulator = paused(&COUNTER);
+        emulator.debugger_mut().unwrap().step_over();
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Step { pc: 0x8002 }));
+    }
+
+    #[test]
+    fn step_over_jsr() {
+        let mut emulator = paused(&SUBROUTINE);
+        emulator.debugger_mut().unwrap().step_over();
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Step { pc: 0x8003 }));
+        assert_eq!(emulator.cpu.y, 2);
+    }
+
+    #[test]
+    fn step_out() {
+        let mut emulator = paused(&SUBROUTINE);
+        for pc in [0x8007, 0x8008] {
+            emulator.debugger_mut().unwrap().step_into();
+            emulator.run_frame();
+            assert_eq!(poll_event(&mut emulator), Some(Event::Step { pc }));
+        }
+        emulator.debugger_mut().unwrap().step_out();
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Step { pc: 0x8003 }));
+        assert_eq!((emulator.cpu.x, emulator.cpu.y), (0, 2));
+    }
+
+    #[test]
+    fn run_to_scanline() {
+        let mut emulator = paused(&COUNTER);
+        emulator.debugger_mut().unwrap().run_to_scanline(100);
+        emulator.run_frame();
+        assert_eq!(poll_event(&mut emulator), Some(Event::Scanline { scanline: 100 }));
+        assert_eq!(emulator.cpu.bus.ppu.scanline(), 100);
+        assert_eq!(emulator.cpu.bus.ppu.dot(), 0);
+    }
+
+    #[test]
+    fn run_to_frame() {
+        let mut emulator = paused(&COUNTER);
+        emulator.debugger_mut().unwrap().run_to_frame(3);
+        for _ in 0..5 {
+            emulator.run_frame();
+        }
+        assert_eq!(poll_event(&mut emulator), Some(Event::Frame { frame: 3 }));
+        assert_eq!(emulator.cpu.bus.ppu.frame_count(), 3);
+    }
+}
+
+#[cfg(test)]
+mod conditions {
+    use super::*;
+
+    fn holds(condition: &str, emulator: &Emulator) -> bool {
+        Condition::parse(condition).unwrap().holds(&emulator.cpu)
+    }
+
+    #[test]
+    fn registers_and_memory() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        emulator.cpu.acc = 0x10;
+        emulator.cpu.x = 2;
+        emulator.cpu.bus.ram[0x0300] = 0x42;
+
+        assert!(holds("a == $10", &emulator));
+        assert!(holds("A == 0x10 && x < 3", &emulator));
+        assert!(holds("[$0300] == 66", &emulator));
+        // mirrored
+        assert!(holds("[$0B00] == $42", &emulator));
+        // anywhere on the bus
+        assert!(holds("[$8000] == $D0", &emulator));
+        assert!(holds("pc == $8000", &emulator));
+        assert!(!holds("x != 2 || y", &emulator));
+        assert!(holds("!(x > 2)", &emulator));
+    }
+
+    #[test]
+    fn precedence() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        emulator.cpu.x = 3;
+        // & binds tighter than ==
+        assert!(holds("x & 1 == 1", &emulator));
+        assert!(holds("x == 0 || x == 3 && a == 0", &emulator));
+        assert!(!holds("(x == 0 || x == 3) && a == 1", &emulator));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Condition::parse("x ==").unwrap_err().position, 4);
+        assert_eq!(Condition::parse("q == 1").unwrap_err().position, 0);
+        assert!(Condition::parse("(x == 1").is_err());
+        assert!(Condition::parse("[$10").is_err());
+        assert!(Condition::parse("x == 1 1").is_err());
+        assert!(Debugger::new().add_conditional_breakpoint(0x8000, "a ==").is_err());
+    }
+}
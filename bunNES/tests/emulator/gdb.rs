@@ -0,0 +1,192 @@
+use super::helpers::*;
+use bunNES::emulator::Emulator;
+use bunNES::gdb::{self, GdbStub};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+
+/// counts x up and stores it to $10
+const COUNTER: [u8; 11] = [
+    0xA2, 0x00, // $8000 ldx #$00
+    // loop
+    0xE8,       // $8002 inx
+    0x86, 0x10, // $8003 stx $10
+    0xA5, 0x10, // $8005 lda $10
+    0xA9, 0x01, // $8007 lda #$01
+    0xD0, 0xF7, // $8009 bne loop
+];
+
+/// the other end of the connection, talking like gdb
+struct Client {
+    stream: TcpStream,
+    no_ack: bool,
+}
+
+impl Client {
+    fn send_raw(&mut self, data: &[u8]) {
+        self.stream.write_all(data).unwrap();
+    }
+
+    fn send(&mut self, data: &str) {
+        let checksum = data.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        self.send_raw(format!("${data}#{checksum:02x}").as_bytes());
+        if !self.no_ack {
+            assert_eq!(self.read_byte(), b'+', "{data} wasn't acknowledged");
+        }
+    }
+
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn reply(&mut self) -> String {
+        while self.read_byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+        let checksum = [self.read_byte(), self.read_byte()];
+        let expected = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        assert_eq!(std::str::from_utf8(&checksum).unwrap(), format!("{expected:02x}"));
+        if !self.no_ack {
+            self.send_raw(b"+");
+        }
+        String::from_utf8(data).unwrap()
+    }
+
+    fn command(&mut self, data: &str) -> String {
+        self.send(data);
+        self.reply()
+    }
+}
+
+/// serves `emulator` to `script` running against it on another thread
+fn session<F: FnOnce(&mut Client) + Send + 'static>(emulator: &mut Emulator, script: F) {
+    let listener = gdb::listen(0).unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let client = thread::spawn(move || {
+        let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        script(&mut Client { stream, no_ack: false });
+    });
+
+    let (stream, _) = listener.accept().unwrap();
+    GdbStub::new(emulator, stream).run().unwrap();
+    client.join().unwrap();
+}
+
+#[cfg(test)]
+mod stub {
+    use super::*;
+
+    #[test]
+    fn registers() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            assert_eq!(client.command("?"), "S05");
+            // a x y p sp pc
+            assert_eq!(client.command("g"), "00000004ff0080");
+            assert_eq!(client.command("P1=7f"), "OK");
+            assert_eq!(client.command("p1"), "7f");
+            assert_eq!(client.command("G010203c0fd0280"), "OK");
+            assert_eq!(client.command("p5"), "0280");
+            assert_eq!(client.command("p6"), "E01");
+            assert_eq!(client.command("D"), "OK");
+        });
+        assert_eq!((emulator.cpu.acc, emulator.cpu.x, emulator.cpu.y), (1, 2, 3));
+        assert_eq!((emulator.cpu.ps.get_reg(), emulator.cpu.sp, emulator.cpu.pc), (0xC0, 0xFD, 0x8002));
+    }
+
+    #[test]
+    fn memory() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            assert_eq!(client.command("M0300,2:abcd"), "OK");
+            assert_eq!(client.command("m02ff,4"), "00abcd00");
+            // prg rom
+            assert_eq!(client.command("m8000,3"), "a200e8");
+            assert_eq!(client.command("M0300,2:ab"), "E01");
+            // kill isn't answered
+            client.send("k");
+        });
+        assert_eq!(emulator.cpu.bus.ram[0x300..0x302], [0xAB, 0xCD]);
+        // the stub's debugger went away with it
+        assert!(emulator.debugger().is_none());
+    }
+
+    #[test]
+    fn breakpoints_and_steps() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            assert_eq!(client.command("Z0,8003,1"), "OK");
+            assert_eq!(client.command("c"), "S05");
+            assert_eq!(client.command("p5"), "0380");
+            assert_eq!(client.command("p1"), "01");
+            assert_eq!(client.command("c"), "S05");
+            assert_eq!(client.command("p1"), "02");
+            assert_eq!(client.command("z0,8003,1"), "OK");
+
+            assert_eq!(client.command("s"), "S05");
+            assert_eq!(client.command("p5"), "0580");
+            assert_eq!(client.command("s"), "S05");
+            assert_eq!(client.command("p5"), "0780");
+            assert_eq!(client.command("D"), "OK");
+        });
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            assert_eq!(client.command("Z2,10,1"), "OK");
+            assert_eq!(client.command("c"), "T05watch:0010;");
+            assert_eq!(client.command("m10,1"), "01");
+            assert_eq!(client.command("z2,10,1"), "OK");
+
+            assert_eq!(client.command("Z3,10,1"), "OK");
+            assert_eq!(client.command("c"), "T05rwatch:0010;");
+            assert_eq!(client.command("p0"), "01");
+            assert_eq!(client.command("z3,10,1"), "OK");
+
+            // longer than the address space
+            assert_eq!(client.command("Z2,10,10000"), "E01");
+
+            assert_eq!(client.command("Z4,f,2"), "OK");
+            assert_eq!(client.command("c"), "T05awatch:0010;");
+            assert_eq!(client.command("D"), "OK");
+        });
+    }
+
+    #[test]
+    fn interrupt() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            client.send("c");
+            thread::sleep(Duration::from_millis(50));
+            client.send_raw(&[0x03]);
+            assert_eq!(client.reply(), "S02");
+            assert_eq!(client.command("D"), "OK");
+        });
+        assert!(emulator.cpu.bus.ppu.frame_count() > 0);
+    }
+
+    #[test]
+    fn no_ack_mode_and_checksums() {
+        let mut emulator = get_emulator(COUNTER.to_vec());
+        session(&mut emulator, |client| {
+            client.send_raw(b"$g#00");
+            assert_eq!(client.read_byte(), b'-');
+            assert!(client.command("qSupported:swbreak+").contains("QStartNoAckMode+"));
+            assert_eq!(client.command("vMustReplyEmpty"), "");
+            assert_eq!(client.command("QStartNoAckMode"), "OK");
+            client.no_ack = true;
+            assert_eq!(client.command("?"), "S05");
+            assert_eq!(client.command("D"), "OK");
+        });
+    }
+}
@@ -0,0 +1,110 @@
+use super::helpers::*;
+use bunNES::nes::input::controller::Buttons;
+
+/// stripes of tile 1 over the top half, rendering turns off while A is held
+const STRIPES: [u8; 127] = [
+    // palette
+    0xA9, 0x3F, 0x8D, 0x06, 0x20, // lda #$3F, sta $2006
+    0xA9, 0x00, 0x8D, 0x06, 0x20, // lda #$00, sta $2006
+    0xA9, 0x0F, 0x8D, 0x07, 0x20, // lda #$0F, sta $2007
+    0xA9, 0x30, 0x8D, 0x07, 0x20, // lda #$30, sta $2007
+    // tile 1 solid color 1
+    0xA9, 0x00, 0x8D, 0x06, 0x20, // lda #$00, sta $2006
+    0xA9, 0x10, 0x8D, 0x06, 0x20, // lda #$10, sta $2006
+    0xA9, 0xFF,                   // lda #$FF
+    0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, // sta $2007 x4
+    0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, 0x8D, 0x07, 0x20, // sta $2007 x4
+    // 512 bytes of nametable 0
+    0xA9, 0x20, 0x8D, 0x06, 0x20, // lda #$20, sta $2006
+    0xA9, 0x00, 0x8D, 0x06, 0x20, // lda #$00, sta $2006
+    0xA2, 0x00,                   // ldx #$00
+    // fill
+    0xA9, 0x01, 0x8D, 0x07, 0x20, // lda #$01, sta $2007
+    0xA9, 0x00, 0x8D, 0x07, 0x20, // lda #$00, sta $2007
+    0xE8,                         // inx
+    0xD0, 0xF3,                   // bne fill
+    // scroll to the top left, background on
+    0xA9, 0x00,                   // lda #$00
+    0x8D, 0x00, 0x20,             // sta $2000
+    0x8D, 0x05, 0x20,             // sta $2005
+    0x8D, 0x05, 0x20,             // sta $2005
+    0xA9, 0x0A, 0x8D, 0x01, 0x20, // lda #$0A, sta $2001
+    // poll
+    0xA9, 0x01, 0x8D, 0x16, 0x40, // lda #$01, sta $4016
+    0xA9, 0x00, 0x8D, 0x16, 0x40, // lda #$00, sta $4016
+    0xAD, 0x16, 0x40,             // lda $4016
+    0x29, 0x01,                   // and #$01
+    0xD0, 0x04,                   // bne pressed
+    0xA9, 0x01,                   // lda #$01
+    0xD0, 0xEB,                   // bne poll
+    // pressed
+    0xA9, 0x00, 0x8D, 0x01, 0x20, // lda #$00, sta $2001
+    0xA9, 0x01,                   // lda #$01
+    0xD0, 0xFE,                   // bne to itself
+];
+
+#[cfg(test)]
+mod hash {
+    use super::*;
+
+    #[test]
+    fn stripes() {
+        let mut emulator = get_emulator(STRIPES.to_vec());
+        assert_golden(&mut emulator, 3, &[], Golden::Hash(0x356AFD36D33F1325));
+    }
+
+    #[test]
+    fn input() {
+        let mut emulator = get_emulator(STRIPES.to_vec());
+        assert_golden(&mut emulator, 4, &[(2, Buttons::A)], Golden::Hash(0xCFE400C84DBD5325));
+    }
+}
+
+#[cfg(test)]
+mod png {
+    use super::*;
+    use bunNES::png::{self, PngError};
+
+    #[test]
+    fn stripes() {
+        let mut emulator = get_emulator(STRIPES.to_vec());
+        assert_golden(&mut emulator, 3, &[], Golden::Png("stripes.png"));
+    }
+
+    #[test]
+    fn other_encoder() {
+        // stripes.png as a 1 bit palette image with every filter type, a stored
+        // and a dynamic huffman block and a text chunk
+        let mut emulator = get_emulator(STRIPES.to_vec());
+        assert_golden(&mut emulator, 3, &[], Golden::Png("stripes_palette.png"));
+    }
+
+    #[test]
+    fn screenshot() {
+        let emulator = get_emulator(STRIPES.to_vec());
+        let png = emulator.screenshot();
+        assert_eq!(png[0..8], [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n']);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(png[16..24], [0, 0, 1, 0, 0, 0, 0, 240]);
+        assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    }
+
+    #[test]
+    fn round_trip() {
+        let rgb: Vec<u8> = (0..7 * 5 * 3).map(|i| (i * 37 % 256) as u8).collect();
+        let image = png::decode(&png::encode(7, 5, &rgb)).unwrap();
+        assert_eq!((image.width, image.height), (7, 5));
+        assert_eq!(image.rgb, rgb);
+    }
+
+    #[test]
+    fn decode_errors() {
+        let mut encoded = png::encode(2, 2, &[0; 12]);
+        assert_eq!(png::decode(&encoded[1..]), Err(PngError::Invalid("signature")));
+        assert_eq!(png::decode(&encoded[..encoded.len() - 12]), Err(PngError::Invalid("missing IEND")));
+
+        // the interlace method, without fixing the crc
+        encoded[28] = 1;
+        assert_eq!(png::decode(&encoded), Err(PngError::Invalid("chunk crc")));
+    }
+}
@@ -0,0 +1,101 @@
+use crate::ppu::helpers::{get_bus, set_addr, write_vram};
+use bunNES::nes::memory::MemorySpace;
+
+#[cfg(test)]
+mod peek {
+    use super::*;
+
+    #[test]
+    fn spaces() {
+        let mut bus = get_bus();
+        bus.ram[0x10] = 0x01;
+        bus.write(0x6001, 0x02);
+        write_vram(&mut bus.ppu, 0x0011, &[0x03]);
+        write_vram(&mut bus.ppu, 0x2001, &[0x04]);
+        write_vram(&mut bus.ppu, 0x3F02, &[0x05]);
+        bus.write(0x2003, 0x07);
+        bus.write(0x2004, 0x06);
+
+        assert_eq!(MemorySpace::Cpu.peek(&bus, 0x0810), 0x01);
+        assert_eq!(MemorySpace::Cpu.peek(&bus, 0x6001), 0x02);
+        assert_eq!(MemorySpace::PrgRam.peek(&bus, 0x0001), 0x02);
+        assert_eq!(MemorySpace::Chr.peek(&bus, 0x0011), 0x03);
+        assert_eq!(MemorySpace::Ppu.peek(&bus, 0x0011), 0x03);
+        assert_eq!(MemorySpace::Ppu.peek(&bus, 0x2001), 0x04);
+        assert_eq!(MemorySpace::Palette.peek(&bus, 0x02), 0x05);
+        assert_eq!(MemorySpace::Oam.peek(&bus, 0x07), 0x06);
+    }
+
+    #[test]
+    fn lengths() {
+        let bus = get_bus();
+        let lengths: Vec<usize> = MemorySpace::ALL.iter().map(|space| space.len(&bus)).collect();
+        assert_eq!(lengths, [0x10000, 0x4000, 256, 32, 0x2000, 0x2000]);
+    }
+
+    #[test]
+    fn range_stops_at_the_end() {
+        let mut bus = get_bus();
+        bus.ppu.poke_oam(0xFF, 0x42);
+        assert_eq!(MemorySpace::Oam.peek_range(&bus, 0xFE, 16), [0x00, 0x42]);
+    }
+
+    #[test]
+    fn no_side_effects() {
+        let mut bus = get_bus();
+        write_vram(&mut bus.ppu, 0x2000, &[0x11, 0x22]);
+        set_addr(&mut bus.ppu, 0x2000);
+        bus.read_8(0x2007);
+        for addr in 0x2000..0x2008 {
+            MemorySpace::Cpu.peek(&bus, addr);
+        }
+        assert_eq!(bus.read_8(0x2007), 0x11);
+        assert_eq!(bus.read_8(0x2007), 0x22);
+    }
+}
+
+#[cfg(test)]
+mod poke {
+    use super::*;
+
+    #[test]
+    fn cpu() {
+        let mut bus = get_bus();
+        MemorySpace::Cpu.poke(&mut bus, 0x1805, 0x01);
+        MemorySpace::Cpu.poke(&mut bus, 0x7FFF, 0x02);
+        assert_eq!(bus.ram[0x005], 0x01);
+        assert_eq!(bus.prg_ram()[0x1FFF], 0x02);
+        assert_eq!(MemorySpace::PrgRam.peek(&bus, 0x1FFF), 0x02);
+    }
+
+    #[test]
+    fn registers_and_rom_are_left_alone() {
+        let mut bus = get_bus();
+        let status = bus.peek(0x2002);
+        MemorySpace::Cpu.poke(&mut bus, 0x2000, 0x80);
+        MemorySpace::Cpu.poke(&mut bus, 0x4014, 0x02);
+        MemorySpace::Cpu.poke(&mut bus, 0x8000, 0xEA);
+        assert_eq!(bus.peek(0x2002), status);
+        assert_eq!(bus.take_dma_stall(), 0);
+        assert_eq!(bus.peek(0x8000), 0x00);
+    }
+
+    #[test]
+    fn ppu() {
+        let mut bus = get_bus();
+        set_addr(&mut bus.ppu, 0x2400);
+        MemorySpace::Ppu.poke(&mut bus, 0x2003, 0x01);
+        MemorySpace::Chr.poke(&mut bus, 0x1FFF, 0x02);
+        MemorySpace::Palette.poke(&mut bus, 0x10, 0x03);
+        MemorySpace::Oam.poke(&mut bus, 0x80, 0x04);
+
+        // horizontal mirroring
+        assert_eq!(bus.peek_ppu(0x2403), 0x01);
+        assert_eq!(bus.peek_ppu(0x1FFF), 0x02);
+        // $3F10 is $3F00
+        assert_eq!(MemorySpace::Palette.peek(&bus, 0x00), 0x03);
+        assert_eq!(bus.ppu.oam()[0x80], 0x04);
+        // the vram address didn't move
+        assert_eq!(bus.ppu.vram_addr(), 0x2400);
+    }
+}
@@ -0,0 +1,116 @@
+mod bus;
+mod debugger;
+mod gdb;
+mod golden;
+mod memory;
+mod movie;
+mod ram_search;
+mod rewind;
+mod rom;
+mod state;
+mod wav;
+
+pub(crate) mod helpers {
+    use bunNES::emulator::Emulator;
+    use bunNES::nes::input::controller::Buttons;
+    use bunNES::nes::rom::Cartridge;
+    use bunNES::png;
+    use std::path::PathBuf;
+
+    /// bne to itself, the zero flag is clear on power up
+    pub const IDLE_LOOP: [u8; 2] = [0xD0, 0xFE];
+
+    /// keeps the apu, ppu, ram and cartridge ram busy
+    pub const BUSY_LOOP: [u8; 40] = [
+        0xA9, 0x0F,       // lda #$0F
+        0x8D, 0x15, 0x40, // sta $4015
+        0xA9, 0xBF,       // lda #$BF
+        0x8D, 0x00, 0x40, // sta $4000
+        0xA9, 0x40,       // lda #$40
+        0x8D, 0x02, 0x40, // sta $4002
+        0xA9, 0x01,       // lda #$01
+        0x8D, 0x03, 0x40, // sta $4003
+        0xA9, 0x1E,       // lda #$1E
+        0x8D, 0x01, 0x20, // sta $2001
+        // loop
+        0xE6, 0x00,       // inc $00
+        0xA5, 0x00,       // lda $00
+        0x9D, 0x00, 0x60, // sta $6000,x
+        0x8D, 0x07, 0x20, // sta $2007
+        0xE8,             // inx
+        0xA9, 0x01,       // lda #$01
+        0xD0, 0xF1,       // bne loop
+    ];
+
+    /// emulator running `code` at $8000 after reset
+    pub fn get_emulator(mut code: Vec<u8>) -> Emulator {
+        code.resize(0x4000, 0);
+        // reset vector
+        code[0x3FFC] = 0x00;
+        code[0x3FFD] = 0x80;
+        let mut emulator = Emulator::new(Cartridge::test_cartride(code));
+        emulator.reset();
+        emulator
+    }
+
+    pub fn temp_path(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join("bunNES-tests");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    /// set to rewrite golden images instead of comparing against them
+    pub const UPDATE_GOLDEN_ENV: &str = "BUNNES_UPDATE_GOLDEN";
+
+    /// what the last frame is compared against
+    pub enum Golden {
+        /// png in tests/golden
+        Png(&'static str),
+        /// [`Emulator::frame_hash`]
+        Hash(u64),
+    }
+
+    /// runs `frames` frames and compares the last one. `input` holds player 1's buttons
+    /// starting at the given frame, until the next entry
+    pub fn assert_golden(emulator: &mut Emulator, frames: u32, input: &[(u32, Buttons)], golden: Golden) {
+        for frame in 0..frames {
+            if let Some((_, buttons)) = input.iter().find(|(start, _)| *start == frame) {
+                emulator.set_buttons(0, *buttons);
+            }
+            emulator.run_frame();
+        }
+
+        match golden {
+            Golden::Hash(expected) => {
+                let hash = emulator.frame_hash();
+                assert_eq!(hash, expected, "frame hash {hash:#018X} doesn't match {expected:#018X}");
+            }
+            Golden::Png(name) => {
+                let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden").join(name);
+                let screenshot = emulator.screenshot();
+                let actual = png::decode(&screenshot).unwrap();
+                let update = std::env::var_os(UPDATE_GOLDEN_ENV).is_some();
+                // pixels are compared, a golden image re-saved by another encoder or an optimizer still matches
+                let expected = match std::fs::read(&path) {
+                    Ok(bytes) => Some(png::decode(&bytes).unwrap_or_else(|e| panic!("{}: {e}", path.display()))),
+                    Err(_) if update => None,
+                    Err(e) => panic!("couldn't read {}: {e}, run with {UPDATE_GOLDEN_ENV}=1 to create it", path.display()),
+                };
+                if update {
+                    if expected.as_ref() != Some(&actual) {
+                        std::fs::write(&path, &screenshot).unwrap();
+                    }
+                    return;
+                }
+
+                let expected = expected.unwrap();
+                if actual != expected {
+                    let written = temp_path(name);
+                    std::fs::write(&written, &screenshot).unwrap();
+                    let differing = actual.rgb.chunks(3).zip(expected.rgb.chunks(3)).filter(|(a, b)| a != b).count();
+                    panic!("frame doesn't match {} in {differing} pixels, it was written to {}", path.display(), written.display());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::emulator::helpers::{get_emulator, IDLE_LOOP};
+use bunNES::emulator::Emulator;
+use bunNES::movie::{Commands, Movie, MovieError, MovieMode, MovieStart};
+use bunNES::nes::input::controller::Buttons;
+use bunNES::nes::rom::Cartridge;
+use bunNES::rewind::RewindConfig;
+
+/// strobes the controllers and logs $4016 reads to $0200
+const POLL_LOOP: [u8; 21] = [
+    0xA9, 0x01,       // lda #$01
+    0x8D, 0x16, 0x40, // sta $4016
+    0xA9, 0x00,       // lda #$00
+    0x8D, 0x16, 0x40, // sta $4016
+    0xAD, 0x16, 0x40, // lda $4016
+    0x9D, 0x00, 0x02, // sta $0200,x
+    0xE8,             // inx
+    0xA9, 0x01,       // lda #$01
+    0xD0, 0xEB,       // bne $8000
+];
+
+/// presses A on some frames and resets on frame 15
+fn play_input(emulator: &mut Emulator, frames: usize) {
+    for frame in 0..frames {
+        let buttons = if frame % 7 < 3 { Buttons::A } else { Buttons::empty() };
+        emulator.set_buttons(0, buttons);
+        if frame == 15 {
+            emulator.reset();
+        }
+        emulator.run_frame();
+    }
+}
+
+const FCEUX_MOVIE: &str = "version 3
+emuVersion 22020
+rerecordCount 7
+palFlag 0
+romFilename smb
+romChecksum base64:kAFQmDzST7DWlj99KOF/cg==
+guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B
+fourscore 0
+microphone 0
+port0 1
+port1 1
+port2 0
+FDS 0
+NewPPU 0
+comment author someone
+|1|........|........||
+|0|.......A|........||
+|0|RLDUTSBA|...U....||
+";
+
+#[cfg(test)]
+mod fm2 {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let movie = Movie::parse(FCEUX_MOVIE).unwrap();
+        assert_eq!(movie.rom_filename, "smb");
+        assert_eq!(movie.rerecord_count, 7);
+        assert_eq!(movie.gamepads, [true, true]);
+        assert!(!movie.four_score);
+        assert_eq!(movie.comments, vec!["author someone".to_string()]);
+        assert_eq!(movie.frames.len(), 3);
+        assert_eq!(movie.frames[0].commands, Commands::SOFT_RESET);
+        assert_eq!(movie.frames[1].buttons[0], Buttons::A);
+        assert_eq!(movie.frames[2].buttons[0], Buttons::all());
+        assert_eq!(movie.frames[2].buttons[1], Buttons::UP);
+    }
+
+    #[test]
+    fn checksum_is_md5_of_rom() {
+        let movie = Movie::new(&Cartridge::test_cartride(b"abc".to_vec()), "abc");
+        assert_eq!(movie.rom_checksum, Movie::parse(FCEUX_MOVIE).unwrap().rom_checksum);
+        assert!(movie.to_fm2().contains("romChecksum base64:kAFQmDzST7DWlj99KOF/cg==\n"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let movie = Movie::parse(FCEUX_MOVIE).unwrap();
+        assert_eq!(Movie::parse(&movie.to_fm2()).unwrap(), movie);
+        assert!(movie.to_fm2().ends_with("|0|RLDUTSBA|...U....||\n"));
+    }
+
+    #[test]
+    fn four_score() {
+        let text = FCEUX_MOVIE.replace("fourscore 0", "fourscore 1")
+            .replace("|0|.......A|........||", "|0|.......A|........|......B.|.......A|");
+        let text = text.lines().filter(|line| !line.starts_with("|1|") && !line.starts_with("|0|R")).collect::<Vec<_>>().join("\n");
+        let movie = Movie::parse(&text).unwrap();
+        assert_eq!(movie.frames[0].buttons, [Buttons::A, Buttons::empty(), Buttons::B, Buttons::A]);
+        assert_eq!(Movie::parse(&movie.to_fm2()).unwrap(), movie);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(Movie::parse("|0|........|........||"), Err(MovieError::Parse { line: 1, .. })));
+        assert!(matches!(Movie::parse(&FCEUX_MOVIE.replace("version 3", "version 2")), Err(MovieError::Unsupported(_))));
+        assert!(matches!(Movie::parse(&FCEUX_MOVIE.replace("port0 1", "port0 2")), Err(MovieError::Unsupported(_))));
+        assert!(matches!(Movie::parse(&FCEUX_MOVIE.replace(".......A", "..A")), Err(MovieError::Parse { line: 17, .. })));
+    }
+}
+
+#[cfg(test)]
+mod playback {
+    use super::*;
+
+    fn recorded(start: MovieStart, frames: usize) -> (Emulator, Movie) {
+        let mut emulator = get_emulator(POLL_LOOP.to_vec());
+        emulator.run_frame();
+        emulator.record_movie(start, "poll");
+        play_input(&mut emulator, frames);
+        let movie = emulator.stop_movie().unwrap();
+        (emulator, movie)
+    }
+
+    #[test]
+    fn from_power_on() {
+        let (recorder, movie) = recorded(MovieStart::PowerOn, 30);
+        assert_eq!(movie.frames.len(), 30);
+        assert_eq!(movie.frames[15].commands, Commands::SOFT_RESET);
+
+        let mut emulator = get_emulator(POLL_LOOP.to_vec());
+        play_input(&mut emulator, 4);
+        emulator.play_movie(Movie::parse(&movie.to_fm2()).unwrap()).unwrap();
+        for _ in 0..30 {
+            // the movie has the say
+            emulator.set_buttons(0, Buttons::all());
+            emulator.run_frame();
+        }
+        assert_eq!(emulator.movie_mode(), Some(MovieMode::Playing));
+        assert_eq!(emulator.save_state(), recorder.save_state());
+        assert_eq!(&emulator.cpu.bus.ram[0x200..0x300], &recorder.cpu.bus.ram[0x200..0x300]);
+
+        emulator.run_frame();
+        assert_eq!(emulator.movie_mode(), Some(MovieMode::Finished));
+    }
+
+    #[test]
+    fn from_save_state() {
+        let (recorder, movie) = recorded(MovieStart::SaveState, 20);
+        assert!(movie.save_state.is_some());
+        assert!(movie.to_fm2().contains("\nsavestate base64:"));
+
+        let mut emulator = get_emulator(POLL_LOOP.to_vec());
+        emulator.play_movie(Movie::parse(&movie.to_fm2()).unwrap()).unwrap();
+        for _ in 0..20 {
+            emulator.run_frame();
+        }
+        assert_eq!(emulator.save_state(), recorder.save_state());
+    }
+
+    #[test]
+    fn wrong_rom() {
+        let (_, movie) = recorded(MovieStart::PowerOn, 1);
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        assert!(matches!(emulator.play_movie(movie), Err(MovieError::WrongRom)));
+        assert_eq!(emulator.movie_mode(), None);
+    }
+
+    #[test]
+    fn rewind_rerecords() {
+        let mut emulator = get_emulator(POLL_LOOP.to_vec());
+        emulator.enable_rewind(RewindConfig::default());
+        emulator.record_movie(MovieStart::PowerOn, "poll");
+        play_input(&mut emulator, 10);
+        assert_eq!(emulator.rewind(3), 3);
+
+        let movie = emulator.movie().unwrap();
+        assert_eq!(movie.frames.len(), 7);
+        assert_eq!(movie.rerecord_count, 1);
+    }
+}
@@ -0,0 +1,180 @@
+use super::helpers::*;
+use crate::ppu::helpers::get_bus;
+use bunNES::nes::bus::Bus;
+use bunNES::ram_search::{Cheat, Comparison, Operand, RamSearch, SearchResult, Size};
+
+fn addrs(search: &RamSearch) -> Vec<u16> {
+    search.candidates().to_vec()
+}
+
+/// the game counting lives down from 3 at $0042 and a timer up at $6010
+fn lives_and_timer(bus: &mut Bus) {
+    bus.ram[0x42] = 3;
+    bus.prg_ram_mut()[0x10] = 100;
+}
+
+#[cfg(test)]
+mod search {
+    use super::*;
+
+    #[test]
+    fn every_byte_of_ram() {
+        let bus = get_bus();
+        let search = RamSearch::new(&bus);
+        assert_eq!(search.candidates().len(), 0x0800 + 0x2000);
+        assert_eq!(search.candidates()[0x07FF], 0x07FF);
+        assert_eq!(search.candidates()[0x0800], 0x6000);
+    }
+
+    #[test]
+    fn previous() {
+        let mut bus = get_bus();
+        lives_and_timer(&mut bus);
+        let mut search = RamSearch::new(&bus);
+
+        bus.ram[0x42] = 2;
+        bus.prg_ram_mut()[0x10] = 101;
+        search.search(&bus, Comparison::Less, Operand::Previous);
+        assert_eq!(addrs(&search), [0x0042]);
+
+        bus.ram[0x42] = 1;
+        search.search(&bus, Comparison::ChangedBy(-1), Operand::Previous);
+        assert_eq!(addrs(&search), [0x0042]);
+        search.search(&bus, Comparison::NotEqual, Operand::Previous);
+        assert!(search.candidates().is_empty());
+    }
+
+    #[test]
+    fn values() {
+        let mut bus = get_bus();
+        lives_and_timer(&mut bus);
+        let mut search = RamSearch::new(&bus);
+
+        search.search(&bus, Comparison::Greater, Operand::Value(2));
+        assert_eq!(addrs(&search), [0x0042, 0x6010]);
+        search.search(&bus, Comparison::Equal, Operand::Value(100));
+        assert_eq!(addrs(&search), [0x6010]);
+
+        bus.prg_ram_mut()[0x10] = 104;
+        search.search(&bus, Comparison::ChangedBy(4), Operand::Previous);
+        assert_eq!(search.results(&bus), [SearchResult { addr: 0x6010, value: 104, previous: 104 }]);
+    }
+
+    #[test]
+    fn results() {
+        let mut bus = get_bus();
+        lives_and_timer(&mut bus);
+        let mut search = RamSearch::new(&bus);
+        search.search(&bus, Comparison::Equal, Operand::Value(3));
+        bus.ram[0x42] = 9;
+        assert_eq!(search.results(&bus), [SearchResult { addr: 0x0042, value: 9, previous: 3 }]);
+    }
+
+    #[test]
+    fn signed() {
+        let mut bus = get_bus();
+        bus.ram[0x10] = 0xFF;
+        let mut search = RamSearch::new(&bus);
+        search.signed = true;
+        search.search(&bus, Comparison::Equal, Operand::Value(-1));
+        assert_eq!(addrs(&search), [0x0010]);
+
+        search.signed = false;
+        search.search(&bus, Comparison::Equal, Operand::Value(255));
+        assert_eq!(addrs(&search), [0x0010]);
+    }
+
+    #[test]
+    fn words() {
+        let mut bus = get_bus();
+        bus.ram[0x20] = 0x34;
+        bus.ram[0x21] = 0x12;
+        bus.ram[0x7FF] = 0x34;
+        let mut search = RamSearch::new(&bus);
+        search.size = Size::Word;
+        search.search(&bus, Comparison::Equal, Operand::Value(0x1234));
+        assert_eq!(addrs(&search), [0x0020]);
+
+        bus.ram[0x20] = 0x00;
+        bus.ram[0x21] = 0x80;
+        search.signed = true;
+        search.search(&bus, Comparison::Less, Operand::Previous);
+        assert_eq!(search.results(&bus)[0].value, -0x8000);
+    }
+
+    #[test]
+    fn words_stay_in_their_ram() {
+        let bus = get_bus();
+        let mut search = RamSearch::new(&bus);
+        search.size = Size::Word;
+        search.search(&bus, Comparison::Equal, Operand::Previous);
+        assert!(!search.candidates().contains(&0x07FF));
+        assert!(!search.candidates().contains(&0x7FFF));
+        assert!(search.candidates().contains(&0x7FFE));
+    }
+
+    #[test]
+    fn reset() {
+        let mut bus = get_bus();
+        let mut search = RamSearch::new(&bus);
+        search.search(&bus, Comparison::NotEqual, Operand::Previous);
+        assert!(search.candidates().is_empty());
+        bus.ram[0] = 1;
+        search.reset(&bus);
+        assert_eq!(search.results(&bus)[0], SearchResult { addr: 0x0000, value: 1, previous: 1 });
+    }
+}
+
+#[cfg(test)]
+mod cheats {
+    use super::*;
+
+    /// copies $00 to $01 over and over
+    const COPY_LOOP: [u8; 7] = [
+        0xA5, 0x00,       // lda $00
+        0x85, 0x01,       // sta $01
+        0x4C, 0x00, 0x80, // jmp $8000
+    ];
+
+    #[test]
+    fn from_results() {
+        let bus = get_bus();
+        let mut search = RamSearch::new(&bus);
+        assert_eq!(search.cheats(0x0042, 9), [Cheat { addr: 0x0042, value: 9 }]);
+        search.size = Size::Word;
+        assert_eq!(search.cheats(0x6010, -2), [
+            Cheat { addr: 0x6010, value: 0xFE },
+            Cheat { addr: 0x6011, value: 0xFF },
+        ]);
+    }
+
+    #[test]
+    fn held_every_frame() {
+        let mut emulator = get_emulator(COPY_LOOP.to_vec());
+        emulator.add_cheat(Cheat { addr: 0x0000, value: 0x63 });
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0x01], 0x63);
+
+        emulator.cpu.bus.ram[0x00] = 0x10;
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0x01], 0x63);
+    }
+
+    #[test]
+    fn replaced_and_removed() {
+        let mut emulator = get_emulator(COPY_LOOP.to_vec());
+        emulator.add_cheat(Cheat { addr: 0x0000, value: 0x01 });
+        emulator.add_cheat(Cheat { addr: 0x0000, value: 0x02 });
+        emulator.add_cheat(Cheat { addr: 0x6000, value: 0x03 });
+        assert_eq!(emulator.cheats().len(), 2);
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0x01], 0x02);
+        assert_eq!(emulator.cpu.bus.prg_ram()[0], 0x03);
+
+        assert!(emulator.remove_cheat(0x0000));
+        assert!(!emulator.remove_cheat(0x0000));
+        emulator.cpu.bus.ram[0x00] = 0x10;
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0x01], 0x10);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::emulator::helpers::{get_emulator, BUSY_LOOP};
+use bunNES::emulator::Emulator;
+use bunNES::rewind::{RewindBuffer, RewindConfig};
+
+/// emulator with rewind enabled and the save state after each of `frames` frames
+fn recorded(frames: usize) -> (Emulator, Vec<Vec<u8>>) {
+    let mut emulator = get_emulator(BUSY_LOOP.to_vec());
+    emulator.enable_rewind(RewindConfig { keyframe_interval: 4, ..RewindConfig::default() });
+    let mut states = Vec::new();
+    for _ in 0..frames {
+        emulator.run_frame();
+        states.push(emulator.save_state());
+    }
+    (emulator, states)
+}
+
+fn config(keyframe_interval: u32, memory_budget: usize) -> RewindConfig {
+    RewindConfig { interval: 1, keyframe_interval, memory_budget }
+}
+
+#[cfg(test)]
+mod emulator {
+    use super::*;
+
+    #[test]
+    fn restores_earlier_frame() {
+        let (mut emulator, states) = recorded(10);
+        assert_eq!(emulator.rewind(3), 3);
+        assert_eq!(emulator.save_state(), states[6]);
+        // from a keyframe
+        assert_eq!(emulator.rewind(3), 3);
+        assert_eq!(emulator.save_state(), states[3]);
+    }
+
+    #[test]
+    fn continues_after_rewind() {
+        let (mut emulator, states) = recorded(10);
+        emulator.rewind(5);
+        for state in &states[5..] {
+            emulator.run_frame();
+            assert_eq!(&emulator.save_state(), state);
+        }
+    }
+
+    #[test]
+    fn stops_at_oldest_snapshot() {
+        let (mut emulator, states) = recorded(5);
+        assert_eq!(emulator.rewind(100), 4);
+        assert_eq!(emulator.save_state(), states[0]);
+        assert_eq!(emulator.rewind(1), 0);
+    }
+
+    #[test]
+    fn interval() {
+        let mut emulator = get_emulator(BUSY_LOOP.to_vec());
+        emulator.enable_rewind(RewindConfig { interval: 3, ..RewindConfig::default() });
+        for _ in 0..10 {
+            emulator.run_frame();
+        }
+        assert_eq!(emulator.rewind_buffer().unwrap().len(), 4);
+        // the closest snapshot is 2 frames further back
+        assert_eq!(emulator.rewind(2), 3);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut emulator = get_emulator(BUSY_LOOP.to_vec());
+        emulator.run_frame();
+        assert!(emulator.rewind_buffer().is_none());
+        assert_eq!(emulator.rewind(1), 0);
+    }
+
+    #[test]
+    fn load_state_clears_history() {
+        let (mut emulator, states) = recorded(3);
+        emulator.load_state(&states[0]).unwrap();
+        assert!(emulator.rewind_buffer().unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod buffer {
+    use super::*;
+
+    fn state(frame: u8) -> Vec<u8> {
+        let mut state = vec![0; 1000];
+        state[10] = frame;
+        state[500] = 0xFF;
+        state
+    }
+
+    #[test]
+    fn deltas_are_small() {
+        let mut buffer = RewindBuffer::new(config(10, usize::MAX));
+        buffer.capture(0, &state(0));
+        let keyframe = buffer.memory_usage();
+        buffer.capture(1, &state(1));
+        assert!(keyframe < 100);
+        assert!(buffer.memory_usage() - keyframe < 10);
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buffer = RewindBuffer::new(config(3, usize::MAX));
+        for frame in 0..8 {
+            buffer.capture(frame, &state(frame as u8));
+        }
+        for frame in (0..8).rev() {
+            assert_eq!(buffer.restore(frame), Some((frame, state(frame as u8))));
+        }
+    }
+
+    #[test]
+    fn restore_discards_newer() {
+        let mut buffer = RewindBuffer::new(config(3, usize::MAX));
+        for frame in 0..8 {
+            buffer.capture(frame, &state(frame as u8));
+        }
+        buffer.restore(4);
+        assert_eq!(buffer.len(), 5);
+        buffer.capture(5, &state(50));
+        assert_eq!(buffer.restore(5), Some((5, state(50))));
+    }
+
+    #[test]
+    fn memory_budget_drops_oldest_keyframes() {
+        let mut buffer = RewindBuffer::new(config(4, 1));
+        for frame in 0..10 {
+            buffer.capture(frame, &state(frame as u8));
+        }
+        // only the newest keyframe and its deltas are left
+        assert_eq!(buffer.oldest_frame(), Some(8));
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.restore(0), Some((8, state(8))));
+    }
+
+    #[test]
+    fn empty() {
+        let mut buffer = RewindBuffer::new(RewindConfig::default());
+        assert!(buffer.wants(0));
+        assert_eq!(buffer.restore(0), None);
+    }
+}
@@ -0,0 +1,33 @@
+use bunNES::nes::rom::{Cartridge, Region};
+
+/// nrom with byte 9 and 12 of the header, NES 2.0 or iNES
+fn cartridge(nes_2: bool, flags9: u8, flags12: u8) -> Cartridge {
+    let flags7 = if nes_2 { 0b0000_1000 } else { 0 };
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 1, 0, 0, flags7, 0, flags9, 0, 0, flags12, 0, 0, 0];
+    rom.resize(16 + 0x4000, 0);
+    Cartridge::new(rom)
+}
+
+#[cfg(test)]
+mod region {
+    use super::*;
+
+    #[test]
+    fn ines() {
+        assert_eq!(cartridge(false, 0, 0).region(), Region::Ntsc);
+        assert_eq!(cartridge(false, 1, 0).region(), Region::Pal);
+        // byte 12 is unused padding in iNES
+        assert_eq!(cartridge(false, 0, 3).region(), Region::Ntsc);
+    }
+
+    #[test]
+    fn nes_2() {
+        assert_eq!(cartridge(true, 0, 0).region(), Region::Ntsc);
+        assert_eq!(cartridge(true, 0, 1).region(), Region::Pal);
+        // multi-region
+        assert_eq!(cartridge(true, 0, 2).region(), Region::Ntsc);
+        assert_eq!(cartridge(true, 0, 3).region(), Region::Dendy);
+        // byte 9 holds the rom size msbs in NES 2.0
+        assert_eq!(cartridge(true, 1, 0).region(), Region::Ntsc);
+    }
+}
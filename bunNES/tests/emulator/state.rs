@@ -0,0 +1,149 @@
+use crate::emulator::helpers::{get_emulator, BUSY_LOOP, IDLE_LOOP};
+use bunNES::emulator::Emulator;
+use bunNES::nes::rom::Cartridge;
+use bunNES::state::{StateError, VERSION};
+
+/// emulator stopped somewhere in the middle of its second frame
+fn mid_frame() -> Emulator {
+    let mut emulator = get_emulator(BUSY_LOOP.to_vec());
+    emulator.run_frame();
+    for _ in 0..12345 {
+        emulator.cpu.tick();
+    }
+    emulator
+}
+
+#[cfg(test)]
+mod save_load {
+    use super::*;
+
+    #[test]
+    fn continues_identically() {
+        let mut emulator = mid_frame();
+        let state = emulator.save_state();
+
+        emulator.run_frame();
+        emulator.run_frame();
+        let expected = emulator.save_state();
+        let expected_frame = emulator.frame_buffer().to_vec();
+
+        emulator.load_state(&state).unwrap();
+        assert_eq!(emulator.save_state(), state);
+
+        emulator.run_frame();
+        emulator.run_frame();
+        assert_eq!(emulator.save_state(), expected);
+        assert_eq!(emulator.frame_buffer(), &expected_frame[..]);
+    }
+
+    #[test]
+    fn into_fresh_emulator() {
+        let mut emulator = mid_frame();
+        let state = emulator.save_state();
+        emulator.run_frame();
+
+        let mut other = get_emulator(BUSY_LOOP.to_vec());
+        other.load_state(&state).unwrap();
+        other.run_frame();
+
+        assert_eq!(other.cpu.pc, emulator.cpu.pc);
+        assert_eq!(other.cpu.cycles_to_finish, emulator.cpu.cycles_to_finish);
+        assert_eq!(other.cpu.bus.ram, emulator.cpu.bus.ram);
+        assert_eq!(other.save_state(), emulator.save_state());
+    }
+
+    #[test]
+    fn stopped_frame_finishes_where_it_would_have() {
+        let mut emulator = get_emulator(BUSY_LOOP.to_vec());
+        let mut ticks = 0;
+        assert!(!emulator.run_frame_with(|_, _| { ticks += 1; ticks < 12345 }));
+        let state = emulator.save_state();
+
+        emulator.run_frame();
+        let expected = emulator.cpu.bus.ppu.cycle_count();
+
+        emulator.load_state(&state).unwrap();
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ppu.cycle_count(), expected);
+    }
+
+    #[test]
+    fn cartridge_ram() {
+        let mut emulator = mid_frame();
+        let state = emulator.save_state();
+        let value = emulator.cpu.bus.read_8(0x6000);
+
+        emulator.cpu.bus.write(0x6000, value.wrapping_add(1));
+        emulator.load_state(&state).unwrap();
+        assert_eq!(emulator.cpu.bus.read_8(0x6000), value);
+    }
+}
+
+#[cfg(test)]
+mod errors {
+    use super::*;
+
+    #[test]
+    fn invalid_magic() {
+        let mut emulator = mid_frame();
+        let mut state = emulator.save_state();
+        state[0] = b'X';
+        assert_eq!(emulator.load_state(&state), Err(StateError::InvalidMagic));
+        assert_eq!(emulator.load_state(&[]), Err(StateError::InvalidMagic));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut emulator = mid_frame();
+        let mut state = emulator.save_state();
+        state[4..6].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        assert_eq!(emulator.load_state(&state), Err(StateError::UnsupportedVersion(VERSION + 1)));
+    }
+
+    #[test]
+    fn wrong_cartridge() {
+        let state = mid_frame().save_state();
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        assert_eq!(emulator.load_state(&state), Err(StateError::WrongCartridge));
+    }
+
+    #[test]
+    fn truncated_rolls_back() {
+        let mut emulator = mid_frame();
+        let state = emulator.save_state();
+        emulator.run_frame();
+        let before = emulator.save_state();
+
+        assert_eq!(emulator.load_state(&state[..state.len() - 1]), Err(StateError::UnexpectedEof));
+        assert_eq!(emulator.save_state(), before);
+    }
+
+    #[test]
+    fn trailing_data() {
+        let mut emulator = mid_frame();
+        let mut state = emulator.save_state();
+        state.push(0);
+        assert_eq!(emulator.load_state(&state), Err(StateError::Mismatch("state size")));
+    }
+
+    #[test]
+    fn different_input_device() {
+        let mut emulator = mid_frame();
+        let state = emulator.save_state();
+        emulator.input().set_device(bunNES::nes::input::Port::Two, Box::new(bunNES::nes::input::zapper::Zapper::new()));
+        assert_eq!(emulator.load_state(&state), Err(StateError::Mismatch("input device")));
+    }
+}
+
+#[cfg(test)]
+mod cartridge {
+    use super::*;
+
+    #[test]
+    fn hash_covers_prg() {
+        let a = Cartridge::test_cartride(vec![1, 2, 3]);
+        let b = Cartridge::test_cartride(vec![1, 2, 4]);
+        assert_ne!(a.hash(), b.hash());
+        assert_eq!(a.hash(), Cartridge::test_cartride(vec![1, 2, 3]).hash());
+    }
+}
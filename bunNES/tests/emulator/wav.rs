@@ -0,0 +1,72 @@
+use crate::emulator::helpers::{get_emulator, temp_path, IDLE_LOOP};
+use bunNES::wav::WavWriter;
+use std::io::Cursor;
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod writer {
+    use super::*;
+
+    #[test]
+    fn header() {
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), 44100).unwrap();
+        writer.write_samples(&[0, 1, -1]).unwrap();
+        let bytes = writer.finish().unwrap().into_inner();
+
+        assert_eq!(bytes.len(), 44 + 6);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(&bytes, 4), 36 + 6);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        // mono
+        assert_eq!(u16::from_le_bytes([bytes[22], bytes[23]]), 1);
+        assert_eq!(u32_at(&bytes, 24), 44100);
+        assert_eq!(u32_at(&bytes, 28), 44100 * 2);
+        assert_eq!(&bytes[36..40], b"data");
+        assert_eq!(u32_at(&bytes, 40), 6);
+        assert_eq!(&bytes[44..], &[0, 0, 1, 0, 0xFF, 0xFF]);
+    }
+}
+
+#[cfg(test)]
+mod recording {
+    use super::*;
+
+    #[test]
+    fn record_mixed() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        let path = temp_path("record_mixed.wav");
+        emulator.record_audio(&path, 60, false).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        let samples = u32_at(&bytes, 40) as i64 / 2;
+        // one second of audio, frames are a bit shorter than 1/60s
+        assert!((samples - 44100).abs() < 500, "{samples}");
+        assert_eq!(bytes.len() as i64, 44 + samples * 2);
+    }
+
+    #[test]
+    fn record_channels() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        emulator.cpu.bus.write(0x4015, 0b0000_0001);
+        emulator.cpu.bus.write(0x4000, 0b1011_1111);
+        emulator.cpu.bus.write(0x4002, 0xFD);
+        emulator.cpu.bus.write(0x4003, 0b0000_1000);
+
+        let path = temp_path("record_channels.wav");
+        emulator.record_audio(&path, 10, true).unwrap();
+
+        let mixed = std::fs::read(&path).unwrap();
+        let pulse_1 = std::fs::read(temp_path("record_channels.pulse_1.wav")).unwrap();
+        let noise = std::fs::read(temp_path("record_channels.noise.wav")).unwrap();
+        assert_eq!(mixed.len(), pulse_1.len());
+        assert_eq!(mixed.len(), noise.len());
+
+        assert!(mixed[44..].iter().any(|byte| *byte != 0));
+        assert!(pulse_1[44..].iter().any(|byte| *byte != 0));
+        // length counter of the noise channel is zero
+        assert!(noise[44..].iter().all(|byte| *byte == 0));
+    }
+}
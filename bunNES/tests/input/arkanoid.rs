@@ -0,0 +1,32 @@
+use bunNES::nes::input::arkanoid::ArkanoidPaddle;
+use crate::input::helpers::poll_all;
+
+#[cfg(test)]
+mod paddle {
+    use super::*;
+
+    #[test]
+    fn position_inverted_msb_first() {
+        let mut paddle = ArkanoidPaddle::new();
+        paddle.set_position(0b1010_0000);
+        let reads = poll_all(&mut paddle, 0x4017, 8);
+        let data: Vec<u8> = reads.iter().map(|value| (value >> 3) & 1).collect();
+        assert_eq!(data, [0, 1, 0, 1, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn button() {
+        let mut paddle = ArkanoidPaddle::new();
+        paddle.set_button(true);
+        assert_eq!(poll_all(&mut paddle, 0x4017, 1)[0] & 0b1_0000, 0b1_0000);
+    }
+
+    #[test]
+    fn famicom() {
+        let mut paddle = ArkanoidPaddle::famicom();
+        paddle.set_position(0x7F);
+        paddle.set_button(true);
+        assert_eq!(poll_all(&mut paddle, 0x4016, 1), [0b10]);
+        assert_eq!(poll_all(&mut paddle, 0x4017, 2), [0b10, 0]);
+    }
+}
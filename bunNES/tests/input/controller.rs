@@ -0,0 +1,122 @@
+use crate::apu::helpers::get_bus;
+use crate::emulator::helpers::{get_emulator, IDLE_LOOP};
+use bunNES::nes::input::controller::{Buttons, Controller};
+
+fn read_all(controller: &mut Controller) -> Vec<u8> {
+    (0..8).map(|_| controller.read()).collect()
+}
+
+#[cfg(test)]
+mod shift_register {
+    use super::*;
+
+    #[test]
+    fn shift_order() {
+        let mut controller = Controller::new();
+        controller.set_buttons(Buttons::A | Buttons::START | Buttons::RIGHT);
+        controller.write(true);
+        controller.write(false);
+        assert_eq!(read_all(&mut controller), vec![1, 0, 0, 1, 0, 0, 0, 1]);
+    }
+
+    #[test]
+    fn after_eight_reads() {
+        let mut controller = Controller::new();
+        controller.write(true);
+        controller.write(false);
+        read_all(&mut controller);
+        assert_eq!(controller.read(), 1);
+        assert_eq!(controller.read(), 1);
+    }
+
+    #[test]
+    fn strobe_high_returns_a() {
+        let mut controller = Controller::new();
+        controller.set_buttons(Buttons::A | Buttons::B);
+        controller.write(true);
+        assert_eq!(controller.read(), 1);
+        assert_eq!(controller.read(), 1);
+        controller.set_buttons(Buttons::B);
+        assert_eq!(controller.read(), 0);
+    }
+
+    #[test]
+    fn latched_on_strobe() {
+        let mut controller = Controller::new();
+        controller.set_buttons(Buttons::UP);
+        controller.write(true);
+        controller.write(false);
+        // changes after the strobe aren't visible until the next one
+        controller.set_buttons(Buttons::DOWN);
+        assert_eq!(read_all(&mut controller), vec![0, 0, 0, 0, 1, 0, 0, 0]);
+    }
+}
+
+#[cfg(test)]
+mod bus {
+    use super::*;
+
+    #[test]
+    fn ports() {
+        let mut bus = get_bus();
+        bus.input.set_buttons(0, Buttons::A);
+        bus.input.set_buttons(1, Buttons::B);
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+
+        assert_eq!(bus.read_8(0x4016) & 1, 1);
+        assert_eq!(bus.read_8(0x4016) & 1, 0);
+        assert_eq!(bus.read_8(0x4017) & 1, 0);
+        assert_eq!(bus.read_8(0x4017) & 1, 1);
+    }
+
+    #[test]
+    fn open_bus() {
+        // the high byte of the operand is the last thing on the bus
+        let mut emulator = get_emulator(vec![
+            0xAD, 0x16, 0x40, // lda $4016
+            0x85, 0x00,       // sta $00
+            0xAD, 0x17, 0x40, // lda $4017
+            0x85, 0x01,       // sta $01
+            0xD0, 0xFE,       // bne to itself
+        ]);
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.bus.ram[0] & 0b1110_0000, 0x40);
+        assert_eq!(emulator.cpu.bus.ram[1] & 0b1110_0000, 0x40);
+    }
+
+    #[test]
+    fn writing_4017_does_not_strobe() {
+        let mut bus = get_bus();
+        bus.input.set_buttons(1, Buttons::A);
+        bus.write(0x4017, 1);
+        bus.write(0x4017, 0);
+        // still shifting out the empty power up state
+        assert_eq!(bus.read_8(0x4017) & 1, 0);
+    }
+}
+
+#[cfg(test)]
+mod emulator {
+    use super::*;
+
+    #[test]
+    fn set_buttons() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        emulator.set_buttons(1, Buttons::SELECT);
+        emulator.cpu.bus.write(0x4016, 1);
+        emulator.cpu.bus.write(0x4016, 0);
+        let bits: Vec<u8> = (0..8).map(|_| emulator.cpu.bus.read_8(0x4017) & 1).collect();
+        assert_eq!(bits, vec![0, 0, 1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn set_buttons_past_the_last_player() {
+        let mut emulator = get_emulator(IDLE_LOOP.to_vec());
+        emulator.set_buttons(4, Buttons::SELECT);
+        emulator.cpu.bus.write(0x4016, 1);
+        emulator.cpu.bus.write(0x4016, 0);
+        let bits: Vec<u8> = (0..8).map(|_| emulator.cpu.bus.read_8(0x4016) & 1).collect();
+        assert_eq!(bits, vec![0; 8]);
+    }
+}
@@ -0,0 +1,52 @@
+use bunNES::nes::input::controller::Buttons;
+use bunNES::nes::input::four_score::{FourPlayerAdapter, FourScore};
+use bunNES::nes::input::Port;
+use crate::input::helpers::poll_all;
+
+fn bits(byte: u8) -> Vec<u8> {
+    (0..8).map(|i| (byte >> i) & 1).collect()
+}
+
+#[cfg(test)]
+mod nes_four_score {
+    use super::*;
+
+    #[test]
+    fn port_one() {
+        let mut four_score = FourScore::new(Port::One);
+        four_score.set_buttons(0, Buttons::A);
+        four_score.set_buttons(1, Buttons::START);
+        let reads = poll_all(&mut four_score, 0x4016, 24);
+        assert_eq!(reads[0..8], bits(Buttons::A.bits()));
+        assert_eq!(reads[8..16], bits(Buttons::START.bits()));
+        assert_eq!(reads[16..24], [0, 0, 0, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn port_two_signature() {
+        let mut four_score = FourScore::new(Port::Two);
+        let reads = poll_all(&mut four_score, 0x4017, 24);
+        assert_eq!(reads[16..24], [0, 0, 1, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn ones_after_signature() {
+        let mut four_score = FourScore::new(Port::One);
+        let reads = poll_all(&mut four_score, 0x4016, 26);
+        assert_eq!(reads[24..], [1, 1]);
+    }
+}
+
+#[cfg(test)]
+mod four_player_adapter {
+    use super::*;
+
+    #[test]
+    fn players_on_d1() {
+        let mut adapter = FourPlayerAdapter::new();
+        adapter.set_buttons(0, Buttons::B);
+        adapter.set_buttons(1, Buttons::A);
+        assert_eq!(poll_all(&mut adapter, 0x4016, 2), [0, 0b10]);
+        assert_eq!(poll_all(&mut adapter, 0x4017, 2), [0b10, 0]);
+    }
+}
@@ -0,0 +1,47 @@
+use bunNES::nes::input::keyboard::FamilyKeyboard;
+use bunNES::nes::input::InputDevice;
+use crate::input::helpers::{blank_frame, screen};
+
+/// reads every row and column after a reset
+fn scan(keyboard: &mut FamilyKeyboard) -> Vec<u8> {
+    let frame = blank_frame();
+    let mut reads = Vec::new();
+    keyboard.strobe(0b101);
+    for _ in 0..9 {
+        keyboard.strobe(0b100);
+        reads.push(keyboard.poll(0x4017, &screen(&frame, 0)));
+        keyboard.strobe(0b110);
+        reads.push(keyboard.poll(0x4017, &screen(&frame, 0)));
+    }
+    reads
+}
+
+#[cfg(test)]
+mod family_keyboard {
+    use super::*;
+
+    #[test]
+    fn nothing_pressed() {
+        let mut keyboard = FamilyKeyboard::new();
+        assert!(scan(&mut keyboard).iter().all(|value| *value == 0b1_1110));
+    }
+
+    #[test]
+    fn matrix() {
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.set_pressed(0, 1, 2, true);
+        keyboard.set_pressed(8, 0, 0, true);
+        let reads = scan(&mut keyboard);
+        assert_eq!(reads[1], 0b1_0110);
+        assert_eq!(reads[16], 0b1_1100);
+        assert_eq!(reads.iter().filter(|value| **value != 0b1_1110).count(), 2);
+    }
+
+    #[test]
+    fn disabled() {
+        let frame = blank_frame();
+        let mut keyboard = FamilyKeyboard::new();
+        keyboard.strobe(0b001);
+        assert_eq!(keyboard.poll(0x4017, &screen(&frame, 0)), 0);
+    }
+}
@@ -0,0 +1,28 @@
+mod controller;
+mod zapper;
+mod four_score;
+mod power_pad;
+mod arkanoid;
+mod keyboard;
+mod ports;
+
+pub(crate) mod helpers {
+    use bunNES::nes::input::{InputDevice, Screen};
+    use bunNES::nes::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+
+    pub fn blank_frame() -> Vec<u8> {
+        vec![0x0F; SCREEN_WIDTH * SCREEN_HEIGHT]
+    }
+
+    pub fn screen(frame_buffer: &[u8], scanline: u16) -> Screen<'_> {
+        Screen { frame_buffer, scanline, dot: 0 }
+    }
+
+    /// strobes the device and polls `addr` `count` times
+    pub fn poll_all<D: InputDevice>(device: &mut D, addr: u16, count: usize) -> Vec<u8> {
+        let frame = blank_frame();
+        device.strobe(1);
+        device.strobe(0);
+        (0..count).map(|_| device.poll(addr, &screen(&frame, 0))).collect()
+    }
+}
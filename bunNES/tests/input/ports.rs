@@ -0,0 +1,158 @@
+use bunNES::nes::input::controller::{Buttons, Controller};
+use bunNES::nes::input::arkanoid::ArkanoidPaddle;
+use bunNES::nes::input::four_score::{FourPlayerAdapter, FourScore};
+use bunNES::nes::input::keyboard::FamilyKeyboard;
+use bunNES::nes::input::power_pad::PowerPad;
+use bunNES::nes::input::zapper::Zapper;
+use bunNES::nes::input::{InputDevice, InputPorts, Port, PLAYERS};
+use crate::apu::helpers::get_bus;
+use crate::input::helpers::{blank_frame, screen};
+
+fn read_bits(ports: &mut InputPorts, addr: u16, count: usize) -> Vec<u8> {
+    let frame = blank_frame();
+    ports.write(1);
+    ports.write(0);
+    (0..count).map(|_| ports.read(addr, &screen(&frame, 0))).collect()
+}
+
+#[cfg(test)]
+mod default_device {
+    use super::*;
+
+    #[test]
+    fn standard_controllers() {
+        let mut ports = InputPorts::from_expansion_device(0x01);
+        assert!(ports.device_mut::<Controller>(Port::One).is_some());
+        assert!(ports.device_mut::<Controller>(Port::Two).is_some());
+        assert!(ports.expansion_mut::<FourPlayerAdapter>().is_none());
+    }
+
+    #[test]
+    fn zapper() {
+        let mut ports = InputPorts::from_expansion_device(0x08);
+        assert!(ports.device_mut::<Controller>(Port::One).is_some());
+        assert!(ports.device_mut::<Zapper>(Port::Two).is_some());
+    }
+
+    #[test]
+    fn four_score() {
+        let mut ports = InputPorts::from_expansion_device(0x02);
+        assert!(ports.device_mut::<FourScore>(Port::One).is_some());
+        assert!(ports.device_mut::<FourScore>(Port::Two).is_some());
+    }
+
+    #[test]
+    fn unsupported() {
+        let mut ports = InputPorts::from_expansion_device(0x3F);
+        assert!(ports.device_mut::<Controller>(Port::Two).is_some());
+    }
+}
+
+#[cfg(test)]
+mod set_buttons {
+    use super::*;
+
+    #[test]
+    fn four_score_players() {
+        let mut ports = InputPorts::from_expansion_device(0x02);
+        ports.set_buttons(3, Buttons::A);
+        let reads = read_bits(&mut ports, 0x4017, 9);
+        assert_eq!(reads[0], 0);
+        assert_eq!(reads[8], 1);
+    }
+
+    #[test]
+    fn famicom_players() {
+        let mut ports = InputPorts::from_expansion_device(0x03);
+        ports.set_buttons(0, Buttons::A);
+        ports.set_buttons(2, Buttons::A);
+        // player 1 on D0, player 3 on D1
+        assert_eq!(read_bits(&mut ports, 0x4016, 1), [0b11]);
+    }
+
+    #[test]
+    fn past_the_last_player() {
+        for expansion_device in [0x01, 0x02, 0x03] {
+            let mut ports = InputPorts::from_expansion_device(expansion_device);
+            ports.set_buttons(PLAYERS, Buttons::A);
+            ports.set_buttons(usize::MAX, Buttons::A);
+            assert_eq!(read_bits(&mut ports, 0x4016, 8), [0; 8]);
+            assert_eq!(read_bits(&mut ports, 0x4017, 8), [0; 8]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod bus {
+    use super::*;
+
+    #[test]
+    fn zapper_open_bus() {
+        let mut bus = get_bus();
+        bus.input.set_device(Port::Two, Box::new(Zapper::new()));
+        bus.input.device_mut::<Zapper>(Port::Two).unwrap().set_trigger(true);
+        // the operand fetch of lda $4017 leaves $40 on the bus
+        bus.ram[1] = 0x40;
+        bus.read_8(0x0001);
+        // no light and the trigger pulled
+        assert_eq!(bus.read_8(0x4017), 0x40 | 0b0001_1000);
+    }
+}
+
+#[cfg(test)]
+mod peek {
+    use super::*;
+
+    /// every peek matches the poll that follows it
+    fn assert_peeks_match<D: InputDevice>(mut device: D, addr: u16) {
+        let frame = blank_frame();
+        let screen = screen(&frame, 0);
+        device.strobe(1);
+        assert_eq!(device.peek(addr, &screen), device.poll(addr, &screen));
+        device.strobe(0);
+        for _ in 0..30 {
+            let peeked = device.peek(addr, &screen);
+            assert_eq!(device.peek(addr, &screen), peeked);
+            assert_eq!(device.poll(addr, &screen), peeked);
+        }
+    }
+
+    #[test]
+    fn devices() {
+        let mut controller = Controller::new();
+        controller.set_buttons(Buttons::A | Buttons::START);
+        assert_peeks_match(controller, 0x4016);
+
+        let mut four_score = FourScore::new(Port::One);
+        four_score.set_buttons(1, Buttons::B);
+        assert_peeks_match(four_score, 0x4016);
+
+        let mut adapter = FourPlayerAdapter::new();
+        adapter.set_buttons(1, Buttons::SELECT);
+        assert_peeks_match(adapter, 0x4017);
+
+        let mut power_pad = PowerPad::new();
+        power_pad.set_pressed(3, true);
+        assert_peeks_match(power_pad, 0x4017);
+
+        let mut paddle = ArkanoidPaddle::new();
+        paddle.set_position(0xA5);
+        assert_peeks_match(paddle, 0x4017);
+        assert_peeks_match(ArkanoidPaddle::famicom(), 0x4016);
+
+        assert_peeks_match(FamilyKeyboard::new(), 0x4017);
+        assert_peeks_match(Zapper::new(), 0x4017);
+    }
+
+    #[test]
+    fn bus_doesnt_shift() {
+        let mut bus = get_bus();
+        bus.input.set_buttons(0, Buttons::A);
+        bus.write(0x4016, 1);
+        bus.write(0x4016, 0);
+        assert_eq!(bus.peek(0x4016) & 1, 1);
+        assert_eq!(bus.peek(0x4016) & 1, 1);
+        assert_eq!(bus.read_8(0x4016) & 1, 1);
+        assert_eq!(bus.peek(0x4016) & 1, 0);
+    }
+}
@@ -0,0 +1,52 @@
+use bunNES::nes::input::power_pad::PowerPad;
+use bunNES::nes::input::InputDevice;
+use crate::input::helpers::{blank_frame, poll_all, screen};
+
+#[cfg(test)]
+mod nes_power_pad {
+    use super::*;
+
+    #[test]
+    fn serial_order() {
+        let mut pad = PowerPad::new();
+        // second on D3, third on D4
+        pad.set_pressed(1, true);
+        pad.set_pressed(12, true);
+        let reads = poll_all(&mut pad, 0x4017, 8);
+        let d3: Vec<u8> = reads.iter().map(|value| (value >> 3) & 1).collect();
+        let d4: Vec<u8> = reads.iter().map(|value| (value >> 4) & 1).collect();
+        assert_eq!(d3, [0, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(d4, [0, 0, 1, 0, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn released() {
+        let mut pad = PowerPad::new();
+        pad.set_pressed(5, true);
+        pad.set_pressed(5, false);
+        let reads = poll_all(&mut pad, 0x4017, 4);
+        assert!(reads.iter().all(|value| *value == 0));
+    }
+}
+
+#[cfg(test)]
+mod family_trainer {
+    use super::*;
+
+    #[test]
+    fn rows() {
+        let frame = blank_frame();
+        let mut pad = PowerPad::family_trainer();
+        pad.set_pressed(2, true);
+        pad.set_pressed(12, true);
+
+        // row of buttons 1-4 selected with OUT2 low
+        pad.strobe(0b011);
+        assert_eq!(pad.poll(0x4017, &screen(&frame, 0)), 0b1_1010);
+        // buttons 9-12 with OUT0 low
+        pad.strobe(0b110);
+        assert_eq!(pad.poll(0x4017, &screen(&frame, 0)), 0b0_1110);
+        pad.strobe(0b111);
+        assert_eq!(pad.poll(0x4017, &screen(&frame, 0)), 0b1_1110);
+    }
+}
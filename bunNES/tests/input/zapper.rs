@@ -0,0 +1,72 @@
+use bunNES::nes::input::zapper::Zapper;
+use bunNES::nes::input::InputDevice;
+use bunNES::nes::ppu::SCREEN_WIDTH;
+use crate::input::helpers::{blank_frame, screen};
+
+const WHITE: u8 = 0x30;
+const LIGHT_NOT_DETECTED: u8 = 0b0000_1000;
+
+/// a white 4x4 box with its top left corner at 100, 100
+fn target_frame() -> Vec<u8> {
+    let mut frame = blank_frame();
+    for y in 100..104 {
+        for x in 100..104 {
+            frame[y * SCREEN_WIDTH + x] = WHITE;
+        }
+    }
+    frame
+}
+
+#[cfg(test)]
+mod light_gun {
+    use super::*;
+
+    #[test]
+    fn light_near_aim() {
+        let frame = target_frame();
+        let mut zapper = Zapper::new();
+        zapper.aim(101, 101);
+        assert_eq!(zapper.poll(0x4017, &screen(&frame, 110)) & LIGHT_NOT_DETECTED, 0);
+        zapper.aim(105, 99);
+        assert_eq!(zapper.poll(0x4017, &screen(&frame, 110)) & LIGHT_NOT_DETECTED, 0);
+    }
+
+    #[test]
+    fn dark_away_from_target() {
+        let frame = target_frame();
+        let mut zapper = Zapper::new();
+        zapper.aim(20, 20);
+        assert_eq!(zapper.poll(0x4017, &screen(&frame, 30)) & LIGHT_NOT_DETECTED, LIGHT_NOT_DETECTED);
+    }
+
+    #[test]
+    fn only_after_beam_passed() {
+        let frame = target_frame();
+        let mut zapper = Zapper::new();
+        zapper.aim(101, 101);
+        // not drawn yet this frame
+        assert!(!zapper.light_detected(&screen(&frame, 90)));
+        // the sensor has gone dark again
+        assert!(!zapper.light_detected(&screen(&frame, 200)));
+        assert!(zapper.light_detected(&screen(&frame, 101)));
+    }
+
+    #[test]
+    fn off_screen() {
+        let frame = target_frame();
+        let mut zapper = Zapper::new();
+        zapper.aim(-1, 101);
+        assert!(!zapper.light_detected(&screen(&frame, 110)));
+        zapper.aim(256, 101);
+        assert!(!zapper.light_detected(&screen(&frame, 110)));
+    }
+
+    #[test]
+    fn trigger() {
+        let frame = blank_frame();
+        let mut zapper = Zapper::new();
+        assert_eq!(zapper.poll(0x4017, &screen(&frame, 0)) & 0b0001_0000, 0);
+        zapper.set_trigger(true);
+        assert_eq!(zapper.poll(0x4017, &screen(&frame, 0)) & 0b0001_0000, 0b0001_0000);
+    }
+}
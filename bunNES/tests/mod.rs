@@ -1,2 +1,7 @@
 #[macro_use]
 mod opcodes;
+mod apu;
+mod emulator;
+mod input;
+mod ppu;
+mod test_roms;
@@ -1,6 +1,7 @@
 use bunNES::nes::opcodes::{AddrMode, OpCode};
 use crate::opcodes::helpers::{get_cpu, instruction};
-use bunNES::nes::cpu::Cpu;
+use bunNES::nes::cpu::{Cpu, Variant};
+use bunNES::nes::ram_bus::RamBus;
 
 // 8/8
 #[cfg(test)]
@@ -29,7 +30,8 @@ mod adc {
         while !cpu.step() {};
         assert_eq!(cpu.ps.carry(), true);
         assert_eq!(cpu.ps.zero(), true);
-        assert_eq!(cpu.ps.overflow(), true);
+        // -1 + 1 is in range
+        assert_eq!(cpu.ps.overflow(), false);
 
         cpu.acc = 128;
         while !cpu.step() {};
@@ -331,7 +333,7 @@ mod dec {
         test_flags(&mut cpu);
     }
     
-    fn test_flags(cpu: &mut Cpu) {
+    fn test_flags(cpu: &mut Cpu<RamBus>) {
         while !cpu.step() {};
         assert_eq!(cpu.bus.ram[0x01], 1);
         assert_eq!(cpu.ps.zero(), false);
@@ -492,7 +494,7 @@ mod inc {
         test_flags(&mut cpu);
     }
 
-    fn test_flags(cpu: &mut Cpu) {
+    fn test_flags(cpu: &mut Cpu<RamBus>) {
         while !cpu.step() {};
         assert_eq!(cpu.bus.ram[0x01], 255);
         assert_eq!(cpu.ps.zero(), false);
@@ -965,16 +967,18 @@ mod sbc {
         let mut cpu = get_cpu(code);
         cpu.ps.set_carry(true);
         cpu.acc = 35;
+        // no borrow
         while !cpu.step() {};
-        assert_eq!(cpu.ps.carry(), false);
+        assert_eq!(cpu.ps.carry(), true);
         assert_eq!(cpu.ps.zero(), false);
         assert_eq!(cpu.ps.overflow(), false);
         assert_eq!(cpu.ps.negative(), false);
         
         cpu.ps.set_carry(false);
         cpu.acc = 35;
+        // no borrow
         while !cpu.step() {};
-        assert_eq!(cpu.ps.carry(), false);
+        assert_eq!(cpu.ps.carry(), true);
         assert_eq!(cpu.ps.zero(), true);
         assert_eq!(cpu.ps.overflow(), false);
         assert_eq!(cpu.ps.negative(), false);
@@ -991,7 +995,8 @@ mod sbc {
         cpu.acc = 129;
         while !cpu.step() {};
         assert_eq!(cpu.ps.negative(), false);
-        assert_eq!(cpu.ps.overflow(), false);
+        // -127 - 10 is out of range
+        assert_eq!(cpu.ps.overflow(), true);
     }
 
     #[test]
@@ -1121,3 +1126,63 @@ mod sbc {
     }
 }
 
+#[cfg(test)]
+mod decimal {
+    use super::*;
+
+    /// runs `op_code` on every (acc, operand, carry in) and checks acc and carry out
+    fn test_decimal(op_code: OpCode, variant: Variant, cases: &[(u8, u8, bool, u8, bool)]) {
+        for &(acc, operand, carry, result, carry_out) in cases {
+            let mut cpu = get_cpu(vec![instruction(op_code, AddrMode::Immediate), operand]);
+            cpu.variant = variant;
+            cpu.ps.set_decimal(true);
+            cpu.ps.set_carry(carry);
+            cpu.acc = acc;
+            while !cpu.step() {};
+            assert_eq!((cpu.acc, cpu.ps.carry()), (result, carry_out), "{op_code} {acc:02X} {operand:02X} c={carry}");
+        }
+    }
+
+    #[test]
+    fn adc() {
+        test_decimal(OpCode::Adc, Variant::Nmos6502, &[
+            (0x12, 0x34, false, 0x46, false),
+            (0x15, 0x26, false, 0x41, false),
+            (0x58, 0x46, true, 0x05, true),
+            (0x81, 0x92, false, 0x73, true),
+            (0x99, 0x00, true, 0x00, true),
+        ]);
+    }
+
+    #[test]
+    fn sbc() {
+        test_decimal(OpCode::Sbc, Variant::Nmos6502, &[
+            (0x46, 0x12, true, 0x34, true),
+            (0x40, 0x13, true, 0x27, true),
+            (0x32, 0x02, false, 0x29, true),
+            (0x12, 0x21, true, 0x91, false),
+            (0x21, 0x34, true, 0x87, false),
+        ]);
+    }
+
+    #[test]
+    fn nmos_flags() {
+        // z comes from the binary sum, n and v from the sum before the high digit is adjusted
+        let mut cpu = get_cpu(vec![instruction(OpCode::Adc, AddrMode::Immediate), 0x01]);
+        cpu.variant = Variant::Nmos6502;
+        cpu.ps.set_decimal(true);
+        cpu.acc = 0x99;
+        while !cpu.step() {};
+        assert_eq!(cpu.acc, 0x00);
+        assert_eq!(cpu.ps.carry(), true);
+        assert_eq!(cpu.ps.zero(), false);
+        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.overflow(), false);
+    }
+
+    #[test]
+    fn ignored_by_the_2a03() {
+        test_decimal(OpCode::Adc, Variant::Ricoh2A03, &[(0x58, 0x46, true, 0x9F, false)]);
+        test_decimal(OpCode::Sbc, Variant::Ricoh2A03, &[(0x40, 0x13, true, 0x2D, true)]);
+    }
+}
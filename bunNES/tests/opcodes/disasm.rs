@@ -0,0 +1,136 @@
+use bunNES::nes::disasm::{disassemble, disassemble_bank, Syntax};
+
+fn line(bytes: &[u8], addr: u16, syntax: Syntax) -> String {
+    disassemble(|at| bytes[at.wrapping_sub(addr) as usize], addr, syntax).to_string()
+}
+
+#[cfg(test)]
+mod instruction {
+    use super::*;
+
+    #[test]
+    fn structure() {
+        let instruction = disassemble(|addr| [0x20, 0x34, 0x12][addr as usize - 0x8000], 0x8000, Syntax::Ca65);
+        assert_eq!(instruction.addr, 0x8000);
+        assert_eq!(instruction.bytes, vec![0x20, 0x34, 0x12]);
+        assert_eq!(instruction.mnemonic, "jsr");
+        assert_eq!(instruction.operand, "$1234");
+        assert_eq!(instruction.target, Some(0x1234));
+    }
+
+    #[test]
+    fn only_reads_its_bytes() {
+        let mut reads = vec![];
+        disassemble(|addr| { reads.push(addr); 0xA9 }, 0x8000, Syntax::Ca65);
+        assert_eq!(reads, vec![0x8000, 0x8001]);
+    }
+
+    #[test]
+    fn addressing_modes() {
+        assert_eq!(line(&[0xEA], 0x8000, Syntax::Nestest), "NOP");
+        assert_eq!(line(&[0x0A], 0x8000, Syntax::Nestest), "ASL A");
+        assert_eq!(line(&[0xA9, 0x01], 0x8000, Syntax::Nestest), "LDA #$01");
+        assert_eq!(line(&[0xA5, 0x10], 0x8000, Syntax::Nestest), "LDA $10");
+        assert_eq!(line(&[0xB5, 0x10], 0x8000, Syntax::Nestest), "LDA $10,X");
+        assert_eq!(line(&[0xB6, 0x10], 0x8000, Syntax::Nestest), "LDX $10,Y");
+        assert_eq!(line(&[0xAD, 0x00, 0x02], 0x8000, Syntax::Nestest), "LDA $0200");
+        assert_eq!(line(&[0xBD, 0x00, 0x02], 0x8000, Syntax::Nestest), "LDA $0200,X");
+        assert_eq!(line(&[0xB9, 0x00, 0x02], 0x8000, Syntax::Nestest), "LDA $0200,Y");
+        assert_eq!(line(&[0x6C, 0x00, 0x02], 0x8000, Syntax::Nestest), "JMP ($0200)");
+        assert_eq!(line(&[0xA1, 0x80], 0x8000, Syntax::Nestest), "LDA ($80,X)");
+        assert_eq!(line(&[0xB1, 0x80], 0x8000, Syntax::Nestest), "LDA ($80),Y");
+    }
+
+    #[test]
+    fn branches() {
+        let forward = disassemble(|addr| [0xD0, 0x00][addr as usize - 0x8000], 0x8000, Syntax::Nestest);
+        assert_eq!(forward.target, Some(0x8002));
+        assert_eq!(forward.to_string(), "BNE $8002");
+
+        assert_eq!(line(&[0xD0, 0xFE], 0x8000, Syntax::Nestest), "BNE $8000");
+        assert_eq!(line(&[0x10, 0x7F], 0xC000, Syntax::Nestest), "BPL $C081");
+        assert_eq!(line(&[0x10, 0x80], 0xC000, Syntax::Nestest), "BPL $BF82");
+    }
+
+    #[test]
+    fn unofficial_opcodes() {
+        assert_eq!(line(&[0xA7, 0x10], 0x8000, Syntax::Nestest), "*LAX $10");
+        assert_eq!(line(&[0xE3, 0x10], 0x8000, Syntax::Nestest), "*ISB ($10,X)");
+        assert_eq!(line(&[0x1A], 0x8000, Syntax::Nestest), "*NOP");
+        assert_eq!(line(&[0x02], 0x8000, Syntax::Nestest), "*KIL");
+        assert_eq!(line(&[0xA7, 0x10], 0x8000, Syntax::Ca65), ".byte $A7, $10");
+        assert_eq!(line(&[0x02], 0x8000, Syntax::Asm6), ".DB $02");
+    }
+}
+
+#[cfg(test)]
+mod syntax {
+    use super::*;
+
+    #[test]
+    fn ca65() {
+        assert_eq!(line(&[0x0A], 0x8000, Syntax::Ca65), "asl a");
+        assert_eq!(line(&[0xB6, 0x10], 0x8000, Syntax::Ca65), "ldx $10,y");
+        assert_eq!(line(&[0xB1, 0x80], 0x8000, Syntax::Ca65), "lda ($80),y");
+        assert_eq!(line(&[0xAD, 0x10, 0x00], 0x8000, Syntax::Ca65), "lda a:$0010");
+        assert_eq!(line(&[0xBD, 0x10, 0x00], 0x8000, Syntax::Ca65), "lda a:$0010,x");
+    }
+
+    #[test]
+    fn asm6() {
+        assert_eq!(line(&[0xBD, 0x00, 0x02], 0x8000, Syntax::Asm6), "LDA $0200,X");
+        assert_eq!(line(&[0xAD, 0x10, 0x00], 0x8000, Syntax::Asm6), ".DB $AD, $10, $00");
+        assert_eq!(line(&[0x4C, 0x10, 0x00], 0x8000, Syntax::Asm6), "JMP $0010");
+    }
+
+    #[test]
+    fn nestest() {
+        assert_eq!(line(&[0xAD, 0x10, 0x00], 0x8000, Syntax::Nestest), "LDA $0010");
+    }
+}
+
+#[cfg(test)]
+mod bank {
+    use super::*;
+
+    #[test]
+    fn labels() {
+        let bank = [
+            0x20, 0x06, 0x80, // jsr sub
+            0x4C, 0x03, 0x80, // jmp to itself
+            // sub
+            0xCA,             // dex
+            0xD0, 0xFD,       // bne sub
+            0x60,             // rts
+        ];
+        assert_eq!(disassemble_bank(&bank, 0x8000, Syntax::Ca65), "\
+.org $8000
+    jsr L8006
+L8003:
+    jmp L8003
+L8006:
+    dex
+    bne L8006
+    rts
+");
+    }
+
+    #[test]
+    fn targets_outside_the_bank() {
+        let bank = [0x20, 0x00, 0xC0, 0x60];
+        assert_eq!(disassemble_bank(&bank, 0x8000, Syntax::Asm6), ".ORG $8000\n    JSR $C000\n    RTS\n");
+    }
+
+    #[test]
+    fn targets_inside_an_instruction() {
+        // the branch lands on the operand of the lda
+        let bank = [0xA9, 0x00, 0xD0, 0xFD];
+        assert_eq!(disassemble_bank(&bank, 0x8000, Syntax::Ca65), ".org $8000\n    lda #$00\n    bne $8001\n");
+    }
+
+    #[test]
+    fn cut_off_instruction() {
+        let bank = [0xEA, 0xAD, 0x00];
+        assert_eq!(disassemble_bank(&bank, 0x8000, Syntax::Ca65), ".org $8000\n    nop\n    .byte $AD, $00\n");
+    }
+}
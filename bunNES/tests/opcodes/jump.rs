@@ -125,6 +125,22 @@ mod bne {
         while !cpu.step() {};
         assert_eq!(cpu.pc, initial_pc + 0x10 + 0x02);
     }
+
+    #[test]
+    fn cycles() {
+        // not taken, taken, taken back across the page to $7FF2
+        for (zero, offset, cycles) in [(true, 0x10, 2), (false, 0x10, 3), (false, 0xF0, 4)] {
+            let mut cpu = get_cpu(vec![instruction(OpCode::Bne, AddrMode::Relative), offset]);
+            cpu.ps.set_zero(zero);
+            assert!(cpu.step());
+            // every step is a cycle, the next instruction starts on the step after the branch's last one
+            let mut steps = 1;
+            while !cpu.step() {
+                steps += 1;
+            }
+            assert_eq!(steps, cycles, "zero={zero} offset={offset:02X}");
+        }
+    }
 }
 
 // 1/1
@@ -210,25 +226,27 @@ mod jmp {
     #[test]
     fn jmp_absolute() {
         let code: Vec<u8> = vec![
-            instruction(OpCode::Bvs, AddrMode::Absolute),
-            0x01, 0x00,
+            instruction(OpCode::Jmp, AddrMode::Absolute),
+            0x34, 0x12,
         ];
         let mut cpu = get_cpu(code);
-        let initial_pc = cpu.pc;
         while !cpu.step() {};
-        assert_eq!(cpu.pc, initial_pc + 0x0100 + 0x01);
+        assert_eq!(cpu.pc, 0x1234);
     }
     
     #[test]
     fn jmp_indirect() {
         let code: Vec<u8> = vec![
-            instruction(OpCode::Bvs, AddrMode::Indirect),
-            0x10,
+            instruction(OpCode::Jmp, AddrMode::Indirect),
+            0x10, 0x02,
+            instruction(OpCode::Jmp, AddrMode::Indirect),
+            0xFF, 0x02,
         ];
         let mut cpu = get_cpu(code);
-        let initial_pc = cpu.pc;
+        cpu.bus.ram[0x0210] = 0x03;
+        cpu.bus.ram[0x0211] = 0x80;
         while !cpu.step() {};
-        assert_eq!(cpu.pc, initial_pc + 0x10 + 0x01);
+        assert_eq!(cpu.pc, 0x8003);
         
         // note: 
         // An original 6502 has does not correctly fetch the target address
@@ -238,6 +256,11 @@ mod jmp {
         // but takes the MSB from $xx00.
         // This is fixed in some later chips like the 65SC02
         // so for compatibility always ensure the indirect vector is not at the end of the page.
+        cpu.bus.ram[0x02FF] = 0x34;
+        cpu.bus.ram[0x0200] = 0x12;
+        cpu.bus.ram[0x0300] = 0x56;
+        while !cpu.step() {};
+        assert_eq!(cpu.pc, 0x1234);
     }
 }
 
@@ -255,7 +278,10 @@ mod jsr {
         let mut cpu = get_cpu(code);
         while !cpu.step() {};
         assert_eq!(cpu.pc, 0x0100);
-        todo!("jsr: check stack")
+        // the address of the jsr's last byte, msb first
+        assert_eq!(cpu.sp, 0xFD);
+        assert_eq!(cpu.bus.ram[0x01FF], 0x80);
+        assert_eq!(cpu.bus.ram[0x01FE], 0x02);
     }
 }
 
@@ -270,12 +296,15 @@ mod rti {
             instruction(OpCode::Rti, AddrMode::Implicit),
         ];
         let mut cpu = get_cpu(code);
-        cpu.bus.ram[0x01FF] = 0b1111_1111;
-        cpu.bus.ram[0x01FF - 1] = 0x01;
-        cpu.bus.ram[0x01FF - 2] = 0x02;
+        cpu.sp = 0xFC;
+        cpu.bus.ram[0x01FD] = 0b1111_1111;
+        cpu.bus.ram[0x01FE] = 0x01;
+        cpu.bus.ram[0x01FF] = 0x02;
         while !cpu.step() {};
-        assert_eq!(cpu.ps.get_reg(), 0b1111_1111);
+        // the break flag and bit 5 only exist on the stack
+        assert_eq!(cpu.ps.get_reg(), 0b1100_1111);
         assert_eq!(cpu.pc, 0x0201);
+        assert_eq!(cpu.sp, 0xFF);
     }
 }
 
@@ -290,10 +319,13 @@ mod rts {
             instruction(OpCode::Rts, AddrMode::Implicit),
         ];
         let mut cpu = get_cpu(code);
-        cpu.bus.ram[0x01FF - 0] = 0x01;
-        cpu.bus.ram[0x01FF - 1] = 0x02;
+        cpu.sp = 0xFD;
+        cpu.bus.ram[0x01FE] = 0x00;
+        cpu.bus.ram[0x01FF] = 0x02;
         while !cpu.step() {};
+        // one past the pushed address
         assert_eq!(cpu.pc, 0x0201);
+        assert_eq!(cpu.sp, 0xFF);
     }
 }
 
@@ -1,6 +1,7 @@
 use bunNES::nes::opcodes::{AddrMode, OpCode};
 use crate::opcodes::helpers::{get_cpu, instruction};
 use bunNES::nes::cpu::Cpu;
+use bunNES::nes::ram_bus::RamBus;
 
 // 8/8
 #[cfg(test)]
@@ -161,7 +162,7 @@ mod and {
         test_results(&mut cpu, 0, 1);
     }
 
-    fn test_results(cpu: &mut Cpu, x_inc: u8, y_inc: u8) {
+    fn test_results(cpu: &mut Cpu<RamBus>, x_inc: u8, y_inc: u8) {
         cpu.acc = 34;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), false);
@@ -359,9 +360,9 @@ mod cmp {
             instruction(OpCode::Cmp, AddrMode::IndirectX),
             0x20,
             instruction(OpCode::Cmp, AddrMode::IndirectX),
-            0x20,
+            0x22,
             instruction(OpCode::Cmp, AddrMode::IndirectX),
-            0x20,
+            0x24,
         ];
         let mut cpu = get_cpu(code);
         cpu.acc = 35;
@@ -385,16 +386,20 @@ mod cmp {
             instruction(OpCode::Cmp, AddrMode::IndirectY),
             0x20,
             instruction(OpCode::Cmp, AddrMode::IndirectY),
-            0x20,
+            0x22,
             instruction(OpCode::Cmp, AddrMode::IndirectY),
-            0x20,
+            0x24,
         ];
         let mut cpu = get_cpu(code);
         cpu.acc = 35;
         cpu.y = 4;
         cpu.bus.ram[0x20] = 0x00;
         cpu.bus.ram[0x21] = 0x05;
-        cpu.bus.ram[0x504] = 34;
+        cpu.bus.ram[0x22] = 0x01;
+        cpu.bus.ram[0x23] = 0x05;
+        cpu.bus.ram[0x24] = 0x02;
+        cpu.bus.ram[0x25] = 0x05;
+        cpu.bus.ram[0x504] = 35;
         cpu.bus.ram[0x505] = 0;
         cpu.bus.ram[0x506] = 255;
 
@@ -402,11 +407,11 @@ mod cmp {
     }
 
 
-    fn test_flags(cpu: &mut Cpu) {
+    fn test_flags(cpu: &mut Cpu<RamBus>) {
         // less
         cpu.acc = 0;
         while !cpu.step() {};
-        assert_eq!(cpu.ps.carry(), true);
+        assert_eq!(cpu.ps.carry(), false);
         assert_eq!(cpu.ps.zero(), false);
         assert_eq!(cpu.ps.negative(), true);
 
@@ -482,24 +487,27 @@ mod cpx {
        test_flags(&mut cpu);
     }
 
-    fn test_flags(cpu: &mut Cpu) {
-        cpu.acc = 0;
+    fn test_flags(cpu: &mut Cpu<RamBus>) {
+        // less
+        cpu.x = 0;
         while !cpu.step() {};
         assert_eq!(cpu.ps.carry(), false);
         assert_eq!(cpu.ps.zero(), false);
-        assert_eq!(cpu.ps.negative(), false);
+        assert_eq!(cpu.ps.negative(), true);
 
-        cpu.acc = 35;
+        // greater
+        cpu.x = 35;
         while !cpu.step() {};
         assert_eq!(cpu.ps.carry(), true);
-        assert_eq!(cpu.ps.zero(), true);
+        assert_eq!(cpu.ps.zero(), false);
         assert_eq!(cpu.ps.negative(), false);
 
-        cpu.acc = 255;
+        // eq
+        cpu.x = 255;
         while !cpu.step() {};
-        assert_eq!(cpu.ps.carry(), false);
+        assert_eq!(cpu.ps.carry(), true);
         assert_eq!(cpu.ps.zero(), true);
-        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.negative(), false);
     }
 
 }
@@ -559,24 +567,27 @@ mod cpy {
         test_flags(&mut cpu);
     }
 
-    fn test_flags(cpu: &mut Cpu) {
-        cpu.acc = 0;
+    fn test_flags(cpu: &mut Cpu<RamBus>) {
+        // less
+        cpu.y = 0;
         while !cpu.step() {};
         assert_eq!(cpu.ps.carry(), false);
         assert_eq!(cpu.ps.zero(), false);
-        assert_eq!(cpu.ps.negative(), false);
+        assert_eq!(cpu.ps.negative(), true);
 
-        cpu.acc = 35;
+        // greater
+        cpu.y = 35;
         while !cpu.step() {};
         assert_eq!(cpu.ps.carry(), true);
-        assert_eq!(cpu.ps.zero(), true);
+        assert_eq!(cpu.ps.zero(), false);
         assert_eq!(cpu.ps.negative(), false);
 
-        cpu.acc = 255;
+        // eq
+        cpu.y = 255;
         while !cpu.step() {};
-        assert_eq!(cpu.ps.carry(), false);
+        assert_eq!(cpu.ps.carry(), true);
         assert_eq!(cpu.ps.zero(), true);
-        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.negative(), false);
     }
 
 }
@@ -642,7 +653,7 @@ mod eor {
         cpu.bus.ram[6] = 35;
         cpu.bus.ram[7] = 0;
         cpu.bus.ram[8] = 255;
-        cpu.bus.ram[4] = 5;
+        cpu.bus.ram[9] = 5;
 
         test_results(&mut cpu, 0, 0);
     }
@@ -736,6 +747,8 @@ mod eor {
         cpu.bus.ram[0x27] = 0x05;
         cpu.bus.ram[0x28] = 0x02;
         cpu.bus.ram[0x29] = 0x05;
+        cpu.bus.ram[0x2A] = 0x03;
+        cpu.bus.ram[0x2B] = 0x05;
         cpu.bus.ram[0x500] = 35;
         cpu.bus.ram[0x501] = 0;
         cpu.bus.ram[0x502] = 255;
@@ -761,7 +774,7 @@ mod eor {
         cpu.y = 4;
         cpu.bus.ram[0x20] = 0x00;
         cpu.bus.ram[0x21] = 0x05;
-        cpu.bus.ram[0x504] = 34;
+        cpu.bus.ram[0x504] = 35;
         cpu.bus.ram[0x505] = 0;
         cpu.bus.ram[0x506] = 255;
         cpu.bus.ram[0x507] = 5;
@@ -769,7 +782,7 @@ mod eor {
         test_results(&mut cpu, 0, 1);
     }
 
-    fn test_results(cpu: &mut Cpu, x_inc: u8, y_inc: u8) {
+    fn test_results(cpu: &mut Cpu<RamBus>, x_inc: u8, y_inc: u8) {
         cpu.acc = 34;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), false);
@@ -789,7 +802,7 @@ mod eor {
         cpu.acc = 255;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), true);
-        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.negative(), false);
         assert_eq!(cpu.acc, 0);
         cpu.x += x_inc;
         cpu.y += y_inc;
@@ -797,7 +810,7 @@ mod eor {
         cpu.acc = 3;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), false);
-        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.negative(), false);
         assert_eq!(cpu.acc, 6);
     }
 }
@@ -959,6 +972,8 @@ mod ora {
         cpu.bus.ram[0x27] = 0x05;
         cpu.bus.ram[0x28] = 0x02;
         cpu.bus.ram[0x29] = 0x05;
+        cpu.bus.ram[0x2A] = 0x03;
+        cpu.bus.ram[0x2B] = 0x05;
         cpu.bus.ram[0x500] = 35;
         cpu.bus.ram[0x501] = 0;
         cpu.bus.ram[0x502] = 255;
@@ -984,7 +999,7 @@ mod ora {
         cpu.y = 4;
         cpu.bus.ram[0x20] = 0x00;
         cpu.bus.ram[0x21] = 0x05;
-        cpu.bus.ram[0x504] = 34;
+        cpu.bus.ram[0x504] = 35;
         cpu.bus.ram[0x505] = 0;
         cpu.bus.ram[0x506] = 255;
         cpu.bus.ram[0x507] = 5;
@@ -992,7 +1007,7 @@ mod ora {
         test_results(&mut cpu, 0, 1);
     }
 
-    fn test_results(cpu: &mut Cpu, x_inc: u8, y_inc: u8) {
+    fn test_results(cpu: &mut Cpu<RamBus>, x_inc: u8, y_inc: u8) {
         cpu.acc = 34;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), false);
@@ -1020,7 +1035,7 @@ mod ora {
         cpu.acc = 3;
         while !cpu.step() {};
         assert_eq!(cpu.ps.zero(), false);
-        assert_eq!(cpu.ps.negative(), true);
+        assert_eq!(cpu.ps.negative(), false);
         assert_eq!(cpu.acc, 7);
     }
 }
@@ -1,5 +1,5 @@
 use bunNES::nes::opcodes::{AddrMode, OpCode};
-use crate::opcodes::helpers::{get_cpu, instruction};
+use crate::opcodes::helpers::{get_cpu, get_cpu_at, instruction};
 
 // 1/1
 #[cfg(test)]
@@ -12,12 +12,15 @@ mod brk {
             instruction(OpCode::Brk, AddrMode::Implicit),
         ];
         let mut cpu = get_cpu(code);
+        cpu.ps.set_carry(true);
         while !cpu.step() {};
-        assert_eq!(cpu.ps.brk(), true);
         assert_eq!(cpu.pc, 0x0100);
-        todo!("stack not implemented");
-        // assert_eq!(cpu.bus.read_16(0x0100 + cpu.sp as u16), pc);
-        // assert_eq!(cpu.bus.read_16(0x0100 + cpu.sp as u16 + 0x02), ps.reg);
+        assert_eq!(cpu.ps.irqb(), true);
+        // skips the byte after brk, the status is pushed with the break flag and bit 5 set
+        assert_eq!(cpu.sp, 0xFC);
+        assert_eq!(cpu.bus.ram[0x01FF], 0x80);
+        assert_eq!(cpu.bus.ram[0x01FE], 0x02);
+        assert_eq!(cpu.bus.ram[0x01FD], 0b0011_0001);
     }
 }
 
@@ -143,7 +146,8 @@ mod php {
         let old_ps = cpu.ps.get_reg();
         while !cpu.step() {};
         assert_eq!(cpu.sp, old_sp - 0x01);
-        assert_eq!(cpu.bus.ram[0x01FF], old_ps);
+        // pushed with the break flag and bit 5 set
+        assert_eq!(cpu.bus.ram[0x01FF], old_ps | 0b0011_0000);
     }
 }
 
@@ -158,10 +162,10 @@ mod pla {
             instruction(OpCode::Pla, AddrMode::Implicit),
         ];
         let mut cpu = get_cpu(code);
+        cpu.sp = 0xFE;
         cpu.bus.ram[0x01FF] = 69;
-        let old_sp = cpu.sp;
         while !cpu.step() {};
-        assert_eq!(cpu.sp, old_sp - 0x01);
+        assert_eq!(cpu.sp, 0xFF);
         assert_eq!(cpu.acc, 69);
     }
 }
@@ -176,11 +180,12 @@ mod plp {
             instruction(OpCode::Plp, AddrMode::Implicit),
         ];
         let mut cpu = get_cpu(code);
+        cpu.sp = 0xFE;
         cpu.bus.ram[0x01FF] = 0b1111_1111;
-        let old_sp = cpu.sp;
         while !cpu.step() {};
-        assert_eq!(cpu.sp, old_sp - 0x01);
-        assert_eq!(cpu.ps.get_reg(), 0b1111_1111);
+        assert_eq!(cpu.sp, 0xFF);
+        // the break flag and bit 5 only exist on the stack
+        assert_eq!(cpu.ps.get_reg(), 0b1100_1111);
     }
 }
 
@@ -235,4 +240,57 @@ mod sei {
     }
 }
 
+#[cfg(test)]
+mod ram_bus {
+    use super::*;
+
+    #[test]
+    fn zero_page_code() {
+        let code: Vec<u8> = vec![
+            instruction(OpCode::Lda, AddrMode::Immediate), 0x42,
+            instruction(OpCode::Sta, AddrMode::Zp), 0x00,
+            // writes over its own operand
+            instruction(OpCode::Inc, AddrMode::Zp), 0x15,
+        ];
+        let mut cpu = get_cpu_at(0x0010, &code);
+        for _ in 0..3 {
+            while !cpu.step() {};
+        }
+        assert_eq!(cpu.bus.ram[0x00], 0x42);
+        assert_eq!(cpu.bus.ram[0x15], 0x16);
+        assert_eq!(cpu.pc, 0x0016);
+    }
 
+    #[test]
+    fn nmi() {
+        let mut cpu = get_cpu(vec![instruction(OpCode::Nop, AddrMode::Implicit); 4]);
+        cpu.bus.load(0xFFFA, &[0x00, 0x02]);
+        cpu.bus.nmi = true;
+        while !cpu.step() {};
+        assert_eq!(cpu.pc, 0x0200);
+        assert_eq!(cpu.bus.nmi, false);
+        assert_eq!(cpu.bus.ram[0x1FF], 0x80);
+        // seven cycles until the handler's first instruction
+        let mut steps = 1;
+        while !cpu.step() {
+            steps += 1;
+        }
+        assert_eq!(steps, 7);
+    }
+
+    #[test]
+    fn irq() {
+        let mut cpu = get_cpu(vec![instruction(OpCode::Nop, AddrMode::Implicit); 4]);
+        cpu.bus.irq = true;
+        cpu.ps.set_irqb(true);
+        while !cpu.step() {};
+        assert_eq!(cpu.pc, 0x8001);
+
+        cpu.ps.set_irqb(false);
+        while !cpu.step() {};
+        assert_eq!(cpu.pc, 0x0100);
+        // the line stays low, the i flag holds it off
+        assert_eq!(cpu.bus.irq, true);
+        assert_eq!(cpu.ps.irqb(), true);
+    }
+}
@@ -4,43 +4,36 @@ mod jump;
 mod logic;
 mod arithmetic;
 mod misc;
+mod disasm;
+mod table;
 
 #[macro_use]
-mod helpers {
-    use bunNES::nes::bus::Bus;
-    use bunNES::nes::cpu::{Cpu, ProcessorStatus};
-    use bunNES::nes::opcodes::{op_code_from_instruction, AddrMode, Instruction, OpCode};
-    use bunNES::nes::rom::Cartridge;
-      
-    pub fn get_cpu(mut code: Vec<u8>) -> Cpu {
-        code.resize(0x4000, 0);
-        // interrupt vector
-        code[0x3FFE] = 0x00;
-        code[0x3FFF] = 0x01;
-        let cartridge = Cartridge::test_cartride(code);
+pub(crate) mod helpers {
+    use bunNES::nes::cpu::{Cpu, Variant};
+    use bunNES::nes::opcodes::{op_code_from_instruction, AddrMode, OpCode};
+    use bunNES::nes::ram_bus::RamBus;
 
-        Cpu {
-            pc: 0x8000,
-            sp: 0xFF,
-            acc: 0,
-            x: 0,
-            y: 0,
-            ps: ProcessorStatus::new(),
-            bus: Bus::new(cartridge),
-            cycles_to_finish: 0,
-        }
+    /// `code` at $8000, brk and irqs go to $0100
+    pub fn get_cpu(code: Vec<u8>) -> Cpu<RamBus> {
+        get_cpu_at(0x8000, &code)
+    }
+
+    /// `code` at `addr`, anywhere in the 64k including the zero page
+    pub fn get_cpu_at(addr: u16, code: &[u8]) -> Cpu<RamBus> {
+        let mut bus = RamBus::new();
+        bus.load(addr, code);
+        // interrupt vector
+        bus.load(0xFFFE, &[0x00, 0x01]);
+        let mut cpu = Cpu::with_bus(bus, Variant::Ricoh2A03);
+        cpu.pc = addr;
+        cpu
     }
 
     pub fn instruction(op_code: OpCode, addr_mode: AddrMode) -> u8 {
-        let instruction = Instruction {
-            op_code,
-            addr_mode,
-            size: 1
-        };
-        if let Some(byte_code) = op_code_from_instruction(instruction) {
+        if let Some(byte_code) = op_code_from_instruction(op_code, addr_mode) {
             byte_code as u8
         } else {
             panic!("Invalid instruction: {} {}", op_code, addr_mode)
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,142 @@
+use bunNES::nes::opcodes::{Access, AddrMode, OpCode, OP_CODES};
+
+// http://www.oxyron.de/html/opcodes02.html, mnemonic, addressing mode and cycles.
+// unofficial opcodes are starred, a starred cycle count has the page cross penalty
+const REFERENCE: [&str; 256] = [
+    // 0x0_
+    "BRK 7", "ORA izx 6", "*KIL", "*SLO izx 8", "*NOP zp 3", "ORA zp 3", "ASL zp 5", "*SLO zp 5",
+    "PHP 3", "ORA imm 2", "ASL acc 2", "*ANC imm 2", "*NOP abs 4", "ORA abs 4", "ASL abs 6", "*SLO abs 6",
+    // 0x1_
+    "BPL rel 2*", "ORA izy 5*", "*KIL", "*SLO izy 8", "*NOP zpx 4", "ORA zpx 4", "ASL zpx 6", "*SLO zpx 6",
+    "CLC 2", "ORA aby 4*", "*NOP 2", "*SLO aby 7", "*NOP abx 4*", "ORA abx 4*", "ASL abx 7", "*SLO abx 7",
+    // 0x2_
+    "JSR abs 6", "AND izx 6", "*KIL", "*RLA izx 8", "BIT zp 3", "AND zp 3", "ROL zp 5", "*RLA zp 5",
+    "PLP 4", "AND imm 2", "ROL acc 2", "*ANC imm 2", "BIT abs 4", "AND abs 4", "ROL abs 6", "*RLA abs 6",
+    // 0x3_
+    "BMI rel 2*", "AND izy 5*", "*KIL", "*RLA izy 8", "*NOP zpx 4", "AND zpx 4", "ROL zpx 6", "*RLA zpx 6",
+    "SEC 2", "AND aby 4*", "*NOP 2", "*RLA aby 7", "*NOP abx 4*", "AND abx 4*", "ROL abx 7", "*RLA abx 7",
+    // 0x4_
+    "RTI 6", "EOR izx 6", "*KIL", "*SRE izx 8", "*NOP zp 3", "EOR zp 3", "LSR zp 5", "*SRE zp 5",
+    "PHA 3", "EOR imm 2", "LSR acc 2", "*ALR imm 2", "JMP abs 3", "EOR abs 4", "LSR abs 6", "*SRE abs 6",
+    // 0x5_
+    "BVC rel 2*", "EOR izy 5*", "*KIL", "*SRE izy 8", "*NOP zpx 4", "EOR zpx 4", "LSR zpx 6", "*SRE zpx 6",
+    "CLI 2", "EOR aby 4*", "*NOP 2", "*SRE aby 7", "*NOP abx 4*", "EOR abx 4*", "LSR abx 7", "*SRE abx 7",
+    // 0x6_
+    "RTS 6", "ADC izx 6", "*KIL", "*RRA izx 8", "*NOP zp 3", "ADC zp 3", "ROR zp 5", "*RRA zp 5",
+    "PLA 4", "ADC imm 2", "ROR acc 2", "*ARR imm 2", "JMP ind 5", "ADC abs 4", "ROR abs 6", "*RRA abs 6",
+    // 0x7_
+    "BVS rel 2*", "ADC izy 5*", "*KIL", "*RRA izy 8", "*NOP zpx 4", "ADC zpx 4", "ROR zpx 6", "*RRA zpx 6",
+    "SEI 2", "ADC aby 4*", "*NOP 2", "*RRA aby 7", "*NOP abx 4*", "ADC abx 4*", "ROR abx 7", "*RRA abx 7",
+    // 0x8_
+    "*NOP imm 2", "STA izx 6", "*NOP imm 2", "*SAX izx 6", "STY zp 3", "STA zp 3", "STX zp 3", "*SAX zp 3",
+    "DEY 2", "*NOP imm 2", "TXA 2", "*XAA imm 2", "STY abs 4", "STA abs 4", "STX abs 4", "*SAX abs 4",
+    // 0x9_
+    "BCC rel 2*", "STA izy 6", "*KIL", "*AHX izy 6", "STY zpx 4", "STA zpx 4", "STX zpy 4", "*SAX zpy 4",
+    "TYA 2", "STA aby 5", "TXS 2", "*TAS aby 5", "*SHY abx 5", "STA abx 5", "*SHX aby 5", "*AHX aby 5",
+    // 0xA_
+    "LDY imm 2", "LDA izx 6", "LDX imm 2", "*LAX izx 6", "LDY zp 3", "LDA zp 3", "LDX zp 3", "*LAX zp 3",
+    "TAY 2", "LDA imm 2", "TAX 2", "*LAX imm 2", "LDY abs 4", "LDA abs 4", "LDX abs 4", "*LAX abs 4",
+    // 0xB_
+    "BCS rel 2*", "LDA izy 5*", "*KIL", "*LAX izy 5*", "LDY zpx 4", "LDA zpx 4", "LDX zpy 4", "*LAX zpy 4",
+    "CLV 2", "LDA aby 4*", "TSX 2", "*LAS aby 4*", "LDY abx 4*", "LDA abx 4*", "LDX aby 4*", "*LAX aby 4*",
+    // 0xC_
+    "CPY imm 2", "CMP izx 6", "*NOP imm 2", "*DCP izx 8", "CPY zp 3", "CMP zp 3", "DEC zp 5", "*DCP zp 5",
+    "INY 2", "CMP imm 2", "DEX 2", "*AXS imm 2", "CPY abs 4", "CMP abs 4", "DEC abs 6", "*DCP abs 6",
+    // 0xD_
+    "BNE rel 2*", "CMP izy 5*", "*KIL", "*DCP izy 8", "*NOP zpx 4", "CMP zpx 4", "DEC zpx 6", "*DCP zpx 6",
+    "CLD 2", "CMP aby 4*", "*NOP 2", "*DCP aby 7", "*NOP abx 4*", "CMP abx 4*", "DEC abx 7", "*DCP abx 7",
+    // 0xE_
+    "CPX imm 2", "SBC izx 6", "*NOP imm 2", "*ISC izx 8", "CPX zp 3", "SBC zp 3", "INC zp 5", "*ISC zp 5",
+    "INX 2", "SBC imm 2", "NOP 2", "*SBC imm 2", "CPX abs 4", "SBC abs 4", "INC abs 6", "*ISC abs 6",
+    // 0xF_
+    "BEQ rel 2*", "SBC izy 5*", "*KIL", "*ISC izy 8", "*NOP zpx 4", "SBC zpx 4", "INC zpx 6", "*ISC zpx 6",
+    "SED 2", "SBC aby 4*", "*NOP 2", "*ISC aby 7", "*NOP abx 4*", "SBC abx 4*", "INC abx 7", "*ISC abx 7",
+];
+
+fn addr_mode(name: &str) -> AddrMode {
+    match name {
+        "imp" => AddrMode::Implicit,
+        "acc" => AddrMode::Accumulator,
+        "imm" => AddrMode::Immediate,
+        "zp" => AddrMode::Zp,
+        "zpx" => AddrMode::ZpX,
+        "zpy" => AddrMode::ZpY,
+        "rel" => AddrMode::Relative,
+        "abs" => AddrMode::Absolute,
+        "abx" => AddrMode::AbsoluteX,
+        "aby" => AddrMode::AbsoluteY,
+        "ind" => AddrMode::Indirect,
+        "izx" => AddrMode::IndirectX,
+        "izy" => AddrMode::IndirectY,
+        _ => panic!("unknown addressing mode {name}"),
+    }
+}
+
+#[cfg(test)]
+mod reference {
+    use super::*;
+
+    #[test]
+    fn all_opcodes() {
+        for (byte_code, reference) in REFERENCE.iter().enumerate() {
+            let instruction = OP_CODES[byte_code];
+            let official = !reference.starts_with('*');
+            let mut parts = reference.trim_start_matches('*').split(' ').collect::<Vec<_>>();
+            if parts.len() == 2 {
+                parts.insert(1, "imp");
+            }
+            let (mode, cycles) = match parts[..] {
+                [_] => (AddrMode::Implicit, "0"),
+                [_, mode, cycles] => (addr_mode(mode), cycles),
+                _ => panic!("bad reference entry {reference}"),
+            };
+
+            let context = format!("{byte_code:#04X} should be {reference}, is {instruction:?}");
+            assert_eq!(instruction.op_code.to_string(), parts[0], "{context}");
+            assert_eq!(instruction.addr_mode, mode, "{context}");
+            assert_eq!(instruction.size, mode.size(), "{context}");
+            assert_eq!(instruction.cycles.to_string(), cycles.trim_end_matches('*'), "{context}");
+            assert_eq!(instruction.page_penalty, cycles.ends_with('*'), "{context}");
+            assert_eq!(instruction.official, official, "{context}");
+        }
+    }
+
+    #[test]
+    fn official_count() {
+        assert_eq!(OP_CODES.iter().filter(|instruction| instruction.official).count(), 151);
+    }
+}
+
+#[cfg(test)]
+mod access {
+    use super::*;
+
+    #[test]
+    fn categories() {
+        assert_eq!(OP_CODES[0xAD].access, Access::Read); // lda abs
+        assert_eq!(OP_CODES[0xA9].access, Access::Read); // lda #
+        assert_eq!(OP_CODES[0x8D].access, Access::Write); // sta abs
+        assert_eq!(OP_CODES[0x87].access, Access::Write); // sax zp
+        assert_eq!(OP_CODES[0xEE].access, Access::ReadModifyWrite); // inc abs
+        assert_eq!(OP_CODES[0xC7].access, Access::ReadModifyWrite); // dcp zp
+        assert_eq!(OP_CODES[0x0A].access, Access::None); // asl a
+        assert_eq!(OP_CODES[0x4C].access, Access::None); // jmp abs
+        assert_eq!(OP_CODES[0xD0].access, Access::None); // bne
+    }
+
+    #[test]
+    fn writes_have_no_page_penalty() {
+        for instruction in OP_CODES.iter().filter(|instruction| instruction.access != Access::Read) {
+            let branch = instruction.addr_mode == AddrMode::Relative;
+            assert!(!instruction.page_penalty || branch, "{instruction:?}");
+        }
+    }
+
+    #[test]
+    fn stores() {
+        for op_code in [OpCode::Sta, OpCode::Stx, OpCode::Sty] {
+            let mut stores = OP_CODES.iter().filter(|instruction| instruction.op_code == op_code);
+            assert!(stores.all(|instruction| instruction.access == Access::Write));
+        }
+    }
+}
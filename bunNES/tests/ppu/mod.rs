@@ -0,0 +1,40 @@
+mod registers;
+mod rendering;
+mod viewer;
+
+pub(crate) mod helpers {
+    use bunNES::nes::bus::Bus;
+    use bunNES::nes::ppu::Ppu;
+    use bunNES::nes::rom::Cartridge;
+    use std::ops::RangeInclusive;
+    use std::sync::Arc;
+
+    /// horizontal mirroring and 8k chr ram
+    pub fn get_ppu() -> Ppu {
+        Ppu::new(Arc::new(Cartridge::test_cartride(vec![0; 0x4000])))
+    }
+
+    pub fn get_bus() -> Bus {
+        Bus::new(Cartridge::test_cartride(vec![0; 0x4000]))
+    }
+
+    pub fn run_scanlines(ppu: &mut Ppu, scanlines: RangeInclusive<u64>) {
+        for scanline in scanlines {
+            for _ in 0..341 {
+                ppu.step(scanline);
+            }
+        }
+    }
+
+    pub fn set_addr(ppu: &mut Ppu, addr: u16) {
+        ppu.set_register(6, (addr >> 8) as u8);
+        ppu.set_register(6, addr as u8);
+    }
+
+    pub fn write_vram(ppu: &mut Ppu, addr: u16, values: &[u8]) {
+        set_addr(ppu, addr);
+        for value in values {
+            ppu.set_register(7, *value);
+        }
+    }
+}
@@ -0,0 +1,319 @@
+use crate::emulator::helpers::get_emulator;
+use crate::ppu::helpers::{get_bus, get_ppu, run_scanlines, set_addr, write_vram};
+use bunNES::nes::ppu::Ppu;
+
+#[cfg(test)]
+mod ppudata {
+    use super::*;
+
+    #[test]
+    fn read_buffer() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x2000, &[0x42, 0x43]);
+        set_addr(&mut ppu, 0x2000);
+        // the first read returns the stale buffer
+        ppu.register(7);
+        assert_eq!(ppu.register(7), 0x42);
+        assert_eq!(ppu.register(7), 0x43);
+    }
+
+    #[test]
+    fn increment_32() {
+        let mut ppu = get_ppu();
+        ppu.set_register(0, 0b0000_0100);
+        write_vram(&mut ppu, 0x2000, &[0x01, 0x02]);
+        ppu.set_register(0, 0);
+        set_addr(&mut ppu, 0x2020);
+        ppu.register(7);
+        assert_eq!(ppu.register(7), 0x02);
+    }
+
+    #[test]
+    fn palette_reads_are_immediate() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x3F01, &[0x2A]);
+        set_addr(&mut ppu, 0x3F01);
+        assert_eq!(ppu.register(7), 0x2A);
+    }
+
+    #[test]
+    fn palette_mirrors() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x3F10, &[0x12]);
+        set_addr(&mut ppu, 0x3F00);
+        assert_eq!(ppu.register(7), 0x12);
+        set_addr(&mut ppu, 0x3F20);
+        assert_eq!(ppu.register(7), 0x12);
+    }
+
+    #[test]
+    fn horizontal_mirroring() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x2400, &[0x55]);
+        set_addr(&mut ppu, 0x2000);
+        ppu.register(7);
+        assert_eq!(ppu.register(7), 0x55);
+        set_addr(&mut ppu, 0x2800);
+        ppu.register(7);
+        assert_ne!(ppu.register(7), 0x55);
+    }
+
+    #[test]
+    fn chr_ram() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x0010, &[0xAA]);
+        set_addr(&mut ppu, 0x0010);
+        ppu.register(7);
+        assert_eq!(ppu.register(7), 0xAA);
+    }
+}
+
+#[cfg(test)]
+mod ppustatus {
+    use super::*;
+
+    #[test]
+    fn vblank() {
+        let mut ppu = get_ppu();
+        ppu.register(2);
+        run_scanlines(&mut ppu, 0..=240);
+        assert_eq!(ppu.register(2) & 0x80, 0);
+        run_scanlines(&mut ppu, 241..=241);
+        assert_eq!(ppu.register(2) & 0x80, 0x80);
+        // cleared by the read
+        assert_eq!(ppu.register(2) & 0x80, 0);
+    }
+
+    #[test]
+    fn cleared_on_pre_render_line() {
+        let mut ppu = get_ppu();
+        run_scanlines(&mut ppu, 0..=261);
+        assert_eq!(ppu.register(2) & 0x80, 0);
+    }
+
+    #[test]
+    fn resets_write_toggle() {
+        let mut ppu = get_ppu();
+        ppu.set_register(6, 0x21);
+        ppu.register(2);
+        write_vram(&mut ppu, 0x2001, &[0x77]);
+        set_addr(&mut ppu, 0x2001);
+        ppu.register(7);
+        assert_eq!(ppu.register(7), 0x77);
+    }
+
+    #[test]
+    fn stale_bits() {
+        let mut ppu = get_ppu();
+        ppu.register(2);
+        ppu.set_register(0, 0b0001_0101);
+        assert_eq!(ppu.register(2) & 0b0001_1111, 0b0001_0101);
+    }
+}
+
+#[cfg(test)]
+mod peek {
+    use super::*;
+
+    #[test]
+    fn ppustatus() {
+        let mut ppu = get_ppu();
+        run_scanlines(&mut ppu, 0..=241);
+        assert_eq!(ppu.peek_register(2) & 0x80, 0x80);
+        assert_eq!(ppu.peek_register(2) & 0x80, 0x80);
+        assert_eq!(ppu.register(2) & 0x80, 0x80);
+        assert_eq!(ppu.peek_register(2) & 0x80, 0);
+    }
+
+    #[test]
+    fn ppudata() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x2000, &[0x42, 0x43]);
+        set_addr(&mut ppu, 0x2000);
+        ppu.register(7);
+        // neither the buffer nor the address move
+        assert_eq!(ppu.peek_register(7), 0x42);
+        assert_eq!(ppu.peek_register(7), 0x42);
+        assert_eq!(ppu.register(7), 0x42);
+        assert_eq!(ppu.register(7), 0x43);
+    }
+
+    #[test]
+    fn palette() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x3F01, &[0x2A]);
+        set_addr(&mut ppu, 0x3F01);
+        assert_eq!(ppu.peek_register(7), 0x2A);
+    }
+
+    #[test]
+    fn vram() {
+        let mut bus = get_bus();
+        write_vram(&mut bus.ppu, 0x2005, &[0x11]);
+        write_vram(&mut bus.ppu, 0x3F00, &[0x0F]);
+        assert_eq!(bus.peek_ppu(0x2005), 0x11);
+        // horizontal mirroring
+        assert_eq!(bus.peek_ppu(0x2405), 0x11);
+        assert_eq!(bus.peek_ppu(0x3F10), 0x0F);
+    }
+
+    #[test]
+    fn memory_chunk() {
+        let mut bus = get_bus();
+        run_scanlines(&mut bus.ppu, 0..=241);
+        bus.ram[0x10] = 0x99;
+        let chunk = bus.memory_chunk(0x2000, 8);
+        assert_eq!(chunk[2] & 0x80, 0x80);
+        assert_eq!(bus.memory_chunk(0x000F, 2), [0x00, 0x99]);
+        assert_eq!(bus.read_8(0x2002) & 0x80, 0x80);
+    }
+}
+
+#[cfg(test)]
+mod nmi {
+    use super::*;
+
+    #[test]
+    fn on_vblank() {
+        let mut ppu = get_ppu();
+        // the vblank flag is set on power up
+        ppu.register(2);
+        ppu.set_register(0, 0x80);
+        run_scanlines(&mut ppu, 0..=240);
+        assert!(!ppu.take_nmi());
+        run_scanlines(&mut ppu, 241..=241);
+        assert!(ppu.take_nmi());
+        assert!(!ppu.take_nmi());
+    }
+
+    #[test]
+    fn disabled() {
+        let mut ppu = get_ppu();
+        run_scanlines(&mut ppu, 0..=241);
+        assert!(!ppu.take_nmi());
+    }
+
+    #[test]
+    fn enabled_during_vblank() {
+        let mut ppu = get_ppu();
+        run_scanlines(&mut ppu, 0..=241);
+        ppu.set_register(0, 0x80);
+        assert!(ppu.take_nmi());
+    }
+
+    #[test]
+    fn cpu() {
+        let mut code = vec![
+            // bit $2002, lda #$80, sta $2000
+            0x2C, 0x02, 0x20, 0xA9, 0x80, 0x8D, 0x00, 0x20,
+            // bne to itself
+            0xD0, 0xFE,
+        ];
+        code.resize(0x100, 0);
+        // nmi handler at $8100
+        code.extend([0xD0, 0xFE]);
+        code.resize(0x4000, 0);
+        code[0x3FFA] = 0x00;
+        code[0x3FFB] = 0x81;
+
+        let mut emulator = get_emulator(code);
+        emulator.run_frame();
+        assert_eq!(emulator.cpu.pc, 0x8100);
+        // return address and status on the stack
+        assert_eq!(emulator.cpu.sp, 0xFC);
+        assert_eq!(emulator.cpu.bus.ram[0x1FF], 0x80);
+        assert_eq!(emulator.cpu.bus.ram[0x1FE], 0x08);
+    }
+}
+
+#[cfg(test)]
+mod oam {
+    use super::*;
+
+    #[test]
+    fn oamdata() {
+        let mut ppu = get_ppu();
+        ppu.set_register(3, 0x10);
+        ppu.set_register(4, 0x01);
+        ppu.set_register(4, 0x02);
+        ppu.set_register(3, 0x11);
+        assert_eq!(ppu.register(4), 0x02);
+    }
+
+    #[test]
+    fn dma() {
+        let mut bus = get_bus();
+        for i in 0..256 {
+            bus.ram[0x200 + i] = i as u8;
+        }
+        bus.write(0x2003, 0);
+        bus.write(0x4014, 0x02);
+        assert_eq!(bus.take_dma_stall(), 513);
+        assert_eq!(bus.take_dma_stall(), 0);
+
+        bus.write(0x2003, 0x80);
+        assert_eq!(bus.read_8(0x2004), 0x80);
+        bus.write(0x2003, 0xFF);
+        assert_eq!(bus.read_8(0x2004), 0xFF);
+    }
+
+    #[test]
+    fn dma_wraps_at_oam_addr() {
+        let mut bus = get_bus();
+        bus.ram[0x300] = 0xAB;
+        bus.write(0x2003, 0x10);
+        bus.write(0x4014, 0x03);
+        bus.write(0x2003, 0x10);
+        assert_eq!(bus.read_8(0x2004), 0xAB);
+    }
+}
+
+#[cfg(test)]
+mod open_bus {
+    use super::*;
+
+    /// a bit of the latch fades after about 600ms
+    const DECAY_FRAMES: usize = 36;
+
+    fn run_frames(ppu: &mut Ppu, frames: usize) {
+        for _ in 0..frames {
+            run_scanlines(ppu, 0..=261);
+        }
+    }
+
+    #[test]
+    fn write_only_registers_decay() {
+        let mut ppu = get_ppu();
+        ppu.set_register(3, 0xA5);
+        assert_eq!(ppu.register(5), 0xA5);
+        run_frames(&mut ppu, DECAY_FRAMES - 1);
+        // reading doesn't refresh it
+        assert_eq!(ppu.register(0), 0xA5);
+        assert_eq!(ppu.peek_register(0), 0xA5);
+        run_frames(&mut ppu, 1);
+        assert_eq!(ppu.peek_register(0), 0);
+        assert_eq!(ppu.register(0), 0);
+    }
+
+    #[test]
+    fn ppustatus_refreshes_the_top_bits() {
+        let mut ppu = get_ppu();
+        ppu.set_register(3, 0xFF);
+        run_frames(&mut ppu, DECAY_FRAMES / 2);
+        let status = ppu.register(2);
+        assert_eq!(status & 0b0001_1111, 0b0001_1111);
+        run_frames(&mut ppu, DECAY_FRAMES / 2 + 1);
+        assert_eq!(ppu.register(0), status & 0b1110_0000);
+    }
+
+    #[test]
+    fn palette_reads_keep_the_top_bits() {
+        let mut ppu = get_ppu();
+        write_vram(&mut ppu, 0x3F01, &[0x2A]);
+        set_addr(&mut ppu, 0x3F01);
+        // only moves t
+        ppu.set_register(5, 0xC0);
+        assert_eq!(ppu.register(7), 0xC0 | 0x2A);
+        assert_eq!(ppu.register(0), 0xC0 | 0x2A);
+    }
+}
@@ -0,0 +1,155 @@
+use bunNES::nes::ppu::{Ppu, SCREEN_WIDTH};
+use crate::ppu::helpers::{get_ppu, run_scanlines, write_vram};
+
+const BACKDROP: u8 = 0x0F;
+const BACKGROUND_COLOR: u8 = 0x30;
+const SPRITE_COLOR: u8 = 0x16;
+
+/// tile 1 is solid color 1, tile 2 only has its leftmost column set.
+/// nametable 0 starts with tile 1
+fn setup() -> Ppu {
+    let mut ppu = get_ppu();
+    write_vram(&mut ppu, 0x0010, &[0xFF; 8]);
+    write_vram(&mut ppu, 0x0020, &[0x80; 8]);
+    write_vram(&mut ppu, 0x2000, &[0x01]);
+    write_vram(&mut ppu, 0x3F00, &[BACKDROP, BACKGROUND_COLOR]);
+    write_vram(&mut ppu, 0x3F11, &[SPRITE_COLOR]);
+    // scroll to the top left
+    ppu.set_register(0, 0);
+    ppu.set_register(5, 0);
+    ppu.set_register(5, 0);
+    ppu
+}
+
+fn render(ppu: &mut Ppu) {
+    run_scanlines(ppu, 261..=261);
+    run_scanlines(ppu, 0..=239);
+}
+
+fn sprite(ppu: &mut Ppu, index: u8, y: u8, tile: u8, attributes: u8, x: u8) {
+    ppu.set_register(3, index * 4);
+    for value in [y, tile, attributes, x] {
+        ppu.set_register(4, value);
+    }
+}
+
+#[cfg(test)]
+mod background {
+    use super::*;
+
+    #[test]
+    fn tile() {
+        let mut ppu = setup();
+        // background including the leftmost 8 pixels
+        ppu.set_register(1, 0b0000_1010);
+        render(&mut ppu);
+
+        let frame = ppu.frame_buffer();
+        assert!(frame[0..8].iter().all(|color| *color == BACKGROUND_COLOR));
+        assert_eq!(frame[8], BACKDROP);
+        assert_eq!(frame[7 * SCREEN_WIDTH + 7], BACKGROUND_COLOR);
+        assert_eq!(frame[8 * SCREEN_WIDTH], BACKDROP);
+    }
+
+    #[test]
+    fn leftmost_hidden() {
+        let mut ppu = setup();
+        ppu.set_register(1, 0b0000_1000);
+        render(&mut ppu);
+        assert_eq!(ppu.frame_buffer()[0], BACKDROP);
+    }
+
+    #[test]
+    fn fine_x_scroll() {
+        let mut ppu = setup();
+        ppu.set_register(1, 0b0000_1010);
+        ppu.set_register(5, 3);
+        ppu.set_register(5, 0);
+        render(&mut ppu);
+
+        let frame = ppu.frame_buffer();
+        assert_eq!(frame[4], BACKGROUND_COLOR);
+        assert_eq!(frame[5], BACKDROP);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut ppu = setup();
+        render(&mut ppu);
+        assert!(ppu.frame_buffer().iter().all(|color| *color == BACKDROP));
+    }
+}
+
+#[cfg(test)]
+mod sprites {
+    use super::*;
+
+    #[test]
+    fn drawn_below_y() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 20, 1, 0, 30);
+        ppu.set_register(1, 0b0001_0100);
+        render(&mut ppu);
+
+        let frame = ppu.frame_buffer();
+        assert_eq!(frame[20 * SCREEN_WIDTH + 30], BACKDROP);
+        assert_eq!(frame[21 * SCREEN_WIDTH + 30], SPRITE_COLOR);
+        assert_eq!(frame[28 * SCREEN_WIDTH + 37], SPRITE_COLOR);
+        assert_eq!(frame[29 * SCREEN_WIDTH + 30], BACKDROP);
+    }
+
+    #[test]
+    fn flip_horizontal() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 20, 2, 0b0100_0000, 30);
+        ppu.set_register(1, 0b0001_0100);
+        render(&mut ppu);
+
+        let frame = ppu.frame_buffer();
+        assert_eq!(frame[21 * SCREEN_WIDTH + 30], BACKDROP);
+        assert_eq!(frame[21 * SCREEN_WIDTH + 37], SPRITE_COLOR);
+    }
+
+    #[test]
+    fn behind_background() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 0, 1, 0b0010_0000, 4);
+        ppu.set_register(1, 0b0001_1110);
+        render(&mut ppu);
+
+        let frame = ppu.frame_buffer();
+        assert_eq!(frame[SCREEN_WIDTH + 4], BACKGROUND_COLOR);
+        assert_eq!(frame[SCREEN_WIDTH + 8], SPRITE_COLOR);
+    }
+
+    #[test]
+    fn sprite_0_hit() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 0, 1, 0, 4);
+        ppu.set_register(1, 0b0001_1110);
+        render(&mut ppu);
+        assert_eq!(ppu.register(2) & 0b0100_0000, 0b0100_0000);
+    }
+
+    #[test]
+    fn no_sprite_0_hit_on_transparent_background() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 20, 1, 0, 30);
+        ppu.set_register(1, 0b0001_1110);
+        render(&mut ppu);
+        assert_eq!(ppu.register(2) & 0b0100_0000, 0);
+    }
+
+    #[test]
+    fn overflow() {
+        let mut ppu = setup();
+        for i in 0..9 {
+            sprite(&mut ppu, i, 50, 1, 0, i * 10);
+        }
+        ppu.set_register(1, 0b0001_0100);
+        render(&mut ppu);
+        assert_eq!(ppu.register(2) & 0b0010_0000, 0b0010_0000);
+        // the ninth sprite isn't drawn
+        assert_eq!(ppu.frame_buffer()[51 * SCREEN_WIDTH + 80], BACKDROP);
+    }
+}
@@ -0,0 +1,220 @@
+use bunNES::nes::palette;
+use bunNES::nes::ppu::Ppu;
+use bunNES::nes::ppu_viewer::{self, Image};
+use crate::ppu::helpers::{get_ppu, write_vram};
+
+const BACKDROP: u8 = 0x0F;
+
+/// tile 1 is color 1 on the left half and color 2 on the right half, its top row is color 3
+fn setup() -> Ppu {
+    let mut ppu = get_ppu();
+    write_vram(&mut ppu, 0x0010, &[0xFF, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0, 0xF0]);
+    write_vram(&mut ppu, 0x0018, &[0xFF, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F, 0x0F]);
+    write_vram(&mut ppu, 0x3F00, &[BACKDROP, 0x01, 0x02, 0x03, BACKDROP, 0x05, 0x06, 0x07]);
+    write_vram(&mut ppu, 0x3F11, &[0x11, 0x12, 0x13]);
+    ppu
+}
+
+/// also picks the nametable the scroll starts in
+fn set_scroll(ppu: &mut Ppu, ctrl: u8, x: u8, y: u8) {
+    ppu.set_register(0, ctrl);
+    ppu.set_register(5, x);
+    ppu.set_register(5, y);
+}
+
+fn color(image: &Image, x: usize, y: usize) -> [u8; 3] {
+    let [r, g, b, a] = image.pixel(x, y);
+    assert_eq!(a, 255);
+    [r, g, b]
+}
+
+#[cfg(test)]
+mod pattern_tables {
+    use super::*;
+
+    #[test]
+    fn tiles() {
+        let ppu = setup();
+        let image = ppu_viewer::pattern_table(&ppu, 0, 0);
+        assert_eq!((image.width, image.height), (128, 128));
+        assert_eq!(color(&image, 0, 0), palette::rgb(BACKDROP));
+        // tile 1 is right of tile 0
+        assert_eq!(color(&image, 8, 0), palette::rgb(0x03));
+        assert_eq!(color(&image, 8, 1), palette::rgb(0x01));
+        assert_eq!(color(&image, 15, 1), palette::rgb(0x02));
+    }
+
+    #[test]
+    fn palettes() {
+        let ppu = setup();
+        let image = ppu_viewer::pattern_table(&ppu, 0, 1);
+        assert_eq!(color(&image, 8, 1), palette::rgb(0x05));
+        // the backdrop in every palette
+        assert_eq!(color(&image, 0, 0), palette::rgb(BACKDROP));
+
+        let image = ppu_viewer::pattern_table(&ppu, 0, 4);
+        assert_eq!(color(&image, 8, 0), palette::rgb(0x13));
+    }
+
+    #[test]
+    fn second_table() {
+        let mut ppu = setup();
+        write_vram(&mut ppu, 0x1000, &[0x80]);
+        let image = ppu_viewer::pattern_table(&ppu, 1, 0);
+        assert_eq!(color(&image, 0, 0), palette::rgb(0x01));
+        assert_eq!(color(&image, 8, 0), palette::rgb(BACKDROP));
+    }
+}
+
+#[cfg(test)]
+mod nametables {
+    use super::*;
+
+    #[test]
+    fn mirroring() {
+        let mut ppu = setup();
+        write_vram(&mut ppu, 0x2001, &[0x01]);
+        write_vram(&mut ppu, 0x2801, &[0x01]);
+        // keeps the scroll window out of the way
+        set_scroll(&mut ppu, 0, 4, 4);
+        let image = ppu_viewer::nametables(&ppu);
+        assert_eq!((image.width, image.height), (512, 480));
+
+        // horizontal mirroring, $2400 is $2000
+        for left in [8, 256 + 8] {
+            assert_eq!(color(&image, left, 0), palette::rgb(0x03));
+            assert_eq!(color(&image, left + 7, 1), palette::rgb(0x02));
+        }
+        assert_eq!(color(&image, 8, 240), palette::rgb(0x03));
+        assert_eq!(color(&image, 16, 1), palette::rgb(BACKDROP));
+    }
+
+    #[test]
+    fn attributes() {
+        let mut ppu = setup();
+        write_vram(&mut ppu, 0x2021, &[0x01]);
+        // bottom left quadrant of the first attribute byte uses palette 1
+        write_vram(&mut ppu, 0x23C0, &[0b0001_0000]);
+        write_vram(&mut ppu, 0x2041, &[0x01]);
+        set_scroll(&mut ppu, 0, 4, 4);
+        let image = ppu_viewer::nametables(&ppu);
+        assert_eq!(color(&image, 8, 9), palette::rgb(0x01));
+        assert_eq!(color(&image, 8, 17), palette::rgb(0x05));
+    }
+
+    #[test]
+    fn background_pattern_table() {
+        let mut ppu = setup();
+        write_vram(&mut ppu, 0x1010, &[0xFF]);
+        write_vram(&mut ppu, 0x2001, &[0x01]);
+        set_scroll(&mut ppu, 0b0001_0000, 4, 4);
+        let image = ppu_viewer::nametables(&ppu);
+        assert_eq!(color(&image, 8, 0), palette::rgb(0x01));
+        assert_eq!(color(&image, 8, 1), palette::rgb(BACKDROP));
+    }
+
+    #[test]
+    fn scroll_window() {
+        let mut ppu = setup();
+        // x 300 and y 100 in nametable 1
+        set_scroll(&mut ppu, 0b0000_0001, 44, 100);
+        assert_eq!(ppu.scroll(), (300, 100));
+
+        let image = ppu_viewer::nametables(&ppu);
+        let outline = [255, 0, 255, 255];
+        assert_eq!(image.pixel(300, 100), outline);
+        assert_eq!(image.pixel(511, 100), outline);
+        // wraps around to the left edge
+        assert_eq!(image.pixel(0, 100), outline);
+        assert_eq!(image.pixel(300 + 255 - 512, 100 + 239), outline);
+        assert_eq!(image.pixel(43, 150), outline);
+        assert_ne!(image.pixel(44, 150), outline);
+        assert_ne!(image.pixel(299, 150), outline);
+    }
+}
+
+#[cfg(test)]
+mod palette_ram {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let ppu = setup();
+        let image = ppu_viewer::palette_ram(&ppu);
+        assert_eq!((image.width, image.height), (16, 2));
+        assert_eq!(color(&image, 1, 0), palette::rgb(0x01));
+        assert_eq!(color(&image, 2, 1), palette::rgb(0x12));
+        // $3F10 mirrors $3F00
+        assert_eq!(color(&image, 0, 1), palette::rgb(BACKDROP));
+    }
+}
+
+#[cfg(test)]
+mod sprites {
+    use super::*;
+
+    fn sprite(ppu: &mut Ppu, index: u8, y: u8, tile: u8, attributes: u8, x: u8) {
+        ppu.set_register(3, index * 4);
+        for value in [y, tile, attributes, x] {
+            ppu.set_register(4, value);
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 3, 0x40, 0x01, 0b1110_0010, 0x80);
+        let sprites = ppu_viewer::sprites(&ppu);
+        assert_eq!(sprites.len(), 64);
+
+        let sprite = &sprites[3];
+        assert_eq!((sprite.index, sprite.x, sprite.y, sprite.tile), (3, 0x80, 0x40, 0x01));
+        assert_eq!(sprite.palette, 2);
+        assert!(sprite.behind_background);
+        assert!(sprite.flip_horizontal);
+        assert!(sprite.flip_vertical);
+    }
+
+    #[test]
+    fn image() {
+        let mut ppu = setup();
+        sprite(&mut ppu, 0, 0, 0x01, 0b0000_0000, 0);
+        sprite(&mut ppu, 1, 0, 0x01, 0b1100_0000, 0);
+        let sprites = ppu_viewer::sprites(&ppu);
+
+        let image = &sprites[0].image;
+        assert_eq!((image.width, image.height), (8, 8));
+        assert_eq!(color(image, 0, 0), palette::rgb(0x13));
+        assert_eq!(color(image, 0, 1), palette::rgb(0x11));
+        assert_eq!(color(image, 7, 1), palette::rgb(0x12));
+
+        // flipped both ways
+        let image = &sprites[1].image;
+        assert_eq!(color(image, 0, 7), palette::rgb(0x13));
+        assert_eq!(color(image, 0, 0), palette::rgb(0x12));
+    }
+
+    #[test]
+    fn transparent() {
+        let mut ppu = setup();
+        write_vram(&mut ppu, 0x0020, &[0x80]);
+        sprite(&mut ppu, 0, 0, 0x02, 0, 0);
+        let image = &ppu_viewer::sprites(&ppu)[0].image;
+        assert_eq!(image.pixel(0, 0)[3], 255);
+        assert_eq!(image.pixel(1, 0), [0; 4]);
+    }
+
+    #[test]
+    fn tall_sprites() {
+        let mut ppu = setup();
+        // the bottom half is the next tile, from the pattern table in bit 0
+        write_vram(&mut ppu, 0x1010, &[0xFF]);
+        ppu.set_register(0, 0b0010_0000);
+        sprite(&mut ppu, 0, 0, 0x01, 0, 0);
+        let image = &ppu_viewer::sprites(&ppu)[0].image;
+        assert_eq!((image.width, image.height), (8, 16));
+        assert_eq!(image.pixel(0, 0), [0; 4]);
+        assert_eq!(color(image, 0, 8), palette::rgb(0x11));
+        assert_eq!(image.pixel(0, 9), [0; 4]);
+    }
+}
@@ -0,0 +1,99 @@
+// Klaus Dormann's 6502 functional test runs on a plain 6502 with 64k of ram. it loops on
+// the spot when a test fails and at $3469 once every test passed
+// https://github.com/Klaus2m5/6502_65C02_functional_tests
+//
+// point BUNNES_6502_TESTS at the bin_files directory of a checkout and run the ignored tests
+
+use bunNES::nes::cpu::{Cpu, CpuBus, Variant};
+use bunNES::nes::ram_bus::RamBus;
+use std::path::PathBuf;
+
+const TESTS_ENV: &str = "BUNNES_6502_TESTS";
+const START: u16 = 0x0400;
+const SUCCESS: u16 = 0x3469;
+/// a full run is about 30 million instructions
+const TIMEOUT_INSTRUCTIONS: u64 = 100_000_000;
+
+fn flat_cpu(image: Vec<u8>, variant: Variant) -> Cpu<RamBus> {
+    let mut cpu = Cpu::with_bus(RamBus::with_memory(image), variant);
+    cpu.pc = START;
+    cpu
+}
+
+/// runs until an instruction jumps or branches to itself, returns where
+fn run_until_trap(cpu: &mut Cpu<RamBus>) -> u16 {
+    for _ in 0..TIMEOUT_INSTRUCTIONS {
+        let pc = cpu.pc;
+        while !cpu.step() {}
+        if cpu.pc == pc {
+            return pc;
+        }
+    }
+    panic!("no trap after {TIMEOUT_INSTRUCTIONS} instructions, at ${:04X}", cpu.pc);
+}
+
+/// decimal adc through a subroutine, traps at $040A when the sum is right, else at $040D
+fn decimal_program() -> Vec<u8> {
+    let mut image = vec![0; START as usize];
+    image.extend([
+        0xF8,             // $0400 sed
+        0x18,             // $0401 clc
+        0xA9, 0x58,       // $0402 lda #$58
+        0x69, 0x46,       // $0404 adc #$46
+        0xD8,             // $0406 cld
+        0x20, 0x10, 0x04, // $0407 jsr check
+        0x4C, 0x0A, 0x04, // $040A jmp to itself, passed
+        0x4C, 0x0D, 0x04, // $040D jmp to itself, failed
+        // check
+        0xC9, 0x04,       // $0410 cmp #$04
+        0xD0, 0xF9,       // $0412 bne failed
+        0x60,             // $0414 rts
+    ]);
+    image
+}
+
+#[cfg(test)]
+mod ram_bus {
+    use super::*;
+
+    #[test]
+    fn ram_everywhere() {
+        let mut bus = RamBus::with_memory(vec![0xEA; 3]);
+        assert_eq!(bus.ram[..4], [0xEA, 0xEA, 0xEA, 0x00]);
+        for addr in [0x0000, 0x0800, 0x2002, 0x4015, 0x4016, 0x5000, 0x8000, 0xFFFF] {
+            bus.write(addr, addr as u8 ^ 0x5A);
+            assert_eq!(bus.read_8(addr), addr as u8 ^ 0x5A);
+        }
+        assert_eq!(bus.read_16(0xFFFF), u16::from_le_bytes([0xFF ^ 0x5A, 0x5A]));
+        assert!(!bus.nmi());
+        assert!(!bus.irq());
+    }
+
+    #[test]
+    fn traps() {
+        let mut cpu = flat_cpu(decimal_program(), Variant::Nmos6502);
+        assert_eq!(run_until_trap(&mut cpu), 0x040A);
+        assert_eq!(cpu.sp, 0xFF);
+
+        // binary adc on the nes cpu
+        let mut cpu = flat_cpu(decimal_program(), Variant::Ricoh2A03);
+        assert_eq!(run_until_trap(&mut cpu), 0x040D);
+    }
+}
+
+#[cfg(test)]
+mod functional {
+    use super::*;
+
+    #[test]
+    #[ignore = "needs BUNNES_6502_TESTS"]
+    fn functional_test() {
+        let dir = std::env::var_os(TESTS_ENV).unwrap_or_else(|| panic!("{TESTS_ENV} isn't set"));
+        let path = PathBuf::from(dir).join("6502_functional_test.bin");
+        let image = std::fs::read(&path).unwrap_or_else(|e| panic!("couldn't read {}: {e}", path.display()));
+
+        let mut cpu = flat_cpu(image, Variant::Nmos6502);
+        let trap = run_until_trap(&mut cpu);
+        assert!(trap == SUCCESS, "trapped at ${trap:04X}, look it up in the listing for the failed test");
+    }
+}
@@ -0,0 +1,101 @@
+// blargg style test roms report through cartridge ram:
+//   $6000     status, $80 while running, $81 when the reset button has to be pressed, else the result code
+//   $6001-3   DE B0 61 once the status is valid
+//   $6004     zero terminated text, the message shown on screen
+// https://github.com/christopherpow/nes-test-roms
+//
+// the roms aren't committed, point BUNNES_TEST_ROMS at a checkout of the collection above
+// and run the ignored tests
+
+#[macro_use]
+pub(crate) mod helpers {
+    use bunNES::emulator::Emulator;
+    use bunNES::nes::rom::Cartridge;
+    use std::path::PathBuf;
+
+    pub const ROMS_ENV: &str = "BUNNES_TEST_ROMS";
+
+    const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+    const STATUS_RUNNING: u8 = 0x80;
+    const STATUS_RESET: u8 = 0x81;
+    /// roms want at least 100ms before the reset
+    const RESET_DELAY_FRAMES: u32 = 6;
+    /// the slowest roms need about 20 seconds
+    const TIMEOUT_FRAMES: u32 = 60 * 60;
+
+    /// runs a freshly reset test rom to the end, the text it printed on success or the code and
+    /// text on failure
+    pub fn run_test_rom(mut emulator: Emulator) -> Result<String, String> {
+        let mut reset_in = None;
+        for _ in 0..TIMEOUT_FRAMES {
+            emulator.run_frame();
+
+            if (0..3).any(|i| emulator.cpu.bus.peek(0x6001 + i) != SIGNATURE[i as usize]) {
+                continue;
+            }
+            match emulator.cpu.bus.peek(0x6000) {
+                STATUS_RUNNING => {}
+                STATUS_RESET => {
+                    match reset_in {
+                        Some(0) => {
+                            emulator.reset();
+                            reset_in = None;
+                        }
+                        Some(frames) => reset_in = Some(frames - 1),
+                        None => reset_in = Some(RESET_DELAY_FRAMES),
+                    }
+                }
+                0 => return Ok(message(&emulator)),
+                code => return Err(format!("failed with code {code}:\n{}", message(&emulator))),
+            }
+        }
+        Err(format!("timed out after {TIMEOUT_FRAMES} frames:\n{}", message(&emulator)))
+    }
+
+    fn message(emulator: &Emulator) -> String {
+        let mut text = Vec::new();
+        for addr in 0x6004..0x8000 {
+            match emulator.cpu.bus.peek(addr) {
+                0 => break,
+                byte => text.push(byte),
+            }
+        }
+        String::from_utf8_lossy(&text).trim().to_string()
+    }
+
+    /// runs `path` relative to $BUNNES_TEST_ROMS
+    pub fn run_test_rom_file(path: &str) {
+        let dir = std::env::var_os(ROMS_ENV).unwrap_or_else(|| panic!("{ROMS_ENV} isn't set"));
+        let path = PathBuf::from(dir).join(path);
+        let bytes = std::fs::read(&path).unwrap_or_else(|e| panic!("couldn't read {}: {e}", path.display()));
+
+        let cartridge = Cartridge::new(bytes);
+        assert_eq!(cartridge.mapper(), 0, "{}: mapper {} isn't supported", path.display(), cartridge.mapper());
+        let mut emulator = Emulator::new(cartridge);
+        emulator.reset();
+        if let Err(e) = run_test_rom(emulator) {
+            panic!("{}: {e}", path.display());
+        }
+    }
+
+    /// an ignored test per rom, `name => "path/relative/to/the/collection.nes"`.
+    /// `ignore = "reason";` first when the roms can't pass yet, e.g. for a missing mapper
+    macro_rules! test_roms {
+        (ignore = $reason:literal; $($name:ident => $path:expr),* $(,)?) => {
+            $(
+                #[test]
+                #[ignore = $reason]
+                fn $name() {
+                    $crate::test_roms::helpers::run_test_rom_file($path);
+                }
+            )*
+        };
+        ($($name:ident => $path:expr),* $(,)?) => {
+            test_roms! { ignore = "needs BUNNES_TEST_ROMS"; $($name => $path),* }
+        };
+    }
+}
+
+mod dormann;
+mod protocol;
+mod suites;
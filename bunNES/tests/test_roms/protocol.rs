@@ -0,0 +1,82 @@
+use super::helpers::*;
+use crate::emulator::helpers::get_emulator;
+
+/// lda #value, sta addr
+fn store(code: &mut Vec<u8>, addr: u16, value: u8) {
+    code.extend([0xA9, value, 0x8D, addr as u8, (addr >> 8) as u8]);
+}
+
+/// writes `text` and the signature, the status last like the real roms
+fn report(code: &mut Vec<u8>, status: u8, text: &str) {
+    for (i, byte) in text.bytes().chain([0]).enumerate() {
+        store(code, 0x6004 + i as u16, byte);
+    }
+    for (i, byte) in [0xDE, 0xB0, 0x61].into_iter().enumerate() {
+        store(code, 0x6001 + i as u16, byte);
+    }
+    store(code, 0x6000, status);
+}
+
+/// lda #1, bne to itself
+fn idle(code: &mut Vec<u8>) {
+    code.extend([0xA9, 0x01, 0xD0, 0xFE]);
+}
+
+#[cfg(test)]
+mod results {
+    use super::*;
+
+    #[test]
+    fn passed() {
+        let mut code = vec![];
+        store(&mut code, 0x6000, 0x80);
+        report(&mut code, 0, "ok");
+        idle(&mut code);
+
+        assert_eq!(run_test_rom(get_emulator(code)), Ok("ok".to_string()));
+    }
+
+    #[test]
+    fn failed() {
+        let mut code = vec![];
+        report(&mut code, 3, "  wrong flags\n");
+        idle(&mut code);
+
+        let error = run_test_rom(get_emulator(code)).unwrap_err();
+        assert!(error.contains("code 3"), "{error}");
+        assert!(error.ends_with("wrong flags"), "{error}");
+    }
+
+    #[test]
+    fn running_without_signature_is_ignored() {
+        let mut code = vec![];
+        // a result without signature, left over from whatever ran before
+        store(&mut code, 0x6000, 1);
+        // a few frames of busy work
+        code.extend([0xE8, 0xD0, 0xFD]); // inx, bne -3
+        code.extend([0xC8, 0xD0, 0xFA]); // iny, bne -6
+        report(&mut code, 0, "done");
+        idle(&mut code);
+
+        assert_eq!(run_test_rom(get_emulator(code)), Ok("done".to_string()));
+    }
+
+    #[test]
+    fn reset_needed() {
+        let mut after_reset = vec![];
+        report(&mut after_reset, 0, "reset");
+        idle(&mut after_reset);
+
+        let mut first_boot = vec![];
+        store(&mut first_boot, 0x6010, 1);
+        report(&mut first_boot, 0x81, "press reset");
+        idle(&mut first_boot);
+
+        // lda $6010, bne after_reset
+        let mut code = vec![0xAD, 0x10, 0x60, 0xD0, first_boot.len() as u8];
+        code.extend(first_boot);
+        code.extend(after_reset);
+
+        assert_eq!(run_test_rom(get_emulator(code)), Ok("reset".to_string()));
+    }
+}
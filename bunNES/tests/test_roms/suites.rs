@@ -0,0 +1,75 @@
+// cpu_timing_test6, sprite_overflow_tests and the old sprite_hit_tests only report on screen,
+// instr_timing covers the cpu timing through $6000
+
+#[cfg(test)]
+mod instr_test_v5 {
+    test_roms! {
+        basics => "instr_test-v5/rom_singles/01-basics.nes",
+        implied => "instr_test-v5/rom_singles/02-implied.nes",
+        immediate => "instr_test-v5/rom_singles/03-immediate.nes",
+        zero_page => "instr_test-v5/rom_singles/04-zero_page.nes",
+        zp_xy => "instr_test-v5/rom_singles/05-zp_xy.nes",
+        absolute => "instr_test-v5/rom_singles/06-absolute.nes",
+        abs_xy => "instr_test-v5/rom_singles/07-abs_xy.nes",
+        ind_x => "instr_test-v5/rom_singles/08-ind_x.nes",
+        ind_y => "instr_test-v5/rom_singles/09-ind_y.nes",
+        branches => "instr_test-v5/rom_singles/10-branches.nes",
+        stack => "instr_test-v5/rom_singles/11-stack.nes",
+        jmp_jsr => "instr_test-v5/rom_singles/12-jmp_jsr.nes",
+        rts => "instr_test-v5/rom_singles/13-rts.nes",
+        rti => "instr_test-v5/rom_singles/14-rti.nes",
+        brk => "instr_test-v5/rom_singles/15-brk.nes",
+        special => "instr_test-v5/rom_singles/16-special.nes",
+    }
+}
+
+#[cfg(test)]
+mod instr_timing {
+    test_roms! {
+        instr_timing => "instr_timing/rom_singles/1-instr_timing.nes",
+        branch_timing => "instr_timing/rom_singles/2-branch_timing.nes",
+    }
+}
+
+#[cfg(test)]
+mod ppu_vbl_nmi {
+    test_roms! {
+        vbl_basics => "ppu_vbl_nmi/rom_singles/01-vbl_basics.nes",
+        vbl_set_time => "ppu_vbl_nmi/rom_singles/02-vbl_set_time.nes",
+        vbl_clear_time => "ppu_vbl_nmi/rom_singles/03-vbl_clear_time.nes",
+        nmi_control => "ppu_vbl_nmi/rom_singles/04-nmi_control.nes",
+        nmi_timing => "ppu_vbl_nmi/rom_singles/05-nmi_timing.nes",
+        suppression => "ppu_vbl_nmi/rom_singles/06-suppression.nes",
+        nmi_on_timing => "ppu_vbl_nmi/rom_singles/07-nmi_on_timing.nes",
+        nmi_off_timing => "ppu_vbl_nmi/rom_singles/08-nmi_off_timing.nes",
+        even_odd_frames => "ppu_vbl_nmi/rom_singles/09-even_odd_frames.nes",
+        even_odd_timing => "ppu_vbl_nmi/rom_singles/10-even_odd_timing.nes",
+    }
+}
+
+#[cfg(test)]
+mod apu_test {
+    test_roms! {
+        len_ctr => "apu_test/rom_singles/1-len_ctr.nes",
+        len_table => "apu_test/rom_singles/2-len_table.nes",
+        irq_flag => "apu_test/rom_singles/3-irq_flag.nes",
+        jitter => "apu_test/rom_singles/4-jitter.nes",
+        len_timing => "apu_test/rom_singles/5-len_timing.nes",
+        irq_flag_timing => "apu_test/rom_singles/6-irq_flag_timing.nes",
+        dmc_basics => "apu_test/rom_singles/7-dmc_basics.nes",
+        dmc_rates => "apu_test/rom_singles/8-dmc_rates.nes",
+    }
+}
+
+#[cfg(test)]
+mod mmc3_test {
+    test_roms! {
+        ignore = "needs mapper 4";
+        clocking => "mmc3_test_2/rom_singles/1-clocking.nes",
+        details => "mmc3_test_2/rom_singles/2-details.nes",
+        a12_clocking => "mmc3_test_2/rom_singles/3-A12_clocking.nes",
+        scanline_timing => "mmc3_test_2/rom_singles/4-scanline_timing.nes",
+        mmc3 => "mmc3_test_2/rom_singles/5-MMC3.nes",
+        mmc3_alt => "mmc3_test_2/rom_singles/6-MMC3_alt.nes",
+    }
+}
@@ -0,0 +1,283 @@
+use bunNES::emulator::Emulator;
+use bunNES::gdb::{self, GdbStub};
+use bunNES::movie::Movie;
+use bunNES::nes::cpu::Cpu;
+use bunNES::nes::disasm::{self, Syntax};
+use bunNES::nes::rom::Cartridge;
+use bunNES::wav::WavWriter;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+const USAGE: &str = "usage: bunnes-cli <rom> [options]
+
+runs a rom without a window, stops after --frames or as soon as an --until condition is met
+
+options:
+  --frames N             frames to run at most (default 600)
+  --until-pc ADDR        stop when the cpu is about to execute ADDR
+  --until-mem ADDR=VALUE stop when the byte at ADDR equals VALUE
+  --until-loop           stop at an instruction jumping to itself
+  --movie FILE           play back an fm2 movie
+  --screenshot FILE      last frame as png
+  --audio FILE           16 bit wav of the audio output
+  --ram FILE             the 2k of internal ram
+  --trace FILE           one line per executed instruction
+  --gdb PORT             wait for a gdb client on localhost:PORT before running, the
+                         frames run after it detaches
+
+numbers are decimal, or hex with a $ or 0x prefix.
+exits with 0 when done, 1 when an --until condition wasn't met and 2 on errors";
+
+const SAMPLE_RATE: u32 = 44100;
+
+/// all frames ran or an --until condition was met
+pub const EXIT_DONE: u8 = 0;
+/// all frames ran without meeting the --until conditions
+pub const EXIT_NOT_MET: u8 = 1;
+/// bad arguments or the rom couldn't be run
+pub const EXIT_ERROR: u8 = 2;
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub rom: PathBuf,
+    pub frames: u32,
+    pub until_pc: Option<u16>,
+    pub until_mem: Option<(u16, u8)>,
+    pub until_loop: bool,
+    pub movie: Option<PathBuf>,
+    pub screenshot: Option<PathBuf>,
+    pub audio: Option<PathBuf>,
+    pub ram: Option<PathBuf>,
+    pub trace: Option<PathBuf>,
+    pub gdb: Option<u16>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+        let mut options = Options { frames: 600, ..Options::default() };
+        let mut rom = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--frames" => options.frames = parse_number(&value()?)?,
+                "--until-pc" => options.until_pc = Some(parse_number(&value()?)?),
+                "--until-mem" => {
+                    let value = value()?;
+                    let (addr, expected) = value.split_once('=').ok_or("--until-mem expects ADDR=VALUE")?;
+                    options.until_mem = Some((parse_number(addr)?, parse_number(expected)?));
+                }
+                "--until-loop" => options.until_loop = true,
+                "--movie" => options.movie = Some(value()?.into()),
+                "--screenshot" => options.screenshot = Some(value()?.into()),
+                "--audio" => options.audio = Some(value()?.into()),
+                "--ram" => options.ram = Some(value()?.into()),
+                "--trace" => options.trace = Some(value()?.into()),
+                "--gdb" => options.gdb = Some(parse_number(&value()?)?),
+                "-h" | "--help" => return Err(String::new()),
+                _ if arg.starts_with('-') => return Err(format!("unknown option {arg}")),
+                _ if rom.is_none() => rom = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument {arg}")),
+            }
+        }
+
+        options.rom = rom.ok_or("no rom given")?;
+        Ok(options)
+    }
+
+    pub fn has_condition(&self) -> bool {
+        self.until_pc.is_some() || self.until_mem.is_some() || self.until_loop
+    }
+}
+
+pub fn parse_number<T: TryFrom<u32>>(text: &str) -> Result<T, String> {
+    let value = if let Some(hex) = text.strip_prefix('$').or_else(|| text.strip_prefix("0x")) {
+        u32::from_str_radix(hex, 16)
+    } else {
+        text.parse()
+    };
+    value.ok().and_then(|value| T::try_from(value).ok()).ok_or_else(|| format!("invalid number {text}"))
+}
+
+/// why the run ended
+#[derive(Debug, Eq, PartialEq)]
+pub enum Stop {
+    Frames,
+    Pc(u16),
+    Memory(u16, u8),
+    Loop(u16),
+}
+
+impl Display for Stop {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stop::Frames => write!(f, "ran all frames"),
+            Stop::Pc(pc) => write!(f, "reached pc ${pc:04X}"),
+            Stop::Memory(addr, value) => write!(f, "${addr:04X} is ${value:02X}"),
+            Stop::Loop(pc) => write!(f, "infinite loop at ${pc:04X}"),
+        }
+    }
+}
+
+/// registers and ppu position before the instruction the cpu is about to execute
+#[derive(Debug, Copy, Clone)]
+struct Registers {
+    pc: u16,
+    acc: u8,
+    x: u8,
+    y: u8,
+    ps: u8,
+    sp: u8,
+    scanline: u16,
+    dot: u16,
+}
+
+impl Registers {
+    fn of(cpu: &Cpu) -> Registers {
+        Registers {
+            pc: cpu.pc,
+            acc: cpu.acc,
+            x: cpu.x,
+            y: cpu.y,
+            ps: cpu.ps.get_reg(),
+            sp: cpu.sp,
+            scanline: cpu.bus.ppu.scanline(),
+            dot: cpu.bus.ppu.dot(),
+        }
+    }
+}
+
+/// parses `args`, the program name already skipped, runs the rom and returns the exit code
+pub fn run_cli(args: impl Iterator<Item = String>) -> u8 {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            if !e.is_empty() {
+                eprintln!("{e}\n");
+            }
+            eprintln!("{USAGE}");
+            return EXIT_ERROR;
+        }
+    };
+
+    match run(&options) {
+        Ok(Stop::Frames) if options.has_condition() => {
+            eprintln!("condition not met after {} frames", options.frames);
+            EXIT_NOT_MET
+        }
+        Ok(stop) => {
+            println!("{stop}");
+            EXIT_DONE
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            EXIT_ERROR
+        }
+    }
+}
+
+pub fn run(options: &Options) -> Result<Stop, Box<dyn Error>> {
+    let rom = fs::read(&options.rom).map_err(|e| format!("couldn't read {}: {e}", options.rom.display()))?;
+    let mut emulator = Emulator::new(Cartridge::new(rom));
+    emulator.reset();
+    emulator.set_sample_rate(SAMPLE_RATE);
+
+    if let Some(path) = &options.movie {
+        emulator.play_movie(Movie::load(path)?)?;
+    }
+    if let Some(port) = options.gdb {
+        let listener = gdb::listen(port)?;
+        eprintln!("waiting for gdb on {}", listener.local_addr()?);
+        let (stream, _) = listener.accept()?;
+        GdbStub::new(&mut emulator, stream).run()?;
+    }
+
+    let mut audio = match &options.audio {
+        Some(path) => Some(WavWriter::create(path, SAMPLE_RATE)?),
+        None => None,
+    };
+    let mut trace = match &options.trace {
+        Some(path) => Some(BufWriter::new(File::create(path)?)),
+        None => None,
+    };
+
+    let mut stop = Stop::Frames;
+    let mut trace_error = None;
+    let mut samples = Vec::new();
+    for _ in 0..options.frames {
+        let mut before = Registers::of(&emulator.cpu);
+        let completed = emulator.run_frame_with(|cpu, started| {
+            if !started {
+                // the ppu only moves until the next instruction starts
+                before = Registers::of(cpu);
+                return true;
+            }
+            if let Some(trace) = trace.as_mut() {
+                if let Err(e) = trace_line(trace, cpu, &before) {
+                    trace_error = Some(e);
+                    return false;
+                }
+            }
+            if options.until_loop && cpu.pc == before.pc {
+                stop = Stop::Loop(cpu.pc);
+                return false;
+            }
+            before = Registers::of(cpu);
+
+            // checked before the next instruction runs
+            if options.until_pc == Some(cpu.pc) {
+                stop = Stop::Pc(cpu.pc);
+                return false;
+            }
+            if let Some((addr, expected)) = options.until_mem {
+                if cpu.bus.peek(addr) == expected {
+                    stop = Stop::Memory(addr, expected);
+                    return false;
+                }
+            }
+            true
+        });
+
+        if let Some(audio) = audio.as_mut() {
+            let buffer = emulator.audio_samples();
+            samples.resize(buffer.len(), 0);
+            buffer.pop_i16(&mut samples);
+            audio.write_samples(&samples)?;
+        }
+        if let Some(e) = trace_error.take() {
+            return Err(e.into());
+        }
+        if !completed {
+            break;
+        }
+    }
+
+    if let Some(audio) = audio {
+        audio.finish()?;
+    }
+    if let Some(mut trace) = trace {
+        trace.flush()?;
+    }
+    if let Some(path) = &options.screenshot {
+        emulator.save_screenshot(path)?;
+    }
+    if let Some(path) = &options.ram {
+        fs::write(path, emulator.cpu.bus.ram)?;
+    }
+    Ok(stop)
+}
+
+/// `before` is the state the instruction started from, `cpu` the state after it
+fn trace_line(trace: &mut impl Write, cpu: &Cpu, before: &Registers) -> std::io::Result<()> {
+    let instruction = disasm::disassemble(|addr| cpu.bus.peek(addr), before.pc, Syntax::Nestest);
+    let bytes: String = instruction.bytes.iter().map(|byte| format!("{byte:02X} ")).collect();
+    writeln!(
+        trace,
+        "{:04X}  {:<9} {:<31} A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3}",
+        before.pc, bytes, instruction.to_string(), before.acc, before.x, before.y, before.ps, before.sp,
+        before.scanline, before.dot,
+    )
+}
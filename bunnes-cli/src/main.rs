@@ -0,0 +1,5 @@
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    ExitCode::from(bunnes_cli::run_cli(std::env::args().skip(1)))
+}
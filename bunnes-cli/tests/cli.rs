@@ -0,0 +1,177 @@
+use bunnes_cli::{parse_number, run, run_cli, Options, Stop, EXIT_DONE, EXIT_ERROR, EXIT_NOT_MET};
+use std::path::PathBuf;
+
+/// lda #$42, sta $10, nop, then a jmp to itself
+const PROGRAM: [u8; 8] = [0xA9, 0x42, 0x85, 0x10, 0xEA, 0x4C, 0x05, 0x80];
+
+/// nrom cartridge with chr ram running `code` at $8000, written to a temp file
+fn rom_file(name: &str, code: &[u8]) -> PathBuf {
+    let mut prg = code.to_vec();
+    prg.resize(0x4000, 0);
+    // reset vector
+    prg[0x3FFC] = 0x00;
+    prg[0x3FFD] = 0x80;
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    rom.extend(prg);
+
+    let dir = std::env::temp_dir().join("bunnes-cli-tests");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, rom).unwrap();
+    path
+}
+
+fn args(args: &[&str]) -> impl Iterator<Item = String> {
+    args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>().into_iter()
+}
+
+fn parse(arguments: &[&str]) -> Result<Options, String> {
+    Options::parse(args(arguments))
+}
+
+#[cfg(test)]
+mod options {
+    use super::*;
+
+    #[test]
+    fn defaults() {
+        let options = parse(&["game.nes"]).unwrap();
+        assert_eq!(options.rom, PathBuf::from("game.nes"));
+        assert_eq!(options.frames, 600);
+        assert!(!options.has_condition());
+        assert!(options.movie.is_none() && options.screenshot.is_none() && options.gdb.is_none());
+    }
+
+    #[test]
+    fn all() {
+        let options = parse(&[
+            "--frames", "10", "--until-pc", "$C000", "--until-mem", "0x6000=$80", "--until-loop",
+            "--movie", "a.fm2", "--screenshot", "a.png", "--audio", "a.wav", "--ram", "a.bin",
+            "--trace", "a.log", "--gdb", "1234", "game.nes",
+        ]).unwrap();
+        assert_eq!(options.rom, PathBuf::from("game.nes"));
+        assert_eq!(options.frames, 10);
+        assert_eq!(options.until_pc, Some(0xC000));
+        assert_eq!(options.until_mem, Some((0x6000, 0x80)));
+        assert!(options.until_loop);
+        assert_eq!(options.movie, Some(PathBuf::from("a.fm2")));
+        assert_eq!(options.screenshot, Some(PathBuf::from("a.png")));
+        assert_eq!(options.audio, Some(PathBuf::from("a.wav")));
+        assert_eq!(options.ram, Some(PathBuf::from("a.bin")));
+        assert_eq!(options.trace, Some(PathBuf::from("a.log")));
+        assert_eq!(options.gdb, Some(1234));
+        assert!(options.has_condition());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(&[]).unwrap_err(), "no rom given");
+        assert_eq!(parse(&["game.nes", "--frames"]).unwrap_err(), "--frames needs a value");
+        assert_eq!(parse(&["game.nes", "--fast"]).unwrap_err(), "unknown option --fast");
+        assert_eq!(parse(&["a.nes", "b.nes"]).unwrap_err(), "unexpected argument b.nes");
+        assert_eq!(parse(&["game.nes", "--until-mem", "$10"]).unwrap_err(), "--until-mem expects ADDR=VALUE");
+        assert_eq!(parse(&["game.nes", "--until-mem", "$10=256"]).unwrap_err(), "invalid number 256");
+        // prints just the usage
+        assert_eq!(parse(&["--help"]).unwrap_err(), "");
+    }
+}
+
+#[cfg(test)]
+mod numbers {
+    use super::*;
+
+    #[test]
+    fn formats() {
+        assert_eq!(parse_number::<u16>("49152"), Ok(0xC000));
+        assert_eq!(parse_number::<u16>("$C000"), Ok(0xC000));
+        assert_eq!(parse_number::<u16>("0xc000"), Ok(0xC000));
+        assert_eq!(parse_number::<u8>("$FF"), Ok(0xFF));
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(parse_number::<u8>("256"), Err("invalid number 256".to_string()));
+        assert_eq!(parse_number::<u8>("$100"), Err("invalid number $100".to_string()));
+        assert_eq!(parse_number::<u16>("0x10000"), Err("invalid number 0x10000".to_string()));
+        assert!(parse_number::<u32>("$100000000").is_err());
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse_number::<u16>("").is_err());
+        assert!(parse_number::<u16>("$").is_err());
+        assert!(parse_number::<u16>("-1").is_err());
+        assert!(parse_number::<u16>("$G0").is_err());
+        assert!(parse_number::<u16>("0X10").is_err());
+    }
+}
+
+#[cfg(test)]
+mod stops {
+    use super::*;
+
+    fn run_program(name: &str, arguments: &[&str]) -> Stop {
+        let rom = rom_file(name, &PROGRAM);
+        let mut options = parse(arguments).unwrap();
+        options.rom = rom;
+        run(&options).unwrap()
+    }
+
+    #[test]
+    fn frames() {
+        assert_eq!(run_program("frames.nes", &["_", "--frames", "2"]), Stop::Frames);
+    }
+
+    #[test]
+    fn until_pc() {
+        assert_eq!(run_program("until_pc.nes", &["_", "--until-pc", "$8004"]), Stop::Pc(0x8004));
+    }
+
+    #[test]
+    fn until_mem() {
+        // met right after the sta
+        assert_eq!(run_program("until_mem.nes", &["_", "--until-mem", "$10=$42"]), Stop::Memory(0x10, 0x42));
+        // never met
+        assert_eq!(run_program("until_mem_not_met.nes", &["_", "--frames", "2", "--until-mem", "$10=$43"]), Stop::Frames);
+    }
+
+    #[test]
+    fn until_loop() {
+        assert_eq!(run_program("until_loop.nes", &["_", "--until-loop"]), Stop::Loop(0x8005));
+    }
+
+    #[test]
+    fn ram() {
+        let ram = std::env::temp_dir().join("bunnes-cli-tests").join("ram.bin");
+        run_program("ram.nes", &["_", "--frames", "1", "--ram", ram.to_str().unwrap()]);
+        let ram = std::fs::read(ram).unwrap();
+        assert_eq!(ram.len(), 0x800);
+        assert_eq!(ram[0x10], 0x42);
+    }
+}
+
+#[cfg(test)]
+mod exit_codes {
+    use super::*;
+
+    #[test]
+    fn done() {
+        let rom = rom_file("exit_done.nes", &PROGRAM);
+        assert_eq!(run_cli(args(&[rom.to_str().unwrap(), "--frames", "1"])), EXIT_DONE);
+        assert_eq!(run_cli(args(&[rom.to_str().unwrap(), "--until-pc", "$8005"])), EXIT_DONE);
+    }
+
+    #[test]
+    fn not_met() {
+        let rom = rom_file("exit_not_met.nes", &PROGRAM);
+        assert_eq!(run_cli(args(&[rom.to_str().unwrap(), "--frames", "1", "--until-pc", "$9000"])), EXIT_NOT_MET);
+    }
+
+    #[test]
+    fn error() {
+        assert_eq!(run_cli(args(&[])), EXIT_ERROR);
+        assert_eq!(run_cli(args(&["game.nes", "--frames", "x"])), EXIT_ERROR);
+        let missing = std::env::temp_dir().join("bunnes-cli-tests").join("missing.nes");
+        assert_eq!(run_cli(args(&[missing.to_str().unwrap()])), EXIT_ERROR);
+    }
+}
@@ -0,0 +1,437 @@
+use bunNES::nes::input::controller::Buttons;
+use raylib::prelude::*;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const BINDINGS_PATH: &str = "bindings.toml";
+
+/// stick deflection at which an axis counts as pressed
+const AXIS_THRESHOLD: f32 = 0.5;
+
+const KEYS: [(&str, KeyboardKey); 62] = [
+    ("A", KeyboardKey::KEY_A), ("B", KeyboardKey::KEY_B), ("C", KeyboardKey::KEY_C),
+    ("D", KeyboardKey::KEY_D), ("E", KeyboardKey::KEY_E), ("F", KeyboardKey::KEY_F),
+    ("G", KeyboardKey::KEY_G), ("H", KeyboardKey::KEY_H), ("I", KeyboardKey::KEY_I),
+    ("J", KeyboardKey::KEY_J), ("K", KeyboardKey::KEY_K), ("L", KeyboardKey::KEY_L),
+    ("M", KeyboardKey::KEY_M), ("N", KeyboardKey::KEY_N), ("O", KeyboardKey::KEY_O),
+    ("P", KeyboardKey::KEY_P), ("Q", KeyboardKey::KEY_Q), ("R", KeyboardKey::KEY_R),
+    ("S", KeyboardKey::KEY_S), ("T", KeyboardKey::KEY_T), ("U", KeyboardKey::KEY_U),
+    ("V", KeyboardKey::KEY_V), ("W", KeyboardKey::KEY_W), ("X", KeyboardKey::KEY_X),
+    ("Y", KeyboardKey::KEY_Y), ("Z", KeyboardKey::KEY_Z),
+    ("0", KeyboardKey::KEY_ZERO), ("1", KeyboardKey::KEY_ONE), ("2", KeyboardKey::KEY_TWO),
+    ("3", KeyboardKey::KEY_THREE), ("4", KeyboardKey::KEY_FOUR), ("5", KeyboardKey::KEY_FIVE),
+    ("6", KeyboardKey::KEY_SIX), ("7", KeyboardKey::KEY_SEVEN), ("8", KeyboardKey::KEY_EIGHT),
+    ("9", KeyboardKey::KEY_NINE),
+    ("Up", KeyboardKey::KEY_UP), ("Down", KeyboardKey::KEY_DOWN),
+    ("Left", KeyboardKey::KEY_LEFT), ("Right", KeyboardKey::KEY_RIGHT),
+    ("Space", KeyboardKey::KEY_SPACE), ("Enter", KeyboardKey::KEY_ENTER),
+    ("Tab", KeyboardKey::KEY_TAB), ("Backspace", KeyboardKey::KEY_BACKSPACE),
+    ("LeftShift", KeyboardKey::KEY_LEFT_SHIFT), ("RightShift", KeyboardKey::KEY_RIGHT_SHIFT),
+    ("LeftControl", KeyboardKey::KEY_LEFT_CONTROL), ("RightControl", KeyboardKey::KEY_RIGHT_CONTROL),
+    ("LeftAlt", KeyboardKey::KEY_LEFT_ALT), ("RightAlt", KeyboardKey::KEY_RIGHT_ALT),
+    ("F1", KeyboardKey::KEY_F1), ("F2", KeyboardKey::KEY_F2), ("F3", KeyboardKey::KEY_F3),
+    ("F4", KeyboardKey::KEY_F4), ("F5", KeyboardKey::KEY_F5), ("F6", KeyboardKey::KEY_F6),
+    ("F7", KeyboardKey::KEY_F7), ("F8", KeyboardKey::KEY_F8), ("F9", KeyboardKey::KEY_F9),
+    ("F10", KeyboardKey::KEY_F10), ("F11", KeyboardKey::KEY_F11), ("F12", KeyboardKey::KEY_F12),
+];
+
+const GAMEPAD_BUTTONS: [(&str, GamepadButton); 17] = [
+    ("LeftFaceUp", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP),
+    ("LeftFaceRight", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT),
+    ("LeftFaceDown", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN),
+    ("LeftFaceLeft", GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT),
+    ("RightFaceUp", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP),
+    ("RightFaceRight", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_RIGHT),
+    ("RightFaceDown", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN),
+    ("RightFaceLeft", GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_LEFT),
+    ("LeftTrigger1", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1),
+    ("LeftTrigger2", GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_2),
+    ("RightTrigger1", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_1),
+    ("RightTrigger2", GamepadButton::GAMEPAD_BUTTON_RIGHT_TRIGGER_2),
+    ("MiddleLeft", GamepadButton::GAMEPAD_BUTTON_MIDDLE_LEFT),
+    ("Middle", GamepadButton::GAMEPAD_BUTTON_MIDDLE),
+    ("MiddleRight", GamepadButton::GAMEPAD_BUTTON_MIDDLE_RIGHT),
+    ("LeftThumb", GamepadButton::GAMEPAD_BUTTON_LEFT_THUMB),
+    ("RightThumb", GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB),
+];
+
+const GAMEPAD_AXES: [(&str, GamepadAxis); 6] = [
+    ("LeftX", GamepadAxis::GAMEPAD_AXIS_LEFT_X),
+    ("LeftY", GamepadAxis::GAMEPAD_AXIS_LEFT_Y),
+    ("RightX", GamepadAxis::GAMEPAD_AXIS_RIGHT_X),
+    ("RightY", GamepadAxis::GAMEPAD_AXIS_RIGHT_Y),
+    ("LeftTrigger", GamepadAxis::GAMEPAD_AXIS_LEFT_TRIGGER),
+    ("RightTrigger", GamepadAxis::GAMEPAD_AXIS_RIGHT_TRIGGER),
+];
+
+fn key(name: &str) -> Option<KeyboardKey> {
+    KEYS.iter().find(|(n, _)| *n == name).map(|(_, key)| *key)
+}
+
+fn key_name(key: KeyboardKey) -> Option<&'static str> {
+    KEYS.iter().find(|(_, k)| *k == key).map(|(name, _)| *name)
+}
+
+fn gamepad_button(name: &str) -> Option<GamepadButton> {
+    GAMEPAD_BUTTONS.iter().find(|(n, _)| *n == name).map(|(_, button)| *button)
+}
+
+fn gamepad_button_name(button: GamepadButton) -> Option<&'static str> {
+    GAMEPAD_BUTTONS.iter().find(|(_, b)| *b == button).map(|(name, _)| *name)
+}
+
+fn gamepad_axis(name: &str) -> Option<GamepadAxis> {
+    GAMEPAD_AXES.iter().find(|(n, _)| *n == name).map(|(_, axis)| *axis)
+}
+
+/// a single physical input, written to the config file as `X`, `pad:RightFaceDown` or `axis:LeftY-`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Binding {
+    Key { key: String },
+    GamepadButton { button: String },
+    /// an axis pushed past the threshold in one direction
+    GamepadAxis { axis: String, positive: bool },
+}
+
+impl From<Binding> for String {
+    fn from(binding: Binding) -> String {
+        match binding {
+            Binding::Key { key } => key,
+            Binding::GamepadButton { button } => format!("pad:{button}"),
+            Binding::GamepadAxis { axis, positive } => format!("axis:{axis}{}", if positive { "+" } else { "-" }),
+        }
+    }
+}
+
+impl TryFrom<String> for Binding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Binding, String> {
+        if let Some(button) = value.strip_prefix("pad:") {
+            gamepad_button(button).ok_or(format!("unknown gamepad button: {button}"))?;
+            Ok(Binding::button(button))
+        } else if let Some(axis) = value.strip_prefix("axis:") {
+            let (name, positive) = if let Some(name) = axis.strip_suffix('+') {
+                (name, true)
+            } else if let Some(name) = axis.strip_suffix('-') {
+                (name, false)
+            } else {
+                return Err(format!("axis without direction: {axis}"));
+            };
+            gamepad_axis(name).ok_or(format!("unknown gamepad axis: {name}"))?;
+            Ok(Binding::axis(name, positive))
+        } else {
+            key(&value).ok_or(format!("unknown key: {value}"))?;
+            Ok(Binding::key(&value))
+        }
+    }
+}
+
+impl Binding {
+    fn key(name: &str) -> Binding {
+        Binding::Key { key: name.to_string() }
+    }
+
+    fn button(name: &str) -> Binding {
+        Binding::GamepadButton { button: name.to_string() }
+    }
+
+    fn axis(name: &str, positive: bool) -> Binding {
+        Binding::GamepadAxis { axis: name.to_string(), positive }
+    }
+
+    fn is_down(&self, rl: &RaylibHandle, gamepad: i32) -> bool {
+        match self {
+            Binding::Key { key: name } => key(name).is_some_and(|key| rl.is_key_down(key)),
+            Binding::GamepadButton { button } => rl.is_gamepad_available(gamepad)
+                && gamepad_button(button).is_some_and(|button| rl.is_gamepad_button_down(gamepad, button)),
+            Binding::GamepadAxis { axis, positive } => rl.is_gamepad_available(gamepad)
+                && gamepad_axis(axis).is_some_and(|axis| {
+                    let movement = rl.get_gamepad_axis_movement(gamepad, axis);
+                    if *positive { movement > AXIS_THRESHOLD } else { movement < -AXIS_THRESHOLD }
+                }),
+        }
+    }
+
+    pub fn is_gamepad(&self) -> bool {
+        !matches!(self, Binding::Key { .. })
+    }
+
+    /// the input pressed this frame, used when rebinding
+    pub fn pressed(rl: &mut RaylibHandle, gamepad: i32) -> Option<Binding> {
+        if let Some(name) = rl.get_key_pressed().and_then(key_name) {
+            return Some(Binding::key(name));
+        }
+        if !rl.is_gamepad_available(gamepad) {
+            return None;
+        }
+        if let Some(name) = rl.get_gamepad_button_pressed().and_then(gamepad_button_name) {
+            return Some(Binding::button(name));
+        }
+        GAMEPAD_AXES.iter().find_map(|(name, axis)| {
+            let movement = rl.get_gamepad_axis_movement(gamepad, *axis);
+            if movement.abs() > AXIS_THRESHOLD {
+                Some(Binding::axis(name, movement > 0.0))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", String::from(self.clone()))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Action {
+    A,
+    B,
+    Select,
+    Start,
+    Up,
+    Down,
+    Left,
+    Right,
+    TurboA,
+    TurboB,
+}
+
+impl Action {
+    pub const ALL: [Action; 10] = [
+        Action::A, Action::B, Action::Select, Action::Start,
+        Action::Up, Action::Down, Action::Left, Action::Right,
+        Action::TurboA, Action::TurboB,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::A => "A",
+            Action::B => "B",
+            Action::Select => "Select",
+            Action::Start => "Start",
+            Action::Up => "Up",
+            Action::Down => "Down",
+            Action::Left => "Left",
+            Action::Right => "Right",
+            Action::TurboA => "Turbo A",
+            Action::TurboB => "Turbo B",
+        }
+    }
+
+    fn buttons(&self) -> Buttons {
+        match self {
+            Action::A | Action::TurboA => Buttons::A,
+            Action::B | Action::TurboB => Buttons::B,
+            Action::Select => Buttons::SELECT,
+            Action::Start => Buttons::START,
+            Action::Up => Buttons::UP,
+            Action::Down => Buttons::DOWN,
+            Action::Left => Buttons::LEFT,
+            Action::Right => Buttons::RIGHT,
+        }
+    }
+
+    fn turbo(&self) -> bool {
+        matches!(self, Action::TurboA | Action::TurboB)
+    }
+}
+
+/// missing fields are filled in from the player's defaults, see [`players`]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PlayerBindings {
+    /// raylib gamepad index
+    pub gamepad: i32,
+    pub a: Vec<Binding>,
+    pub b: Vec<Binding>,
+    pub select: Vec<Binding>,
+    pub start: Vec<Binding>,
+    pub up: Vec<Binding>,
+    pub down: Vec<Binding>,
+    pub left: Vec<Binding>,
+    pub right: Vec<Binding>,
+    pub turbo_a: Vec<Binding>,
+    pub turbo_b: Vec<Binding>,
+}
+
+impl PlayerBindings {
+    fn player_1() -> PlayerBindings {
+        PlayerBindings {
+            gamepad: 0,
+            a: vec![Binding::key("X"), Binding::button("RightFaceDown")],
+            b: vec![Binding::key("Z"), Binding::button("RightFaceLeft")],
+            select: vec![Binding::key("RightShift"), Binding::button("MiddleLeft")],
+            start: vec![Binding::key("Enter"), Binding::button("MiddleRight")],
+            up: vec![Binding::key("Up"), Binding::button("LeftFaceUp"), Binding::axis("LeftY", false)],
+            down: vec![Binding::key("Down"), Binding::button("LeftFaceDown"), Binding::axis("LeftY", true)],
+            left: vec![Binding::key("Left"), Binding::button("LeftFaceLeft"), Binding::axis("LeftX", false)],
+            right: vec![Binding::key("Right"), Binding::button("LeftFaceRight"), Binding::axis("LeftX", true)],
+            turbo_a: vec![Binding::key("D"), Binding::button("RightFaceRight")],
+            turbo_b: vec![Binding::key("C"), Binding::button("RightFaceUp")],
+        }
+    }
+
+    fn player_2() -> PlayerBindings {
+        PlayerBindings {
+            gamepad: 1,
+            a: vec![Binding::key("O"), Binding::button("RightFaceDown")],
+            b: vec![Binding::key("U"), Binding::button("RightFaceLeft")],
+            select: vec![Binding::key("7"), Binding::button("MiddleLeft")],
+            start: vec![Binding::key("8"), Binding::button("MiddleRight")],
+            up: vec![Binding::key("I"), Binding::button("LeftFaceUp"), Binding::axis("LeftY", false)],
+            down: vec![Binding::key("K"), Binding::button("LeftFaceDown"), Binding::axis("LeftY", true)],
+            left: vec![Binding::key("J"), Binding::button("LeftFaceLeft"), Binding::axis("LeftX", false)],
+            right: vec![Binding::key("L"), Binding::button("LeftFaceRight"), Binding::axis("LeftX", true)],
+            turbo_a: vec![Binding::button("RightFaceRight")],
+            turbo_b: vec![Binding::button("RightFaceUp")],
+        }
+    }
+
+    pub fn action(&self, action: Action) -> &Vec<Binding> {
+        match action {
+            Action::A => &self.a,
+            Action::B => &self.b,
+            Action::Select => &self.select,
+            Action::Start => &self.start,
+            Action::Up => &self.up,
+            Action::Down => &self.down,
+            Action::Left => &self.left,
+            Action::Right => &self.right,
+            Action::TurboA => &self.turbo_a,
+            Action::TurboB => &self.turbo_b,
+        }
+    }
+
+    pub fn action_mut(&mut self, action: Action) -> &mut Vec<Binding> {
+        match action {
+            Action::A => &mut self.a,
+            Action::B => &mut self.b,
+            Action::Select => &mut self.select,
+            Action::Start => &mut self.start,
+            Action::Up => &mut self.up,
+            Action::Down => &mut self.down,
+            Action::Left => &mut self.left,
+            Action::Right => &mut self.right,
+            Action::TurboA => &mut self.turbo_a,
+            Action::TurboB => &mut self.turbo_b,
+        }
+    }
+
+    /// replaces the keyboard or gamepad bindings of `action`, depending on which kind `binding` is
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.action_mut(action);
+        bindings.retain(|b| b.is_gamepad() != binding.is_gamepad());
+        bindings.push(binding);
+    }
+}
+
+/// debugger hotkeys, by key name
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct DebuggerBindings {
+    pub step: String,
+    pub run: String,
+    pub reset: String,
+    pub menu: String,
+    /// rewinds while held
+    pub rewind: String,
+    /// switches the ppu panel between vram and oam
+    pub ppu_view: String,
+    /// cycles the palette the pattern tables are drawn in
+    pub ppu_palette: String,
+    /// opens the hex editor
+    pub memory: String,
+    /// opens the ram search
+    pub ram_search: String,
+}
+
+impl Default for DebuggerBindings {
+    fn default() -> Self {
+        DebuggerBindings {
+            step: "S".to_string(),
+            run: "Space".to_string(),
+            reset: "R".to_string(),
+            menu: "F1".to_string(),
+            rewind: "Backspace".to_string(),
+            ppu_view: "F2".to_string(),
+            ppu_palette: "F3".to_string(),
+            memory: "F4".to_string(),
+            ram_search: "F5".to_string(),
+        }
+    }
+}
+
+impl DebuggerBindings {
+    pub fn pressed(rl: &RaylibHandle, name: &str) -> bool {
+        key(name).is_some_and(|key| rl.is_key_pressed(key))
+    }
+
+    pub fn held(rl: &RaylibHandle, name: &str) -> bool {
+        key(name).is_some_and(|key| rl.is_key_down(key))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Bindings {
+    /// frames a turbo button stays pressed and released
+    pub turbo_period: u32,
+    pub debugger: DebuggerBindings,
+    #[serde(deserialize_with = "players")]
+    pub players: [PlayerBindings; 2],
+}
+
+/// each player's table on top of that player's defaults, a partial player 2 keeps player 2's keys
+fn players<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[PlayerBindings; 2], D::Error> {
+    let tables = Vec::<toml::Table>::deserialize(deserializer)?;
+    let mut players = [PlayerBindings::player_1(), PlayerBindings::player_2()];
+    for (player, table) in players.iter_mut().zip(tables) {
+        let mut merged = toml::Table::try_from(&*player).map_err(D::Error::custom)?;
+        merged.extend(table);
+        *player = merged.try_into().map_err(D::Error::custom)?;
+    }
+    Ok(players)
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            turbo_period: 2,
+            debugger: DebuggerBindings::default(),
+            players: [PlayerBindings::player_1(), PlayerBindings::player_2()],
+        }
+    }
+}
+
+impl Bindings {
+    /// bindings from `path`, the defaults if it doesn't exist
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Bindings> {
+        match fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Bindings::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = toml::to_string_pretty(self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, text)
+    }
+
+    /// buttons held by `player`, turbo buttons toggle every `turbo_period` frames
+    pub fn buttons(&self, rl: &RaylibHandle, player: usize, frame: u64) -> Buttons {
+        let bindings = &self.players[player];
+        let turbo_on = (frame / self.turbo_period.max(1) as u64) % 2 == 0;
+
+        Action::ALL.iter()
+            .filter(|action| !action.turbo() || turbo_on)
+            .filter(|action| bindings.action(**action).iter().any(|binding| binding.is_down(rl, bindings.gamepad)))
+            .fold(Buttons::empty(), |buttons, action| buttons | action.buttons())
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{FONT_SIZE, NES_HEIGHT, NES_SCALE, NES_WIDTH, PADDING};
+use bunNES::nes::bus::Bus;
+use bunNES::nes::memory::MemorySpace;
+use raylib::prelude::*;
+
+const BYTES_PER_ROW: usize = 16;
+/// rows that fit between the header and the status line
+const ROWS: usize = ((NES_HEIGHT * NES_SCALE) / (FONT_SIZE + PADDING)) as usize - 2;
+const PAGE: usize = BYTES_PER_ROW * ROWS;
+/// rows the mouse wheel scrolls
+const WHEEL_ROWS: usize = 3;
+
+const CURSOR: Color = Color::new(150, 255, 0, 255);
+const CHANGED: Color = Color::new(255, 120, 80, 255);
+
+/// scrollable hex editor over one [`MemorySpace`] at a time.
+///
+/// tab switches the address space, the arrows and page up/down move the cursor, two hex
+/// digits overwrite the byte under it and G followed by an address and enter jumps there.
+/// bytes that changed with the last emulated frame are highlighted
+#[derive(Debug)]
+pub struct HexEditor {
+    pub open: bool,
+    space: MemorySpace,
+    cursor: usize,
+    /// first byte on screen, a multiple of [`BYTES_PER_ROW`]
+    top: usize,
+    /// first digit typed at the cursor, the byte is written with the second
+    high_nibble: Option<u8>,
+    /// digits of the address to jump to while typing one
+    jump: Option<String>,
+
+    /// the space as of the last two frames, what the highlight compares
+    previous: Vec<u8>,
+    current: Vec<u8>,
+    frame: Option<u64>,
+    status: String,
+}
+
+impl Default for HexEditor {
+    fn default() -> Self {
+        HexEditor {
+            open: false,
+            space: MemorySpace::Cpu,
+            cursor: 0,
+            top: 0,
+            high_nibble: None,
+            jump: None,
+            previous: Vec::new(),
+            current: Vec::new(),
+            frame: None,
+            status: String::new(),
+        }
+    }
+}
+
+impl HexEditor {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.high_nibble = None;
+        self.jump = None;
+        self.status.clear();
+    }
+
+    /// `frame` is the emulator's frame count, a new one takes a snapshot for the highlight
+    pub fn update(&mut self, rl: &mut RaylibHandle, bus: &mut Bus, frame: u64) {
+        if self.frame != Some(frame) {
+            self.previous = std::mem::replace(&mut self.current, self.space.peek_range(bus, 0, self.space.len(bus)));
+            self.frame = Some(frame);
+        }
+
+        let len = self.space.len(bus);
+        if self.jump.is_some() {
+            self.update_jump(rl, len);
+            return;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            let index = MemorySpace::ALL.iter().position(|space| *space == self.space).unwrap_or(0);
+            self.set_space(MemorySpace::ALL[(index + 1) % MemorySpace::ALL.len()], bus);
+            return;
+        }
+        if len == 0 {
+            self.status = format!("no {} on this cartridge", self.space.name());
+            return;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            self.jump = Some(String::new());
+            self.high_nibble = None;
+            return;
+        }
+
+        let wheel = rl.get_mouse_wheel_move();
+        let moves = [
+            (rl.is_key_pressed(KeyboardKey::KEY_LEFT), -1),
+            (rl.is_key_pressed(KeyboardKey::KEY_RIGHT), 1),
+            (rl.is_key_pressed(KeyboardKey::KEY_UP), -(BYTES_PER_ROW as isize)),
+            (rl.is_key_pressed(KeyboardKey::KEY_DOWN), BYTES_PER_ROW as isize),
+            (rl.is_key_pressed(KeyboardKey::KEY_PAGE_UP), -(PAGE as isize)),
+            (rl.is_key_pressed(KeyboardKey::KEY_PAGE_DOWN), PAGE as isize),
+            (wheel > 0.0, -((WHEEL_ROWS * BYTES_PER_ROW) as isize)),
+            (wheel < 0.0, (WHEEL_ROWS * BYTES_PER_ROW) as isize),
+        ];
+        for (_, offset) in moves.iter().filter(|(pressed, _)| *pressed) {
+            self.move_cursor(self.cursor as isize + offset, len);
+        }
+
+        while let Some(c) = rl.get_char_pressed() {
+            let Some(digit) = c.to_digit(16) else {
+                continue;
+            };
+            match self.high_nibble.take() {
+                None => self.high_nibble = Some(digit as u8),
+                Some(high) => {
+                    let value = high << 4 | digit as u8;
+                    self.space.poke(bus, self.cursor, value);
+                    // an edit isn't a change made by the game
+                    let value = self.space.peek(bus, self.cursor);
+                    if let Some(byte) = self.current.get_mut(self.cursor) {
+                        *byte = value;
+                    }
+                    if let Some(byte) = self.previous.get_mut(self.cursor) {
+                        *byte = value;
+                    }
+                    self.status = format!("{} ${:04X} = ${value:02X}", self.space.name(), self.cursor);
+                    self.move_cursor(self.cursor as isize + 1, len);
+                }
+            }
+        }
+    }
+
+    fn update_jump(&mut self, rl: &mut RaylibHandle, len: usize) {
+        let Some(jump) = self.jump.as_mut() else {
+            return;
+        };
+        while let Some(c) = rl.get_char_pressed() {
+            if c.is_ascii_hexdigit() && jump.len() < 4 {
+                jump.push(c.to_ascii_uppercase());
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) && jump.pop().is_none() {
+            self.jump = None;
+            return;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            match usize::from_str_radix(jump, 16) {
+                Ok(addr) if addr < len => {
+                    self.move_cursor(addr as isize, len);
+                    self.status.clear();
+                }
+                _ => self.status = format!("no ${jump} in {}", self.space.name()),
+            }
+            self.jump = None;
+        }
+    }
+
+    fn set_space(&mut self, space: MemorySpace, bus: &Bus) {
+        self.space = space;
+        self.cursor = 0;
+        self.top = 0;
+        self.high_nibble = None;
+        self.current = space.peek_range(bus, 0, space.len(bus));
+        self.previous = self.current.clone();
+        self.status.clear();
+    }
+
+    /// clamps to the space and scrolls the cursor into view
+    fn move_cursor(&mut self, cursor: isize, len: usize) {
+        self.cursor = cursor.clamp(0, len as isize - 1) as usize;
+        self.high_nibble = None;
+        if self.cursor < self.top {
+            self.top = self.cursor - self.cursor % BYTES_PER_ROW;
+        } else if self.cursor >= self.top + PAGE {
+            self.top = self.cursor - self.cursor % BYTES_PER_ROW - (ROWS - 1) * BYTES_PER_ROW;
+        }
+    }
+
+    /// over the nes screen, like the bindings menu
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font) {
+        d.draw_rectangle(0, 0,
+                         NES_WIDTH * NES_SCALE, NES_HEIGHT * NES_SCALE,
+                         Color::new(0, 0, 0, 220));
+
+        let char_width = measure_text_ex(font, "0", FONT_SIZE as f32, 0.0).x as i32;
+        let mut y = PADDING;
+        let header = format!("< {} >  ${:04X}  tab: space  g: jump  0-F: edit", self.space.name(), self.cursor);
+        draw_text(d, font, &header, PADDING, y, Color::WHITE);
+        y += FONT_SIZE + PADDING;
+
+        for row in 0..ROWS {
+            let start = self.top + row * BYTES_PER_ROW;
+            if start >= self.current.len() {
+                break;
+            }
+
+            draw_text(d, font, &format!("{start:04X}:"), PADDING, y, Color::LIGHTGRAY);
+            let end = (start + BYTES_PER_ROW).min(self.current.len());
+            for addr in start..end {
+                let x = PADDING + char_width * (6 + 3 * (addr - start) as i32);
+                let value = self.current[addr];
+                let changed = self.previous.get(addr).is_some_and(|previous| *previous != value);
+
+                let text = if addr == self.cursor {
+                    d.draw_rectangle(x, y, char_width * 2, FONT_SIZE, CURSOR);
+                    match self.high_nibble {
+                        Some(high) => format!("{high:X}_"),
+                        None => format!("{value:02X}"),
+                    }
+                } else {
+                    format!("{value:02X}")
+                };
+                let color = if addr == self.cursor { Color::BLACK } else if changed { CHANGED } else { Color::WHITE };
+                draw_text(d, font, &text, x, y, color);
+            }
+
+            let ascii: String = self.current[start..end].iter()
+                .map(|byte| if byte.is_ascii_graphic() { *byte as char } else { '.' })
+                .collect();
+            let x = PADDING + char_width * (7 + 3 * BYTES_PER_ROW as i32);
+            draw_text(d, font, &ascii, x, y, Color::LIGHTGRAY);
+            y += FONT_SIZE + PADDING;
+        }
+
+        let status = match &self.jump {
+            Some(jump) => format!("jump to ${jump}_"),
+            None => self.status.clone(),
+        };
+        let y = NES_HEIGHT * NES_SCALE - FONT_SIZE - PADDING;
+        draw_text(d, font, &status, PADDING, y, Color::WHITE);
+    }
+}
+
+fn draw_text(d: &mut RaylibDrawHandle, font: &Font, text: &str, x: i32, y: i32, color: Color) {
+    d.draw_text_ex(font, text, Vector2 { x: x as f32, y: y as f32 }, FONT_SIZE as f32, 0f32, color);
+}
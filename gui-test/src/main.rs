@@ -1,10 +1,22 @@
+mod bindings;
+mod hex_editor;
+mod menu;
+mod ppu_panel;
+mod ram_search_panel;
+
 use bunNES::emulator::*;
+use crate::bindings::{Bindings, DebuggerBindings, BINDINGS_PATH};
+use crate::hex_editor::HexEditor;
+use crate::menu::BindingsMenu;
+use crate::ppu_panel::{PpuPanel, PPU_PANEL_WIDTH};
+use crate::ram_search_panel::RamSearchPanel;
 use bunNES::nes::rom::Cartridge;
+use bunNES::rewind::RewindConfig;
 use raylib::prelude::*;
 use std::fs::File;
 use std::io::Read;
 use std::ops::Add;
-use bunNES::nes::opcodes::{AddrMode, OpCode};
+use bunNES::nes::disasm::{self, Syntax};
 
 const NES_WIDTH: i32 = 256;
 const NES_HEIGHT: i32 = 240;
@@ -15,6 +27,9 @@ const FONT_SIZE: i32 = 20;
 const PADDING: i32 = 5;
 const DEBUG_DISASSEMBLY_WIDTH: i32 = 370;
 
+const SAMPLE_RATE: u32 = 44100;
+const AUDIO_FRAMES: usize = 1024;
+
 type Mem = [u8; 2048];
 
 
@@ -23,20 +38,56 @@ struct Window {
 
     emulator: Emulator,
     running: bool,
+
+    bindings: Bindings,
+    bindings_menu: BindingsMenu,
+    hex_editor: HexEditor,
+    ppu_panel: PpuPanel,
+    ram_search: RamSearchPanel,
+    frame_count: u64,
 }
 
 impl Window {
-    fn new(font: Font, emulator: Emulator) -> Self {
+    fn new(font: Font, emulator: Emulator, bindings: Bindings, ppu_panel: PpuPanel) -> Self {
         Self {
             font,
             emulator,
             running: false,
+
+            bindings,
+            bindings_menu: BindingsMenu::default(),
+            hex_editor: HexEditor::default(),
+            ppu_panel,
+            ram_search: RamSearchPanel::default(),
+            frame_count: 0,
         }
     }
 
 
     fn run(&mut self, rl: &mut RaylibHandle, thread: RaylibThread) {
         self.emulator.reset();
+        self.emulator.enable_rewind(RewindConfig::default());
+
+        // the emulator keeps running without sound, its sample buffer drops the oldest samples.
+        // raylib only logs a warning when there is no device, the handle is returned either way
+        let audio = match RaylibAudio::init_audio_device() {
+            Ok(audio) if audio.is_audio_device_ready() => Some(audio),
+            Ok(_) => {
+                println!("no audio device, running without sound");
+                None
+            }
+            Err(e) => {
+                println!("couldn't init the audio device, running without sound: {e}");
+                None
+            }
+        };
+        self.emulator.set_sample_rate(SAMPLE_RATE);
+        let mut audio_stream = audio.as_ref().map(|audio| {
+            let mut stream = audio.new_audio_stream(SAMPLE_RATE, 16, 1);
+            stream.play();
+            stream
+        });
+        let mut audio_samples = [0i16; AUDIO_FRAMES];
 
         let mut debug_draw_pos = Vector2::new(0.0, 0.0);
 
@@ -45,24 +96,77 @@ impl Window {
 
             d.clear_background(Color::PURPLE);
 
-            if self.running {
-                // fuck cycle accuracy
-                self.emulator.step();
+            // backspace is taken while the hex editor is open
+            let rewinding = !self.hex_editor.open && !self.ram_search.open && DebuggerBindings::held(&d, &self.bindings.debugger.rewind);
+            if rewinding {
+                // one snapshot per drawn frame, plays the game backwards at normal speed
+                self.emulator.rewind(1);
+            } else if self.running {
+                // a frame per drawn frame keeps the audio stream fed
+                self.emulator.run_frame();
             }
 
+            if let Some(stream) = audio_stream.as_mut() {
+                self.update_audio(stream, &mut audio_samples);
+            }
+            self.frame_count += 1;
+
             self.draw_emulator(&mut d);
 
-            if d.is_key_pressed(KeyboardKey::KEY_S) {
+            let debugger = &self.bindings.debugger;
+            if DebuggerBindings::pressed(&d, &debugger.menu) {
+                self.bindings_menu.toggle();
+            }
+
+            if self.bindings_menu.open {
+                self.bindings_menu.update(&mut d, &mut self.bindings);
+                self.draw_bindings_menu(&mut d);
+                continue;
+            }
+
+            if DebuggerBindings::pressed(&d, &self.bindings.debugger.memory) {
+                self.hex_editor.toggle();
+            }
+            if self.hex_editor.open {
+                let frame = self.emulator.cpu.bus.ppu.frame_count();
+                self.hex_editor.update(&mut d, &mut self.emulator.cpu.bus, frame);
+                self.hex_editor.draw(&mut d, &self.font);
+                continue;
+            }
+
+            if DebuggerBindings::pressed(&d, &self.bindings.debugger.ram_search) {
+                self.ram_search.toggle();
+            }
+            if self.ram_search.open {
+                self.ram_search.update(&mut d, &mut self.emulator);
+                self.ram_search.draw(&mut d, &self.font, &self.emulator);
+                continue;
+            }
+
+            for player in 0..2 {
+                let buttons = self.bindings.buttons(&d, player, self.frame_count);
+                self.emulator.set_buttons(player, buttons);
+            }
+
+            let debugger = &self.bindings.debugger;
+            if DebuggerBindings::pressed(&d, &debugger.step) {
                 self.emulator.step();
             }
-            if d.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            if DebuggerBindings::pressed(&d, &debugger.run) {
                 self.running = !self.running;
             }
 
-            if d.is_key_pressed(KeyboardKey::KEY_R) {
+            if DebuggerBindings::pressed(&d, &debugger.reset) {
                 self.emulator.reset();
             }
 
+            if DebuggerBindings::pressed(&d, &debugger.ppu_view) {
+                self.ppu_panel.toggle_view();
+            }
+            if DebuggerBindings::pressed(&d, &debugger.ppu_palette) {
+                self.ppu_panel.next_palette();
+            }
+
             if d.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) {
                 let mouse_pos = d.get_mouse_position();
                 debug_draw_pos = mouse_pos;
@@ -78,9 +182,34 @@ impl Window {
         }
     }
 
+    fn draw_bindings_menu(&mut self, d: &mut RaylibDrawHandle) {
+        d.draw_rectangle(0, 0,
+                         NES_WIDTH * NES_SCALE, NES_HEIGHT * NES_SCALE,
+                         Color::new(0, 0, 0, 220));
+
+        let mut y = PADDING;
+        for (line, selected) in self.bindings_menu.lines(&self.bindings) {
+            let color = if selected { Color::new(150, 255, 0, 255) } else { Color::WHITE };
+            self.draw_text(d, &line, PADDING, y, FONT_SIZE, color);
+            y += FONT_SIZE + PADDING;
+        }
+    }
+
+    fn update_audio(&mut self, stream: &mut AudioStream, samples: &mut [i16]) {
+        if !stream.is_processed() {
+            return;
+        }
+
+        let count = self.emulator.audio_samples().pop_i16(samples);
+        // pad with silence when the emulator doesn't keep up
+        samples[count..].fill(0);
+        stream.update(samples);
+    }
+
     fn draw_emulator(&mut self, d: &mut RaylibDrawHandle) {
         self.draw_nes(d);
         self.draw_debug(d);
+        self.draw_ppu(d);
     }
 
     fn draw_nes(&self, d: &mut RaylibDrawHandle) {
@@ -101,7 +230,17 @@ impl Window {
 
         y = self.draw_disassembly(x, y, d);
         y = self.draw_registers(x + DEBUG_DISASSEMBLY_WIDTH + 50, PADDING, d);
-        // y = self.draw_memory(x, y, d);
+    }
+
+    fn draw_ppu(&mut self, d: &mut RaylibDrawHandle) {
+        let x = NES_WIDTH * NES_SCALE + DEBUG_WIDTH;
+        d.draw_rectangle(x, 0,
+                         PPU_PANEL_WIDTH, NES_HEIGHT * NES_SCALE,
+                         Color::LIGHTGRAY);
+
+        let ppu = &self.emulator.cpu.bus.ppu;
+        self.ppu_panel.update(ppu);
+        self.ppu_panel.draw(d, &self.font, ppu, x + PADDING, PADDING);
     }
 
     fn draw_registers(&mut self, x: i32, y: i32, d: &mut RaylibDrawHandle) -> i32 {
@@ -154,31 +293,6 @@ impl Window {
         y
     }
 
-    fn draw_memory(&mut self, x: i32, y: i32, d: &mut RaylibDrawHandle) -> i32 {
-        let mut y = y;
-
-        let cpu = &mut self.emulator.cpu;
-        const PEEK_MEMORY_SIZE: usize = 0xFF;
-        const BYTES_PER_LINE: i32 = 16;
-        const LINES: i32 = PEEK_MEMORY_SIZE as i32 / BYTES_PER_LINE;
-
-        let memory = cpu.bus.memory_chunk(cpu.pc, PEEK_MEMORY_SIZE);
-
-
-        for i in 0..LINES {
-            let mut str = String::new();
-
-            for j in 0..BYTES_PER_LINE {
-                str.push_str(&format!("{:02X} ", memory[(j + i * BYTES_PER_LINE) as usize]));
-            }
-
-            self.draw_text(d, &str, x, y, FONT_SIZE, Color::BLACK);
-            y += FONT_SIZE + PADDING;
-        }
-
-        y
-    }
-
     fn draw_text(&mut self, d: &mut RaylibDrawHandle, text: &str, x: i32, y: i32, font_size: i32, color: Color) {
         d.draw_text_ex(&self.font, text, Vector2 { x: x as f32, y: y as f32 }, font_size as f32, 0f32, color);
     }
@@ -186,9 +300,7 @@ impl Window {
     fn draw_disassembly(&mut self, x: i32, y: i32, d: &mut RaylibDrawHandle) -> i32 {
         let mut y = y;
 
-        let cpu = &mut self.emulator.cpu;
-
-        let mut location = cpu.pc;
+        let mut location = self.emulator.cpu.pc;
 
         let amount_of_instructions = (((NES_HEIGHT * NES_SCALE) as f32 / (FONT_SIZE + PADDING) as f32) as i32) - 1;
 
@@ -202,72 +314,21 @@ impl Window {
 
     fn draw_instruction(&mut self, location: u16, x: i32, y: i32, d: &mut RaylibDrawHandle) -> (i32, u8) {
         let mut y = y;
-        let cpu = &mut self.emulator.cpu;
+        let cpu = &self.emulator.cpu;
 
         if location == cpu.pc {
             d.draw_rectangle(x - PADDING, y, DEBUG_DISASSEMBLY_WIDTH, FONT_SIZE, Color::new(150, 255, 0, 255));
         }
 
-        let (instruction, byte_code) = cpu.get_instruction(location);
-        
-        let size: u8 = if let Some(instruction) = instruction {
-            let op_code = instruction.op_code;
-            let size = instruction.size;
-
-            // machine code
-            let mut operand = String::new();
-            assert!(size > 0);
-            for i in 1..size {
-                let arg = cpu.bus.read_8(location + i as u16);
-                operand.push_str(format!("{:02X} ", arg).as_str());
-            }
+        let instruction = disasm::disassemble(|addr| cpu.bus.peek(addr), location, Syntax::Nestest);
 
-            // disassembly
-            let operand_normal = match size {
-                1 => 0,
-                2 => cpu.bus.read_8(location + 1) as u16,
-                3 => cpu.bus.read_16(location + 1),
-                _ => panic!("operand size too big: {size}")
-            };
-
-            let operand_normal = match instruction.addr_mode {
-                AddrMode::Implicit => String::new(),
-                AddrMode::Accumulator => String::from("A"),
-                AddrMode::Immediate => format!("#{:02X}", operand_normal),
-                AddrMode::Zp => format!("${:02X}", operand_normal),
-                AddrMode::ZpX => format!("${:02X}, X", operand_normal),
-                AddrMode::ZpY => format!("${:02X}, Y", operand_normal),
-                AddrMode::Relative => {
-                    let op = operand_normal as i8;
-                    let jump_loc = if op >= 0 {
-                        (location + 2).wrapping_add(op as u16)
-                    } else {
-                        (location + 2).wrapping_sub(op.wrapping_abs() as u16)
-                    };
-                    format!(
-                        "*{}{:02} (${:04X})",
-                        if op > 0 { "+" } else { "-" }, (op).abs(),
-                        jump_loc
-                    )
-                },
-                AddrMode::Absolute => format!("${:04X}", operand_normal),
-                AddrMode::AbsoluteX => format!("${:04X}, X", operand_normal),
-                AddrMode::AbsoluteY => format!("${:04X}, Y", operand_normal),
-                AddrMode::Indirect => format!("({:04X})", operand_normal),
-                AddrMode::IndirectX => format!("({:02X}, X)", operand_normal),
-                AddrMode::IndirectY => format!("({:02X}), Y", operand_normal),
-            };
-
-            self.draw_text(d, &format!("{:04X}: {:02X} {}", location, byte_code, operand), x, y, FONT_SIZE, Color::BLACK);
-            self.draw_text(d, &format!("{} {}", op_code, operand_normal), x + 200, y, FONT_SIZE, Color::BLACK);
-            size
-        } else {
-            self.draw_text(d, format!("unknown: {:02X}", byte_code).as_str(), x, y, FONT_SIZE, Color::BLACK);
-            1
-        };
+        // machine code
+        let bytes: String = instruction.bytes.iter().map(|byte| format!("{:02X} ", byte)).collect();
+        self.draw_text(d, &format!("{:04X}: {}", location, bytes), x, y, FONT_SIZE, Color::BLACK);
+        self.draw_text(d, &instruction.to_string(), x + 200, y, FONT_SIZE, Color::BLACK);
 
         y += FONT_SIZE + PADDING;
-        (y, size)
+        (y, instruction.bytes.len() as u8)
     }
 }
 
@@ -286,7 +347,7 @@ fn main() {
 
 
     let (mut rl, thread) = raylib::init()
-        .size(NES_WIDTH * NES_SCALE + DEBUG_WIDTH, NES_HEIGHT * NES_SCALE)
+        .size(NES_WIDTH * NES_SCALE + DEBUG_WIDTH + PPU_PANEL_WIDTH, NES_HEIGHT * NES_SCALE)
         .title("raylib-rs example")
         .build();
 
@@ -296,7 +357,12 @@ fn main() {
     for i in 0..2048 {
         test_memory[i] = (i % 256) as u8;
     }
-    let mut window = Window::new(font, emulator);
+    let bindings = Bindings::load(BINDINGS_PATH).unwrap_or_else(|e| {
+        println!("couldn't load {BINDINGS_PATH}, using the default bindings: {e}");
+        Bindings::default()
+    });
+    let ppu_panel = PpuPanel::new(&mut rl, &thread).unwrap_or_else(|e| panic!("couldn't create the ppu viewer textures: {e}"));
+    let mut window = Window::new(font, emulator, bindings, ppu_panel);
 
     window.run(&mut rl, thread);
 }
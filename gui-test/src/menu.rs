@@ -0,0 +1,73 @@
+use crate::bindings::{Action, Binding, Bindings, BINDINGS_PATH};
+use raylib::prelude::*;
+
+/// in-app rebinding of the controller bindings.
+///
+/// up/down selects an action, left/right the player, enter waits for the next
+/// key, gamepad button or axis, backspace clears the action and S saves to the config file
+#[derive(Debug, Default)]
+pub struct BindingsMenu {
+    pub open: bool,
+    player: usize,
+    selected: usize,
+    waiting: bool,
+    status: String,
+}
+
+impl BindingsMenu {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.waiting = false;
+        self.status.clear();
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, bindings: &mut Bindings) {
+        let action = Action::ALL[self.selected];
+        let player = &mut bindings.players[self.player];
+
+        if self.waiting {
+            if let Some(binding) = Binding::pressed(rl, player.gamepad) {
+                self.status = format!("{} bound to {}", action.name(), binding);
+                player.rebind(action, binding);
+                self.waiting = false;
+            }
+            return;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = (self.selected + Action::ALL.len() - 1) % Action::ALL.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) {
+            self.selected = (self.selected + 1) % Action::ALL.len();
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_LEFT) || rl.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            self.player = 1 - self.player;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            self.waiting = true;
+            self.status = format!("press a key or gamepad input for {}", action.name());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            player.action_mut(action).clear();
+            self.status = format!("{} cleared", action.name());
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_S) {
+            self.status = match bindings.save(BINDINGS_PATH) {
+                Ok(()) => format!("saved to {BINDINGS_PATH}"),
+                Err(e) => format!("couldn't save bindings: {e}"),
+            };
+        }
+    }
+
+    /// text of the menu and whether the line is selected
+    pub fn lines(&self, bindings: &Bindings) -> Vec<(String, bool)> {
+        let player = &bindings.players[self.player];
+        let mut lines = vec![(format!("< player {} (gamepad {}) >", self.player + 1, player.gamepad), false)];
+        for (i, action) in Action::ALL.iter().enumerate() {
+            let bound: Vec<String> = player.action(*action).iter().map(|binding| binding.to_string()).collect();
+            lines.push((format!("{:<8} {}", action.name(), bound.join(", ")), i == self.selected));
+        }
+        lines.push((self.status.clone(), false));
+        lines
+    }
+}
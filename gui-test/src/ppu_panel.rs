@@ -0,0 +1,173 @@
+use crate::{FONT_SIZE, PADDING};
+use bunNES::nes::ppu::Ppu;
+use bunNES::nes::ppu_viewer::{self, Sprite, NAMETABLES_HEIGHT, NAMETABLES_WIDTH, PALETTES, PATTERN_TABLE_SIZE};
+use raylib::prelude::*;
+
+pub const PPU_PANEL_WIDTH: i32 = NAMETABLES_WIDTH as i32 + 2 * PADDING;
+
+const PATTERN_TABLE_SCALE: f32 = 2.0;
+/// size of a palette ram entry on screen
+const SWATCH_SIZE: i32 = 32;
+
+/// sprites are kept in one texture, 8 to a row in cells big enough for 8x16 sprites
+const SHEET_COLUMNS: usize = 8;
+const SHEET_WIDTH: usize = SHEET_COLUMNS * 8;
+const SHEET_HEIGHT: usize = 64 / SHEET_COLUMNS * 16;
+/// the sprite list is split in two columns of 32
+const SPRITE_ROWS: usize = 32;
+const SPRITE_ROW_HEIGHT: i32 = 28;
+const SPRITE_FONT_SIZE: i32 = 16;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum PpuView {
+    /// nametables, pattern tables and palette ram
+    Vram,
+    Oam,
+}
+
+/// the ppu viewers next to the debugger, their textures are refreshed every drawn frame
+pub struct PpuPanel {
+    pub view: PpuView,
+    /// palette the pattern tables are drawn in, see [`PALETTES`]
+    pub palette: u8,
+
+    nametables: Texture2D,
+    pattern_tables: [Texture2D; 2],
+    palette_ram: Texture2D,
+    sprite_sheet: Texture2D,
+    sprites: Vec<Sprite>,
+}
+
+impl PpuPanel {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread) -> Result<PpuPanel, String> {
+        let mut texture = |width: usize, height: usize| {
+            let image = Image::gen_image_color(width as i32, height as i32, Color::BLACK);
+            rl.load_texture_from_image(thread, &image)
+        };
+
+        Ok(PpuPanel {
+            view: PpuView::Vram,
+            palette: 0,
+
+            nametables: texture(NAMETABLES_WIDTH, NAMETABLES_HEIGHT)?,
+            pattern_tables: [
+                texture(PATTERN_TABLE_SIZE, PATTERN_TABLE_SIZE)?,
+                texture(PATTERN_TABLE_SIZE, PATTERN_TABLE_SIZE)?,
+            ],
+            palette_ram: texture(16, 2)?,
+            sprite_sheet: texture(SHEET_WIDTH, SHEET_HEIGHT)?,
+            sprites: Vec::new(),
+        })
+    }
+
+    pub fn toggle_view(&mut self) {
+        self.view = match self.view {
+            PpuView::Vram => PpuView::Oam,
+            PpuView::Oam => PpuView::Vram,
+        };
+    }
+
+    pub fn next_palette(&mut self) {
+        self.palette = (self.palette + 1) % PALETTES;
+    }
+
+    /// redraws the textures of the current view
+    pub fn update(&mut self, ppu: &Ppu) {
+        match self.view {
+            PpuView::Vram => {
+                self.nametables.update_texture(&ppu_viewer::nametables(ppu).pixels);
+                for (table, texture) in self.pattern_tables.iter_mut().enumerate() {
+                    texture.update_texture(&ppu_viewer::pattern_table(ppu, table as u8, self.palette).pixels);
+                }
+                self.palette_ram.update_texture(&ppu_viewer::palette_ram(ppu).pixels);
+            }
+            PpuView::Oam => {
+                self.sprites = ppu_viewer::sprites(ppu);
+                self.sprite_sheet.update_texture(&sprite_sheet(&self.sprites));
+            }
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font, ppu: &Ppu, x: i32, y: i32) {
+        match self.view {
+            PpuView::Vram => self.draw_vram(d, font, ppu, x, y),
+            PpuView::Oam => self.draw_oam(d, font, x, y),
+        }
+    }
+
+    fn draw_vram(&self, d: &mut RaylibDrawHandle, font: &Font, ppu: &Ppu, x: i32, y: i32) {
+        let mut y = y;
+        let (scroll_x, scroll_y) = ppu.scroll();
+        draw_text(d, font, &format!("nametables  scroll {scroll_x}, {scroll_y}"), x, y, FONT_SIZE);
+        y += FONT_SIZE + PADDING;
+        d.draw_texture(&self.nametables, x, y, Color::WHITE);
+        y += NAMETABLES_HEIGHT as i32 + PADDING;
+
+        draw_text(d, font, &format!("pattern tables  palette {}", self.palette), x, y, FONT_SIZE);
+        y += FONT_SIZE + PADDING;
+        for (table, texture) in self.pattern_tables.iter().enumerate() {
+            let left = x + table as i32 * (PATTERN_TABLE_SIZE as f32 * PATTERN_TABLE_SCALE) as i32;
+            d.draw_texture_ex(texture, Vector2::new(left as f32, y as f32), 0.0, PATTERN_TABLE_SCALE, Color::WHITE);
+        }
+        y += (PATTERN_TABLE_SIZE as f32 * PATTERN_TABLE_SCALE) as i32 + PADDING;
+
+        d.draw_texture_ex(&self.palette_ram, Vector2::new(x as f32, y as f32), 0.0, SWATCH_SIZE as f32, Color::WHITE);
+        // the palette the pattern tables are drawn in
+        let selected = Rectangle::new(
+            (x + (self.palette as i32 % 4) * 4 * SWATCH_SIZE) as f32,
+            (y + (self.palette as i32 / 4) * SWATCH_SIZE) as f32,
+            (4 * SWATCH_SIZE) as f32,
+            SWATCH_SIZE as f32,
+        );
+        d.draw_rectangle_lines_ex(selected, 3.0, Color::new(150, 255, 0, 255));
+    }
+
+    fn draw_oam(&self, d: &mut RaylibDrawHandle, font: &Font, x: i32, y: i32) {
+        draw_text(d, font, "oam  #  x,  y tile pal flags", x, y, FONT_SIZE);
+        let top = y + FONT_SIZE + PADDING;
+        let column_width = NAMETABLES_WIDTH as i32 / 2;
+
+        for sprite in &self.sprites {
+            let left = x + (sprite.index as usize / SPRITE_ROWS) as i32 * column_width;
+            let y = top + (sprite.index as usize % SPRITE_ROWS) as i32 * SPRITE_ROW_HEIGHT;
+
+            // 16 pixels tall whatever the sprite size
+            let scale = 16.0 / sprite.image.height as f32;
+            let source = Rectangle::new(
+                (sprite.index as usize % SHEET_COLUMNS * 8) as f32,
+                (sprite.index as usize / SHEET_COLUMNS * 16) as f32,
+                8.0,
+                sprite.image.height as f32,
+            );
+            let dest = Rectangle::new(left as f32, y as f32, 8.0 * scale, 16.0);
+            d.draw_rectangle(left, y, 16, 16, Color::DARKGRAY);
+            d.draw_texture_pro(&self.sprite_sheet, source, dest, Vector2::zero(), 0.0, Color::WHITE);
+
+            let flags: String = [
+                (sprite.flip_horizontal, 'H'),
+                (sprite.flip_vertical, 'V'),
+                (sprite.behind_background, 'B'),
+            ].iter().map(|(set, flag)| if *set { *flag } else { '-' }).collect();
+            let text = format!("{:02} {:3},{:3} ${:02X}  {}  {}", sprite.index, sprite.x, sprite.y, sprite.tile, sprite.palette, flags);
+            draw_text(d, font, &text, left + 24, y, SPRITE_FONT_SIZE);
+        }
+    }
+}
+
+/// all sprites in one rgba image, see [`SHEET_COLUMNS`]
+fn sprite_sheet(sprites: &[Sprite]) -> Vec<u8> {
+    let mut pixels = vec![0; SHEET_WIDTH * SHEET_HEIGHT * 4];
+    for sprite in sprites {
+        let left = sprite.index as usize % SHEET_COLUMNS * 8;
+        let top = sprite.index as usize / SHEET_COLUMNS * 16;
+        for (y, row) in sprite.image.pixels.chunks(8 * 4).enumerate() {
+            let i = ((top + y) * SHEET_WIDTH + left) * 4;
+            pixels[i..i + row.len()].copy_from_slice(row);
+        }
+    }
+    pixels
+}
+
+fn draw_text(d: &mut RaylibDrawHandle, font: &Font, text: &str, x: i32, y: i32, font_size: i32) {
+    d.draw_text_ex(font, text, Vector2 { x: x as f32, y: y as f32 }, font_size as f32, 0f32, Color::BLACK);
+}
@@ -0,0 +1,195 @@
+use crate::{FONT_SIZE, NES_HEIGHT, NES_SCALE, NES_WIDTH, PADDING};
+use bunNES::emulator::Emulator;
+use bunNES::ram_search::{Comparison, Operand, RamSearch, Size};
+use raylib::prelude::*;
+
+/// result rows that fit between the header lines and the cheats
+const ROWS: usize = 24;
+const SELECTED: Color = Color::new(150, 255, 0, 255);
+const FROZEN: Color = Color::new(120, 200, 255, 255);
+
+/// what the comparison key cycles through, changed by takes its difference from the typed number
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+enum Kind {
+    #[default]
+    Equal,
+    NotEqual,
+    Greater,
+    Less,
+    ChangedBy,
+}
+
+impl Kind {
+    const ALL: [Kind; 5] = [Kind::Equal, Kind::NotEqual, Kind::Greater, Kind::Less, Kind::ChangedBy];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Kind::Equal => "=",
+            Kind::NotEqual => "!=",
+            Kind::Greater => ">",
+            Kind::Less => "<",
+            Kind::ChangedBy => "changed by",
+        }
+    }
+}
+
+/// ram search over the running game, drawn over the nes screen like the bindings menu.
+///
+/// tab switches between 8/16 bit and signed/unsigned, C picks the comparison and V whether
+/// it's against the previous search or the typed number. enter searches, R starts over,
+/// up/down select a result and F freezes it at its current value, U unfreezes it
+#[derive(Debug, Default)]
+pub struct RamSearchPanel {
+    pub open: bool,
+    search: Option<RamSearch>,
+    kind: Kind,
+    /// compare against `number` instead of the previous search
+    against_number: bool,
+    /// typed value, or the difference for changed by
+    number: String,
+    selected: usize,
+    status: String,
+}
+
+impl RamSearchPanel {
+    pub fn toggle(&mut self) {
+        self.open = !self.open;
+        self.status.clear();
+    }
+
+    pub fn update(&mut self, rl: &mut RaylibHandle, emulator: &mut Emulator) {
+        let search = self.search.get_or_insert_with(|| RamSearch::new(&emulator.cpu.bus));
+
+        while let Some(c) = rl.get_char_pressed() {
+            if c.is_ascii_digit() || (c == '-' && self.number.is_empty()) {
+                self.number.push(c);
+            }
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_BACKSPACE) {
+            self.number.pop();
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_TAB) {
+            (search.size, search.signed) = match (search.size, search.signed) {
+                (Size::Byte, false) => (Size::Byte, true),
+                (Size::Byte, true) => (Size::Word, false),
+                (Size::Word, false) => (Size::Word, true),
+                (Size::Word, true) => (Size::Byte, false),
+            };
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_C) {
+            let index = Kind::ALL.iter().position(|kind| *kind == self.kind).unwrap_or(0);
+            self.kind = Kind::ALL[(index + 1) % Kind::ALL.len()];
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_V) {
+            self.against_number = !self.against_number;
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_R) {
+            search.reset(&emulator.cpu.bus);
+            self.selected = 0;
+            self.status = "started over".to_string();
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_ENTER) {
+            let number = self.number.parse::<i32>().ok();
+            let comparison = match self.kind {
+                Kind::Equal => Some(Comparison::Equal),
+                Kind::NotEqual => Some(Comparison::NotEqual),
+                Kind::Greater => Some(Comparison::Greater),
+                Kind::Less => Some(Comparison::Less),
+                Kind::ChangedBy => number.map(Comparison::ChangedBy),
+            };
+            let operand = match (self.kind, self.against_number) {
+                (Kind::ChangedBy, _) | (_, false) => Some(Operand::Previous),
+                (_, true) => number.map(Operand::Value),
+            };
+            match comparison.zip(operand) {
+                Some((comparison, operand)) => {
+                    search.search(&emulator.cpu.bus, comparison, operand);
+                    self.selected = 0;
+                    self.status = format!("{} candidates", search.candidates().len());
+                }
+                None => self.status = "type a number first".to_string(),
+            }
+        }
+
+        let count = search.candidates().len();
+        if rl.is_key_pressed(KeyboardKey::KEY_UP) {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_DOWN) && self.selected + 1 < count {
+            self.selected += 1;
+        }
+
+        let results = search.results(&emulator.cpu.bus);
+        let Some(result) = results.get(self.selected) else {
+            return;
+        };
+        if rl.is_key_pressed(KeyboardKey::KEY_F) {
+            for cheat in search.cheats(result.addr, result.value) {
+                emulator.add_cheat(cheat);
+            }
+            self.status = format!("${:04X} frozen at {}", result.addr, result.value);
+        }
+        if rl.is_key_pressed(KeyboardKey::KEY_U) {
+            for cheat in search.cheats(result.addr, result.value) {
+                emulator.remove_cheat(cheat.addr);
+            }
+            self.status = format!("${:04X} unfrozen", result.addr);
+        }
+    }
+
+    pub fn draw(&self, d: &mut RaylibDrawHandle, font: &Font, emulator: &Emulator) {
+        d.draw_rectangle(0, 0,
+                         NES_WIDTH * NES_SCALE, NES_HEIGHT * NES_SCALE,
+                         Color::new(0, 0, 0, 220));
+        let Some(search) = &self.search else {
+            return;
+        };
+
+        let format = match (search.size, search.signed) {
+            (Size::Byte, false) => "8 bit unsigned",
+            (Size::Byte, true) => "8 bit signed",
+            (Size::Word, false) => "16 bit unsigned",
+            (Size::Word, true) => "16 bit signed",
+        };
+        let operand = match (self.kind, self.against_number) {
+            (Kind::ChangedBy, _) => format!("{}_ from previous", self.number),
+            (_, true) => format!("{}_", self.number),
+            (_, false) => "previous".to_string(),
+        };
+        let lines = [
+            format!("ram search  {format}  (tab)"),
+            format!("value {} {operand}  (c, v, enter)", self.kind.name()),
+            format!("{} candidates  r: reset  f: freeze  u: unfreeze", search.candidates().len()),
+        ];
+        let mut y = PADDING;
+        for line in &lines {
+            draw_text(d, font, line, PADDING, y, Color::WHITE);
+            y += FONT_SIZE + PADDING;
+        }
+        y += PADDING;
+
+        let cheats = emulator.cheats();
+        let results = search.results(&emulator.cpu.bus);
+        // keeps the selection in view
+        let first = self.selected.saturating_sub(ROWS - 1);
+        for (i, result) in results.iter().enumerate().skip(first).take(ROWS) {
+            let frozen = cheats.iter().any(|cheat| cheat.addr == result.addr);
+            let text = format!("${:04X}  {:>6}  {:>6}{}", result.addr, result.value, result.previous, if frozen { "  frozen" } else { "" });
+            let color = if i == self.selected { SELECTED } else if frozen { FROZEN } else { Color::WHITE };
+            draw_text(d, font, &text, PADDING, y, color);
+            y += FONT_SIZE + PADDING;
+        }
+
+        let mut y = NES_HEIGHT * NES_SCALE - 2 * (FONT_SIZE + PADDING);
+        let cheats: Vec<String> = cheats.iter().map(|cheat| format!("${:04X}={:02X}", cheat.addr, cheat.value)).collect();
+        draw_text(d, font, &format!("cheats: {}", cheats.join(" ")), PADDING, y, FROZEN);
+        y += FONT_SIZE + PADDING;
+        draw_text(d, font, &self.status, PADDING, y, Color::WHITE);
+    }
+}
+
+fn draw_text(d: &mut RaylibDrawHandle, font: &Font, text: &str, x: i32, y: i32, color: Color) {
+    d.draw_text_ex(font, text, Vector2 { x: x as f32, y: y as f32 }, FONT_SIZE as f32, 0f32, color);
+}